@@ -0,0 +1,5 @@
+ALTER TABLE CLIENTE
+DROP COLUMN IF EXISTS ANONIMIZADO;
+
+ALTER TABLE USUARIO
+DROP COLUMN IF EXISTS PERFIL;
@@ -0,0 +1,7 @@
+ALTER TABLE USUARIO
+ADD COLUMN PERFIL SMALLINT NOT NULL DEFAULT 0;
+
+UPDATE USUARIO SET PERFIL = 1 WHERE LOGIN = 'admin';
+
+ALTER TABLE CLIENTE
+ADD COLUMN ANONIMIZADO BOOLEAN NOT NULL DEFAULT 'F';
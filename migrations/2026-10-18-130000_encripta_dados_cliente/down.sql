@@ -0,0 +1,11 @@
+ALTER TABLE ENDERECO
+DROP COLUMN IF EXISTS CHAVE_VERSAO;
+
+ALTER TABLE CLIENTE
+DROP CONSTRAINT IF EXISTS UQ_CLIENTE_DOCTO_HASH;
+
+ALTER TABLE CLIENTE
+DROP COLUMN IF EXISTS CHAVE_VERSAO;
+
+ALTER TABLE CLIENTE
+DROP COLUMN IF EXISTS DOCTO_HASH;
@@ -0,0 +1,11 @@
+ALTER TABLE CLIENTE
+ADD COLUMN DOCTO_HASH VARCHAR;
+
+ALTER TABLE CLIENTE
+ADD COLUMN CHAVE_VERSAO SMALLINT;
+
+ALTER TABLE CLIENTE
+ADD CONSTRAINT UQ_CLIENTE_DOCTO_HASH UNIQUE(DOCTO_HASH);
+
+ALTER TABLE ENDERECO
+ADD COLUMN CHAVE_VERSAO SMALLINT;
@@ -0,0 +1,7 @@
+DROP TABLE IF EXISTS TITULO;
+
+ALTER TABLE CLIENTE
+DROP COLUMN IF EXISTS LIBERADO_ATE;
+
+ALTER TABLE CLIENTE
+DROP COLUMN IF EXISTS LIMITE_CREDITO;
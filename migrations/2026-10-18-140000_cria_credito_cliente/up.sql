@@ -0,0 +1,17 @@
+ALTER TABLE CLIENTE
+ADD COLUMN LIMITE_CREDITO NUMERIC(13,2) NOT NULL DEFAULT 0;
+
+ALTER TABLE CLIENTE
+ADD COLUMN LIBERADO_ATE DATE;
+
+CREATE TABLE TITULO (
+       ID          SERIAL         PRIMARY KEY,
+       CLIENTE_ID  INTEGER        NOT NULL,
+       DOCTO       VARCHAR        NOT NULL,
+       VALOR       NUMERIC(13,2)  NOT NULL,
+       VALOR_PAGO  NUMERIC(13,2)  NOT NULL DEFAULT 0,
+       VENCIMENTO  DATE           NOT NULL,
+       DATAHORA    TIMESTAMPTZ    NOT NULL,
+       CONSTRAINT FK_TITULO_CLIENTE
+       FOREIGN KEY(CLIENTE_ID) REFERENCES CLIENTE(ID)
+);
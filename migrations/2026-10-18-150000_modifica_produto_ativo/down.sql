@@ -0,0 +1,2 @@
+ALTER TABLE PRODUTO
+DROP COLUMN IF EXISTS ATIVO;
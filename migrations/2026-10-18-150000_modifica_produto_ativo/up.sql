@@ -0,0 +1,2 @@
+ALTER TABLE PRODUTO
+ADD COLUMN ATIVO BOOLEAN NOT NULL DEFAULT 'T';
@@ -0,0 +1,6 @@
+DROP TABLE IF EXISTS PRODUTO_UNIDADE;
+
+ALTER TABLE PRODUTO
+DROP CONSTRAINT IF EXISTS FK_PRODUTO_UNIDADE_MEDIDA;
+
+DROP TABLE IF EXISTS UNIDADE_MEDIDA;
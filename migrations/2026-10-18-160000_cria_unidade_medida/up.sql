@@ -0,0 +1,44 @@
+CREATE TABLE UNIDADE_MEDIDA (
+       CODIGO         VARCHAR   PRIMARY KEY,
+       DESCRICAO      VARCHAR   NOT NULL,
+       CASAS_DECIMAIS SMALLINT  NOT NULL DEFAULT 0,
+       CONSTRAINT CK_UNIDADE_MEDIDA_CASAS
+       CHECK (CASAS_DECIMAIS BETWEEN 0 AND 3)
+);
+
+INSERT INTO UNIDADE_MEDIDA (CODIGO, DESCRICAO, CASAS_DECIMAIS) VALUES
+       ('UN', 'Unidade',    0),
+       ('CX', 'Caixa',      0),
+       ('FD', 'Fardo',      0),
+       ('PC', 'Peça',       0),
+       ('DZ', 'Dúzia',      0),
+       ('KG', 'Quilograma', 3),
+       ('G',  'Grama',      3),
+       ('L',  'Litro',      3),
+       ('ML', 'Mililitro',  3),
+       ('M',  'Metro',      3);
+
+UPDATE PRODUTO SET UNIDSAIDA = UPPER(TRIM(UNIDSAIDA));
+
+UPDATE PRODUTO SET UNIDSAIDA = 'UN'
+WHERE UNIDSAIDA IN ('U', 'UND', 'UNID', 'UNIDADE');
+
+INSERT INTO UNIDADE_MEDIDA (CODIGO, DESCRICAO, CASAS_DECIMAIS)
+SELECT DISTINCT UNIDSAIDA, UNIDSAIDA, 3 FROM PRODUTO
+WHERE UNIDSAIDA NOT IN (SELECT CODIGO FROM UNIDADE_MEDIDA);
+
+ALTER TABLE PRODUTO
+ADD CONSTRAINT FK_PRODUTO_UNIDADE_MEDIDA
+FOREIGN KEY (UNIDSAIDA) REFERENCES UNIDADE_MEDIDA(CODIGO);
+
+CREATE TABLE PRODUTO_UNIDADE (
+       PRODUTO_ID  INTEGER        NOT NULL,
+       UNIDADE     VARCHAR        NOT NULL,
+       FATOR       NUMERIC(12,6)  NOT NULL,
+       CONSTRAINT PRODUTO_UNIDADE_PKEY PRIMARY KEY (PRODUTO_ID, UNIDADE),
+       CONSTRAINT FK_PRODUTO_UNIDADE_PRODUTO
+       FOREIGN KEY (PRODUTO_ID) REFERENCES PRODUTO(ID) ON DELETE CASCADE,
+       CONSTRAINT FK_PRODUTO_UNIDADE_UNIDADE
+       FOREIGN KEY (UNIDADE) REFERENCES UNIDADE_MEDIDA(CODIGO),
+       CONSTRAINT CK_PRODUTO_UNIDADE_FATOR CHECK (FATOR > 0)
+);
@@ -0,0 +1,15 @@
+CREATE TABLE PRODUTO_GTIN (
+       GTIN        VARCHAR(14)  NOT NULL PRIMARY KEY,
+       PRODUTO_ID  INTEGER      NOT NULL,
+       CONSTRAINT FK_PRODUTO_GTIN_PRODUTO
+       FOREIGN KEY (PRODUTO_ID) REFERENCES PRODUTO(ID) ON DELETE CASCADE,
+       CONSTRAINT CK_PRODUTO_GTIN_FORMATO
+       CHECK (GTIN ~ '^([0-9]{8}|[0-9]{12,14})$')
+);
+
+-- Códigos de tamanhos diferentes representam o mesmo GTIN quando preenchidos
+-- com zeros à esquerda até 14 dígitos.
+CREATE UNIQUE INDEX IDX_PRODUTO_GTIN_NORMALIZADO
+ON PRODUTO_GTIN (LPAD(GTIN, 14, '0'));
+
+CREATE INDEX IDX_PRODUTO_GTIN_PRODUTO ON PRODUTO_GTIN (PRODUTO_ID);
@@ -0,0 +1,6 @@
+DROP INDEX IF EXISTS IDX_PRODUTO_NCM;
+
+ALTER TABLE PRODUTO
+DROP COLUMN IF EXISTS NCM,
+DROP COLUMN IF EXISTS CEST,
+DROP COLUMN IF EXISTS ORIGEM;
@@ -0,0 +1,11 @@
+ALTER TABLE PRODUTO
+ADD COLUMN NCM     VARCHAR(8),
+ADD COLUMN CEST    VARCHAR(7),
+ADD COLUMN ORIGEM  SMALLINT  NOT NULL DEFAULT 0;
+
+ALTER TABLE PRODUTO
+ADD CONSTRAINT CK_PRODUTO_NCM CHECK (NCM ~ '^[0-9]{8}$'),
+ADD CONSTRAINT CK_PRODUTO_CEST CHECK (CEST ~ '^[0-9]{7}$'),
+ADD CONSTRAINT CK_PRODUTO_ORIGEM CHECK (ORIGEM BETWEEN 0 AND 8);
+
+CREATE INDEX IDX_PRODUTO_NCM ON PRODUTO (NCM);
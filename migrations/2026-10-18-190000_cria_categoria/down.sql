@@ -0,0 +1,6 @@
+DROP INDEX IF EXISTS IDX_PRODUTO_CATEGORIA;
+
+ALTER TABLE PRODUTO
+DROP COLUMN IF EXISTS CATEGORIA_ID;
+
+DROP TABLE IF EXISTS CATEGORIA;
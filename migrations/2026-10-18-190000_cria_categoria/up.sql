@@ -0,0 +1,17 @@
+CREATE TABLE CATEGORIA (
+       ID                SERIAL   NOT NULL PRIMARY KEY,
+       DESCRICAO         VARCHAR  NOT NULL,
+       CATEGORIA_PAI_ID  INTEGER,
+       CONSTRAINT FK_CATEGORIA_PAI
+       FOREIGN KEY (CATEGORIA_PAI_ID) REFERENCES CATEGORIA(ID),
+       CONSTRAINT CK_CATEGORIA_PAI CHECK (CATEGORIA_PAI_ID <> ID)
+);
+
+CREATE INDEX IDX_CATEGORIA_PAI ON CATEGORIA (CATEGORIA_PAI_ID);
+
+ALTER TABLE PRODUTO
+ADD COLUMN CATEGORIA_ID INTEGER,
+ADD CONSTRAINT FK_PRODUTO_CATEGORIA
+FOREIGN KEY (CATEGORIA_ID) REFERENCES CATEGORIA(ID);
+
+CREATE INDEX IDX_PRODUTO_CATEGORIA ON PRODUTO (CATEGORIA_ID);
@@ -0,0 +1,6 @@
+DROP TABLE IF EXISTS PRODUTO_ATRIBUTO;
+
+DROP INDEX IF EXISTS IDX_PRODUTO_PAI;
+
+ALTER TABLE PRODUTO
+DROP COLUMN IF EXISTS PRODUTO_PAI_ID;
@@ -0,0 +1,16 @@
+ALTER TABLE PRODUTO
+ADD COLUMN PRODUTO_PAI_ID INTEGER,
+ADD CONSTRAINT FK_PRODUTO_PAI
+FOREIGN KEY (PRODUTO_PAI_ID) REFERENCES PRODUTO(ID),
+ADD CONSTRAINT CK_PRODUTO_PAI CHECK (PRODUTO_PAI_ID <> ID);
+
+CREATE INDEX IDX_PRODUTO_PAI ON PRODUTO (PRODUTO_PAI_ID);
+
+CREATE TABLE PRODUTO_ATRIBUTO (
+       PRODUTO_ID  INTEGER  NOT NULL,
+       ATRIBUTO    VARCHAR  NOT NULL,
+       VALOR       VARCHAR  NOT NULL,
+       CONSTRAINT PRODUTO_ATRIBUTO_PKEY PRIMARY KEY (PRODUTO_ID, ATRIBUTO),
+       CONSTRAINT FK_PRODUTO_ATRIBUTO_PRODUTO
+       FOREIGN KEY (PRODUTO_ID) REFERENCES PRODUTO(ID) ON DELETE CASCADE
+);
@@ -0,0 +1,14 @@
+CREATE TABLE PRODUTO_COMPONENTE (
+       KIT_ID         INTEGER        NOT NULL,
+       COMPONENTE_ID  INTEGER        NOT NULL,
+       QUANTIDADE     NUMERIC(12,3)  NOT NULL,
+       CONSTRAINT PRODUTO_COMPONENTE_PKEY PRIMARY KEY (KIT_ID, COMPONENTE_ID),
+       CONSTRAINT FK_PRODUTO_COMPONENTE_KIT
+       FOREIGN KEY (KIT_ID) REFERENCES PRODUTO(ID) ON DELETE CASCADE,
+       CONSTRAINT FK_PRODUTO_COMPONENTE_COMPONENTE
+       FOREIGN KEY (COMPONENTE_ID) REFERENCES PRODUTO(ID),
+       CONSTRAINT CK_PRODUTO_COMPONENTE_KIT CHECK (KIT_ID <> COMPONENTE_ID),
+       CONSTRAINT CK_PRODUTO_COMPONENTE_QUANTIDADE CHECK (QUANTIDADE > 0)
+);
+
+CREATE INDEX IDX_PRODUTO_COMPONENTE_COMPONENTE ON PRODUTO_COMPONENTE (COMPONENTE_ID);
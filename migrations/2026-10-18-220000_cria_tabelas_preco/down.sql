@@ -0,0 +1,2 @@
+DROP TABLE IF EXISTS PRECO_PRODUTO;
+DROP TABLE IF EXISTS TABELA_PRECO;
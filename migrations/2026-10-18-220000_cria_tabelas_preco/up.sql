@@ -0,0 +1,30 @@
+CREATE TABLE TABELA_PRECO (
+       ID            SERIAL    PRIMARY KEY,
+       DESCRICAO     VARCHAR   NOT NULL,
+       CLIENTE_ID    INTEGER,
+       TIPO_CLIENTE  SMALLINT,
+       CONSTRAINT FK_TABELA_PRECO_CLIENTE
+       FOREIGN KEY (CLIENTE_ID) REFERENCES CLIENTE(ID) ON DELETE CASCADE,
+       CONSTRAINT CK_TABELA_PRECO_APLICACAO
+       CHECK (CLIENTE_ID IS NULL OR TIPO_CLIENTE IS NULL)
+);
+
+CREATE INDEX IDX_TABELA_PRECO_CLIENTE ON TABELA_PRECO (CLIENTE_ID);
+
+CREATE TABLE PRECO_PRODUTO (
+       ID               SERIAL         PRIMARY KEY,
+       TABELA_PRECO_ID  INTEGER        NOT NULL,
+       PRODUTO_ID       INTEGER        NOT NULL,
+       PRECO            NUMERIC(14,4)  NOT NULL,
+       VIGENCIA_INICIO  DATE           NOT NULL,
+       VIGENCIA_FIM     DATE,
+       CONSTRAINT FK_PRECO_PRODUTO_TABELA
+       FOREIGN KEY (TABELA_PRECO_ID) REFERENCES TABELA_PRECO(ID) ON DELETE CASCADE,
+       CONSTRAINT FK_PRECO_PRODUTO_PRODUTO
+       FOREIGN KEY (PRODUTO_ID) REFERENCES PRODUTO(ID) ON DELETE CASCADE,
+       CONSTRAINT CK_PRECO_PRODUTO_PRECO CHECK (PRECO > 0),
+       CONSTRAINT CK_PRECO_PRODUTO_VIGENCIA
+       CHECK (VIGENCIA_FIM IS NULL OR VIGENCIA_FIM >= VIGENCIA_INICIO)
+);
+
+CREATE INDEX IDX_PRECO_PRODUTO_PRODUTO ON PRECO_PRODUTO (PRODUTO_ID);
@@ -0,0 +1,23 @@
+CREATE TABLE ARQUIVO (
+       ID              SERIAL     PRIMARY KEY,
+       CHAVE           VARCHAR    NOT NULL,
+       NOME            VARCHAR    NOT NULL,
+       CONTENT_TYPE    VARCHAR    NOT NULL,
+       TAMANHO         INTEGER    NOT NULL,
+       SHA256          VARCHAR    NOT NULL,
+       PRODUTO_ID      INTEGER,
+       MOV_ESTOQUE_ID  INTEGER,
+       USUARIO         VARCHAR    NOT NULL,
+       DATAHORA        TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+       CONSTRAINT UN_ARQUIVO_CHAVE UNIQUE (CHAVE),
+       CONSTRAINT FK_ARQUIVO_PRODUTO
+       FOREIGN KEY (PRODUTO_ID) REFERENCES PRODUTO(ID) ON DELETE CASCADE,
+       CONSTRAINT FK_ARQUIVO_MOV_ESTOQUE
+       FOREIGN KEY (MOV_ESTOQUE_ID) REFERENCES MOV_ESTOQUE(ID),
+       CONSTRAINT CK_ARQUIVO_TAMANHO CHECK (TAMANHO > 0),
+       CONSTRAINT CK_ARQUIVO_DONO
+       CHECK ((PRODUTO_ID IS NULL) <> (MOV_ESTOQUE_ID IS NULL))
+);
+
+CREATE INDEX IDX_ARQUIVO_PRODUTO ON ARQUIVO (PRODUTO_ID);
+CREATE INDEX IDX_ARQUIVO_MOV_ESTOQUE ON ARQUIVO (MOV_ESTOQUE_ID);
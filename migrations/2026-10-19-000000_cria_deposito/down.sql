@@ -0,0 +1,25 @@
+ALTER TABLE MOV_ESTOQUE
+DROP COLUMN IF EXISTS DEPOSITO_ID;
+
+-- As posições de estoque dos demais depósitos são consolidadas no depósito
+-- principal antes da remoção da coluna.
+UPDATE ESTOQUE E
+SET QUANTIDADE = (SELECT SUM(QUANTIDADE) FROM ESTOQUE T
+                  WHERE T.PRODUTO_ID = E.PRODUTO_ID)
+WHERE E.DEPOSITO_ID = (SELECT MIN(DEPOSITO_ID) FROM ESTOQUE T
+                       WHERE T.PRODUTO_ID = E.PRODUTO_ID);
+
+DELETE FROM ESTOQUE E
+WHERE E.DEPOSITO_ID <> (SELECT MIN(DEPOSITO_ID) FROM ESTOQUE T
+                        WHERE T.PRODUTO_ID = E.PRODUTO_ID);
+
+ALTER TABLE ESTOQUE
+DROP CONSTRAINT IF EXISTS ESTOQUE_PKEY;
+
+ALTER TABLE ESTOQUE
+DROP COLUMN IF EXISTS DEPOSITO_ID;
+
+ALTER TABLE ESTOQUE
+ADD CONSTRAINT ESTOQUE_PKEY PRIMARY KEY (PRODUTO_ID);
+
+DROP TABLE IF EXISTS DEPOSITO;
@@ -0,0 +1,38 @@
+CREATE TABLE DEPOSITO (
+       ID         SERIAL   PRIMARY KEY,
+       DESCRICAO  VARCHAR  NOT NULL,
+       ATIVO      BOOLEAN  NOT NULL DEFAULT TRUE
+);
+
+-- Depósito padrão, ao qual pertencem as posições e movimentações de estoque
+-- já existentes.
+INSERT INTO DEPOSITO (ID, DESCRICAO) VALUES (1, 'Depósito principal');
+SELECT SETVAL('deposito_id_seq', (SELECT MAX(ID) FROM DEPOSITO));
+
+ALTER TABLE ESTOQUE
+ADD COLUMN DEPOSITO_ID INTEGER NOT NULL DEFAULT 1;
+
+ALTER TABLE ESTOQUE
+ALTER COLUMN DEPOSITO_ID DROP DEFAULT;
+
+ALTER TABLE ESTOQUE
+DROP CONSTRAINT IF EXISTS ESTOQUE_PKEY;
+
+ALTER TABLE ESTOQUE
+ADD CONSTRAINT ESTOQUE_PKEY PRIMARY KEY (PRODUTO_ID, DEPOSITO_ID);
+
+ALTER TABLE ESTOQUE
+ADD CONSTRAINT FK_ESTOQUE_DEPOSITO
+FOREIGN KEY (DEPOSITO_ID) REFERENCES DEPOSITO(ID);
+
+CREATE INDEX IDX_ESTOQUE_DEPOSITO ON ESTOQUE (DEPOSITO_ID);
+
+ALTER TABLE MOV_ESTOQUE
+ADD COLUMN DEPOSITO_ID INTEGER NOT NULL DEFAULT 1;
+
+ALTER TABLE MOV_ESTOQUE
+ALTER COLUMN DEPOSITO_ID DROP DEFAULT;
+
+ALTER TABLE MOV_ESTOQUE
+ADD CONSTRAINT FK_MOV_ESTOQUE_DEPOSITO
+FOREIGN KEY (DEPOSITO_ID) REFERENCES DEPOSITO(ID);
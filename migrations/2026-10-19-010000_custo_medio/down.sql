@@ -0,0 +1,5 @@
+ALTER TABLE MOV_ESTOQUE
+DROP COLUMN CUSTO_UNITARIO;
+
+ALTER TABLE ESTOQUE
+DROP COLUMN CUSTO_MEDIO;
@@ -0,0 +1,18 @@
+-- Custo médio ponderado de cada posição de estoque. O preço unitário da
+-- posição passa a ser apenas o preço de venda.
+ALTER TABLE ESTOQUE
+ADD COLUMN CUSTO_MEDIO NUMERIC(13,4) NOT NULL DEFAULT 0;
+
+UPDATE ESTOQUE SET CUSTO_MEDIO = PRECOUNITARIO;
+
+ALTER TABLE ESTOQUE
+ALTER COLUMN CUSTO_MEDIO DROP DEFAULT;
+
+-- Custo unitário pelo qual cada movimentação foi valorada.
+ALTER TABLE MOV_ESTOQUE
+ADD COLUMN CUSTO_UNITARIO NUMERIC(13,4) NOT NULL DEFAULT 0;
+
+UPDATE MOV_ESTOQUE SET CUSTO_UNITARIO = PRECO_UNITARIO;
+
+ALTER TABLE MOV_ESTOQUE
+ALTER COLUMN CUSTO_UNITARIO DROP DEFAULT;
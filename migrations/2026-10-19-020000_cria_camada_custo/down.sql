@@ -0,0 +1,4 @@
+DROP TABLE IF EXISTS CAMADA_CUSTO;
+
+ALTER TABLE PRODUTO
+DROP COLUMN METODO_CUSTO;
@@ -0,0 +1,34 @@
+-- Método de valoração do estoque de cada produto: 0 = custo médio ponderado,
+-- 1 = PEPS (primeiro a entrar, primeiro a sair).
+ALTER TABLE PRODUTO
+ADD COLUMN METODO_CUSTO SMALLINT NOT NULL DEFAULT 0;
+
+-- Camadas de custo das entradas de estoque, consumidas da mais antiga para a
+-- mais recente pelas saídas.
+CREATE TABLE CAMADA_CUSTO (
+       ID              SERIAL         PRIMARY KEY,
+       PRODUTO_ID      INTEGER        NOT NULL,
+       DEPOSITO_ID     INTEGER        NOT NULL,
+       MOV_ESTOQUE_ID  INTEGER,
+       QUANTIDADE      NUMERIC(12,3)  NOT NULL,
+       RESTANTE        NUMERIC(12,3)  NOT NULL,
+       CUSTO_UNITARIO  NUMERIC(13,4)  NOT NULL,
+       DATAHORA        TIMESTAMPTZ    NOT NULL DEFAULT NOW(),
+       CONSTRAINT FK_CAMADA_CUSTO_ESTOQUE
+       FOREIGN KEY (PRODUTO_ID, DEPOSITO_ID)
+       REFERENCES ESTOQUE(PRODUTO_ID, DEPOSITO_ID) ON DELETE CASCADE,
+       CONSTRAINT FK_CAMADA_CUSTO_MOV_ESTOQUE
+       FOREIGN KEY (MOV_ESTOQUE_ID) REFERENCES MOV_ESTOQUE(ID),
+       CONSTRAINT CK_CAMADA_CUSTO_RESTANTE
+       CHECK (RESTANTE >= 0 AND RESTANTE <= QUANTIDADE)
+);
+
+CREATE INDEX IDX_CAMADA_CUSTO_POSICAO ON CAMADA_CUSTO (PRODUTO_ID, DEPOSITO_ID);
+
+-- O saldo já existente de cada posição forma sua primeira camada, valorada
+-- pelo custo médio atual.
+INSERT INTO CAMADA_CUSTO (PRODUTO_ID, DEPOSITO_ID, QUANTIDADE, RESTANTE, CUSTO_UNITARIO)
+SELECT PRODUTO_ID, DEPOSITO_ID, QUANTIDADE, QUANTIDADE, CUSTO_MEDIO
+FROM ESTOQUE
+WHERE QUANTIDADE > 0
+ORDER BY PRODUTO_ID, DEPOSITO_ID;
@@ -0,0 +1,5 @@
+DROP TABLE IF EXISTS MOV_ESTOQUE_LOTE;
+DROP TABLE IF EXISTS LOTE;
+
+ALTER TABLE PRODUTO
+DROP COLUMN CONTROLA_LOTE;
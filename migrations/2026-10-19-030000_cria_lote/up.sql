@@ -0,0 +1,38 @@
+-- Produtos com controle de lotes devem informar o lote em suas entradas de
+-- estoque.
+ALTER TABLE PRODUTO
+ADD COLUMN CONTROLA_LOTE BOOLEAN NOT NULL DEFAULT FALSE;
+
+-- Lotes de cada posição de estoque, com seus saldos.
+CREATE TABLE LOTE (
+       ID           SERIAL         PRIMARY KEY,
+       PRODUTO_ID   INTEGER        NOT NULL,
+       DEPOSITO_ID  INTEGER        NOT NULL,
+       NUMERO       VARCHAR        NOT NULL,
+       FABRICACAO   DATE,
+       VALIDADE     DATE,
+       QUANTIDADE   NUMERIC(12,3)  NOT NULL DEFAULT 0,
+       CONSTRAINT UN_LOTE_NUMERO UNIQUE (PRODUTO_ID, DEPOSITO_ID, NUMERO),
+       CONSTRAINT FK_LOTE_ESTOQUE
+       FOREIGN KEY (PRODUTO_ID, DEPOSITO_ID)
+       REFERENCES ESTOQUE(PRODUTO_ID, DEPOSITO_ID) ON DELETE CASCADE,
+       CONSTRAINT CK_LOTE_QUANTIDADE CHECK (QUANTIDADE >= 0),
+       CONSTRAINT CK_LOTE_DATAS
+       CHECK (FABRICACAO IS NULL OR VALIDADE IS NULL OR FABRICACAO <= VALIDADE)
+);
+
+CREATE INDEX IDX_LOTE_VALIDADE ON LOTE (VALIDADE);
+
+-- Quantidades movimentadas de cada lote por cada movimentação de estoque.
+CREATE TABLE MOV_ESTOQUE_LOTE (
+       MOV_ESTOQUE_ID  INTEGER        NOT NULL,
+       LOTE_ID         INTEGER        NOT NULL,
+       QUANTIDADE      NUMERIC(12,3)  NOT NULL,
+       PRIMARY KEY (MOV_ESTOQUE_ID, LOTE_ID),
+       CONSTRAINT FK_MOV_ESTOQUE_LOTE_MOV_ESTOQUE
+       FOREIGN KEY (MOV_ESTOQUE_ID) REFERENCES MOV_ESTOQUE(ID),
+       CONSTRAINT FK_MOV_ESTOQUE_LOTE_LOTE
+       FOREIGN KEY (LOTE_ID) REFERENCES LOTE(ID) ON DELETE CASCADE
+);
+
+CREATE INDEX IDX_MOV_ESTOQUE_LOTE_LOTE ON MOV_ESTOQUE_LOTE (LOTE_ID);
@@ -0,0 +1,9 @@
+DROP TABLE IF EXISTS MOV_ESTOQUE_SERIE;
+DROP TABLE IF EXISTS NUMERO_SERIE;
+
+DROP INDEX IF EXISTS IDX_MOV_ESTOQUE_CLIENTE;
+ALTER TABLE MOV_ESTOQUE
+DROP COLUMN CLIENTE_ID;
+
+ALTER TABLE PRODUTO
+DROP COLUMN CONTROLA_SERIE;
@@ -0,0 +1,42 @@
+-- Produtos com controle de números de série devem informar o número de série
+-- de cada unidade movimentada.
+ALTER TABLE PRODUTO
+ADD COLUMN CONTROLA_SERIE BOOLEAN NOT NULL DEFAULT FALSE;
+
+-- Cliente ao qual a movimentação de estoque se refere, quando aplicável (ex:
+-- o cliente de uma venda).
+ALTER TABLE MOV_ESTOQUE
+ADD COLUMN CLIENTE_ID INTEGER,
+ADD CONSTRAINT FK_MOV_ESTOQUE_CLIENTE
+FOREIGN KEY (CLIENTE_ID) REFERENCES CLIENTE(ID);
+
+CREATE INDEX IDX_MOV_ESTOQUE_CLIENTE ON MOV_ESTOQUE (CLIENTE_ID);
+
+-- Números de série de cada produto. Um número de série sem depósito não está
+-- em estoque.
+CREATE TABLE NUMERO_SERIE (
+       ID           SERIAL   PRIMARY KEY,
+       PRODUTO_ID   INTEGER  NOT NULL,
+       NUMERO       VARCHAR  NOT NULL,
+       DEPOSITO_ID  INTEGER,
+       CONSTRAINT UN_NUMERO_SERIE_NUMERO UNIQUE (PRODUTO_ID, NUMERO),
+       CONSTRAINT FK_NUMERO_SERIE_PRODUTO
+       FOREIGN KEY (PRODUTO_ID) REFERENCES PRODUTO(ID) ON DELETE CASCADE,
+       CONSTRAINT FK_NUMERO_SERIE_DEPOSITO
+       FOREIGN KEY (DEPOSITO_ID) REFERENCES DEPOSITO(ID)
+);
+
+CREATE INDEX IDX_NUMERO_SERIE_DEPOSITO ON NUMERO_SERIE (PRODUTO_ID, DEPOSITO_ID);
+
+-- Números de série movimentados por cada movimentação de estoque.
+CREATE TABLE MOV_ESTOQUE_SERIE (
+       MOV_ESTOQUE_ID   INTEGER  NOT NULL,
+       NUMERO_SERIE_ID  INTEGER  NOT NULL,
+       PRIMARY KEY (MOV_ESTOQUE_ID, NUMERO_SERIE_ID),
+       CONSTRAINT FK_MOV_ESTOQUE_SERIE_MOV_ESTOQUE
+       FOREIGN KEY (MOV_ESTOQUE_ID) REFERENCES MOV_ESTOQUE(ID),
+       CONSTRAINT FK_MOV_ESTOQUE_SERIE_NUMERO_SERIE
+       FOREIGN KEY (NUMERO_SERIE_ID) REFERENCES NUMERO_SERIE(ID) ON DELETE CASCADE
+);
+
+CREATE INDEX IDX_MOV_ESTOQUE_SERIE_NUMERO_SERIE ON MOV_ESTOQUE_SERIE (NUMERO_SERIE_ID);
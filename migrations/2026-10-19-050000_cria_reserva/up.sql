@@ -0,0 +1,23 @@
+-- Reservas de estoque de cada posição de estoque, vinculadas a um documento
+-- de referência (ex: um pedido de venda). Reservas ativas e não expiradas
+-- reduzem a quantidade disponível da posição de estoque.
+CREATE TABLE RESERVA (
+       ID              SERIAL         PRIMARY KEY,
+       PRODUTO_ID      INTEGER        NOT NULL,
+       DEPOSITO_ID     INTEGER        NOT NULL,
+       DOCTO           VARCHAR        NOT NULL,
+       QUANTIDADE      NUMERIC(12,3)  NOT NULL,
+       VALIDADE        DATE           NOT NULL,
+       SITUACAO        SMALLINT       NOT NULL DEFAULT 0,
+       DATAHORA        TIMESTAMPTZ    NOT NULL DEFAULT NOW(),
+       MOV_ESTOQUE_ID  INTEGER,
+       CONSTRAINT FK_RESERVA_ESTOQUE
+       FOREIGN KEY (PRODUTO_ID, DEPOSITO_ID)
+       REFERENCES ESTOQUE(PRODUTO_ID, DEPOSITO_ID) ON DELETE CASCADE,
+       CONSTRAINT FK_RESERVA_MOV_ESTOQUE
+       FOREIGN KEY (MOV_ESTOQUE_ID) REFERENCES MOV_ESTOQUE(ID),
+       CONSTRAINT CK_RESERVA_QUANTIDADE CHECK (QUANTIDADE > 0)
+);
+
+CREATE INDEX IDX_RESERVA_POSICAO ON RESERVA (PRODUTO_ID, DEPOSITO_ID, SITUACAO);
+CREATE INDEX IDX_RESERVA_DOCTO ON RESERVA (DOCTO);
@@ -0,0 +1,2 @@
+ALTER TABLE MOV_ESTOQUE
+DROP COLUMN ESTORNO_DE_ID;
@@ -0,0 +1,7 @@
+-- Movimentações de estorno referenciam a movimentação estornada. Cada
+-- movimentação pode ser estornada uma única vez.
+ALTER TABLE MOV_ESTOQUE
+ADD COLUMN ESTORNO_DE_ID INTEGER,
+ADD CONSTRAINT UN_MOV_ESTOQUE_ESTORNO_DE UNIQUE (ESTORNO_DE_ID),
+ADD CONSTRAINT FK_MOV_ESTOQUE_ESTORNO_DE
+FOREIGN KEY (ESTORNO_DE_ID) REFERENCES MOV_ESTOQUE(ID);
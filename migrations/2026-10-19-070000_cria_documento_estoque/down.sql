@@ -0,0 +1,5 @@
+DROP INDEX IF EXISTS IDX_MOV_ESTOQUE_DOCUMENTO_ESTOQUE;
+ALTER TABLE MOV_ESTOQUE
+DROP COLUMN DOCUMENTO_ESTOQUE_ID;
+
+DROP TABLE IF EXISTS DOCUMENTO_ESTOQUE;
@@ -0,0 +1,28 @@
+-- Documentos de estoque (ex: notas fiscais de compra), cujos itens são
+-- registrados como movimentações de estoque em uma única transação. O frete
+-- total do documento é rateado entre os itens por valor (0) ou por peso (1).
+CREATE TABLE DOCUMENTO_ESTOQUE (
+       ID             SERIAL         PRIMARY KEY,
+       DOCTO          VARCHAR        NOT NULL,
+       FORNECEDOR_ID  INTEGER,
+       DATA           DATE           NOT NULL,
+       DEPOSITO_ID    INTEGER        NOT NULL,
+       FRETE          NUMERIC(13,4)  NOT NULL DEFAULT 0,
+       RATEIO_FRETE   SMALLINT       NOT NULL DEFAULT 0,
+       DATAHORA       TIMESTAMPTZ    NOT NULL DEFAULT NOW(),
+       CONSTRAINT FK_DOCUMENTO_ESTOQUE_FORNECEDOR
+       FOREIGN KEY (FORNECEDOR_ID) REFERENCES CLIENTE(ID) ON DELETE SET NULL,
+       CONSTRAINT FK_DOCUMENTO_ESTOQUE_DEPOSITO
+       FOREIGN KEY (DEPOSITO_ID) REFERENCES DEPOSITO(ID),
+       CONSTRAINT CK_DOCUMENTO_ESTOQUE_FRETE CHECK (FRETE >= 0)
+);
+
+CREATE INDEX IDX_DOCUMENTO_ESTOQUE_DOCTO ON DOCUMENTO_ESTOQUE (DOCTO);
+
+-- Documento de estoque ao qual a movimentação pertence, quando aplicável.
+ALTER TABLE MOV_ESTOQUE
+ADD COLUMN DOCUMENTO_ESTOQUE_ID INTEGER,
+ADD CONSTRAINT FK_MOV_ESTOQUE_DOCUMENTO_ESTOQUE
+FOREIGN KEY (DOCUMENTO_ESTOQUE_ID) REFERENCES DOCUMENTO_ESTOQUE(ID);
+
+CREATE INDEX IDX_MOV_ESTOQUE_DOCUMENTO_ESTOQUE ON MOV_ESTOQUE (DOCUMENTO_ESTOQUE_ID);
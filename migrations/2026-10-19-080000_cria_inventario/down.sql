@@ -0,0 +1,7 @@
+DROP INDEX IF EXISTS IDX_MOV_ESTOQUE_INVENTARIO;
+ALTER TABLE MOV_ESTOQUE
+DROP COLUMN INVENTARIO_ID;
+
+DROP TABLE IF EXISTS INVENTARIO_CONTAGEM;
+DROP TABLE IF EXISTS INVENTARIO_ITEM;
+DROP TABLE IF EXISTS INVENTARIO;
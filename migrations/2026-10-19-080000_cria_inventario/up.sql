@@ -0,0 +1,54 @@
+-- Sessões de inventário (contagem física) do estoque de um depósito. Na
+-- abertura, as quantidades esperadas de cada produto são registradas em
+-- INVENTARIO_ITEM; no fechamento, as diferenças contadas geram movimentações
+-- de ajuste.
+CREATE TABLE INVENTARIO (
+       ID           SERIAL       PRIMARY KEY,
+       DEPOSITO_ID  INTEGER      NOT NULL,
+       DESCRICAO    VARCHAR      NOT NULL,
+       SITUACAO     SMALLINT     NOT NULL DEFAULT 0,
+       ABERTURA     TIMESTAMPTZ  NOT NULL DEFAULT NOW(),
+       FECHAMENTO   TIMESTAMPTZ,
+       CONSTRAINT FK_INVENTARIO_DEPOSITO
+       FOREIGN KEY (DEPOSITO_ID) REFERENCES DEPOSITO(ID)
+);
+
+-- Apenas uma sessão de inventário pode estar aberta em cada depósito.
+CREATE UNIQUE INDEX UN_INVENTARIO_ABERTO ON INVENTARIO (DEPOSITO_ID) WHERE SITUACAO = 0;
+
+CREATE TABLE INVENTARIO_ITEM (
+       INVENTARIO_ID  INTEGER        NOT NULL,
+       PRODUTO_ID     INTEGER        NOT NULL,
+       ESPERADO       NUMERIC(12,3)  NOT NULL,
+       PRIMARY KEY (INVENTARIO_ID, PRODUTO_ID),
+       CONSTRAINT FK_INVENTARIO_ITEM_INVENTARIO
+       FOREIGN KEY (INVENTARIO_ID) REFERENCES INVENTARIO(ID) ON DELETE CASCADE,
+       CONSTRAINT FK_INVENTARIO_ITEM_PRODUTO
+       FOREIGN KEY (PRODUTO_ID) REFERENCES PRODUTO(ID)
+);
+
+-- Contagens de cada item do inventário. Um mesmo produto pode ser contado
+-- várias vezes, por um ou mais contadores.
+CREATE TABLE INVENTARIO_CONTAGEM (
+       ID             SERIAL         PRIMARY KEY,
+       INVENTARIO_ID  INTEGER        NOT NULL,
+       PRODUTO_ID     INTEGER        NOT NULL,
+       CONTADOR       VARCHAR        NOT NULL,
+       QUANTIDADE     NUMERIC(12,3)  NOT NULL,
+       DATAHORA       TIMESTAMPTZ    NOT NULL DEFAULT NOW(),
+       CONSTRAINT FK_INVENTARIO_CONTAGEM_ITEM
+       FOREIGN KEY (INVENTARIO_ID, PRODUTO_ID)
+       REFERENCES INVENTARIO_ITEM(INVENTARIO_ID, PRODUTO_ID) ON DELETE CASCADE,
+       CONSTRAINT CK_INVENTARIO_CONTAGEM_QUANTIDADE CHECK (QUANTIDADE >= 0)
+);
+
+CREATE INDEX IDX_INVENTARIO_CONTAGEM_ITEM ON INVENTARIO_CONTAGEM (INVENTARIO_ID, PRODUTO_ID);
+
+-- Sessão de inventário cujo fechamento gerou a movimentação de ajuste, quando
+-- aplicável.
+ALTER TABLE MOV_ESTOQUE
+ADD COLUMN INVENTARIO_ID INTEGER,
+ADD CONSTRAINT FK_MOV_ESTOQUE_INVENTARIO
+FOREIGN KEY (INVENTARIO_ID) REFERENCES INVENTARIO(ID);
+
+CREATE INDEX IDX_MOV_ESTOQUE_INVENTARIO ON MOV_ESTOQUE (INVENTARIO_ID);
@@ -0,0 +1,17 @@
+UPDATE CAMADA_CUSTO
+SET MOV_ESTOQUE_ID = NULL
+WHERE MOV_ESTOQUE_ID IN (SELECT ID FROM MOV_ESTOQUE WHERE INICIO);
+
+DELETE FROM MOV_ESTOQUE_LOTE
+WHERE MOV_ESTOQUE_ID IN (SELECT ID FROM MOV_ESTOQUE WHERE INICIO);
+
+DELETE FROM MOV_ESTOQUE_SERIE
+WHERE MOV_ESTOQUE_ID IN (SELECT ID FROM MOV_ESTOQUE WHERE INICIO);
+
+DELETE FROM ARQUIVO
+WHERE MOV_ESTOQUE_ID IN (SELECT ID FROM MOV_ESTOQUE WHERE INICIO);
+
+DELETE FROM MOV_ESTOQUE WHERE INICIO;
+
+ALTER TABLE MOV_ESTOQUE
+DROP COLUMN INICIO;
@@ -0,0 +1,30 @@
+-- O início de estoque de cada posição passa a ser registrado como uma
+-- movimentação de entrada, para que a posição em qualquer data possa ser
+-- reconstruída a partir das movimentações.
+ALTER TABLE MOV_ESTOQUE
+ADD COLUMN INICIO BOOLEAN NOT NULL DEFAULT FALSE;
+
+-- Cada posição já existente recebe seu início de estoque, com a quantidade que
+-- antecede as movimentações registradas, valorada pelo custo médio atual e
+-- datado da primeira movimentação da posição, caso haja.
+INSERT INTO MOV_ESTOQUE (PRODUTO_ID, DOCTO, QUANTIDADE, PRECO_UNITARIO, PRECO_FRETE,
+                         DATAHORA, DEPOSITO_ID, CUSTO_UNITARIO, INICIO)
+SELECT E.PRODUTO_ID, 'INICIO', E.QUANTIDADE - COALESCE(M.QUANTIDADE, 0), E.PRECOUNITARIO, 0,
+       COALESCE(M.DATAHORA, NOW()), E.DEPOSITO_ID, E.CUSTO_MEDIO, TRUE
+FROM ESTOQUE E
+LEFT JOIN (SELECT PRODUTO_ID, DEPOSITO_ID, SUM(QUANTIDADE) AS QUANTIDADE,
+                  MIN(DATAHORA) AS DATAHORA
+           FROM MOV_ESTOQUE
+           GROUP BY PRODUTO_ID, DEPOSITO_ID) M
+ON M.PRODUTO_ID = E.PRODUTO_ID AND M.DEPOSITO_ID = E.DEPOSITO_ID
+ORDER BY E.PRODUTO_ID, E.DEPOSITO_ID;
+
+-- As camadas de custo criadas sem movimentação (no início de estoque ou a
+-- partir do saldo existente) passam a referenciar o início de estoque.
+UPDATE CAMADA_CUSTO C
+SET MOV_ESTOQUE_ID = M.ID
+FROM MOV_ESTOQUE M
+WHERE C.MOV_ESTOQUE_ID IS NULL
+AND M.INICIO
+AND M.PRODUTO_ID = C.PRODUTO_ID
+AND M.DEPOSITO_ID = C.DEPOSITO_ID;
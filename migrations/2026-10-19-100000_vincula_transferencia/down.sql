@@ -0,0 +1,2 @@
+ALTER TABLE MOV_ESTOQUE
+DROP COLUMN TRANSFERENCIA_DE_ID;
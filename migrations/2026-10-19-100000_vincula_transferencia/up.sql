@@ -0,0 +1,26 @@
+-- A entrada de uma transferência entre depósitos referencia a saída
+-- correspondente, para que ambas possam ser estornadas em conjunto.
+ALTER TABLE MOV_ESTOQUE
+ADD COLUMN TRANSFERENCIA_DE_ID INTEGER,
+ADD CONSTRAINT UN_MOV_ESTOQUE_TRANSFERENCIA_DE UNIQUE (TRANSFERENCIA_DE_ID),
+ADD CONSTRAINT FK_MOV_ESTOQUE_TRANSFERENCIA_DE
+FOREIGN KEY (TRANSFERENCIA_DE_ID) REFERENCES MOV_ESTOQUE(ID);
+
+-- As transferências já registradas são vinculadas a partir de suas
+-- movimentações: a saída e a entrada possuem o mesmo produto, documento, data
+-- e hora, quantidades inversas e depósitos diferentes, e são registradas em
+-- sequência.
+UPDATE MOV_ESTOQUE E
+SET TRANSFERENCIA_DE_ID = S.ID
+FROM MOV_ESTOQUE S
+WHERE S.ID = E.ID - 1
+AND E.QUANTIDADE > 0
+AND S.QUANTIDADE = -E.QUANTIDADE
+AND S.PRODUTO_ID = E.PRODUTO_ID
+AND S.DOCTO = E.DOCTO
+AND S.DATAHORA = E.DATAHORA
+AND S.DEPOSITO_ID <> E.DEPOSITO_ID
+AND NOT S.INICIO AND NOT E.INICIO
+AND S.ESTORNO_DE_ID IS NULL AND E.ESTORNO_DE_ID IS NULL
+AND S.DOCUMENTO_ESTOQUE_ID IS NULL AND E.DOCUMENTO_ESTOQUE_ID IS NULL
+AND S.INVENTARIO_ID IS NULL AND E.INVENTARIO_ID IS NULL;
@@ -14,32 +14,57 @@
 // You should have received a copy of the GNU General Public License
 // along with this program.  If not, see <https://www.gnu.org/licenses/>.
 
-extern crate minerva;
-extern crate diesel;
-
-use minerva::model::schema::produto;
-use minerva::model::produto::{ NovoProduto, Produto };
-use diesel::prelude::*;
-
+use dotenv::dotenv;
+use minerva::bo;
+use minerva::controller::{produtos, unidades};
 use minerva::inpututils::*;
+use minerva::model::produto::NovoProduto;
+
+/// Extrai a mensagem de um erro em JSON retornado pelas regras de negócio.
+fn mensagem(erro: &str) -> String {
+    serde_json::from_str::<serde_json::Value>(erro)
+        .ok()
+        .and_then(|v| v["mensagem"].as_str().map(String::from))
+        .unwrap_or_else(|| erro.to_owned())
+}
 
 fn main() {
-    let pool = minerva::db::cria_pool_conexoes();
-    let conn = pool.get().unwrap();
+    dotenv().ok();
+    dotenv::from_filename(".env.local").ok();
+
+    let pool = bo::db::cria_pool_conexoes();
+    let conexao = pool.get().unwrap();
     let mut novoproduto = NovoProduto::new();
 
-    prompt("Descricao: ");
+    prompt("Descrição: ");
     novoproduto.descricao = get_input();
     prompt("Unid. saída: ");
     novoproduto.unidsaida = get_input().to_uppercase();
-    prompt("Qtd. estoque: ");
-    novoproduto.qtdestoque = get_numeric();
-    prompt("Preço de venda: ");
-    novoproduto.precovenda = get_numeric();
-
-    let _p: Produto =
-        diesel::insert_into(produto::table)
-        .values(&novoproduto)
-        .get_result(&conn)
-        .expect("Erro ao salvar produto");
+    prompt("NCM: ");
+    novoproduto.ncm = get_input();
+    prompt("CEST (opcional): ");
+    novoproduto.cest = get_input_opt();
+    prompt("Origem (0-8, padrão 0): ");
+    novoproduto.origem = match get_input_opt() {
+        None => 0,
+        Some(origem) => origem.parse().unwrap_or(-1),
+    };
+
+    if let Err(e) = bo::produtos::valida_produto(&novoproduto) {
+        eprintln!("{}", mensagem(&e));
+        std::process::exit(1);
+    }
+
+    if unidades::get_unidade(&conexao, &novoproduto.unidsaida).is_none() {
+        eprintln!("Unidade de medida {} não cadastrada", novoproduto.unidsaida);
+        std::process::exit(1);
+    }
+
+    match produtos::registra_produto(&conexao, novoproduto) {
+        Ok(id) => println!("Produto cadastrado com id {}.", id),
+        Err(msg) => {
+            eprintln!("{}", msg);
+            std::process::exit(1);
+        }
+    }
 }
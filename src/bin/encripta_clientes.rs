@@ -0,0 +1,42 @@
+// bin/encripta_clientes.rs -- Uma parte de Minerva.rs
+// Copyright (C) 2021 Lucas S. Vieira
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with this program.  If not, see <https://www.gnu.org/licenses/>.
+
+use dotenv::dotenv;
+use minerva::bo;
+use minerva::controller::clientes;
+
+fn main() {
+    dotenv().ok();
+    dotenv::from_filename(".env.local").ok();
+
+    let pool = bo::db::cria_pool_conexoes();
+    let chaveiro = bo::cripto::cria_chaveiro();
+    let conexao = pool.get().unwrap();
+
+    println!(
+        "Encriptando dados de clientes com a chave de versão {}...",
+        chaveiro.versao_atual()
+    );
+    match clientes::encripta_registros(&conexao, &chaveiro) {
+        Ok((num_cl, num_end)) => {
+            println!("Encriptados {} clientes e {} endereços.", num_cl, num_end)
+        }
+        Err(msg) => {
+            eprintln!("{}", msg);
+            std::process::exit(1);
+        }
+    }
+}
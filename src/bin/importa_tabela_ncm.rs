@@ -0,0 +1,59 @@
+// bin/importa_tabela_ncm.rs -- Uma parte de Minerva.rs
+// Copyright (C) 2021 Lucas S. Vieira
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with this program.  If not, see <https://www.gnu.org/licenses/>.
+
+use minerva::bo;
+
+/// Arquivo da tabela NCM embutida, relativo à raiz do projeto.
+const TABELA_NCM: &str = "src/bo/tabela_ncm.csv";
+
+fn main() {
+    let mut args = std::env::args().skip(1);
+    let origem = match args.next() {
+        Some(origem) => origem,
+        None => {
+            eprintln!("Uso: importa-tabela-ncm <tabela.json> [destino.csv]");
+            std::process::exit(1);
+        }
+    };
+    let destino = args.next().unwrap_or_else(|| String::from(TABELA_NCM));
+
+    let tabela = match std::fs::read_to_string(&origem) {
+        Ok(tabela) => tabela,
+        Err(e) => {
+            eprintln!("Erro ao ler {}: {}", origem, e);
+            std::process::exit(1);
+        }
+    };
+
+    let hoje = chrono::Local::today().naive_local();
+    let csv = match bo::produtos::converte_tabela_ncm(&tabela, hoje) {
+        Ok(csv) => csv,
+        Err(msg) => {
+            eprintln!("{}", msg);
+            std::process::exit(1);
+        }
+    };
+
+    if let Err(e) = std::fs::write(&destino, &csv) {
+        eprintln!("Erro ao escrever {}: {}", destino, e);
+        std::process::exit(1);
+    }
+    println!(
+        "Tabela NCM com {} códigos gravada em {}. Recompile o projeto.",
+        csv.lines().count() - 1,
+        destino
+    );
+}
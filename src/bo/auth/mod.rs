@@ -97,6 +97,9 @@ pub mod jwt;
 
 use super::redis::RedisConnection;
 use super::redis::RedisPool;
+use crate::controller::usuarios;
+use crate::model::usuario::PerfilUsuario;
+use diesel::PgConnection;
 use r2d2_redis::redis::Commands;
 use rocket::http::Status;
 use rocket::request::{FromRequest, Outcome, Request};
@@ -118,6 +121,37 @@ use rocket::request::{FromRequest, Outcome, Request};
 /// ```
 pub struct AuthKey<'r>(&'r str);
 
+impl<'r> AuthKey<'r> {
+    /// Retorna o login do usuário a quem pertence a chave de autenticação.
+    ///
+    /// Como a chave já terá sido validada ao ser construída como _request
+    /// guard_, o login será recuperado diretamente do payload do JWT.
+    pub fn login(&self) -> String {
+        jwt::decodifica_jwt(&extrai_payload(self.0))
+            .map(|claims| claims.sub)
+            .unwrap_or_default()
+    }
+}
+
+/// Retorna o perfil do usuário a quem pertence a chave de autenticação.
+///
+/// Caso o usuário não seja encontrado no banco de dados, o mesmo será
+/// considerado como um usuário comum.
+pub fn perfil_usuario(conexao: &PgConnection, auth: &AuthKey<'_>) -> PerfilUsuario {
+    usuarios::encontra_usuario(conexao, &auth.login())
+        .map(|u| u.perfil)
+        .unwrap_or_default()
+}
+
+/// Verifica se o usuário a quem pertence a chave de autenticação possui um
+/// perfil privilegiado.
+///
+/// Caso o usuário não seja encontrado no banco de dados, o mesmo será
+/// considerado como não-privilegiado.
+pub fn usuario_privilegiado(conexao: &PgConnection, auth: &AuthKey<'_>) -> bool {
+    perfil_usuario(conexao, auth).is_privilegiado()
+}
+
 /// Representa um erro de autenticação.
 #[derive(Debug)]
 pub enum AuthError {
@@ -0,0 +1,325 @@
+// bo/categorias.rs -- Uma parte de Minerva.rs
+// Copyright (C) 2021 Lucas S. Vieira
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with this program.  If not, see <https://www.gnu.org/licenses/>.
+
+//! Este módulo contém ferramentas para reforçar regras de negócio relacionadas
+//! à árvore de categorias de produtos.
+//!
+//! As ferramentas deste módulo operam sobre a lista completa de categorias
+//! cadastradas, posto que a quantidade de categorias tende a ser pequena.
+
+use super::produtos::arredonda;
+use crate::model::categoria::{Categoria, CategoriaArvore, NovaCategoria, TotalCategoria};
+use bigdecimal::{BigDecimal, Zero};
+use serde_json::json;
+use std::collections::HashMap;
+
+/// Quantidade máxima de níveis da árvore de categorias (grupo, subgrupo e
+/// família).
+pub const NIVEL_MAXIMO: usize = 3;
+
+/// Retorna o nível de uma categoria na árvore de categorias. Grupos possuem
+/// nível 1, subgrupos possuem nível 2, e assim por diante.
+pub fn nivel(categorias: &[Categoria], id: i32) -> usize {
+    let mut nivel = 0;
+    let mut atual = Some(id);
+    while let Some(id) = atual {
+        nivel += 1;
+        atual = categorias
+            .iter()
+            .find(|c| c.id == id)
+            .and_then(|c| c.categoria_pai_id);
+        if nivel > categorias.len() {
+            break;
+        }
+    }
+    nivel
+}
+
+/// Retorna os ids de uma categoria e de todas as suas subcategorias, em
+/// qualquer nível.
+pub fn descendentes(categorias: &[Categoria], id: i32) -> Vec<i32> {
+    let mut ids = vec![id];
+    let mut i = 0;
+    while i < ids.len() {
+        let pai = ids[i];
+        ids.extend(
+            categorias
+                .iter()
+                .filter(|c| c.categoria_pai_id == Some(pai))
+                .map(|c| c.id),
+        );
+        i += 1;
+    }
+    ids
+}
+
+/// Retorna a altura da subárvore de uma categoria, isto é, a quantidade de
+/// níveis formados pela categoria e suas subcategorias. Uma categoria sem
+/// subcategorias possui altura 1.
+fn altura(categorias: &[Categoria], id: i32) -> usize {
+    1 + categorias
+        .iter()
+        .filter(|c| c.categoria_pai_id == Some(id))
+        .map(|c| altura(categorias, c.id))
+        .max()
+        .unwrap_or(0)
+}
+
+/// Monta a árvore de categorias a partir da lista de categorias cadastradas.
+///
+/// Serão retornados os grupos (categorias sem categoria pai), cada um com
+/// suas respectivas subcategorias. As categorias de um mesmo nível são
+/// ordenadas pela descrição.
+pub fn monta_arvore(categorias: &[Categoria]) -> Vec<CategoriaArvore> {
+    fn filhas(categorias: &[Categoria], pai: Option<i32>) -> Vec<CategoriaArvore> {
+        let mut filhas: Vec<CategoriaArvore> = categorias
+            .iter()
+            .filter(|c| c.categoria_pai_id == pai)
+            .map(|c| CategoriaArvore {
+                id: c.id,
+                descricao: c.descricao.clone(),
+                subcategorias: filhas(categorias, Some(c.id)),
+            })
+            .collect();
+        filhas.sort_by(|a, b| a.descricao.cmp(&b.descricao));
+        filhas
+    }
+    filhas(categorias, None)
+}
+
+/// Realiza validação dos dados recebidos para cadastro ou alteração de uma
+/// categoria.
+///
+/// A descrição não pode estar em branco, e a categoria pai, quando informada,
+/// deve existir. Ao alterar uma categoria (caso `id` seja informado), a
+/// categoria pai não pode ser a própria categoria ou uma de suas
+/// subcategorias. A árvore resultante não pode ultrapassar
+/// [`NIVEL_MAXIMO`] níveis.
+pub fn valida_categoria(
+    categorias: &[Categoria],
+    id: Option<i32>,
+    dados: &NovaCategoria,
+) -> Result<(), String> {
+    if dados.descricao.trim().is_empty() {
+        return Err(json!({
+            "mensagem": "A descrição da categoria não pode estar em branco"
+        })
+        .to_string());
+    }
+
+    let pai = match dados.categoria_pai_id {
+        None => return Ok(()),
+        Some(pai) => pai,
+    };
+
+    if !categorias.iter().any(|c| c.id == pai) {
+        return Err(json!({
+            "mensagem": format!("Categoria pai {} não encontrada", pai)
+        })
+        .to_string());
+    }
+
+    if let Some(id) = id {
+        if descendentes(categorias, id).contains(&pai) {
+            return Err(json!({
+                "mensagem": "A categoria pai não pode ser a própria categoria \
+                             ou uma de suas subcategorias"
+            })
+            .to_string());
+        }
+    }
+
+    let altura_categoria = id.map(|id| altura(categorias, id)).unwrap_or(1);
+    if nivel(categorias, pai) + altura_categoria > NIVEL_MAXIMO {
+        return Err(json!({
+            "mensagem": format!(
+                "A árvore de categorias não pode possuir mais de {} níveis",
+                NIVEL_MAXIMO
+            )
+        })
+        .to_string());
+    }
+
+    Ok(())
+}
+
+#[test]
+fn arvore_de_categorias() {
+    let categoria = |id, descricao: &str, categoria_pai_id| Categoria {
+        id,
+        descricao: descricao.to_owned(),
+        categoria_pai_id,
+    };
+    let categorias = vec![
+        categoria(1, "Bebidas", None),
+        categoria(2, "Alcoólicas", Some(1)),
+        categoria(3, "Cervejas", Some(2)),
+        categoria(4, "Não alcoólicas", Some(1)),
+        categoria(5, "Mercearia", None),
+    ];
+
+    assert_eq!(nivel(&categorias, 1), 1);
+    assert_eq!(nivel(&categorias, 2), 2);
+    assert_eq!(nivel(&categorias, 3), 3);
+
+    assert_eq!(altura(&categorias, 1), 3);
+    assert_eq!(altura(&categorias, 4), 1);
+
+    let mut ids = descendentes(&categorias, 1);
+    ids.sort_unstable();
+    assert_eq!(ids, vec![1, 2, 3, 4]);
+    assert_eq!(descendentes(&categorias, 3), vec![3]);
+    assert_eq!(descendentes(&categorias, 5), vec![5]);
+
+    let arvore = monta_arvore(&categorias);
+    assert_eq!(arvore.len(), 2);
+    assert_eq!(arvore[0].descricao, "Bebidas");
+    assert_eq!(arvore[0].subcategorias[0].descricao, "Alcoólicas");
+    assert_eq!(arvore[0].subcategorias[0].subcategorias[0].id, 3);
+    assert!(arvore[1].subcategorias.is_empty());
+
+    let dados = |descricao: &str, categoria_pai_id| NovaCategoria {
+        descricao: descricao.to_owned(),
+        categoria_pai_id,
+    };
+
+    // Cadastro
+    assert!(valida_categoria(&categorias, None, &dados("Vinhos", Some(2))).is_ok());
+    assert!(valida_categoria(&categorias, None, &dados("Limpeza", None)).is_ok());
+    assert!(valida_categoria(&categorias, None, &dados(" ", None)).is_err());
+    assert!(valida_categoria(&categorias, None, &dados("Pilsen", Some(3))).is_err());
+    assert!(valida_categoria(&categorias, None, &dados("Outros", Some(99))).is_err());
+
+    // Alteração
+    assert!(valida_categoria(&categorias, Some(4), &dados("Sucos", Some(2))).is_ok());
+    assert!(valida_categoria(&categorias, Some(2), &dados("Alcoólicas", None)).is_ok());
+    assert!(valida_categoria(&categorias, Some(1), &dados("Bebidas", Some(3))).is_err());
+    assert!(valida_categoria(&categorias, Some(1), &dados("Bebidas", Some(1))).is_err());
+    assert!(valida_categoria(&categorias, Some(2), &dados("Alcoólicas", Some(5))).is_ok());
+    assert!(valida_categoria(&categorias, Some(1), &dados("Bebidas", Some(5))).is_err());
+    assert!(valida_categoria(&categorias, Some(4), &dados("Sucos", Some(3))).is_err());
+}
+
+/// Totaliza o estoque dos produtos por categoria.
+///
+/// O estoque de cada produto deve ser informado como uma tuple contendo a
+/// categoria do produto, a quantidade em estoque e o valor do estoque segundo
+/// o método de valoração do produto (veja
+/// [`valoriza_produto`][`super::estoque::valoriza_produto`]). Os totais
+/// de cada categoria incluem os produtos de todas as suas subcategorias. Os
+/// produtos sem categoria serão totalizados em um item sem id de categoria,
+/// ao final da lista, caso existam. Os valores são arredondados para quatro
+/// casas decimais.
+pub fn totaliza_categorias(
+    categorias: &[Categoria],
+    produtos: &[(Option<i32>, BigDecimal, BigDecimal)],
+) -> Vec<TotalCategoria> {
+    let novo_total = |categoria_id, descricao: &str| TotalCategoria {
+        categoria_id,
+        descricao: descricao.to_owned(),
+        produtos: 0,
+        quantidade: BigDecimal::zero(),
+        valor: BigDecimal::zero(),
+    };
+
+    let mut totais: HashMap<i32, TotalCategoria> = categorias
+        .iter()
+        .map(|c| (c.id, novo_total(Some(c.id), &c.descricao)))
+        .collect();
+    let mut sem_categoria = novo_total(None, "Sem categoria");
+
+    for (categoria_id, quantidade, valor) in produtos {
+        let soma = |total: &mut TotalCategoria| {
+            total.produtos += 1;
+            total.quantidade += quantidade;
+            total.valor += valor;
+        };
+
+        match categoria_id {
+            None => soma(&mut sem_categoria),
+            Some(id) => {
+                // Soma a posição na categoria do produto e em seus ancestrais.
+                let mut atual = Some(*id);
+                let mut niveis = 0;
+                while let Some(id) = atual {
+                    if let Some(total) = totais.get_mut(&id) {
+                        soma(total);
+                    }
+                    atual = categorias
+                        .iter()
+                        .find(|c| c.id == id)
+                        .and_then(|c| c.categoria_pai_id);
+                    niveis += 1;
+                    if niveis > categorias.len() {
+                        break;
+                    }
+                }
+            }
+        }
+    }
+
+    let mut resultado: Vec<TotalCategoria> = categorias
+        .iter()
+        .filter_map(|c| totais.remove(&c.id))
+        .collect();
+    for total in resultado
+        .iter_mut()
+        .chain(std::iter::once(&mut sem_categoria))
+    {
+        total.valor = arredonda(&total.valor, 4);
+    }
+    if sem_categoria.produtos > 0 {
+        resultado.push(sem_categoria);
+    }
+    resultado
+}
+
+#[test]
+fn totais_por_categoria() {
+    use std::str::FromStr;
+    let valor = |v: &str| BigDecimal::from_str(v).unwrap();
+    let categoria = |id, descricao: &str, categoria_pai_id| Categoria {
+        id,
+        descricao: descricao.to_owned(),
+        categoria_pai_id,
+    };
+    let categorias = vec![
+        categoria(1, "Bebidas", None),
+        categoria(2, "Alcoólicas", Some(1)),
+        categoria(3, "Cervejas", Some(2)),
+        categoria(4, "Não alcoólicas", Some(1)),
+        categoria(5, "Mercearia", None),
+    ];
+    let produtos = vec![
+        (Some(3), valor("10"), valor("25")),
+        (Some(2), valor("5"), valor("50")),
+        (Some(4), valor("1.5"), valor("6")),
+        (None, valor("2"), valor("2")),
+    ];
+
+    let totais = totaliza_categorias(&categorias, &produtos);
+    let total = |id: Option<i32>| totais.iter().find(|t| t.categoria_id == id).unwrap();
+
+    assert_eq!(totais.len(), 6);
+    assert_eq!(total(Some(1)).produtos, 3);
+    assert_eq!(total(Some(1)).quantidade, valor("16.5"));
+    assert_eq!(total(Some(1)).valor, valor("81"));
+    assert_eq!(total(Some(2)).produtos, 2);
+    assert_eq!(total(Some(2)).valor, valor("75"));
+    assert_eq!(total(Some(3)).valor, valor("25"));
+    assert_eq!(total(Some(5)).produtos, 0);
+    assert_eq!(total(None).valor, valor("2"));
+}
@@ -17,9 +17,16 @@
 //! Este módulo contém ferramentas para reforçar regras de negócio relacionadas
 //! a validação de transações envolvendo dados de clientes.
 
-use crate::model::cliente::ClienteRecv;
+use crate::model::cliente::{
+    ClienteRecv, ErroImportacao, LiberacaoCreditoRecv, LimiteCreditoRecv, SituacaoCredito,
+};
+use crate::model::endereco::EnderecoRecv;
+use crate::model::titulo::{PagamentoTituloRecv, Titulo, TituloRecv};
+use bigdecimal::{BigDecimal, Signed, Zero};
+use chrono::NaiveDate;
 use regex::Regex;
 use serde_json::json;
+use std::collections::{BTreeMap, HashMap};
 
 const ESTADOS: [&str; 27] = [
     "AC", "AL", "AM", "AP", "BA", "CE", "DF", "ES", "GO", "MA", "MG", "MS", "MT", "PA", "PB", "PE",
@@ -212,6 +219,57 @@ fn validacao_de_cpf() {
     assert!(!valida_cpf("88.216.800/0001-95"));
 }
 
+/// Mascara um documento (CPF ou CNPJ) para exibição a usuários que não possuam
+/// permissão para vê-lo por completo.
+///
+/// Em um CPF, serão ocultados os três primeiros dígitos e os dígitos
+/// verificadores (ex: `***.453.510-**`). Em um CNPJ, serão ocultados os dois
+/// primeiros dígitos e os dígitos verificadores (ex: `**.578.468/0001-**`).
+/// Caso o documento não seja reconhecido como CPF ou CNPJ, todos os seus
+/// dígitos serão ocultados.
+pub fn mascara_docto(docto: &str) -> String {
+    let num_digitos = docto.chars().filter(|c| c.is_ascii_digit()).count();
+    let ocultos_inicio = if Regex::new(CPF_REGEX).unwrap().is_match(docto) {
+        3
+    } else if Regex::new(CNPJ_REGEX).unwrap().is_match(docto) {
+        2
+    } else {
+        num_digitos
+    };
+
+    let mut posicao = 0;
+    docto
+        .chars()
+        .map(|c| {
+            if !c.is_ascii_digit() {
+                return c;
+            }
+            posicao += 1;
+            if posicao <= ocultos_inicio || posicao > num_digitos - 2 {
+                '*'
+            } else {
+                c
+            }
+        })
+        .collect()
+}
+
+#[test]
+fn mascaramento_de_documentos() {
+    // CPFs
+    assert_eq!(mascara_docto("641.453.510-96"), "***.453.510-**");
+    assert_eq!(mascara_docto("499.225.140-44"), "***.225.140-**");
+
+    // CNPJs
+    assert_eq!(mascara_docto("17.578.468/0001-60"), "**.578.468/0001-**");
+    assert_eq!(mascara_docto("26.440.024/0001-55"), "**.440.024/0001-**");
+
+    // Documentos em formatos desconhecidos
+    assert_eq!(mascara_docto("64145351096"), "***********");
+    assert_eq!(mascara_docto("ANONIMIZADO-7"), "ANONIMIZADO-*");
+    assert_eq!(mascara_docto(""), "");
+}
+
 /// Realiza validação dos dados recebidos para cadastro de um cliente.
 ///
 /// As validações compreendem os dados de CPF ou CNPJ e a existência da Unidade
@@ -242,3 +300,558 @@ pub fn valida_dados(dados: &ClienteRecv) -> Result<(), String> {
 
     Ok(())
 }
+
+/// Extrai a mensagem de um erro de validação em JSON, como os retornados por
+/// [`valida_dados`].
+fn mensagem_erro(erro: String) -> String {
+    serde_json::from_str::<serde_json::Value>(&erro)
+        .ok()
+        .and_then(|v| v["mensagem"].as_str().map(String::from))
+        .unwrap_or(erro)
+}
+
+/// Campos de endereço aceitos em um arquivo de importação de clientes.
+const CAMPOS_ENDERECO: [&str; 6] = [
+    "logradouro",
+    "numero",
+    "complemento",
+    "bairro",
+    "uf",
+    "cidade",
+];
+
+/// Posições das colunas de um arquivo de importação de clientes.
+struct ColunasImportacao {
+    nome: usize,
+    pj: usize,
+    docto: usize,
+    /// Posições dos campos de cada endereço, indexadas pelo número do
+    /// endereço e pelo nome do campo.
+    enderecos: BTreeMap<usize, HashMap<String, usize>>,
+}
+
+/// Interpreta o cabeçalho de um arquivo de importação de clientes.
+fn le_cabecalho_importacao(cabecalho: &csv::StringRecord) -> Result<ColunasImportacao, String> {
+    let mut colunas = HashMap::new();
+    let mut enderecos: BTreeMap<usize, HashMap<String, usize>> = BTreeMap::new();
+
+    for (posicao, coluna) in cabecalho.iter().enumerate() {
+        let coluna = coluna.to_lowercase();
+        let duplicada = if ["nome", "pj", "docto"].contains(&coluna.as_str()) {
+            colunas.insert(coluna.clone(), posicao).is_some()
+        } else {
+            let (campo, numero) = coluna
+                .rsplit_once('_')
+                .filter(|(campo, _)| CAMPOS_ENDERECO.contains(campo))
+                .and_then(|(campo, numero)| Some((campo, numero.parse::<usize>().ok()?)))
+                .filter(|(_, numero)| *numero > 0)
+                .ok_or_else(|| format!("Coluna desconhecida: {}", coluna))?;
+            enderecos
+                .entry(numero)
+                .or_default()
+                .insert(campo.to_owned(), posicao)
+                .is_some()
+        };
+        if duplicada {
+            return Err(format!("Coluna duplicada: {}", coluna));
+        }
+    }
+
+    for (numero, campos) in &enderecos {
+        for campo in CAMPOS_ENDERECO.iter().filter(|c| **c != "complemento") {
+            if !campos.contains_key(*campo) {
+                return Err(format!("Coluna obrigatória ausente: {}_{}", campo, numero));
+            }
+        }
+    }
+
+    let obrigatoria = |nome: &str| {
+        colunas
+            .get(nome)
+            .copied()
+            .ok_or_else(|| format!("Coluna obrigatória ausente: {}", nome))
+    };
+
+    Ok(ColunasImportacao {
+        nome: obrigatoria("nome")?,
+        pj: obrigatoria("pj")?,
+        docto: obrigatoria("docto")?,
+        enderecos,
+    })
+}
+
+/// Interpreta e valida uma linha de um arquivo de importação de clientes.
+fn le_linha_importacao(
+    colunas: &ColunasImportacao,
+    registro: &csv::StringRecord,
+) -> Result<ClienteRecv, String> {
+    let campo = |posicao: usize| registro.get(posicao).unwrap_or("").to_owned();
+
+    let pj = match campo(colunas.pj).to_lowercase().as_str() {
+        "true" | "s" | "sim" | "1" => true,
+        "false" | "n" | "não" | "nao" | "0" => false,
+        outro => return Err(format!("Valor inválido para pj: {}", outro)),
+    };
+
+    let mut enderecos = vec![];
+    for (numero, campos) in &colunas.enderecos {
+        let valor = |c: &str| campos.get(c).map(|p| campo(*p)).unwrap_or_default();
+
+        // Endereços completamente vazios são ignorados, de forma que os
+        // clientes possam possuir quantidades diferentes de endereços
+        if CAMPOS_ENDERECO.iter().all(|c| valor(c).is_empty()) {
+            continue;
+        }
+
+        for c in CAMPOS_ENDERECO.iter().filter(|c| **c != "complemento") {
+            if valor(c).is_empty() {
+                return Err(format!("Campo obrigatório não informado: {}_{}", c, numero));
+            }
+        }
+
+        let complemento = valor("complemento");
+        enderecos.push(EnderecoRecv {
+            logradouro: valor("logradouro"),
+            numero: valor("numero"),
+            complemento: if complemento.is_empty() {
+                None
+            } else {
+                Some(complemento)
+            },
+            bairro: valor("bairro"),
+            uf: valor("uf"),
+            cidade: valor("cidade"),
+        });
+    }
+
+    let dados = ClienteRecv {
+        nome: campo(colunas.nome),
+        pj,
+        docto: campo(colunas.docto),
+        enderecos,
+    };
+
+    if dados.nome.is_empty() {
+        return Err(String::from("Campo obrigatório não informado: nome"));
+    }
+
+    valida_dados(&dados).map_err(mensagem_erro)?;
+    Ok(dados)
+}
+
+/// Interpreta e valida um arquivo CSV para importação de clientes.
+///
+/// O arquivo deve possuir um cabeçalho, e cada linha subsequente representa um
+/// cliente. As colunas `nome`, `pj` e `docto` são obrigatórias. Os endereços
+/// são informados através de grupos de colunas numerados, como `logradouro_1`,
+/// `numero_1`, `complemento_1`, `bairro_1`, `uf_1` e `cidade_1`, podendo haver
+/// quantos grupos forem necessários. Apenas a coluna de complemento é opcional
+/// em cada grupo. Endereços com todos os campos vazios serão ignorados.
+///
+/// O arquivo poderá ser separado por vírgulas ou por ponto-e-vírgula. O
+/// separador será identificado a partir do cabeçalho.
+///
+/// Cada linha será validada através de [`valida_dados`], e documentos
+/// repetidos no mesmo arquivo também serão considerados como erro. Será
+/// retornada uma tuple contendo, respectivamente, os dados de clientes válidos
+/// junto ao número da linha de onde foram lidos, e os erros encontrados em
+/// cada linha inválida.
+pub fn valida_importacao(texto: &str) -> (Vec<(usize, ClienteRecv)>, Vec<ErroImportacao>) {
+    let texto = texto.trim_start_matches('\u{feff}');
+    let primeira_linha = texto.lines().next().unwrap_or("");
+    let delimitador = if primeira_linha.matches(';').count() > primeira_linha.matches(',').count() {
+        b';'
+    } else {
+        b','
+    };
+
+    let mut leitor = csv::ReaderBuilder::new()
+        .delimiter(delimitador)
+        .trim(csv::Trim::All)
+        .from_reader(texto.as_bytes());
+
+    let colunas = match leitor
+        .headers()
+        .map_err(|_| String::from("Cabeçalho mal formatado"))
+        .and_then(le_cabecalho_importacao)
+    {
+        Ok(colunas) => colunas,
+        Err(mensagem) => return (vec![], vec![ErroImportacao { linha: 1, mensagem }]),
+    };
+
+    let mut clientes = vec![];
+    let mut erros = vec![];
+    let mut documentos: HashMap<String, usize> = HashMap::new();
+
+    for registro in leitor.records() {
+        let (linha, resultado) = match registro {
+            Ok(registro) => (
+                registro.position().map(|p| p.line() as usize).unwrap_or(0),
+                le_linha_importacao(&colunas, &registro),
+            ),
+            Err(e) => {
+                let linha = e.position().map(|p| p.line() as usize).unwrap_or(0);
+                let mensagem = match e.kind() {
+                    csv::ErrorKind::UnequalLengths {
+                        expected_len, len, ..
+                    } => format!(
+                        "Quantidade de colunas incorreta: esperadas {}, encontradas {}",
+                        expected_len, len
+                    ),
+                    _ => String::from("Linha mal formatada"),
+                };
+                (linha, Err(mensagem))
+            }
+        };
+
+        let resultado = resultado.and_then(|dados| {
+            let digitos: String = dados.docto.chars().filter(|c| c.is_ascii_digit()).collect();
+            match documentos.get(&digitos) {
+                Some(anterior) => Err(format!(
+                    "Documento {} repetido no arquivo (linha {})",
+                    dados.docto, anterior
+                )),
+                None => {
+                    documentos.insert(digitos, linha);
+                    Ok(dados)
+                }
+            }
+        });
+
+        match resultado {
+            Ok(dados) => clientes.push((linha, dados)),
+            Err(mensagem) => erros.push(ErroImportacao { linha, mensagem }),
+        }
+    }
+
+    if clientes.is_empty() && erros.is_empty() {
+        erros.push(ErroImportacao {
+            linha: 1,
+            mensagem: String::from("Arquivo não possui clientes"),
+        });
+    }
+
+    (clientes, erros)
+}
+
+#[test]
+fn importacao_de_clientes() {
+    let texto = "nome;pj;docto;logradouro_1;numero_1;complemento_1;bairro_1;uf_1;cidade_1;\
+                 logradouro_2;numero_2;bairro_2;uf_2;cidade_2\n\
+                 Fulano;N;641.453.510-96;Rua A;1;;Centro;MG;Cidade;;;;;\n\
+                 Empresa;S;17.578.468/0001-60;Rua B;2;Sala 1;Centro;MG;Cidade;Rua C;3;Bairro;SP;Outra\n\
+                 Ciclano;N;641.453.510-97;;;;;;;;;;;\n\
+                 Beltrano;N;641.453.510-96;;;;;;;;;;;\n\
+                 Sicrano;N;499.225.140-44;Rua D;4;;Centro;XX;Cidade;;;;;\n\
+                 Fulana;talvez;499.225.140-44;;;;;;;;;;;\n\
+                 Fulana;N;499.225.140-44;Rua E;;;Centro;MG;Cidade;;;;;\n\
+                 Curta;N\n";
+    let (clientes, erros) = valida_importacao(texto);
+
+    assert_eq!(clientes.len(), 2);
+    assert_eq!(clientes[0].0, 2);
+    assert_eq!(clientes[0].1.enderecos.len(), 1);
+    assert_eq!(clientes[0].1.enderecos[0].complemento, None);
+    assert_eq!(clientes[1].0, 3);
+    assert!(clientes[1].1.pj);
+    assert_eq!(clientes[1].1.enderecos.len(), 2);
+    assert_eq!(
+        clientes[1].1.enderecos[0].complemento,
+        Some(String::from("Sala 1"))
+    );
+
+    let linhas: Vec<usize> = erros.iter().map(|e| e.linha).collect();
+    assert_eq!(linhas, vec![4, 5, 6, 7, 8, 9]);
+    assert_eq!(erros[0].mensagem, "CPF inválido");
+    assert_eq!(
+        erros[1].mensagem,
+        "Documento 641.453.510-96 repetido no arquivo (linha 2)"
+    );
+    assert_eq!(erros[2].mensagem, "UF desconhecido: XX");
+    assert_eq!(erros[3].mensagem, "Valor inválido para pj: talvez");
+    assert_eq!(
+        erros[4].mensagem,
+        "Campo obrigatório não informado: numero_1"
+    );
+
+    // Erros de cabeçalho
+    let (_, erros) = valida_importacao("nome,docto\nFulano,641.453.510-96\n");
+    assert_eq!(erros[0].mensagem, "Coluna obrigatória ausente: pj");
+    let (_, erros) = valida_importacao("nome,pj,docto,uf_1\n");
+    assert_eq!(
+        erros[0].mensagem,
+        "Coluna obrigatória ausente: logradouro_1"
+    );
+    let (_, erros) = valida_importacao("nome,pj,docto,telefone\n");
+    assert_eq!(erros[0].mensagem, "Coluna desconhecida: telefone");
+    let (_, erros) = valida_importacao("nome,pj,docto\n");
+    assert_eq!(erros[0].mensagem, "Arquivo não possui clientes");
+}
+
+/// Avalia se a situação de crédito de um cliente permite uma operação a prazo
+/// do valor informado, na data informada.
+///
+/// A operação não será permitida caso o cliente possua títulos vencidos em
+/// aberto, ou caso o saldo devedor do cliente, somado ao valor da operação,
+/// exceda seu limite de crédito. Caso o crédito do cliente tenha sido liberado
+/// manualmente até uma data igual ou posterior à data informada, a operação
+/// sempre será permitida.
+///
+/// Esta função não considera se o cliente está ativo ou bloqueado. Para isso,
+/// veja [`verifica_credito`].
+pub fn avalia_credito(
+    situacao: &SituacaoCredito,
+    valor: &BigDecimal,
+    data: NaiveDate,
+) -> Result<(), String> {
+    if situacao.liberado_ate.is_some_and(|d| d >= data) {
+        return Ok(());
+    }
+
+    if situacao.valor_vencido.is_positive() {
+        return Err(json!({
+            "mensagem": format!(
+                "Cliente possui títulos vencidos em aberto: {}",
+                situacao.valor_vencido
+            )
+        })
+        .to_string());
+    }
+
+    if situacao.saldo_devedor.clone() + valor.clone() > situacao.limite_credito {
+        return Err(json!({
+            "mensagem": format!(
+                "Limite de crédito excedido. Crédito disponível: {}",
+                situacao.credito_disponivel
+            )
+        })
+        .to_string());
+    }
+
+    Ok(())
+}
+
+#[test]
+fn regras_de_credito() {
+    use std::str::FromStr;
+    let valor = |v: &str| BigDecimal::from_str(v).unwrap();
+    let data = NaiveDate::from_ymd(2021, 9, 7);
+    let situacao = SituacaoCredito {
+        cliente_id: 1,
+        limite_credito: valor("1000.00"),
+        saldo_devedor: valor("600.00"),
+        credito_disponivel: valor("400.00"),
+        valor_vencido: valor("0.00"),
+        ativo: true,
+        bloqueado: false,
+        liberado_ate: None,
+    };
+
+    // Operações dentro do limite
+    assert!(avalia_credito(&situacao, &valor("0"), data).is_ok());
+    assert!(avalia_credito(&situacao, &valor("400.00"), data).is_ok());
+
+    // Operações além do limite
+    assert!(avalia_credito(&situacao, &valor("400.01"), data).is_err());
+
+    // Saldo devedor além do limite
+    let excedido = SituacaoCredito {
+        saldo_devedor: valor("1000.01"),
+        credito_disponivel: valor("0"),
+        ..situacao.clone()
+    };
+    assert!(avalia_credito(&excedido, &valor("0"), data).is_err());
+
+    // Títulos vencidos
+    let vencido = SituacaoCredito {
+        valor_vencido: valor("10.00"),
+        ..situacao.clone()
+    };
+    assert!(avalia_credito(&vencido, &valor("0"), data).is_err());
+
+    // Liberação manual vale até a data informada, inclusive
+    let liberado = SituacaoCredito {
+        liberado_ate: Some(data),
+        ..vencido
+    };
+    assert!(avalia_credito(&liberado, &valor("5000.00"), data).is_ok());
+    assert!(avalia_credito(&liberado, &valor("0"), data.succ()).is_err());
+}
+
+/// Avalia se um cliente deve ser bloqueado automaticamente pelas regras de
+/// crédito, a partir de sua situação de crédito na data informada.
+///
+/// Caso o cliente não esteja bloqueado, mas possua títulos vencidos em aberto
+/// ou um saldo devedor que exceda seu limite de crédito, será retornado o
+/// motivo do bloqueio. Clientes com crédito liberado manualmente não serão
+/// bloqueados até o fim da liberação; veja [`avalia_credito`].
+///
+/// O desbloqueio de um cliente nunca é automático, e deve ser feito através de
+/// uma liberação de crédito por um usuário privilegiado.
+pub fn motivo_bloqueio(situacao: &SituacaoCredito, data: NaiveDate) -> Option<String> {
+    if situacao.bloqueado {
+        return None;
+    }
+    avalia_credito(situacao, &BigDecimal::zero(), data)
+        .err()
+        .map(mensagem_erro)
+}
+
+#[test]
+fn bloqueio_automatico() {
+    use std::str::FromStr;
+    let valor = |v: &str| BigDecimal::from_str(v).unwrap();
+    let data = NaiveDate::from_ymd(2021, 9, 7);
+    let situacao = SituacaoCredito {
+        cliente_id: 1,
+        limite_credito: valor("1000.00"),
+        saldo_devedor: valor("600.00"),
+        credito_disponivel: valor("400.00"),
+        valor_vencido: valor("0.00"),
+        ativo: true,
+        bloqueado: false,
+        liberado_ate: None,
+    };
+    assert_eq!(motivo_bloqueio(&situacao, data), None);
+
+    let vencido = SituacaoCredito {
+        valor_vencido: valor("10.00"),
+        ..situacao.clone()
+    };
+    assert_eq!(
+        motivo_bloqueio(&vencido, data),
+        Some(String::from(
+            "Cliente possui títulos vencidos em aberto: 10.00"
+        ))
+    );
+
+    // Clientes já bloqueados ou com crédito liberado não são bloqueados
+    let bloqueado = SituacaoCredito {
+        bloqueado: true,
+        ..vencido.clone()
+    };
+    assert_eq!(motivo_bloqueio(&bloqueado, data), None);
+    let liberado = SituacaoCredito {
+        liberado_ate: Some(data),
+        ..vencido
+    };
+    assert_eq!(motivo_bloqueio(&liberado, data), None);
+}
+
+/// Verifica se um cliente pode realizar uma operação a prazo do valor
+/// informado, na data informada, a partir de sua situação de crédito.
+///
+/// A operação não será permitida caso o cliente esteja inativo ou bloqueado,
+/// ou caso a situação de crédito do cliente não a comporte; veja
+/// [`avalia_credito`]. Em caso de erro, será retornada uma mensagem de erro em
+/// JSON.
+///
+/// As regras de bloqueio automático devem ser aplicadas à situação de crédito
+/// antes da verificação; veja
+/// [`verifica_credito`][`crate::controller::clientes::verifica_credito`].
+pub fn verifica_credito(
+    situacao: &SituacaoCredito,
+    valor: &BigDecimal,
+    data: NaiveDate,
+) -> Result<(), String> {
+    if !situacao.ativo {
+        return Err(json!({
+            "mensagem": "Cliente inativo"
+        })
+        .to_string());
+    }
+
+    if situacao.bloqueado {
+        return Err(json!({
+            "mensagem": "Cliente bloqueado"
+        })
+        .to_string());
+    }
+
+    avalia_credito(situacao, valor, data)
+}
+
+/// Realiza validação de um novo limite de crédito para um cliente.
+///
+/// O limite de crédito não pode ser negativo.
+pub fn valida_limite_credito(dados: &LimiteCreditoRecv) -> Result<(), String> {
+    if dados.limite_credito.is_negative() {
+        return Err(json!({
+            "mensagem": "Limite de crédito não pode ser negativo"
+        })
+        .to_string());
+    }
+    Ok(())
+}
+
+/// Realiza validação de uma liberação manual de crédito de um cliente.
+///
+/// O motivo da liberação deve ser informado, e a validade da liberação não
+/// pode ser anterior à data informada.
+pub fn valida_liberacao_credito(
+    dados: &LiberacaoCreditoRecv,
+    data: NaiveDate,
+) -> Result<(), String> {
+    if dados.motivo.trim().is_empty() {
+        return Err(json!({
+            "mensagem": "Necessário informar o motivo da liberação de crédito"
+        })
+        .to_string());
+    }
+
+    if dados.validade < data {
+        return Err(json!({
+            "mensagem": "Validade da liberação de crédito não pode estar no passado"
+        })
+        .to_string());
+    }
+
+    Ok(())
+}
+
+/// Realiza validação dos dados recebidos para cadastro de um título.
+///
+/// O documento do título deve ser informado, e o valor do título deve ser
+/// maior que zero.
+pub fn valida_titulo(dados: &TituloRecv) -> Result<(), String> {
+    if dados.docto.trim().is_empty() {
+        return Err(json!({
+            "mensagem": "Necessário informar o documento do título"
+        })
+        .to_string());
+    }
+
+    if !dados.valor.is_positive() {
+        return Err(json!({
+            "mensagem": "Valor do título deve ser maior que zero"
+        })
+        .to_string());
+    }
+
+    Ok(())
+}
+
+/// Realiza validação de um pagamento de um título.
+///
+/// O valor pago deve ser maior que zero, e não pode ser maior que o valor em
+/// aberto do título.
+pub fn valida_pagamento(titulo: &Titulo, dados: &PagamentoTituloRecv) -> Result<(), String> {
+    if !dados.valor.is_positive() {
+        return Err(json!({
+            "mensagem": "Valor do pagamento deve ser maior que zero"
+        })
+        .to_string());
+    }
+
+    if dados.valor > titulo.valor_aberto() {
+        return Err(json!({
+            "mensagem": format!(
+                "Valor do pagamento excede o valor em aberto do título: {}",
+                titulo.valor_aberto()
+            )
+        })
+        .to_string());
+    }
+
+    Ok(())
+}
@@ -0,0 +1,243 @@
+// bo/cripto.rs -- Uma parte de Minerva.rs
+// Copyright (C) 2021 Lucas S. Vieira
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with this program.  If not, see <https://www.gnu.org/licenses/>.
+
+//! Ferramentas para encriptação de dados pessoais armazenados no banco de
+//! dados.
+//!
+//! Os dados sensíveis (como CPF/CNPJ e endereços de clientes) são encriptados
+//! pela própria aplicação antes de serem armazenados. Cada valor encriptado é
+//! armazenado junto à versão da chave utilizada, o que possibilita a rotação de
+//! chaves sem perda dos dados antigos.
+//!
+//! Como um valor encriptado nunca será igual a outro, mesmo que ambos possuam
+//! o mesmo texto original, buscas exatas são feitas através de um hash com
+//! chave, determinístico, armazenado em uma coluna à parte.
+
+use sodiumoxide::crypto::auth::hmacsha256;
+use sodiumoxide::crypto::secretbox;
+use std::collections::HashMap;
+use std::env;
+
+/// Representa o conjunto de chaves de encriptação da aplicação.
+///
+/// O chaveiro possui todas as versões conhecidas de chaves de encriptação, de
+/// forma que dados encriptados com chaves antigas ainda possam ser lidos. Novos
+/// dados serão sempre encriptados com a chave de maior versão.
+pub struct Chaveiro {
+    chaves: HashMap<i16, secretbox::Key>,
+    versao_atual: i16,
+    chave_hash: hmacsha256::Key,
+}
+
+/// Decodifica uma chave de 32 bytes escrita em hexadecimal.
+fn decodifica_chave(hex: &str) -> Result<[u8; 32], String> {
+    let bytes = sodiumoxide::hex::decode(hex.trim())
+        .map_err(|_| String::from("Chave de encriptação não está em hexadecimal"))?;
+    if bytes.len() != 32 {
+        return Err(String::from("Chave de encriptação deve possuir 32 bytes"));
+    }
+    let mut chave = [0u8; 32];
+    chave.copy_from_slice(&bytes);
+    Ok(chave)
+}
+
+/// Cria o chaveiro da aplicação a partir das variáveis de ambiente.
+/// Deve ser chamada uma vez ao início da aplicação.
+///
+/// As chaves de encriptação devem ser definidas através da variável de ambiente
+/// `CHAVES_CRIPTOGRAFIA`, como uma lista de pares de versão e chave separados
+/// por vírgulas, onde cada chave possui 32 bytes escritos em hexadecimal:
+///
+/// `CHAVES_CRIPTOGRAFIA=1:<chave em hexadecimal>,2:<chave em hexadecimal>`
+///
+/// A chave para cálculo do hash de busca deve ser definida através da variável
+/// de ambiente `CHAVE_HASH`, também com 32 bytes em hexadecimal. Esta chave
+/// não deve ser modificada, já que os hashes armazenados dependem da mesma.
+///
+/// Ambas as variáveis também podem ser definidas em um arquivo `.env`.
+///
+/// # Panics
+///
+/// A função entrará em pânico se alguma das variáveis não for definida no
+/// ambiente, ou se alguma chave estiver em um formato inválido.
+pub fn cria_chaveiro() -> Chaveiro {
+    let chaves = env::var("CHAVES_CRIPTOGRAFIA")
+        .expect("Necessário definir as chaves de encriptação em CHAVES_CRIPTOGRAFIA");
+    let chave_hash =
+        env::var("CHAVE_HASH").expect("Necessário definir a chave de hash em CHAVE_HASH");
+
+    let chaves = chaves
+        .split(',')
+        .map(|par| {
+            let (versao, chave) = par
+                .split_once(':')
+                .expect("Chaves de encriptação devem estar no formato versao:chave");
+            (
+                versao
+                    .trim()
+                    .parse::<i16>()
+                    .expect("Versão de chave de encriptação inválida"),
+                decodifica_chave(chave).unwrap(),
+            )
+        })
+        .collect();
+
+    Chaveiro::new(chaves, decodifica_chave(&chave_hash).unwrap())
+}
+
+impl Chaveiro {
+    /// Cria um chaveiro a partir de uma lista de chaves de encriptação,
+    /// associadas às suas respectivas versões, e de uma chave de hash.
+    ///
+    /// # Panics
+    ///
+    /// A função entrará em pânico caso nenhuma chave de encriptação seja
+    /// informada.
+    pub fn new(chaves: Vec<(i16, [u8; 32])>, chave_hash: [u8; 32]) -> Self {
+        sodiumoxide::init().unwrap();
+        let versao_atual = chaves
+            .iter()
+            .map(|(versao, _)| *versao)
+            .max()
+            .expect("Necessário informar ao menos uma chave de encriptação");
+        Self {
+            chaves: chaves
+                .into_iter()
+                .map(|(versao, chave)| (versao, secretbox::Key(chave)))
+                .collect(),
+            versao_atual,
+            chave_hash: hmacsha256::Key(chave_hash),
+        }
+    }
+
+    /// Retorna a versão da chave utilizada para encriptar novos dados.
+    pub fn versao_atual(&self) -> i16 {
+        self.versao_atual
+    }
+
+    /// Encripta um texto com a chave atual.
+    ///
+    /// Será retornado o texto encriptado, codificado em Base64, junto com um
+    /// nonce aleatório, e a versão da chave utilizada, que deverá ser
+    /// armazenada junto ao valor.
+    pub fn encripta(&self, texto: &str) -> (String, i16) {
+        use sodiumoxide::base64::{encode, Variant};
+        let chave = &self.chaves[&self.versao_atual];
+        let nonce = secretbox::gen_nonce();
+        let mut dados = nonce.0.to_vec();
+        dados.extend(secretbox::seal(texto.as_bytes(), &nonce, chave));
+        (encode(dados, Variant::Original), self.versao_atual)
+    }
+
+    /// Decripta um texto encriptado com a chave de versão informada.
+    ///
+    /// Caso a versão da chave seja desconhecida, ou caso o texto não possa ser
+    /// decriptado, será retornada uma mensagem de erro.
+    pub fn decripta(&self, texto: &str, versao: i16) -> Result<String, String> {
+        use sodiumoxide::base64::{decode, Variant};
+        let chave = self
+            .chaves
+            .get(&versao)
+            .ok_or_else(|| format!("Chave de encriptação desconhecida: versão {}", versao))?;
+        let dados = decode(texto, Variant::Original)
+            .map_err(|_| String::from("Texto encriptado não está em Base64"))?;
+        if dados.len() < secretbox::NONCEBYTES {
+            return Err(String::from("Texto encriptado inválido"));
+        }
+        let (nonce, cifra) = dados.split_at(secretbox::NONCEBYTES);
+        let nonce = secretbox::Nonce::from_slice(nonce).unwrap();
+        let aberto = secretbox::open(cifra, &nonce, chave)
+            .map_err(|_| String::from("Falha ao decriptar texto"))?;
+        String::from_utf8(aberto).map_err(|_| String::from("Texto decriptado inválido"))
+    }
+
+    /// Decripta um texto opcionalmente encriptado.
+    ///
+    /// Caso nenhuma versão de chave seja informada, assume-se que o texto
+    /// esteja armazenado em texto-plano, como ocorre com registros anteriores
+    /// à encriptação.
+    pub fn decripta_opcional(&self, texto: &str, versao: Option<i16>) -> Result<String, String> {
+        match versao {
+            Some(versao) => self.decripta(texto, versao),
+            None => Ok(texto.to_owned()),
+        }
+    }
+
+    /// Calcula o hash determinístico de um documento (CPF ou CNPJ), para busca
+    /// exata.
+    ///
+    /// Apenas os dígitos do documento serão considerados, de forma que a
+    /// formatação do mesmo não interfira no resultado. O hash será retornado
+    /// em hexadecimal.
+    pub fn hash_docto(&self, docto: &str) -> String {
+        let digitos: String = docto.chars().filter(|c| c.is_ascii_digit()).collect();
+        let tag = hmacsha256::authenticate(digitos.as_bytes(), &self.chave_hash);
+        sodiumoxide::hex::encode(tag.0)
+    }
+}
+
+#[test]
+fn encriptacao_de_dados() {
+    let chaveiro = Chaveiro::new(vec![(1, [1u8; 32]), (2, [2u8; 32])], [3u8; 32]);
+    let antigo = Chaveiro::new(vec![(1, [1u8; 32])], [3u8; 32]);
+
+    // Dados são encriptados com a chave mais recente
+    let (cifra, versao) = chaveiro.encripta("641.453.510-96");
+    assert_eq!(versao, 2);
+    assert_ne!(cifra, "641.453.510-96");
+    assert_eq!(chaveiro.decripta(&cifra, versao).unwrap(), "641.453.510-96");
+
+    // O mesmo texto gera cifras diferentes
+    assert_ne!(chaveiro.encripta("641.453.510-96").0, cifra);
+
+    // Dados encriptados com chaves antigas continuam legíveis
+    let (cifra, versao) = antigo.encripta("Rua dos Tolos");
+    assert_eq!(versao, 1);
+    assert_eq!(chaveiro.decripta(&cifra, versao).unwrap(), "Rua dos Tolos");
+
+    // Chaves desconhecidas ou incorretas não decriptam
+    assert!(chaveiro.decripta(&cifra, 3).is_err());
+    assert!(chaveiro.decripta(&cifra, 2).is_err());
+    assert!(chaveiro.decripta("texto", 1).is_err());
+
+    // Textos sem versão são considerados texto-plano
+    assert_eq!(
+        chaveiro.decripta_opcional("Rua dos Tolos", None).unwrap(),
+        "Rua dos Tolos"
+    );
+}
+
+#[test]
+fn hash_de_documentos() {
+    let chaveiro = Chaveiro::new(vec![(1, [1u8; 32])], [3u8; 32]);
+    let outro = Chaveiro::new(vec![(1, [1u8; 32])], [4u8; 32]);
+
+    // O hash é determinístico e ignora a formatação
+    assert_eq!(
+        chaveiro.hash_docto("641.453.510-96"),
+        chaveiro.hash_docto("64145351096")
+    );
+    assert_ne!(
+        chaveiro.hash_docto("641.453.510-96"),
+        chaveiro.hash_docto("499.225.140-44")
+    );
+
+    // O hash depende da chave
+    assert_ne!(
+        chaveiro.hash_docto("641.453.510-96"),
+        outro.hash_docto("641.453.510-96")
+    );
+}
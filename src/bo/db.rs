@@ -99,6 +99,7 @@ pub fn executa_migrations(pool: &ConexaoPool) {
 /// - Nome: Admin
 /// - Login: `admin`
 /// - Senha: `admin`
+/// - Perfil: Administrador
 ///
 /// O usuário será inserido no banco de dados com a senha tendo sido
 /// apropriadamente encriptada, por mais que a senha seja um dado público,
@@ -110,7 +111,7 @@ pub fn executa_migrations(pool: &ConexaoPool) {
 pub fn garante_usuario_inicial(pool: &ConexaoPool) {
     use crate::controller::usuarios;
     use crate::model::schema::usuario;
-    use crate::model::usuario::{NovoUsuario, UsuarioRecv};
+    use crate::model::usuario::{NovoUsuario, PerfilUsuario, UsuarioRecv};
     use diesel::prelude::*;
 
     let conexao = pool.get().unwrap();
@@ -121,6 +122,7 @@ pub fn garante_usuario_inicial(pool: &ConexaoPool) {
             nome: "Admin",
             email: None,
             senha: "admin",
+            perfil: Some(PerfilUsuario::Administrador),
         });
         let _ = diesel::insert_into(usuario::table)
             .values(&novo_admin)
@@ -0,0 +1,1381 @@
+// bo/estoque.rs -- Uma parte de Minerva.rs
+// Copyright (C) 2021 Lucas S. Vieira
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with this program.  If not, see <https://www.gnu.org/licenses/>.
+
+//! Este módulo contém ferramentas para reforçar regras de negócio relacionadas
+//! a posições e movimentações de estoque.
+
+use super::produtos::arredonda;
+use crate::model::deposito::NovoDeposito;
+use crate::model::documento::{DocumentoEstoqueRecv, RateioFrete};
+use crate::model::estoque::{
+    CamadaCusto, Estoque, EstoqueRepr, EstornoRecv, MovEstoque, TransferenciaRecv,
+    ValorizacaoEstoque, ValorizacaoProduto,
+};
+use crate::model::inventario::{
+    ContagemContador, ContagemInventario, ContagemRecv, DiferencaInventario, InventarioRecv,
+    ItemInventario,
+};
+use crate::model::lote::{Lote, LoteRecv};
+use crate::model::produto::{MetodoCusto, Produto, ProdutoComponente};
+use crate::model::reserva::ReservaRecv;
+use bigdecimal::{BigDecimal, Signed, Zero};
+use chrono::NaiveDate;
+use serde_json::json;
+
+/// Consolida um conjunto de posições de estoque, informadas como pares de
+/// quantidade e preço unitário.
+///
+/// A quantidade consolidada será a soma das quantidades. O preço unitário
+/// consolidado será a média dos preços unitários, ponderada pelas quantidades
+/// e arredondada para quatro casas decimais. Caso as posições não possuam
+/// quantidade, será usada a média simples dos preços unitários.
+pub fn consolida_posicoes(posicoes: &[(&BigDecimal, &BigDecimal)]) -> (BigDecimal, BigDecimal) {
+    let quantidade: BigDecimal = posicoes.iter().map(|(q, _)| *q).sum();
+    let preco_unitario = if posicoes.is_empty() {
+        BigDecimal::zero()
+    } else if quantidade.is_zero() {
+        let soma: BigDecimal = posicoes.iter().map(|(_, p)| *p).sum();
+        soma / BigDecimal::from(posicoes.len() as i64)
+    } else {
+        let valor: BigDecimal = posicoes.iter().map(|(q, p)| *q * *p).sum();
+        valor / &quantidade
+    };
+    (quantidade, arredonda(&preco_unitario, 4))
+}
+
+#[test]
+fn consolidacao_de_posicoes() {
+    use std::str::FromStr;
+    let valor = |v: &str| BigDecimal::from_str(v).unwrap();
+    let (dez, vinte, trinta, quarenta) = (valor("10"), valor("20"), valor("30"), valor("40"));
+    assert_eq!(
+        consolida_posicoes(&[(&dez, &vinte), (&trinta, &quarenta)]),
+        (valor("40"), valor("35"))
+    );
+    let zero = valor("0");
+    assert_eq!(
+        consolida_posicoes(&[(&zero, &vinte), (&zero, &quarenta)]),
+        (valor("0"), valor("30"))
+    );
+    assert_eq!(consolida_posicoes(&[]), (valor("0"), valor("0")));
+}
+
+/// Calcula o custo unitário de uma entrada de estoque.
+///
+/// O custo unitário da entrada corresponde ao preço unitário acrescido do
+/// preço do frete rateado pela quantidade da entrada, arredondado para quatro
+/// casas decimais. A quantidade informada deve ser positiva.
+pub fn custo_entrada(
+    preco_unitario: &BigDecimal,
+    preco_frete: &BigDecimal,
+    quantidade: &BigDecimal,
+) -> BigDecimal {
+    arredonda(&(preco_unitario + preco_frete / quantidade), 4)
+}
+
+#[test]
+fn custo_de_entrada() {
+    use std::str::FromStr;
+    let valor = |v: &str| BigDecimal::from_str(v).unwrap();
+    assert_eq!(
+        custo_entrada(&valor("1.70"), &valor("15"), &valor("200")),
+        valor("1.775")
+    );
+    assert_eq!(
+        custo_entrada(&valor("2"), &valor("0"), &valor("3")),
+        valor("2")
+    );
+    assert_eq!(
+        custo_entrada(&valor("1"), &valor("1"), &valor("3")),
+        valor("1.3333")
+    );
+}
+
+/// Calcula o consumo das camadas de custo de uma posição de estoque por uma
+/// saída de `quantidade` unidades, informada como um valor positivo.
+///
+/// As camadas devem ser informadas na ordem em que serão consumidas, da mais
+/// antiga para a mais recente, e são consumidas até que a quantidade da saída
+/// seja atingida. Será retornado um Vec com o id de cada camada consumida e a
+/// quantidade consumida da mesma, junto do custo unitário da saída por PEPS,
+/// arredondado para quatro casas decimais. Caso as camadas não sejam
+/// suficientes para a saída, a quantidade faltante será valorada pelo custo
+/// médio informado.
+pub fn consome_camadas(
+    camadas: &[CamadaCusto],
+    quantidade: &BigDecimal,
+    custo_medio: &BigDecimal,
+) -> (Vec<(i32, BigDecimal)>, BigDecimal) {
+    if !quantidade.is_positive() {
+        return (vec![], custo_medio.clone());
+    }
+
+    let mut faltante = quantidade.clone();
+    let mut custo_total = BigDecimal::zero();
+    let mut consumos = Vec::new();
+    for camada in camadas {
+        if !faltante.is_positive() {
+            break;
+        }
+        if !camada.restante.is_positive() {
+            continue;
+        }
+        let consumo = if camada.restante < faltante {
+            camada.restante.clone()
+        } else {
+            faltante.clone()
+        };
+        custo_total += &consumo * &camada.custo_unitario;
+        faltante -= &consumo;
+        consumos.push((camada.id, consumo));
+    }
+    custo_total += faltante * custo_medio;
+    (consumos, arredonda(&(custo_total / quantidade), 4))
+}
+
+#[test]
+fn consumo_de_camadas() {
+    use std::str::FromStr;
+    let valor = |v: &str| BigDecimal::from_str(v).unwrap();
+    let camada = |id, restante: &str, custo: &str| CamadaCusto {
+        id,
+        produto_id: 1,
+        deposito_id: 1,
+        mov_estoque_id: None,
+        quantidade: valor("10"),
+        restante: valor(restante),
+        custo_unitario: valor(custo),
+        datahora: chrono::offset::Utc::now(),
+    };
+    let camadas = vec![
+        camada(1, "0", "1"),
+        camada(2, "4", "2"),
+        camada(3, "10", "3"),
+    ];
+
+    let (consumos, custo) = consome_camadas(&camadas, &valor("6"), &valor("9"));
+    assert_eq!(consumos, vec![(2, valor("4")), (3, valor("2"))]);
+    assert_eq!(custo, valor("2.3333"));
+
+    let (consumos, custo) = consome_camadas(&camadas, &valor("4"), &valor("9"));
+    assert_eq!(consumos, vec![(2, valor("4"))]);
+    assert_eq!(custo, valor("2"));
+
+    let (consumos, custo) = consome_camadas(&camadas, &valor("16"), &valor("9"));
+    assert_eq!(consumos, vec![(2, valor("4")), (3, valor("10"))]);
+    assert_eq!(custo, valor("3.5"));
+
+    assert_eq!(
+        consome_camadas(&camadas, &valor("0"), &valor("9")),
+        (vec![], valor("9"))
+    );
+}
+
+/// Calcula a valoração do estoque de um produto, a partir de suas posições de
+/// estoque e das camadas de custo destas posições.
+///
+/// O valor pelo custo médio corresponde à soma das quantidades das posições
+/// multiplicadas por seus custos médios, e o valor por PEPS, à soma das
+/// quantidades restantes das camadas multiplicadas por seus custos unitários.
+/// Os valores são arredondados para duas casas decimais.
+pub fn valoriza_produto(
+    produto: &Produto,
+    posicoes: &[Estoque],
+    camadas: &[CamadaCusto],
+) -> ValorizacaoProduto {
+    let quantidade: BigDecimal = posicoes.iter().map(|p| &p.quantidade).sum();
+    let valor_medio: BigDecimal = posicoes
+        .iter()
+        .map(|p| &p.quantidade * &p.custo_medio)
+        .sum();
+    let valor_peps: BigDecimal = camadas
+        .iter()
+        .map(|c| &c.restante * &c.custo_unitario)
+        .sum();
+    let valor_medio = arredonda(&valor_medio, 2);
+    let valor_peps = arredonda(&valor_peps, 2);
+    let valor = match produto.metodo_custo {
+        MetodoCusto::Medio => valor_medio.clone(),
+        MetodoCusto::Peps => valor_peps.clone(),
+    };
+    ValorizacaoProduto {
+        produto_id: produto.id,
+        descricao: produto.descricao.clone(),
+        metodo_custo: produto.metodo_custo,
+        quantidade,
+        valor_medio,
+        valor_peps,
+        valor,
+    }
+}
+
+/// Totaliza a valoração do estoque de um conjunto de produtos, em um depósito
+/// ou em todos os depósitos.
+pub fn valoriza_estoque(
+    deposito_id: Option<i32>,
+    produtos: Vec<ValorizacaoProduto>,
+) -> ValorizacaoEstoque {
+    ValorizacaoEstoque {
+        deposito_id,
+        valor_medio: produtos.iter().map(|p| &p.valor_medio).sum(),
+        valor_peps: produtos.iter().map(|p| &p.valor_peps).sum(),
+        valor: produtos.iter().map(|p| &p.valor).sum(),
+        produtos,
+    }
+}
+
+#[test]
+fn valoracao_de_estoque() {
+    use std::str::FromStr;
+    let valor = |v: &str| BigDecimal::from_str(v).unwrap();
+    let produto = |id, metodo_custo| Produto {
+        id,
+        descricao: format!("Produto {}", id),
+        unidsaida: String::from("UN"),
+        ativo: true,
+        ncm: Some(String::from("04090000")),
+        cest: None,
+        origem: 0,
+        categoria_id: None,
+        produto_pai_id: None,
+        metodo_custo,
+        controla_lote: false,
+        controla_serie: false,
+    };
+    let posicao = |deposito_id, quantidade: &str, custo_medio: &str| Estoque {
+        produto_id: 1,
+        quantidade: valor(quantidade),
+        precounitario: valor("10"),
+        deposito_id,
+        custo_medio: valor(custo_medio),
+    };
+    let camada = |restante: &str, custo: &str| CamadaCusto {
+        id: 1,
+        produto_id: 1,
+        deposito_id: 1,
+        mov_estoque_id: None,
+        quantidade: valor(restante),
+        restante: valor(restante),
+        custo_unitario: valor(custo),
+        datahora: chrono::offset::Utc::now(),
+    };
+    let posicoes = vec![posicao(1, "10", "2.5"), posicao(2, "5", "3")];
+    let camadas = vec![camada("4", "2"), camada("6", "3"), camada("5", "3.001")];
+
+    let medio = valoriza_produto(&produto(1, MetodoCusto::Medio), &posicoes, &camadas);
+    assert_eq!(medio.quantidade, valor("15"));
+    assert_eq!(medio.valor_medio, valor("40"));
+    assert_eq!(medio.valor_peps, valor("41.01"));
+    assert_eq!(medio.valor, valor("40"));
+
+    let peps = valoriza_produto(&produto(2, MetodoCusto::Peps), &posicoes, &camadas);
+    assert_eq!(peps.valor, valor("41.01"));
+
+    let total = valoriza_estoque(None, vec![medio, peps]);
+    assert_eq!(total.valor_medio, valor("80"));
+    assert_eq!(total.valor_peps, valor("82.02"));
+    assert_eq!(total.valor, valor("81.01"));
+    assert_eq!(total.produtos.len(), 2);
+
+    let vazio = valoriza_estoque(Some(1), vec![]);
+    assert_eq!(vazio.valor, valor("0"));
+}
+
+/// Consolida as posições de estoque das variantes de um produto em uma posição
+/// de estoque do produto pai.
+///
+/// A quantidade, o preço unitário e o custo médio consolidados são calculados
+/// como descrito em [`consolida_posicoes`]. As quantidades reservada e
+/// disponível consolidadas são as somas das respectivas quantidades das
+/// variantes.
+pub fn consolida_variantes(pai: &Produto, variantes: Vec<EstoqueRepr>) -> EstoqueRepr {
+    let pares: Vec<_> = variantes
+        .iter()
+        .map(|v| (&v.quantidade, &v.preco_unitario))
+        .collect();
+    let (quantidade, preco_unitario) = consolida_posicoes(&pares);
+    let pares: Vec<_> = variantes
+        .iter()
+        .map(|v| (&v.quantidade, &v.custo_medio))
+        .collect();
+    let (_, custo_medio) = consolida_posicoes(&pares);
+    let reservado = variantes.iter().map(|v| &v.reservado).sum();
+    let disponivel = variantes.iter().map(|v| &v.disponivel).sum();
+
+    EstoqueRepr {
+        id: pai.id,
+        descricao: pai.descricao.clone(),
+        unidsaida: pai.unidsaida.clone(),
+        quantidade,
+        reservado,
+        disponivel,
+        preco_unitario,
+        custo_medio,
+        produto_pai_id: pai.produto_pai_id,
+        deposito_id: None,
+        depositos: None,
+        variantes: Some(variantes),
+    }
+}
+
+#[test]
+fn consolidacao_de_variantes() {
+    use std::str::FromStr;
+    let valor = |v: &str| BigDecimal::from_str(v).unwrap();
+    let pai = Produto {
+        id: 1,
+        descricao: String::from("Camiseta"),
+        unidsaida: String::from("UN"),
+        ativo: true,
+        ncm: Some(String::from("61091000")),
+        cest: None,
+        origem: 0,
+        categoria_id: None,
+        produto_pai_id: None,
+        metodo_custo: crate::model::produto::MetodoCusto::Medio,
+        controla_lote: false,
+        controla_serie: false,
+    };
+    let variante = |id, quantidade: &str, preco: &str| EstoqueRepr {
+        id,
+        descricao: format!("Camiseta {}", id),
+        unidsaida: String::from("UN"),
+        quantidade: valor(quantidade),
+        reservado: valor("1"),
+        disponivel: valor(quantidade) - valor("1"),
+        preco_unitario: valor(preco),
+        custo_medio: valor(preco) / BigDecimal::from(2),
+        produto_pai_id: Some(1),
+        deposito_id: None,
+        depositos: None,
+        variantes: None,
+    };
+
+    let consolidado =
+        consolida_variantes(&pai, vec![variante(2, "10", "20"), variante(3, "30", "40")]);
+    assert_eq!(consolidado.id, 1);
+    assert_eq!(consolidado.quantidade, valor("40"));
+    assert_eq!(consolidado.preco_unitario, valor("35"));
+    assert_eq!(consolidado.custo_medio, valor("17.5"));
+    assert_eq!(consolidado.reservado, valor("2"));
+    assert_eq!(consolidado.disponivel, valor("38"));
+    assert_eq!(consolidado.variantes.as_ref().unwrap().len(), 2);
+
+    let zerado = consolida_variantes(&pai, vec![variante(2, "0", "20"), variante(3, "0", "25")]);
+    assert_eq!(zerado.quantidade, valor("0"));
+    assert_eq!(zerado.preco_unitario, valor("22.5"));
+
+    let dizima = consolida_variantes(&pai, vec![variante(2, "1", "1"), variante(3, "2", "1.5")]);
+    assert_eq!(dizima.preco_unitario, valor("1.3333"));
+
+    let vazio = consolida_variantes(&pai, vec![]);
+    assert_eq!(vazio.quantidade, valor("0"));
+    assert!(vazio.variantes.unwrap().is_empty());
+}
+
+/// Calcula as movimentações de estoque dos componentes de um kit, decorrentes
+/// de uma saída de `quantidade` unidades do kit.
+///
+/// Os componentes devem ser informados junto de suas posições de estoque
+/// atuais. A quantidade informada deve ser negativa, como em qualquer saída de
+/// estoque. Será retornado um Vec com o id de cada componente e a respectiva
+/// quantidade a ser movimentada, também negativa. Caso a saída torne o estoque
+/// de qualquer um dos componentes negativo, será retornada uma mensagem de
+/// erro.
+pub fn movimentos_componentes(
+    quantidade: &BigDecimal,
+    componentes: &[(ProdutoComponente, Estoque)],
+) -> Result<Vec<(i32, BigDecimal)>, String> {
+    componentes
+        .iter()
+        .map(|(componente, estoque)| {
+            let movimento = quantidade * &componente.quantidade;
+            if (&estoque.quantidade + &movimento).is_negative() {
+                return Err(json!({
+                    "mensagem": format!(
+                        "Movimentações de estoque não podem torná-lo negativo! \
+                         Estoque atual do componente {}: {}",
+                        componente.componente_id, estoque.quantidade
+                    )
+                })
+                .to_string());
+            }
+            Ok((componente.componente_id, movimento))
+        })
+        .collect()
+}
+
+#[test]
+fn movimentos_de_componentes_de_kit() {
+    use std::str::FromStr;
+    let valor = |v: &str| BigDecimal::from_str(v).unwrap();
+    let componente = |id, quantidade: &str, estoque: &str| {
+        (
+            ProdutoComponente {
+                kit_id: 1,
+                componente_id: id,
+                quantidade: valor(quantidade),
+            },
+            Estoque {
+                produto_id: id,
+                quantidade: valor(estoque),
+                precounitario: valor("1"),
+                deposito_id: 1,
+                custo_medio: valor("1"),
+            },
+        )
+    };
+
+    let componentes = vec![componente(2, "2", "10"), componente(3, "0.5", "1.5")];
+    assert_eq!(
+        movimentos_componentes(&valor("-3"), &componentes).unwrap(),
+        vec![(2, valor("-6")), (3, valor("-1.5"))]
+    );
+    assert!(movimentos_componentes(&valor("-4"), &componentes).is_err());
+    assert!(movimentos_componentes(&valor("-5"), &componentes).is_err());
+    assert!(movimentos_componentes(&valor("-1"), &[])
+        .unwrap()
+        .is_empty());
+}
+
+/// Realiza validação dos dados recebidos para cadastro ou alteração de um
+/// depósito. A descrição não pode estar em branco.
+pub fn valida_deposito(dados: &NovoDeposito) -> Result<(), String> {
+    if dados.descricao.trim().is_empty() {
+        return Err(json!({
+            "mensagem": "A descrição do depósito não pode estar em branco"
+        })
+        .to_string());
+    }
+    Ok(())
+}
+
+/// Realiza validação dos dados recebidos para uma transferência de estoque
+/// entre depósitos.
+///
+/// A quantidade transferida deve ser positiva, e os depósitos de origem e
+/// destino devem ser diferentes.
+pub fn valida_transferencia(dados: &TransferenciaRecv) -> Result<(), String> {
+    if !dados.quantidade.is_positive() {
+        return Err(json!({
+            "mensagem": "A quantidade transferida deve ser maior que zero"
+        })
+        .to_string());
+    }
+    if dados.origem_id == dados.destino_id {
+        return Err(json!({
+            "mensagem": "Os depósitos de origem e destino devem ser diferentes"
+        })
+        .to_string());
+    }
+    Ok(())
+}
+
+#[test]
+fn validacao_de_transferencia() {
+    use std::str::FromStr;
+    let transferencia = |quantidade: &str, origem_id, destino_id| TransferenciaRecv {
+        produto_id: 1,
+        docto: String::from("TRANSF-0001"),
+        quantidade: BigDecimal::from_str(quantidade).unwrap(),
+        origem_id,
+        destino_id,
+        lote: None,
+        numeros_serie: vec![],
+    };
+    assert!(valida_transferencia(&transferencia("5", 1, 2)).is_ok());
+    assert!(valida_transferencia(&transferencia("0", 1, 2)).is_err());
+    assert!(valida_transferencia(&transferencia("-5", 1, 2)).is_err());
+    assert!(valida_transferencia(&transferencia("5", 2, 2)).is_err());
+}
+
+/// Realiza validação do lote informado em uma movimentação ou em um início de
+/// estoque de `quantidade` unidades de um produto.
+///
+/// Apenas produtos com controle de lotes podem informar lotes, e suas entradas
+/// de estoque devem obrigatoriamente informá-los. O número do lote não pode
+/// estar em branco, e a data de fabricação, quando informada, não pode ser
+/// posterior à data de validade.
+pub fn valida_lote(
+    produto: &Produto,
+    quantidade: &BigDecimal,
+    lote: Option<&LoteRecv>,
+) -> Result<(), String> {
+    let erro = |mensagem: String| Err(json!({ "mensagem": mensagem }).to_string());
+    let lote = match lote {
+        None if produto.controla_lote && quantidade.is_positive() => {
+            return erro(format!(
+                "Entradas de estoque do produto {} devem informar o lote",
+                produto.id
+            ))
+        }
+        None => return Ok(()),
+        Some(_) if !produto.controla_lote => {
+            return erro(format!(
+                "O produto {} não possui controle de lotes",
+                produto.id
+            ))
+        }
+        Some(lote) => lote,
+    };
+
+    if lote.numero.trim().is_empty() {
+        return erro(String::from("O número do lote não pode estar em branco"));
+    }
+
+    if let (Some(fabricacao), Some(validade)) = (lote.fabricacao, lote.validade) {
+        if fabricacao > validade {
+            return erro(String::from(
+                "A data de fabricação do lote não pode ser posterior à data de validade",
+            ));
+        }
+    }
+    Ok(())
+}
+
+/// Realiza validação de uma entrada de estoque em um lote já existente, de
+/// acordo com os dados do lote informados na entrada.
+///
+/// As datas de fabricação e de validade, quando informadas, devem ser iguais
+/// às datas do lote. Lotes vencidos, isto é, com data de validade anterior à
+/// data informada como `hoje`, não podem receber entradas.
+pub fn valida_entrada_lote(lote: &Lote, recv: &LoteRecv, hoje: NaiveDate) -> Result<(), String> {
+    let erro = |mensagem: String| Err(json!({ "mensagem": mensagem }).to_string());
+    if recv.fabricacao.is_some_and(|f| Some(f) != lote.fabricacao)
+        || recv.validade.is_some_and(|v| Some(v) != lote.validade)
+    {
+        return erro(format!(
+            "As datas informadas divergem das datas do lote {}",
+            lote.numero
+        ));
+    }
+    match lote.validade {
+        Some(validade) if validade < hoje => erro(format!(
+            "O lote {} está vencido desde {}",
+            lote.numero, validade
+        )),
+        _ => Ok(()),
+    }
+}
+
+#[test]
+fn validacao_de_lotes() {
+    use std::str::FromStr;
+    let valor = |v: &str| BigDecimal::from_str(v).unwrap();
+    let data = |d: &str| NaiveDate::from_str(d).unwrap();
+    let produto = |controla_lote| Produto {
+        id: 1,
+        descricao: String::from("Iogurte"),
+        unidsaida: String::from("UN"),
+        ativo: true,
+        ncm: Some(String::from("04031000")),
+        cest: None,
+        origem: 0,
+        categoria_id: None,
+        produto_pai_id: None,
+        metodo_custo: crate::model::produto::MetodoCusto::Medio,
+        controla_lote,
+        controla_serie: false,
+    };
+    let recv = |numero: &str, fabricacao: Option<&str>, validade: Option<&str>| LoteRecv {
+        numero: numero.to_owned(),
+        fabricacao: fabricacao.map(data),
+        validade: validade.map(data),
+    };
+    let l1 = recv("L1", Some("2026-10-01"), Some("2026-11-30"));
+
+    assert!(valida_lote(&produto(true), &valor("5"), Some(&l1)).is_ok());
+    assert!(valida_lote(&produto(true), &valor("-5"), None).is_ok());
+    assert!(valida_lote(&produto(false), &valor("5"), None).is_ok());
+    assert!(valida_lote(&produto(true), &valor("5"), None).is_err());
+    assert!(valida_lote(&produto(false), &valor("-5"), Some(&l1)).is_err());
+    assert!(valida_lote(&produto(true), &valor("5"), Some(&recv(" ", None, None))).is_err());
+    let invertido = recv("L2", Some("2026-12-01"), Some("2026-11-30"));
+    assert!(valida_lote(&produto(true), &valor("5"), Some(&invertido)).is_err());
+
+    let lote = Lote {
+        id: 1,
+        produto_id: 1,
+        deposito_id: 1,
+        numero: String::from("L1"),
+        fabricacao: Some(data("2026-10-01")),
+        validade: Some(data("2026-11-30")),
+        quantidade: valor("10"),
+    };
+    let hoje = data("2026-11-30");
+    assert!(valida_entrada_lote(&lote, &l1, hoje).is_ok());
+    assert!(valida_entrada_lote(&lote, &recv("L1", None, None), hoje).is_ok());
+    assert!(valida_entrada_lote(&lote, &recv("L1", None, Some("2026-12-31")), hoje).is_err());
+    assert!(valida_entrada_lote(&lote, &recv("L1", Some("2026-09-01"), None), hoje).is_err());
+    assert!(valida_entrada_lote(&lote, &l1, data("2026-12-01")).is_err());
+}
+
+/// Calcula o consumo dos lotes de uma posição de estoque por uma saída de
+/// `quantidade` unidades, informada como um valor positivo, segundo o critério
+/// FEFO (primeiro a vencer, primeiro a sair).
+///
+/// Lotes vencidos em relação à data informada como `hoje`, e lotes sem saldo,
+/// não são consumidos. Os demais lotes são consumidos em ordem de validade,
+/// sendo que lotes sem data de validade são consumidos por último, e lotes com
+/// a mesma validade são consumidos em ordem de cadastro. Será retornado um Vec
+/// com o id de cada lote consumido e a quantidade consumida do mesmo, ou uma
+/// mensagem de erro, caso o saldo dos lotes não seja suficiente.
+pub fn consome_lotes(
+    lotes: &[Lote],
+    quantidade: &BigDecimal,
+    hoje: NaiveDate,
+) -> Result<Vec<(i32, BigDecimal)>, String> {
+    let mut disponiveis: Vec<&Lote> = lotes
+        .iter()
+        .filter(|l| l.quantidade.is_positive())
+        .filter(|l| l.validade.is_none_or(|v| v >= hoje))
+        .collect();
+    disponiveis.sort_by_key(|l| (l.validade.is_none(), l.validade, l.id));
+
+    let mut faltante = quantidade.clone();
+    let mut consumos = Vec::new();
+    for lote in &disponiveis {
+        if !faltante.is_positive() {
+            break;
+        }
+        let consumo = if lote.quantidade < faltante {
+            lote.quantidade.clone()
+        } else {
+            faltante.clone()
+        };
+        faltante -= &consumo;
+        consumos.push((lote.id, consumo));
+    }
+
+    if faltante.is_positive() {
+        let saldo: BigDecimal = disponiveis.iter().map(|l| &l.quantidade).sum();
+        return Err(json!({
+            "mensagem": format!(
+                "Saldo insuficiente nos lotes não vencidos do produto! Saldo atual: {}",
+                saldo
+            )
+        })
+        .to_string());
+    }
+    Ok(consumos)
+}
+
+#[test]
+fn consumo_de_lotes() {
+    use std::str::FromStr;
+    let valor = |v: &str| BigDecimal::from_str(v).unwrap();
+    let data = |d: &str| NaiveDate::from_str(d).unwrap();
+    let lote = |id, validade: Option<&str>, quantidade: &str| Lote {
+        id,
+        produto_id: 1,
+        deposito_id: 1,
+        numero: format!("L{}", id),
+        fabricacao: None,
+        validade: validade.map(data),
+        quantidade: valor(quantidade),
+    };
+    let lotes = vec![
+        lote(1, None, "10"),
+        lote(2, Some("2026-12-31"), "5"),
+        lote(3, Some("2026-11-30"), "3"),
+        lote(4, Some("2026-10-01"), "8"),
+        lote(5, Some("2026-11-30"), "0"),
+        lote(6, Some("2026-11-30"), "2"),
+    ];
+    let hoje = data("2026-10-18");
+
+    assert_eq!(
+        consome_lotes(&lotes, &valor("4"), hoje).unwrap(),
+        vec![(3, valor("3")), (6, valor("1"))]
+    );
+    assert_eq!(
+        consome_lotes(&lotes, &valor("12"), hoje).unwrap(),
+        vec![
+            (3, valor("3")),
+            (6, valor("2")),
+            (2, valor("5")),
+            (1, valor("2"))
+        ]
+    );
+    assert!(consome_lotes(&lotes, &valor("20"), hoje).is_ok());
+    assert!(consome_lotes(&lotes, &valor("20.001"), hoje).is_err());
+    assert!(consome_lotes(&[], &valor("1"), hoje).is_err());
+}
+
+/// Realiza validação dos números de série informados em uma movimentação ou
+/// em um início de estoque de `quantidade` unidades de um produto.
+///
+/// Apenas produtos com controle de números de série podem informar números de
+/// série. Para estes produtos, a quantidade deve ser inteira, e deve ser
+/// informado exatamente um número de série por unidade movimentada, seja em
+/// entradas ou em saídas. Os números de série não podem estar em branco nem
+/// repetidos.
+pub fn valida_numeros_serie(
+    produto: &Produto,
+    quantidade: &BigDecimal,
+    numeros: &[String],
+) -> Result<(), String> {
+    let erro = |mensagem: String| Err(json!({ "mensagem": mensagem }).to_string());
+    if !produto.controla_serie {
+        if !numeros.is_empty() {
+            return erro(format!(
+                "O produto {} não possui controle de números de série",
+                produto.id
+            ));
+        }
+        return Ok(());
+    }
+
+    if !quantidade.is_integer() {
+        return erro(format!(
+            "A quantidade movimentada do produto {} deve ser inteira",
+            produto.id
+        ));
+    }
+
+    if BigDecimal::from(numeros.len() as u64) != quantidade.abs() {
+        return erro(format!(
+            "Devem ser informados {} números de série para o produto {}; \
+             foram informados {}",
+            quantidade.abs().with_scale(0),
+            produto.id,
+            numeros.len()
+        ));
+    }
+
+    let mut vistos = std::collections::BTreeSet::new();
+    for numero in numeros {
+        let numero = numero.trim();
+        if numero.is_empty() {
+            return erro(String::from("O número de série não pode estar em branco"));
+        }
+        if !vistos.insert(numero) {
+            return erro(format!(
+                "O número de série {} foi informado mais de uma vez",
+                numero
+            ));
+        }
+    }
+    Ok(())
+}
+
+#[test]
+fn validacao_de_numeros_de_serie() {
+    use std::str::FromStr;
+    let valor = |v: &str| BigDecimal::from_str(v).unwrap();
+    let numeros = |ns: &[&str]| ns.iter().map(|n| n.to_string()).collect::<Vec<_>>();
+    let produto = |controla_serie| Produto {
+        id: 1,
+        descricao: String::from("Furadeira"),
+        unidsaida: String::from("UN"),
+        ativo: true,
+        ncm: Some(String::from("84672100")),
+        cest: None,
+        origem: 0,
+        categoria_id: None,
+        produto_pai_id: None,
+        metodo_custo: MetodoCusto::Medio,
+        controla_lote: false,
+        controla_serie,
+    };
+
+    assert!(valida_numeros_serie(&produto(true), &valor("2"), &numeros(&["A1", "A2"])).is_ok());
+    assert!(
+        valida_numeros_serie(&produto(true), &valor("-2.000"), &numeros(&["A1", "A2"])).is_ok()
+    );
+    assert!(valida_numeros_serie(&produto(true), &valor("0"), &[]).is_ok());
+    assert!(valida_numeros_serie(&produto(false), &valor("5"), &[]).is_ok());
+    assert!(valida_numeros_serie(&produto(false), &valor("1"), &numeros(&["A1"])).is_err());
+    assert!(valida_numeros_serie(&produto(true), &valor("2"), &numeros(&["A1"])).is_err());
+    assert!(valida_numeros_serie(&produto(true), &valor("-1"), &[]).is_err());
+    assert!(valida_numeros_serie(&produto(true), &valor("1.5"), &numeros(&["A1"])).is_err());
+    assert!(valida_numeros_serie(&produto(true), &valor("2"), &numeros(&["A1", " A1 "])).is_err());
+    assert!(valida_numeros_serie(&produto(true), &valor("1"), &numeros(&[" "])).is_err());
+}
+
+/// Realiza validação dos dados recebidos para uma reserva de estoque.
+///
+/// A quantidade reservada deve ser positiva, o documento de referência não
+/// pode estar em branco, e a data de validade não pode ser anterior à data
+/// informada como `hoje`.
+pub fn valida_reserva(dados: &ReservaRecv, hoje: NaiveDate) -> Result<(), String> {
+    let erro = |mensagem: &str| Err(json!({ "mensagem": mensagem }).to_string());
+    if !dados.quantidade.is_positive() {
+        return erro("A quantidade reservada deve ser maior que zero");
+    }
+    if dados.docto.trim().is_empty() {
+        return erro("O documento da reserva não pode estar em branco");
+    }
+    if dados.validade < hoje {
+        return erro("A validade da reserva não pode ser anterior à data atual");
+    }
+    Ok(())
+}
+
+#[test]
+fn validacao_de_reservas() {
+    use std::str::FromStr;
+    let data = |d: &str| NaiveDate::from_str(d).unwrap();
+    let reserva = |quantidade: &str, docto: &str, validade: &str| ReservaRecv {
+        produto_id: 1,
+        docto: docto.to_owned(),
+        quantidade: BigDecimal::from_str(quantidade).unwrap(),
+        validade: data(validade),
+        deposito_id: 1,
+    };
+    let hoje = data("2026-10-18");
+    assert!(valida_reserva(&reserva("3", "PED-1", "2026-10-25"), hoje).is_ok());
+    assert!(valida_reserva(&reserva("0.5", "PED-1", "2026-10-18"), hoje).is_ok());
+    assert!(valida_reserva(&reserva("0", "PED-1", "2026-10-25"), hoje).is_err());
+    assert!(valida_reserva(&reserva("-1", "PED-1", "2026-10-25"), hoje).is_err());
+    assert!(valida_reserva(&reserva("3", " ", "2026-10-25"), hoje).is_err());
+    assert!(valida_reserva(&reserva("3", "PED-1", "2026-10-17"), hoje).is_err());
+}
+
+/// Realiza validação dos dados recebidos para o estorno de uma movimentação de
+/// estoque. O motivo do estorno não pode estar em branco.
+pub fn valida_estorno(dados: &EstornoRecv) -> Result<(), String> {
+    if dados.motivo.trim().is_empty() {
+        return Err(json!({
+            "mensagem": "O motivo do estorno deve ser informado"
+        })
+        .to_string());
+    }
+    Ok(())
+}
+
+#[test]
+fn validacao_de_estorno() {
+    assert!(valida_estorno(&EstornoRecv {
+        motivo: String::from("Quantidade digitada incorretamente")
+    })
+    .is_ok());
+    assert!(valida_estorno(&EstornoRecv {
+        motivo: String::from("  ")
+    })
+    .is_err());
+}
+
+/// Calcula o custo médio de uma posição de estoque após o estorno de uma
+/// entrada de `quantidade` unidades com o custo unitário informado.
+///
+/// O custo médio é recalculado de forma inversa ao cálculo de uma entrada,
+/// retirando o valor da entrada estornada do valor da posição de estoque, e
+/// dividindo o resultado pela quantidade `restante` na posição de estoque após
+/// o estorno. Caso não haja quantidade restante, o custo médio será mantido.
+/// Caso o valor restante seja negativo (ex: a entrada tenha tido um custo
+/// superior ao de todo o estoque), o custo médio será zero. O resultado é
+/// arredondado para quatro casas decimais.
+pub fn custo_estorno_entrada(
+    restante: &BigDecimal,
+    custo_medio: &BigDecimal,
+    quantidade: &BigDecimal,
+    custo: &BigDecimal,
+) -> BigDecimal {
+    if !restante.is_positive() {
+        return custo_medio.clone();
+    }
+    let valor = (restante + quantidade) * custo_medio - quantidade * custo;
+    if valor.is_negative() {
+        return BigDecimal::zero();
+    }
+    arredonda(&(valor / restante), 4)
+}
+
+#[test]
+fn custo_de_estorno_de_entrada() {
+    use std::str::FromStr;
+    let valor = |v: &str| BigDecimal::from_str(v).unwrap();
+    assert_eq!(
+        custo_estorno_entrada(&valor("10"), &valor("3"), &valor("10"), &valor("4")),
+        valor("2")
+    );
+    assert_eq!(
+        custo_estorno_entrada(&valor("3"), &valor("2.5"), &valor("1"), &valor("1")),
+        valor("3")
+    );
+    assert_eq!(
+        custo_estorno_entrada(&valor("0"), &valor("3"), &valor("5"), &valor("4")),
+        valor("3")
+    );
+    assert_eq!(
+        custo_estorno_entrada(&valor("1"), &valor("1"), &valor("1"), &valor("5")),
+        valor("0")
+    );
+    assert_eq!(
+        custo_estorno_entrada(&valor("3"), &valor("1"), &valor("1"), &valor("2")),
+        valor("0.6667")
+    );
+}
+
+/// Determina as movimentações a serem estornadas no estorno de uma
+/// movimentação de estoque.
+///
+/// Caso a movimentação seja uma das movimentações de uma transferência entre
+/// depósitos, a outra movimentação da transferência deve ser informada em
+/// `par`, e ambas serão estornadas, de forma que a quantidade transferida
+/// retorne ao depósito de origem e deixe o depósito de destino. As
+/// movimentações são retornadas na ordem entrada e saída, de forma que a
+/// quantidade seja retirada do depósito de destino antes de retornar ao
+/// depósito de origem. Do contrário, apenas a própria movimentação será
+/// retornada.
+///
+/// Caso `par` não corresponda à outra movimentação da transferência, será
+/// retornada uma mensagem de erro em JSON.
+pub fn movimentos_estorno(
+    movimento: &MovEstoque,
+    par: Option<&MovEstoque>,
+) -> Result<Vec<MovEstoque>, String> {
+    if par.is_none() && movimento.transferencia_de_id.is_none() {
+        return Ok(vec![movimento.clone()]);
+    }
+    let (saida, entrada) = match par {
+        Some(par) if movimento.transferencia_de_id == Some(par.id) => (par, movimento),
+        Some(par) if par.transferencia_de_id == Some(movimento.id) => (movimento, par),
+        _ => {
+            return Err(json!({
+                "mensagem": format!(
+                    "A transferência da movimentação {} não pôde ser identificada",
+                    movimento.id
+                )
+            })
+            .to_string())
+        }
+    };
+    if saida.produto_id != entrada.produto_id
+        || saida.quantidade != -entrada.quantidade.clone()
+        || !entrada.quantidade.is_positive()
+    {
+        return Err(json!({
+            "mensagem": format!(
+                "As movimentações {} e {} não correspondem a uma transferência",
+                saida.id, entrada.id
+            )
+        })
+        .to_string());
+    }
+    Ok(vec![entrada.clone(), saida.clone()])
+}
+
+#[test]
+fn estorno_de_transferencia() {
+    use std::str::FromStr;
+    let movimento = |id, quantidade: &str, deposito_id, transferencia_de_id| MovEstoque {
+        id,
+        produto_id: 1,
+        docto: String::from("TR-1"),
+        quantidade: BigDecimal::from_str(quantidade).unwrap(),
+        preco_frete: BigDecimal::zero(),
+        datahora: chrono::Utc::now(),
+        preco_unitario: BigDecimal::from(5),
+        deposito_id,
+        custo_unitario: BigDecimal::from(3),
+        cliente_id: None,
+        estorno_de_id: None,
+        documento_estoque_id: None,
+        inventario_id: None,
+        inicio: false,
+        transferencia_de_id,
+    };
+    let ids = |movs: Vec<MovEstoque>| movs.iter().map(|m| m.id).collect::<Vec<_>>();
+
+    // Movimentações comuns são estornadas isoladamente
+    let comum = movimento(1, "-4", 1, None);
+    assert_eq!(ids(movimentos_estorno(&comum, None).unwrap()), vec![1]);
+
+    // Qualquer uma das movimentações de uma transferência estorna ambas,
+    // retirando a quantidade do destino antes de devolvê-la à origem
+    let saida = movimento(2, "-4", 1, None);
+    let entrada = movimento(3, "4", 2, Some(2));
+    assert_eq!(
+        ids(movimentos_estorno(&saida, Some(&entrada)).unwrap()),
+        vec![3, 2]
+    );
+    assert_eq!(
+        ids(movimentos_estorno(&entrada, Some(&saida)).unwrap()),
+        vec![3, 2]
+    );
+    let pernas = movimentos_estorno(&saida, Some(&entrada)).unwrap();
+    let total: BigDecimal = pernas.iter().map(|m| m.quantidade.clone()).sum();
+    assert!(total.is_zero());
+
+    // Transferências sem a outra movimentação, ou com movimentações que não
+    // correspondem, não podem ser estornadas
+    assert!(movimentos_estorno(&entrada, None).is_err());
+    assert!(movimentos_estorno(&saida, Some(&comum)).is_err());
+    let divergente = movimento(4, "3", 2, Some(2));
+    assert!(movimentos_estorno(&saida, Some(&divergente)).is_err());
+}
+
+/// Realiza validação dos dados recebidos para o registro de um documento de
+/// estoque.
+///
+/// O número do documento não pode estar em branco, o documento deve possuir
+/// ao menos um item, e o frete, quando informado, não pode ser negativo. Os
+/// itens são entradas de estoque, e devem possuir quantidade positiva; caso o
+/// frete seja rateado por peso, todos os itens devem informar um peso
+/// positivo.
+pub fn valida_documento(dados: &DocumentoEstoqueRecv) -> Result<(), String> {
+    let erro = |mensagem: String| Err(json!({ "mensagem": mensagem }).to_string());
+    if dados.docto.trim().is_empty() {
+        return erro(String::from("O número do documento deve ser informado"));
+    }
+    if dados.itens.is_empty() {
+        return erro(String::from("O documento deve possuir ao menos um item"));
+    }
+    if dados.frete.as_ref().is_some_and(|f| f.is_negative()) {
+        return erro(String::from("O frete do documento não pode ser negativo"));
+    }
+    for (i, item) in dados.itens.iter().enumerate() {
+        if !item.quantidade.is_positive() {
+            return erro(format!(
+                "Item {}: a quantidade deve ser maior que zero",
+                i + 1
+            ));
+        }
+        if dados.rateio_frete == RateioFrete::Peso
+            && !item.peso.as_ref().is_some_and(|p| p.is_positive())
+        {
+            return erro(format!(
+                "Item {}: o peso deve ser informado e maior que zero para o rateio do \
+                 frete por peso",
+                i + 1
+            ));
+        }
+    }
+    Ok(())
+}
+
+#[test]
+fn validacao_de_documento() {
+    use crate::model::documento::ItemDocumentoRecv;
+    use std::str::FromStr;
+    let valor = |v: &str| BigDecimal::from_str(v).unwrap();
+    let item = |quantidade: &str, peso: Option<&str>| ItemDocumentoRecv {
+        produto_id: 1,
+        quantidade: valor(quantidade),
+        preco_unitario: valor("2"),
+        unidade: None,
+        peso: peso.map(valor),
+        lote: None,
+        numeros_serie: vec![],
+    };
+    let documento = |docto: &str, frete: &str, rateio_frete, itens| DocumentoEstoqueRecv {
+        docto: docto.to_owned(),
+        fornecedor_id: None,
+        data: NaiveDate::from_ymd(2026, 10, 18),
+        frete: Some(valor(frete)),
+        rateio_frete,
+        deposito_id: 1,
+        itens,
+    };
+    assert!(valida_documento(&documento(
+        "NF-1",
+        "10",
+        RateioFrete::Valor,
+        vec![item("2", None)]
+    ))
+    .is_ok());
+    assert!(valida_documento(&documento(
+        " ",
+        "10",
+        RateioFrete::Valor,
+        vec![item("2", None)]
+    ))
+    .is_err());
+    assert!(valida_documento(&documento("NF-1", "10", RateioFrete::Valor, vec![])).is_err());
+    assert!(valida_documento(&documento(
+        "NF-1",
+        "-1",
+        RateioFrete::Valor,
+        vec![item("2", None)]
+    ))
+    .is_err());
+    assert!(valida_documento(&documento(
+        "NF-1",
+        "10",
+        RateioFrete::Valor,
+        vec![item("-2", None)]
+    ))
+    .is_err());
+    assert!(valida_documento(&documento(
+        "NF-1",
+        "10",
+        RateioFrete::Peso,
+        vec![item("2", Some("1.5"))]
+    ))
+    .is_ok());
+    assert!(valida_documento(&documento(
+        "NF-1",
+        "10",
+        RateioFrete::Peso,
+        vec![item("2", Some("1.5")), item("1", None)]
+    ))
+    .is_err());
+    assert!(valida_documento(&documento(
+        "NF-1",
+        "10",
+        RateioFrete::Peso,
+        vec![item("2", Some("0"))]
+    ))
+    .is_err());
+}
+
+/// Rateia o frete total de um documento de estoque entre seus itens,
+/// proporcionalmente às bases de rateio informadas (ex: o valor ou o peso de
+/// cada item).
+///
+/// Retorna o frete de cada item, na ordem das bases, arredondado para quatro
+/// casas decimais. A diferença de arredondamento é atribuída ao último item,
+/// de forma que a soma dos fretes seja igual ao frete total. Caso a soma das
+/// bases seja zero, o frete será dividido igualmente entre os itens.
+pub fn rateia_frete(frete: &BigDecimal, bases: &[BigDecimal]) -> Vec<BigDecimal> {
+    if bases.is_empty() {
+        return vec![];
+    }
+    let total: BigDecimal = bases.iter().sum();
+    let mut fretes: Vec<BigDecimal> = bases
+        .iter()
+        .map(|base| {
+            if total.is_zero() {
+                arredonda(&(frete / BigDecimal::from(bases.len() as i64)), 4)
+            } else {
+                arredonda(&(frete * base / &total), 4)
+            }
+        })
+        .collect();
+    let ultimo = fretes.len() - 1;
+    let rateado: BigDecimal = fretes[..ultimo].iter().sum();
+    fretes[ultimo] = (frete - rateado).with_scale(4);
+    fretes
+}
+
+#[test]
+fn rateio_de_frete() {
+    use std::str::FromStr;
+    let valor = |v: &str| BigDecimal::from_str(v).unwrap();
+    assert_eq!(
+        rateia_frete(&valor("30"), &[valor("100"), valor("200")]),
+        vec![valor("10"), valor("20")]
+    );
+    assert_eq!(
+        rateia_frete(&valor("10"), &[valor("1"), valor("1"), valor("1")]),
+        vec![valor("3.3333"), valor("3.3333"), valor("3.3334")]
+    );
+    assert_eq!(
+        rateia_frete(&valor("1"), &[valor("0"), valor("0")]),
+        vec![valor("0.5"), valor("0.5")]
+    );
+    assert_eq!(
+        rateia_frete(&valor("0"), &[valor("3"), valor("7")]),
+        vec![valor("0"), valor("0")]
+    );
+    assert!(rateia_frete(&valor("5"), &[]).is_empty());
+}
+
+/// Realiza validação dos dados recebidos para a abertura de uma sessão de
+/// inventário. A descrição da sessão não pode estar em branco.
+pub fn valida_inventario(dados: &InventarioRecv) -> Result<(), String> {
+    if dados.descricao.trim().is_empty() {
+        return Err(json!({
+            "mensagem": "A descrição do inventário deve ser informada"
+        })
+        .to_string());
+    }
+    Ok(())
+}
+
+/// Realiza validação dos dados recebidos para uma contagem de inventário.
+///
+/// A quantidade contada não pode ser negativa, e o contador, quando
+/// informado, não pode estar em branco.
+pub fn valida_contagem(dados: &ContagemRecv) -> Result<(), String> {
+    let erro = |mensagem: &str| Err(json!({ "mensagem": mensagem }).to_string());
+    if dados.quantidade.is_negative() {
+        return erro("A quantidade contada não pode ser negativa");
+    }
+    if dados.contador.as_ref().is_some_and(|c| c.trim().is_empty()) {
+        return erro("O contador não pode estar em branco");
+    }
+    Ok(())
+}
+
+#[test]
+fn validacao_de_inventario() {
+    use std::str::FromStr;
+    let contagem = |quantidade: &str, contador: Option<&str>| ContagemRecv {
+        produto_id: 1,
+        quantidade: BigDecimal::from_str(quantidade).unwrap(),
+        contador: contador.map(String::from),
+    };
+    let inventario = |descricao: &str| InventarioRecv {
+        descricao: descricao.to_owned(),
+        deposito_id: 1,
+    };
+    assert!(valida_inventario(&inventario("Inventário do 3º trimestre")).is_ok());
+    assert!(valida_inventario(&inventario(" ")).is_err());
+    assert!(valida_contagem(&contagem("0", None)).is_ok());
+    assert!(valida_contagem(&contagem("12.5", Some("Equipe A"))).is_ok());
+    assert!(valida_contagem(&contagem("-1", None)).is_err());
+    assert!(valida_contagem(&contagem("1", Some("  "))).is_err());
+}
+
+/// Revisa um item de uma sessão de inventário a partir de suas contagens,
+/// informadas na ordem em que foram registradas.
+///
+/// Apenas a última contagem de cada contador é considerada, de forma que um
+/// contador possa recontar um item. Caso as últimas contagens de todos os
+/// contadores coincidam, esta será a quantidade contada do item, e a
+/// diferença será a quantidade contada menos a quantidade esperada. Caso
+/// divirjam, o item será marcado como divergente, e não haverá quantidade
+/// contada nem diferença. Itens sem contagens também não possuem quantidade
+/// contada. As contagens retornadas são ordenadas pelo contador.
+pub fn revisa_item_inventario(
+    item: &ItemInventario,
+    contagens: &[ContagemInventario],
+) -> DiferencaInventario {
+    let mut ultimas = std::collections::BTreeMap::new();
+    for contagem in contagens.iter().filter(|c| c.produto_id == item.produto_id) {
+        ultimas.insert(contagem.contador.clone(), contagem.quantidade.clone());
+    }
+    let contagens: Vec<ContagemContador> = ultimas
+        .into_iter()
+        .map(|(contador, quantidade)| ContagemContador {
+            contador,
+            quantidade,
+        })
+        .collect();
+    let divergente = contagens
+        .iter()
+        .any(|c| c.quantidade != contagens[0].quantidade);
+    let contado = if divergente {
+        None
+    } else {
+        contagens.first().map(|c| c.quantidade.clone())
+    };
+    DiferencaInventario {
+        produto_id: item.produto_id,
+        esperado: item.esperado.clone(),
+        diferenca: contado.as_ref().map(|c| c - &item.esperado),
+        contado,
+        contagens,
+        divergente,
+    }
+}
+
+#[test]
+fn revisao_de_item_de_inventario() {
+    use std::str::FromStr;
+    let valor = |v: &str| BigDecimal::from_str(v).unwrap();
+    let item = ItemInventario {
+        inventario_id: 1,
+        produto_id: 8,
+        esperado: valor("10"),
+    };
+    let contagem = |id, produto_id, contador: &str, quantidade: &str| ContagemInventario {
+        id,
+        inventario_id: 1,
+        produto_id,
+        contador: contador.to_owned(),
+        quantidade: valor(quantidade),
+        datahora: chrono::Utc::now(),
+    };
+
+    let revisao = revisa_item_inventario(&item, &[]);
+    assert!(revisao.contagens.is_empty());
+    assert_eq!(revisao.contado, None);
+    assert_eq!(revisao.diferenca, None);
+    assert!(!revisao.divergente);
+
+    let revisao = revisa_item_inventario(
+        &item,
+        &[
+            contagem(1, 8, "B", "7"),
+            contagem(2, 9, "A", "1"),
+            contagem(3, 8, "A", "8.000"),
+            contagem(4, 8, "B", "8"),
+        ],
+    );
+    assert_eq!(revisao.contagens.len(), 2);
+    assert_eq!(revisao.contagens[0].contador, "A");
+    assert_eq!(revisao.contado, Some(valor("8")));
+    assert_eq!(revisao.diferenca, Some(valor("-2")));
+    assert!(!revisao.divergente);
+
+    let revisao = revisa_item_inventario(
+        &item,
+        &[contagem(1, 8, "A", "12"), contagem(2, 8, "B", "11")],
+    );
+    assert_eq!(revisao.contado, None);
+    assert_eq!(revisao.diferenca, None);
+    assert!(revisao.divergente);
+}
+
+/// Reconstrói o saldo de uma posição de estoque a partir de suas
+/// movimentações, informadas como pares de quantidade e custo unitário.
+///
+/// A quantidade será a soma das quantidades das movimentações, e o valor, a
+/// soma das quantidades multiplicadas pelos custos unitários, arredondada para
+/// duas casas decimais. Como as saídas são valoradas pelo custo médio ou pelas
+/// camadas de custo consumidas, o valor corresponde ao valor da posição pelo
+/// método de valoração do produto. O custo médio será o valor dividido pela
+/// quantidade, arredondado para quatro casas decimais, ou zero, caso não haja
+/// quantidade positiva. Retorna a quantidade, o custo médio e o valor,
+/// respectivamente.
+pub fn reconstroi_saldo(
+    movimentos: &[(&BigDecimal, &BigDecimal)],
+) -> (BigDecimal, BigDecimal, BigDecimal) {
+    let quantidade: BigDecimal = movimentos.iter().map(|(q, _)| *q).sum();
+    let valor: BigDecimal = movimentos.iter().map(|(q, c)| *q * *c).sum();
+    let custo_medio = if quantidade.is_positive() {
+        arredonda(&(&valor / &quantidade), 4)
+    } else {
+        BigDecimal::zero()
+    };
+    (quantidade, custo_medio, arredonda(&valor, 2))
+}
+
+#[test]
+fn reconstrucao_de_saldo() {
+    use std::str::FromStr;
+    let valor = |v: &str| BigDecimal::from_str(v).unwrap();
+    let (inicio, custo_inicio) = (valor("10"), valor("5"));
+    let (entrada, custo_entrada) = (valor("10"), valor("7"));
+    let (saida, custo_saida) = (valor("-15"), valor("6"));
+    assert_eq!(
+        reconstroi_saldo(&[(&inicio, &custo_inicio)]),
+        (valor("10"), valor("5"), valor("50"))
+    );
+    assert_eq!(
+        reconstroi_saldo(&[
+            (&inicio, &custo_inicio),
+            (&entrada, &custo_entrada),
+            (&saida, &custo_saida)
+        ]),
+        (valor("5"), valor("6"), valor("30"))
+    );
+    let (terco, custo_terco) = (valor("3"), valor("3.3333"));
+    assert_eq!(
+        reconstroi_saldo(&[(&terco, &custo_terco)]),
+        (valor("3"), valor("3.3333"), valor("10"))
+    );
+    let zero = valor("0");
+    assert_eq!(
+        reconstroi_saldo(&[(&zero, &custo_inicio)]),
+        (valor("0"), valor("0"), valor("0"))
+    );
+    assert_eq!(reconstroi_saldo(&[]), (valor("0"), valor("0"), valor("0")));
+}
@@ -17,9 +17,15 @@
 //! Este módulo contém estruturas e ferramentas (_Business Objects_) para
 //! manutenção e validação de regras de negócio específicas para o sistema.
 
+pub mod arquivos;
 pub mod auth;
+pub mod categorias;
 pub mod clientes;
+pub mod cripto;
 pub mod db;
+pub mod estoque;
+pub mod precos;
+pub mod produtos;
 pub mod redis;
 pub mod usuarios;
 //pub mod whatsapp;
@@ -0,0 +1,238 @@
+// bo/precos.rs -- Uma parte de Minerva.rs
+// Copyright (C) 2021 Lucas S. Vieira
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with this program.  If not, see <https://www.gnu.org/licenses/>.
+
+//! Este módulo contém ferramentas para reforçar regras de negócio relacionadas
+//! a tabelas de preço e à resolução do preço efetivo de um produto.
+//!
+//! A tabela de preço aplicável a um cliente é determinada pela especificidade
+//! da tabela: tabelas exclusivas do cliente têm precedência sobre tabelas do
+//! tipo (segmento) do cliente, que por sua vez têm precedência sobre tabelas
+//! gerais.
+
+use super::produtos::respeita_casas_decimais;
+use crate::model::preco::{NovaTabelaPreco, PrecoProduto, PrecoProdutoRecv, TabelaPreco};
+use bigdecimal::Signed;
+use chrono::NaiveDate;
+use serde_json::json;
+
+/// Realiza validação dos dados recebidos para cadastro ou alteração de uma
+/// tabela de preço.
+///
+/// A descrição não pode estar em branco, e a tabela não pode ser, ao mesmo
+/// tempo, exclusiva de um cliente e de um tipo de cliente.
+pub fn valida_tabela(dados: &NovaTabelaPreco) -> Result<(), String> {
+    if dados.descricao.trim().is_empty() {
+        return Err(json!({
+            "mensagem": "A descrição da tabela de preço não pode estar em branco"
+        })
+        .to_string());
+    }
+
+    if dados.cliente_id.is_some() && dados.tipo_cliente.is_some() {
+        return Err(json!({
+            "mensagem": "A tabela de preço deve ser aplicada a um cliente ou a \
+                         um tipo de cliente, mas não a ambos"
+        })
+        .to_string());
+    }
+
+    Ok(())
+}
+
+/// Realiza validação dos dados recebidos para cadastro de um preço de produto.
+///
+/// O preço deve ser maior que zero e possuir no máximo quatro casas decimais,
+/// e o fim da vigência, quando informado, não pode ser anterior ao início.
+pub fn valida_preco(dados: &PrecoProdutoRecv) -> Result<(), String> {
+    if !dados.preco.is_positive() || !respeita_casas_decimais(&dados.preco, 4) {
+        return Err(json!({
+            "mensagem": "O preço deve ser maior que zero e possuir no máximo \
+                         quatro casas decimais"
+        })
+        .to_string());
+    }
+
+    if dados
+        .vigencia_fim
+        .is_some_and(|fim| fim < dados.vigencia_inicio)
+    {
+        return Err(json!({
+            "mensagem": "O fim da vigência não pode ser anterior ao seu início"
+        })
+        .to_string());
+    }
+
+    Ok(())
+}
+
+/// Informa se um preço está vigente em uma data.
+pub fn vigente(preco: &PrecoProduto, data: NaiveDate) -> bool {
+    preco.vigencia_inicio <= data && preco.vigencia_fim.is_none_or(|fim| data <= fim)
+}
+
+/// Informa se a vigência de um novo preço se sobrepõe à vigência de um preço
+/// existente.
+pub fn sobrepoe(existente: &PrecoProduto, novo: &PrecoProdutoRecv) -> bool {
+    let comeca_antes_do_fim = existente
+        .vigencia_fim
+        .is_none_or(|fim| novo.vigencia_inicio <= fim);
+    let termina_depois_do_inicio = novo
+        .vigencia_fim
+        .is_none_or(|fim| existente.vigencia_inicio <= fim);
+    comeca_antes_do_fim && termina_depois_do_inicio
+}
+
+/// Retorna a prioridade de uma tabela de preço para um cliente, caso a tabela
+/// seja aplicável ao mesmo. Quanto maior o número, maior a prioridade.
+///
+/// O cliente deve ser informado através de seu id e de seu tipo. Caso nenhum
+/// cliente seja informado, apenas tabelas gerais serão aplicáveis.
+fn prioridade(tabela: &TabelaPreco, cliente: Option<(i32, i16)>) -> Option<u8> {
+    match (tabela.cliente_id, tabela.tipo_cliente, cliente) {
+        (None, None, _) => Some(0),
+        (None, Some(tipo), Some((_, tipo_cliente))) if tipo == tipo_cliente => Some(1),
+        (Some(id), _, Some((cliente_id, _))) if id == cliente_id => Some(2),
+        _ => None,
+    }
+}
+
+/// Resolve o preço efetivo de um produto para um cliente em uma data.
+///
+/// Dentre os preços vigentes na data, em tabelas aplicáveis ao cliente, será
+/// escolhido o preço da tabela mais específica (exclusiva do cliente, depois
+/// do tipo do cliente, depois geral). Em caso de empate, será escolhido o preço
+/// de vigência mais recente e, persistindo o empate, o preço da tabela de
+/// menor id. Caso nenhum preço seja aplicável, será retornado `None`.
+///
+/// Os preços informados devem ser todos de um mesmo produto.
+pub fn resolve_preco<'a>(
+    tabelas: &'a [TabelaPreco],
+    precos: &'a [PrecoProduto],
+    cliente: Option<(i32, i16)>,
+    data: NaiveDate,
+) -> Option<(&'a TabelaPreco, &'a PrecoProduto)> {
+    precos
+        .iter()
+        .filter(|p| vigente(p, data))
+        .filter_map(|p| {
+            let tabela = tabelas.iter().find(|t| t.id == p.tabela_preco_id)?;
+            prioridade(tabela, cliente).map(|prioridade| (prioridade, tabela, p))
+        })
+        .max_by(|(pa, ta, a), (pb, tb, b)| {
+            pa.cmp(pb)
+                .then(a.vigencia_inicio.cmp(&b.vigencia_inicio))
+                .then(tb.id.cmp(&ta.id))
+        })
+        .map(|(_, tabela, preco)| (tabela, preco))
+}
+
+#[test]
+fn resolucao_de_preco() {
+    use bigdecimal::BigDecimal;
+    let data = |d: &str| NaiveDate::parse_from_str(d, "%Y-%m-%d").unwrap();
+    let tabela = |id, cliente_id, tipo_cliente| TabelaPreco {
+        id,
+        descricao: format!("Tabela {}", id),
+        cliente_id,
+        tipo_cliente,
+    };
+    let preco = |id, tabela_preco_id, valor: i32, inicio: &str, fim: Option<&str>| PrecoProduto {
+        id,
+        tabela_preco_id,
+        produto_id: 1,
+        preco: BigDecimal::from(valor),
+        vigencia_inicio: data(inicio),
+        vigencia_fim: fim.map(data),
+    };
+
+    let tabelas = vec![
+        tabela(1, None, None),
+        tabela(2, None, Some(1)),
+        tabela(3, Some(7), None),
+        tabela(4, None, None),
+    ];
+    let precos = vec![
+        preco(1, 1, 10, "2021-01-01", None),
+        preco(2, 2, 9, "2021-01-01", None),
+        preco(3, 3, 8, "2021-06-01", Some("2021-06-30")),
+        preco(4, 4, 11, "2021-03-01", None),
+    ];
+    let resolve = |cliente, d: &str| {
+        resolve_preco(&tabelas, &precos, cliente, data(d)).map(|(t, p)| (t.id, p.id))
+    };
+
+    // Sem cliente: apenas tabelas gerais, com a vigência mais recente.
+    assert_eq!(resolve(None, "2021-02-01"), Some((1, 1)));
+    assert_eq!(resolve(None, "2021-06-15"), Some((4, 4)));
+    // Cliente de tipo 1: tabela do segmento.
+    assert_eq!(resolve(Some((5, 1)), "2021-06-15"), Some((2, 2)));
+    // Cliente 7, de tipo 1: tabela exclusiva durante sua vigência.
+    assert_eq!(resolve(Some((7, 1)), "2021-06-15"), Some((3, 3)));
+    assert_eq!(resolve(Some((7, 1)), "2021-07-01"), Some((2, 2)));
+    // Cliente de outro tipo: tabelas gerais.
+    assert_eq!(resolve(Some((8, 0)), "2021-06-15"), Some((4, 4)));
+    // Nenhum preço vigente.
+    assert_eq!(resolve(None, "2020-12-31"), None);
+}
+
+#[test]
+fn validacao_de_precos() {
+    use bigdecimal::BigDecimal;
+    use std::str::FromStr;
+    let data = |d: &str| NaiveDate::parse_from_str(d, "%Y-%m-%d").unwrap();
+    let recv = |valor: &str, inicio: &str, fim: Option<&str>| PrecoProdutoRecv {
+        produto_id: 1,
+        preco: BigDecimal::from_str(valor).unwrap(),
+        vigencia_inicio: data(inicio),
+        vigencia_fim: fim.map(data),
+    };
+
+    assert!(valida_preco(&recv("4.99", "2021-01-01", None)).is_ok());
+    assert!(valida_preco(&recv("4.99", "2021-01-01", Some("2021-01-01"))).is_ok());
+    assert!(valida_preco(&recv("0", "2021-01-01", None)).is_err());
+    assert!(valida_preco(&recv("4.99999", "2021-01-01", None)).is_err());
+    assert!(valida_preco(&recv("4.99", "2021-01-02", Some("2021-01-01"))).is_err());
+
+    let existente = PrecoProduto {
+        id: 1,
+        tabela_preco_id: 1,
+        produto_id: 1,
+        preco: BigDecimal::from(5),
+        vigencia_inicio: data("2021-03-01"),
+        vigencia_fim: Some(data("2021-03-31")),
+    };
+    assert!(sobrepoe(&existente, &recv("1", "2021-03-31", None)));
+    assert!(sobrepoe(
+        &existente,
+        &recv("1", "2021-01-01", Some("2021-03-01"))
+    ));
+    assert!(sobrepoe(&existente, &recv("1", "2021-01-01", None)));
+    assert!(!sobrepoe(&existente, &recv("1", "2021-04-01", None)));
+    assert!(!sobrepoe(
+        &existente,
+        &recv("1", "2021-01-01", Some("2021-02-28"))
+    ));
+
+    let tabela = |descricao: &str, cliente_id, tipo_cliente| NovaTabelaPreco {
+        descricao: descricao.to_owned(),
+        cliente_id,
+        tipo_cliente,
+    };
+    assert!(valida_tabela(&tabela("Varejo", None, None)).is_ok());
+    assert!(valida_tabela(&tabela("Contrato", Some(1), None)).is_ok());
+    assert!(valida_tabela(&tabela(" ", None, None)).is_err());
+    assert!(valida_tabela(&tabela("Misto", Some(1), Some(1))).is_err());
+}
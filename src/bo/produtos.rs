@@ -0,0 +1,651 @@
+// bo/produtos.rs -- Uma parte de Minerva.rs
+// Copyright (C) 2021 Lucas S. Vieira
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with this program.  If not, see <https://www.gnu.org/licenses/>.
+
+//! Este módulo contém ferramentas para reforçar regras de negócio relacionadas
+//! à validação de transações envolvendo dados de produtos.
+
+use crate::model::produto::{
+    AlteracaoProduto, ComponenteRecv, GtinRecv, NovoProduto, VarianteRecv,
+};
+use crate::model::unidade::{ProdutoUnidadeRecv, UnidadeMedida};
+use bigdecimal::{BigDecimal, Signed};
+use regex::Regex;
+use serde_json::json;
+use std::collections::BTreeMap;
+
+/// Expressão regular representando um código GTIN-8, GTIN-12, GTIN-13 ou
+/// GTIN-14. O dígito verificador foi discriminado separadamente para facilitar
+/// na captura.
+const GTIN_REGEX: &str = r"^(\d{7}|\d{11,13})(\d)$";
+
+/// Expressão regular representando um código NCM no formato 9999.99.99, com
+/// pontuação opcional.
+const NCM_REGEX: &str = r"^\d{4}\.?\d{2}\.?\d{2}$";
+
+/// Expressão regular representando um código CEST no formato 99.999.99, com
+/// pontuação opcional.
+const CEST_REGEX: &str = r"^\d{2}\.?\d{3}\.?\d{2}$";
+
+/// Tabela NCM embutida no binário, no formato `codigo;descricao`, com os
+/// códigos sem pontuação e uma linha de cabeçalho.
+///
+/// Para atualizar a tabela, gere o arquivo `tabela_ncm.csv` a partir da tabela
+/// vigente publicada no Portal Único Siscomex (veja [`converte_tabela_ncm`]),
+/// e recompile o projeto.
+const TABELA_NCM: &str = include_str!("tabela_ncm.csv");
+
+/// Remove a pontuação de um código fiscal (NCM ou CEST), mantendo apenas seus
+/// dígitos.
+pub fn remove_pontuacao(codigo: &str) -> String {
+    codigo.trim().replace('.', "")
+}
+
+/// Retorna a descrição de um código NCM na tabela NCM embutida, caso o código
+/// exista. O código pode ser informado com ou sem pontuação.
+pub fn descricao_ncm(ncm: &str) -> Option<&'static str> {
+    let ncm = remove_pontuacao(ncm);
+    TABELA_NCM
+        .lines()
+        .skip(1)
+        .filter_map(|linha| linha.split_once(';'))
+        .find(|(codigo, _)| *codigo == ncm)
+        .map(|(_, descricao)| descricao)
+}
+
+/// Informa se um código NCM é válido. O código deve possuir oito dígitos, com
+/// ou sem pontuação, e deve existir na tabela NCM embutida.
+pub fn valida_ncm(ncm: &str) -> bool {
+    Regex::new(NCM_REGEX).unwrap().is_match(ncm) && descricao_ncm(ncm).is_some()
+}
+
+/// Informa se um código CEST é válido. O código deve possuir sete dígitos, com
+/// ou sem pontuação.
+pub fn valida_cest(cest: &str) -> bool {
+    Regex::new(CEST_REGEX).unwrap().is_match(cest)
+}
+
+/// Informa se um código de origem da mercadoria é válido, isto é, se está
+/// entre 0 e 8.
+pub fn valida_origem(origem: i16) -> bool {
+    (0..=8).contains(&origem)
+}
+
+#[test]
+fn validacao_de_classificacao_fiscal() {
+    assert!(valida_ncm("22030000"));
+    assert!(valida_ncm("2203.00.00"));
+    assert!(valida_ncm("0409.00.00"));
+    assert_eq!(descricao_ncm("2203.00.00"), Some("Cervejas de malte"));
+
+    // Formatos inválidos
+    assert!(!valida_ncm(""));
+    assert!(!valida_ncm("2203"));
+    assert!(!valida_ncm("220300000"));
+    assert!(!valida_ncm("2203-00-00"));
+    assert!(!valida_ncm("codigo"));
+    assert!(descricao_ncm("codigo").is_none());
+
+    // Formato válido, mas inexistente na tabela
+    assert!(!valida_ncm("9999.99.99"));
+
+    assert!(valida_cest("1700100"));
+    assert!(valida_cest("17.001.00"));
+    assert!(!valida_cest("17.001.0"));
+    assert!(!valida_cest("170010000"));
+    assert!(!valida_cest(""));
+
+    assert!(valida_origem(0));
+    assert!(valida_origem(8));
+    assert!(!valida_origem(9));
+    assert!(!valida_origem(-1));
+
+    assert_eq!(remove_pontuacao(" 2203.00.00 "), "22030000");
+}
+
+/// Converte a tabela NCM publicada no Portal Único Siscomex, no formato JSON,
+/// para o formato da tabela NCM embutida (veja [`TABELA_NCM`]).
+///
+/// Apenas os códigos com oito dígitos (subitens) são considerados, e códigos
+/// cuja vigência tenha terminado antes da data informada são descartados. A
+/// descrição de cada código é mantida sem os traços que indicam seu nível na
+/// nomenclatura e sem marcações HTML. Caso o JSON não possua o formato
+/// esperado, será retornada uma mensagem de erro.
+pub fn converte_tabela_ncm(tabela: &str, data: chrono::NaiveDate) -> Result<String, String> {
+    let tabela: serde_json::Value =
+        serde_json::from_str(tabela).map_err(|e| format!("Tabela NCM inválida: {}", e))?;
+    let nomenclaturas = tabela["Nomenclaturas"]
+        .as_array()
+        .ok_or_else(|| String::from("Tabela NCM não possui nomenclaturas"))?;
+    let marcacao = Regex::new(r"<[^>]*>").unwrap();
+
+    let mut codigos = BTreeMap::new();
+    for item in nomenclaturas {
+        let codigo = remove_pontuacao(item["Codigo"].as_str().unwrap_or_default());
+        if codigo.len() != 8 || !codigo.chars().all(|c| c.is_ascii_digit()) {
+            continue;
+        }
+        let fim = item["Data_Fim"]
+            .as_str()
+            .and_then(|d| chrono::NaiveDate::parse_from_str(d, "%d/%m/%Y").ok());
+        if fim.is_some_and(|fim| fim < data) {
+            continue;
+        }
+        let descricao = item["Descricao"].as_str().unwrap_or_default();
+        let descricao = marcacao
+            .replace_all(descricao, "")
+            .split_whitespace()
+            .collect::<Vec<_>>()
+            .join(" ");
+        let descricao = descricao.trim_start_matches(['-', ' ']);
+        codigos.insert(codigo, descricao.to_owned());
+    }
+
+    if codigos.is_empty() {
+        return Err(String::from("Tabela NCM não possui códigos vigentes"));
+    }
+    let mut resultado = String::from("codigo;descricao\n");
+    for (codigo, descricao) in codigos {
+        resultado.push_str(&format!("{};{}\n", codigo, descricao));
+    }
+    Ok(resultado)
+}
+
+#[test]
+fn conversao_de_tabela_ncm() {
+    let data = chrono::NaiveDate::from_ymd(2026, 10, 18);
+    let tabela = r#"{
+        "Data_Ultima_Atualizacao_NCM": "Vigente em 01/10/2026",
+        "Nomenclaturas": [
+            {"Codigo": "22", "Descricao": "Bebidas, líquidos alcoólicos e vinagres.",
+             "Data_Inicio": "01/04/2022", "Data_Fim": "31/12/9999"},
+            {"Codigo": "2203.00.00", "Descricao": "Cervejas de malte.",
+             "Data_Inicio": "01/04/2022", "Data_Fim": "31/12/9999"},
+            {"Codigo": "0101.21.00", "Descricao": "-- Reprodutores de <i>raça</i>  pura",
+             "Data_Inicio": "01/04/2022", "Data_Fim": "31/12/9999"},
+            {"Codigo": "0101.29.00", "Descricao": "-- Outros",
+             "Data_Inicio": "01/04/2022", "Data_Fim": "31/12/2023"}
+        ]
+    }"#;
+    assert_eq!(
+        converte_tabela_ncm(tabela, data).unwrap(),
+        "codigo;descricao\n\
+         01012100;Reprodutores de raça pura\n\
+         22030000;Cervejas de malte.\n"
+    );
+
+    assert!(converte_tabela_ncm("codigo;descricao", data).is_err());
+    assert!(converte_tabela_ncm(r#"{"Nomenclaturas": []}"#, data).is_err());
+}
+
+/// Realiza validação dos dados fiscais de um produto, quando informados.
+fn valida_fiscal(ncm: Option<&str>, cest: Option<&str>, origem: Option<i16>) -> Result<(), String> {
+    if let Some(ncm) = ncm {
+        if !valida_ncm(ncm) {
+            return Err(json!({
+                "mensagem": format!("NCM inválido ou inexistente na tabela NCM: {}", ncm)
+            })
+            .to_string());
+        }
+    }
+
+    if let Some(cest) = cest {
+        if !valida_cest(cest) {
+            return Err(json!({
+                "mensagem": format!("CEST inválido: {}", cest)
+            })
+            .to_string());
+        }
+    }
+
+    if let Some(origem) = origem {
+        if !valida_origem(origem) {
+            return Err(json!({
+                "mensagem": "A origem da mercadoria deve estar entre 0 e 8"
+            })
+            .to_string());
+        }
+    }
+
+    Ok(())
+}
+
+/// Realiza validação dos dados recebidos para cadastro de um produto.
+///
+/// A descrição e a unidade de saída não podem estar em branco. O NCM é
+/// obrigatório, e deve existir na tabela NCM. O CEST, quando informado, deve
+/// possuir sete dígitos, e a origem da mercadoria deve estar entre 0 e 8.
+pub fn valida_produto(dados: &NovoProduto) -> Result<(), String> {
+    if dados.descricao.trim().is_empty() {
+        return Err(json!({
+            "mensagem": "A descrição do produto não pode estar em branco"
+        })
+        .to_string());
+    }
+
+    if dados.unidsaida.trim().is_empty() {
+        return Err(json!({
+            "mensagem": "A unidade de saída do produto não pode estar em branco"
+        })
+        .to_string());
+    }
+
+    valida_fiscal(
+        Some(&dados.ncm),
+        dados.cest.as_deref().filter(|c| !c.trim().is_empty()),
+        Some(dados.origem),
+    )
+}
+
+#[test]
+fn validacao_de_cadastro() {
+    let produto = |descricao: &str, ncm: &str, cest: Option<&str>, origem| NovoProduto {
+        descricao: descricao.to_owned(),
+        unidsaida: String::from("UN"),
+        ncm: ncm.to_owned(),
+        cest: cest.map(String::from),
+        origem,
+        ..Default::default()
+    };
+
+    assert!(valida_produto(&produto("Cerveja", "2203.00.00", None, 0)).is_ok());
+    assert!(valida_produto(&produto("Cerveja", "22030000", Some("03.021.00"), 1)).is_ok());
+    assert!(valida_produto(&produto("Cerveja", "22030000", Some(""), 0)).is_ok());
+
+    assert!(valida_produto(&produto("", "22030000", None, 0)).is_err());
+    assert!(valida_produto(&produto("Cerveja", "", None, 0)).is_err());
+    assert!(valida_produto(&produto("Cerveja", "2203", None, 0)).is_err());
+    assert!(valida_produto(&produto("Cerveja", "22030000", Some("0302100X"), 0)).is_err());
+    assert!(valida_produto(&produto("Cerveja", "22030000", None, 10)).is_err());
+}
+
+/// Realiza validação dos dados recebidos para alteração de um produto.
+///
+/// Ao menos um campo deverá ser alterado, e a descrição e a unidade de saída,
+/// quando informadas, não podem estar em branco. Os dados fiscais, quando
+/// informados, seguem as mesmas regras do cadastro de produtos.
+pub fn valida_alteracao(dados: &AlteracaoProduto) -> Result<(), String> {
+    if dados.descricao.is_none()
+        && dados.unidsaida.is_none()
+        && dados.ativo.is_none()
+        && dados.ncm.is_none()
+        && dados.cest.is_none()
+        && dados.origem.is_none()
+        && dados.categoria_id.is_none()
+        && dados.metodo_custo.is_none()
+        && dados.controla_lote.is_none()
+        && dados.controla_serie.is_none()
+    {
+        return Err(json!({
+            "mensagem": "Nenhuma alteração informada"
+        })
+        .to_string());
+    }
+
+    if dados
+        .descricao
+        .as_ref()
+        .is_some_and(|d| d.trim().is_empty())
+    {
+        return Err(json!({
+            "mensagem": "A descrição do produto não pode estar em branco"
+        })
+        .to_string());
+    }
+
+    if dados
+        .unidsaida
+        .as_ref()
+        .is_some_and(|u| u.trim().is_empty())
+    {
+        return Err(json!({
+            "mensagem": "A unidade de saída do produto não pode estar em branco"
+        })
+        .to_string());
+    }
+
+    valida_fiscal(
+        dados.ncm.as_deref(),
+        dados
+            .cest
+            .as_ref()
+            .and_then(|c| c.as_deref())
+            .filter(|c| !c.trim().is_empty()),
+        dados.origem,
+    )
+}
+
+#[test]
+fn validacao_de_alteracao() {
+    let alteracao = |descricao: Option<&str>, unidsaida: Option<&str>, ativo| AlteracaoProduto {
+        descricao: descricao.map(String::from),
+        unidsaida: unidsaida.map(String::from),
+        ativo,
+        ..Default::default()
+    };
+
+    assert!(valida_alteracao(&alteracao(Some("Produto"), Some("UN"), None)).is_ok());
+    assert!(valida_alteracao(&alteracao(Some("Produto"), None, None)).is_ok());
+    assert!(valida_alteracao(&alteracao(None, None, Some(true))).is_ok());
+
+    assert!(valida_alteracao(&alteracao(None, None, None)).is_err());
+    assert!(valida_alteracao(&alteracao(Some("  "), None, None)).is_err());
+    assert!(valida_alteracao(&alteracao(None, Some(""), None)).is_err());
+
+    let fiscal = |ncm: Option<&str>, cest: Option<Option<&str>>, origem| AlteracaoProduto {
+        ncm: ncm.map(String::from),
+        cest: cest.map(|c| c.map(String::from)),
+        origem,
+        ..Default::default()
+    };
+
+    assert!(valida_alteracao(&fiscal(Some("2203.00.00"), None, None)).is_ok());
+    assert!(valida_alteracao(&fiscal(None, Some(Some("")), None)).is_ok());
+    assert!(valida_alteracao(&fiscal(None, Some(None), None)).is_ok());
+    assert!(valida_alteracao(&fiscal(None, None, Some(2))).is_ok());
+
+    assert!(valida_alteracao(&fiscal(Some("9999.99.99"), None, None)).is_err());
+    assert!(valida_alteracao(&fiscal(None, Some(Some("123")), None)).is_err());
+    assert!(valida_alteracao(&fiscal(None, None, Some(9))).is_err());
+}
+
+/// Arredonda um valor decimal para a quantidade de casas decimais informada.
+///
+/// Valores exatamente na metade entre dois valores possíveis serão
+/// arredondados para longe do zero (ex: `0.125` torna-se `0.13` com duas
+/// casas decimais).
+pub fn arredonda(valor: &BigDecimal, casas: i64) -> BigDecimal {
+    let meio = BigDecimal::new(5.into(), casas + 1);
+    if valor.is_negative() {
+        (valor - meio).with_scale(casas)
+    } else {
+        (valor + meio).with_scale(casas)
+    }
+}
+
+/// Informa se um valor decimal possui, no máximo, a quantidade de casas
+/// decimais informada. Zeros à direita não são considerados.
+pub fn respeita_casas_decimais(valor: &BigDecimal, casas: i64) -> bool {
+    valor.with_scale(casas) == *valor
+}
+
+#[test]
+fn arredondamento_de_valores() {
+    use std::str::FromStr;
+    let valor = |v: &str| BigDecimal::from_str(v).unwrap();
+
+    assert_eq!(arredonda(&valor("0.125"), 2), valor("0.13"));
+    assert_eq!(arredonda(&valor("0.124"), 2), valor("0.12"));
+    assert_eq!(arredonda(&valor("-0.125"), 2), valor("-0.13"));
+    assert_eq!(arredonda(&valor("10"), 4), valor("10"));
+    assert_eq!(arredonda(&(valor("10") / valor("3")), 4), valor("3.3333"));
+
+    assert!(respeita_casas_decimais(&valor("1.500"), 1));
+    assert!(respeita_casas_decimais(&valor("18"), 0));
+    assert!(!respeita_casas_decimais(&valor("1.2"), 0));
+    assert!(!respeita_casas_decimais(&valor("0.0001"), 3));
+}
+
+/// Realiza validação dos dados recebidos para cadastro de uma unidade de
+/// medida.
+///
+/// O código e a descrição da unidade devem ser informados, sendo que o código
+/// não pode possuir espaços. A quantidade de casas decimais deve estar entre
+/// 0 e 3, posto que as quantidades em estoque admitem até três casas decimais.
+pub fn valida_unidade(dados: &UnidadeMedida) -> Result<(), String> {
+    if dados.codigo.is_empty() || dados.codigo.contains(char::is_whitespace) {
+        return Err(json!({
+            "mensagem": "Código da unidade de medida inválido"
+        })
+        .to_string());
+    }
+
+    if dados.descricao.trim().is_empty() {
+        return Err(json!({
+            "mensagem": "A descrição da unidade de medida não pode estar em branco"
+        })
+        .to_string());
+    }
+
+    if !(0..=3).contains(&dados.casas_decimais) {
+        return Err(json!({
+            "mensagem": "A unidade de medida deve admitir entre 0 e 3 casas decimais"
+        })
+        .to_string());
+    }
+
+    Ok(())
+}
+
+/// Realiza validação dos dados recebidos para cadastro de um fator de
+/// conversão de unidade de um produto.
+///
+/// O fator de conversão deve ser maior que zero e possuir no máximo seis casas
+/// decimais.
+pub fn valida_conversao(dados: &ProdutoUnidadeRecv) -> Result<(), String> {
+    if !dados.fator.is_positive() || !respeita_casas_decimais(&dados.fator, 6) {
+        return Err(json!({
+            "mensagem": "O fator de conversão deve ser maior que zero e \
+                         possuir no máximo seis casas decimais"
+        })
+        .to_string());
+    }
+    Ok(())
+}
+
+/// Realiza validação dos dados recebidos para cadastro de um componente de
+/// kit.
+///
+/// A quantidade do componente deve ser maior que zero e possuir no máximo três
+/// casas decimais.
+pub fn valida_componente(dados: &ComponenteRecv) -> Result<(), String> {
+    if !dados.quantidade.is_positive() || !respeita_casas_decimais(&dados.quantidade, 3) {
+        return Err(json!({
+            "mensagem": "A quantidade do componente deve ser maior que zero e \
+                         possuir no máximo três casas decimais"
+        })
+        .to_string());
+    }
+    Ok(())
+}
+
+/// Verifica se uma quantidade respeita as casas decimais permitidas por uma
+/// unidade de medida.
+pub fn valida_quantidade(quantidade: &BigDecimal, unidade: &UnidadeMedida) -> Result<(), String> {
+    if !respeita_casas_decimais(quantidade, unidade.casas_decimais.into()) {
+        return Err(json!({
+            "mensagem": format!(
+                "A quantidade {} não é válida para a unidade {}, que admite {} casas decimais",
+                quantidade, unidade.codigo, unidade.casas_decimais
+            )
+        })
+        .to_string());
+    }
+    Ok(())
+}
+
+/// Informa se um código GTIN (EAN/UPC) é válido. O código deve ser repassado
+/// como um string slice, sem espaços extras, contendo apenas os 8, 12, 13 ou
+/// 14 dígitos do código.
+pub fn valida_gtin(gtin: &str) -> bool {
+    let re = Regex::new(GTIN_REGEX).unwrap();
+    let captures = match re.captures(gtin) {
+        Some(c) => c,
+        None => return false,
+    };
+
+    // Os dígitos são ponderados da direita para a esquerda, alternando pesos
+    // 3 e 1, a partir do dígito imediatamente anterior ao verificador.
+    let calcula_digito_gtin = |digitos: Vec<u32>| {
+        let soma: u32 = digitos
+            .iter()
+            .rev()
+            .enumerate()
+            .map(|(i, d)| if i % 2 == 0 { d * 3 } else { *d })
+            .sum();
+        (10 - (soma % 10)) % 10
+    };
+
+    let digito = calcula_digito_gtin(
+        captures[1]
+            .chars()
+            .map(|c| c.to_digit(10).unwrap())
+            .collect(),
+    );
+
+    digito == captures[2].parse::<u32>().unwrap()
+}
+
+#[test]
+fn validacao_de_gtin() {
+    assert!(valida_gtin("96385074")); // GTIN-8
+    assert!(valida_gtin("036000291452")); // GTIN-12
+    assert!(valida_gtin("4006381333931")); // GTIN-13
+    assert!(valida_gtin("7891000315507")); // GTIN-13
+    assert!(valida_gtin("10012345678902")); // GTIN-14
+    assert!(valida_gtin("0036000291452")); // GTIN-12 preenchido com zero
+
+    // Dígitos verificadores incorretos
+    assert!(!valida_gtin("96385075"));
+    assert!(!valida_gtin("4006381333932"));
+    assert!(!valida_gtin("17891000315505"));
+
+    // Formatos inválidos
+    assert!(!valida_gtin(""));
+    assert!(!valida_gtin("teste"));
+    assert!(!valida_gtin("123456789"));
+    assert!(!valida_gtin("400638133393 "));
+    assert!(!valida_gtin("400-6381-33393-1"));
+    assert!(!valida_gtin("100123456789023"));
+}
+
+/// Normaliza um código GTIN para 14 dígitos, preenchendo-o com zeros à
+/// esquerda. Códigos de tamanhos diferentes que possuam a mesma forma
+/// normalizada representam o mesmo GTIN.
+pub fn normaliza_gtin(gtin: &str) -> String {
+    format!("{:0>14}", gtin)
+}
+
+/// Realiza validação dos dados recebidos para associação de um código GTIN a
+/// um produto.
+pub fn valida_novo_gtin(dados: &GtinRecv) -> Result<(), String> {
+    if !valida_gtin(&dados.gtin) {
+        return Err(json!({
+            "mensagem": format!("Código GTIN inválido: {}", dados.gtin)
+        })
+        .to_string());
+    }
+    Ok(())
+}
+
+/// Normaliza os atributos de uma variante de produto. Os nomes dos atributos
+/// serão convertidos para lowercase, e os nomes e valores terão espaços extras
+/// removidos.
+pub fn normaliza_atributos(atributos: &BTreeMap<String, String>) -> BTreeMap<String, String> {
+    atributos
+        .iter()
+        .map(|(atributo, valor)| (atributo.trim().to_lowercase(), valor.trim().to_owned()))
+        .collect()
+}
+
+/// Realiza validação dos dados recebidos para cadastro de uma variante de
+/// produto.
+///
+/// Ao menos um atributo deve ser informado, e os nomes e valores dos atributos
+/// não podem estar em branco. A descrição, quando informada, também não pode
+/// estar em branco.
+pub fn valida_variante(dados: &VarianteRecv) -> Result<(), String> {
+    if dados.atributos.is_empty() {
+        return Err(json!({
+            "mensagem": "A variante deve possuir ao menos um atributo"
+        })
+        .to_string());
+    }
+
+    if dados
+        .atributos
+        .iter()
+        .any(|(atributo, valor)| atributo.trim().is_empty() || valor.trim().is_empty())
+    {
+        return Err(json!({
+            "mensagem": "Os atributos da variante não podem possuir nomes ou valores em branco"
+        })
+        .to_string());
+    }
+
+    if normaliza_atributos(&dados.atributos).len() != dados.atributos.len() {
+        return Err(json!({
+            "mensagem": "Os atributos da variante não podem ser repetidos"
+        })
+        .to_string());
+    }
+
+    if dados
+        .descricao
+        .as_ref()
+        .is_some_and(|d| d.trim().is_empty())
+    {
+        return Err(json!({
+            "mensagem": "A descrição da variante não pode estar em branco"
+        })
+        .to_string());
+    }
+
+    Ok(())
+}
+
+/// Gera a descrição padrão de uma variante, formada pela descrição do produto
+/// pai seguida dos valores dos atributos da variante, na ordem dos nomes dos
+/// atributos.
+pub fn descricao_variante(descricao_pai: &str, atributos: &BTreeMap<String, String>) -> String {
+    atributos
+        .values()
+        .fold(descricao_pai.to_owned(), |descricao, valor| {
+            format!("{} {}", descricao, valor)
+        })
+}
+
+#[test]
+fn validacao_de_variante() {
+    let variante = |atributos: &[(&str, &str)], descricao: Option<&str>| VarianteRecv {
+        descricao: descricao.map(String::from),
+        atributos: atributos
+            .iter()
+            .map(|(a, v)| (a.to_string(), v.to_string()))
+            .collect(),
+    };
+
+    assert!(valida_variante(&variante(&[("tamanho", "M")], None)).is_ok());
+    assert!(valida_variante(&variante(
+        &[("tamanho", "M"), ("cor", "Azul")],
+        Some("Camiseta")
+    ))
+    .is_ok());
+
+    assert!(valida_variante(&variante(&[], None)).is_err());
+    assert!(valida_variante(&variante(&[("tamanho", " ")], None)).is_err());
+    assert!(valida_variante(&variante(&[(" ", "M")], None)).is_err());
+    assert!(valida_variante(&variante(&[("Cor", "Azul"), ("cor ", "Verde")], None)).is_err());
+    assert!(valida_variante(&variante(&[("tamanho", "M")], Some(""))).is_err());
+
+    let atributos =
+        normaliza_atributos(&variante(&[(" Tamanho", "M "), ("COR", "Azul")], None).atributos);
+    assert_eq!(atributos.get("tamanho").map(String::as_str), Some("M"));
+    assert_eq!(atributos.get("cor").map(String::as_str), Some("Azul"));
+    assert_eq!(
+        descricao_variante("Camiseta", &atributos),
+        "Camiseta Azul M"
+    );
+}
@@ -17,6 +17,8 @@
 //! Este módulo contém ferramentas para reforçar regras de negócio relacionadas
 //! à validação de transações envolvendo dados de usuários do sistema.
 
+use crate::model::usuario::PerfilUsuario;
+use serde_json::json;
 use sodiumoxide::crypto::pwhash::argon2id13;
 
 /// Compara por igualdade uma senha fornecida em texto e a senha de um usuário,
@@ -67,3 +69,38 @@ pub fn gera_hash_senha(senha_bytes: &[u8]) -> argon2id13::HashedPassword {
     )
     .unwrap()
 }
+
+/// Verifica se um usuário pode cadastrar outro usuário com o perfil informado.
+///
+/// Apenas usuários privilegiados podem cadastrar usuários com perfis
+/// diferentes do perfil comum, já que esses perfis concedem acesso a dados
+/// sensíveis (como os documentos dos clientes) ou a operações restritas (como
+/// o gerenciamento de crédito). Caso o cadastro não seja permitido, será
+/// retornada uma mensagem de erro em JSON.
+pub fn valida_perfil_cadastro(
+    perfil: PerfilUsuario,
+    solicitante_privilegiado: bool,
+) -> Result<(), String> {
+    if perfil != PerfilUsuario::Comum && !solicitante_privilegiado {
+        return Err(json!({
+            "mensagem": "Apenas usuários privilegiados podem cadastrar usuários \
+                         com perfis diferentes do perfil comum"
+        })
+        .to_string());
+    }
+    Ok(())
+}
+
+#[test]
+fn cadastro_de_perfis() {
+    assert!(valida_perfil_cadastro(PerfilUsuario::Comum, false).is_ok());
+    assert!(valida_perfil_cadastro(PerfilUsuario::Comum, true).is_ok());
+    for perfil in [
+        PerfilUsuario::Administrador,
+        PerfilUsuario::Vendas,
+        PerfilUsuario::Financeiro,
+    ] {
+        assert!(valida_perfil_cadastro(perfil, true).is_ok());
+        assert!(valida_perfil_cadastro(perfil, false).is_err());
+    }
+}
@@ -0,0 +1,224 @@
+// controller/arquivos.rs -- Uma parte de Minerva.rs
+// Copyright (C) 2021 Lucas S. Vieira
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with this program.  If not, see <https://www.gnu.org/licenses/>.
+
+//! Ferramentas para tráfego de dados entre as rotas de arquivos anexos, o
+//! banco de dados e o armazenamento de arquivos.
+//!
+//! Os metadados dos arquivos são armazenados na tabela `arquivo`, enquanto seu
+//! conteúdo é gravado em um [`FileStore`]. A gravação do conteúdo sempre
+//! precede a inserção dos metadados, e a remoção dos metadados sempre precede
+//! a remoção do conteúdo, de forma que não existam metadados apontando para
+//! conteúdos inexistentes.
+
+use super::log::*;
+use crate::bo::arquivos::FileStore;
+use crate::model::arquivo::{Arquivo, NovoArquivo};
+use crate::model::schema::arquivo;
+use crate::routes::respostas::Resposta;
+use diesel::prelude::*;
+use serde_json::json;
+
+/// Lista os arquivos anexos a um produto, em ordem de envio.
+pub fn lista_arquivos_produto(conexao: &PgConnection, prodid: i32) -> Vec<Arquivo> {
+    arquivo::table
+        .filter(arquivo::produto_id.eq(prodid))
+        .order(arquivo::id.asc())
+        .load::<Arquivo>(conexao)
+        .expect("Erro ao carregar arquivos do produto")
+}
+
+/// Lista os arquivos anexos a uma movimentação de estoque, em ordem de envio.
+pub fn lista_arquivos_movimento(conexao: &PgConnection, movid: i32) -> Vec<Arquivo> {
+    arquivo::table
+        .filter(arquivo::mov_estoque_id.eq(movid))
+        .order(arquivo::id.asc())
+        .load::<Arquivo>(conexao)
+        .expect("Erro ao carregar arquivos da movimentação de estoque")
+}
+
+/// Retorna os metadados de um arquivo anexo, caso existente.
+pub fn get_arquivo(conexao: &PgConnection, arquivo_id: i32) -> Option<Arquivo> {
+    arquivo::table
+        .find(arquivo_id)
+        .first::<Arquivo>(conexao)
+        .optional()
+        .expect("Erro ao carregar arquivo")
+}
+
+/// Registra um novo arquivo anexo.
+///
+/// O conteúdo do arquivo é gravado no armazenamento sob a chave informada nos
+/// metadados, e então os metadados são inseridos no banco de dados. Caso a
+/// inserção falhe, o conteúdo gravado será removido. Esta função assume que o
+/// arquivo já tenha sido validado, e que seu dono exista.
+///
+/// Caso o arquivo seja registrado, serão retornados seus metadados.
+pub fn registra_arquivo(
+    conexao: &PgConnection,
+    armazenamento: &dyn FileStore,
+    dados: NovoArquivo,
+    conteudo: &[u8],
+) -> Resposta {
+    if armazenamento
+        .grava(&dados.chave, conteudo, &dados.content_type)
+        .is_err()
+    {
+        return Resposta::ErroInterno(
+            json!({
+                "mensagem":
+                    "Erro interno ao gravar arquivo. \
+                     Contate o suporte para mais informações."
+            })
+            .to_string(),
+        );
+    }
+
+    match diesel::insert_into(arquivo::table)
+        .values(&dados)
+        .get_result::<Arquivo>(conexao)
+    {
+        Ok(a) => {
+            let _ = registra_log(
+                conexao,
+                String::from("ARQUIVO"),
+                dados.usuario.clone(),
+                DBOperacao::Insercao,
+                Some(format!("Arquivo {} ({})", a.id, a.nome)),
+            );
+            Resposta::Ok(serde_json::to_string(&a).unwrap())
+        }
+        Err(e) => {
+            let _ = armazenamento.remove(&dados.chave);
+            match e {
+                diesel::result::Error::DatabaseError(
+                    diesel::result::DatabaseErrorKind::ForeignKeyViolation,
+                    _,
+                ) => Resposta::Conflito(
+                    json!({
+                        "mensagem": "O dono do arquivo não existe mais"
+                    })
+                    .to_string(),
+                ),
+                _ => Resposta::ErroInterno(
+                    json!({
+                        "mensagem":
+                            "Erro interno ao registrar arquivo. \
+                             Contate o suporte para mais informações."
+                    })
+                    .to_string(),
+                ),
+            }
+        }
+    }
+}
+
+/// Lê o conteúdo de um arquivo anexo a partir do armazenamento.
+///
+/// Caso o arquivo exista, serão retornados seus metadados e seu conteúdo.
+/// Caso contrário, será retornado um erro 404.
+pub fn le_arquivo(
+    conexao: &PgConnection,
+    armazenamento: &dyn FileStore,
+    arquivo_id: i32,
+) -> Result<(Arquivo, Vec<u8>), Resposta> {
+    let dados = get_arquivo(conexao, arquivo_id).ok_or_else(|| {
+        Resposta::NaoEncontrado(
+            json!({
+                "mensagem": "Arquivo não encontrado"
+            })
+            .to_string(),
+        )
+    })?;
+    match armazenamento.le(&dados.chave) {
+        Ok(conteudo) => Ok((dados, conteudo)),
+        Err(_) => Err(Resposta::ErroInterno(
+            json!({
+                "mensagem":
+                    "Erro interno ao ler arquivo. \
+                     Contate o suporte para mais informações."
+            })
+            .to_string(),
+        )),
+    }
+}
+
+/// Remove um arquivo anexo, tanto seus metadados quanto seu conteúdo.
+///
+/// Retorna `true` caso o arquivo existisse e tenha sido removido. A operação
+/// será registrada no log em nome do usuário informado.
+pub fn deleta_arquivo(
+    conexao: &PgConnection,
+    armazenamento: &dyn FileStore,
+    arquivo_id: i32,
+    usuario: &str,
+) -> bool {
+    let removido = diesel::delete(arquivo::table.find(arquivo_id))
+        .get_result::<Arquivo>(conexao)
+        .optional()
+        .expect("Erro ao remover arquivo");
+    match removido {
+        None => false,
+        Some(a) => {
+            let _ = armazenamento.remove(&a.chave);
+            let _ = registra_log(
+                conexao,
+                String::from("ARQUIVO"),
+                usuario.to_owned(),
+                DBOperacao::Remocao,
+                Some(format!("Arquivo {} ({})", a.id, a.nome)),
+            );
+            true
+        }
+    }
+}
+
+/// Lista as chaves de conteúdo de todos os arquivos anexos a produtos. Caso um
+/// produto seja informado, apenas as chaves de seus arquivos serão listadas.
+pub fn chaves_produtos(conexao: &PgConnection, prodid: Option<i32>) -> Vec<String> {
+    let mut query = arquivo::table
+        .select(arquivo::chave)
+        .filter(arquivo::produto_id.is_not_null())
+        .into_boxed();
+    if let Some(prodid) = prodid {
+        query = query.filter(arquivo::produto_id.eq(prodid));
+    }
+    query
+        .load::<String>(conexao)
+        .expect("Erro ao carregar chaves de arquivos")
+}
+
+/// Remove do armazenamento os conteúdos cujas chaves não possuam mais
+/// metadados no banco de dados.
+///
+/// Deve ser utilizada após operações que removam metadados de arquivos em
+/// cascata, como a remoção de produtos. Retorna a quantidade de conteúdos
+/// removidos.
+pub fn remove_orfaos(
+    conexao: &PgConnection,
+    armazenamento: &dyn FileStore,
+    chaves: Vec<String>,
+) -> usize {
+    let existentes: Vec<String> = arquivo::table
+        .select(arquivo::chave)
+        .filter(arquivo::chave.eq_any(&chaves))
+        .load(conexao)
+        .expect("Erro ao carregar chaves de arquivos");
+    chaves
+        .into_iter()
+        .filter(|c| !existentes.contains(c))
+        .filter(|c| armazenamento.remove(c).is_ok())
+        .count()
+}
@@ -0,0 +1,176 @@
+// controller/categorias.rs -- Uma parte de Minerva.rs
+// Copyright (C) 2021 Lucas S. Vieira
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with this program.  If not, see <https://www.gnu.org/licenses/>.
+
+//! Ferramentas para tráfego de dados entre as rotas de categorias de produtos
+//! e o banco de dados.
+//!
+//! As ferramentas deste módulo realizam o tráfego de dados entre as rotas de
+//! categorias e a tabela `categoria` do banco de dados.
+
+use super::log::*;
+use crate::bo;
+use crate::model::categoria::{Categoria, NovaCategoria};
+use crate::model::schema::categoria::dsl::*;
+use crate::routes::respostas::Resposta;
+use diesel::prelude::*;
+use serde_json::json;
+
+/// Lista todas as categorias cadastradas no sistema, em ordem de id.
+pub fn lista_categorias(conexao: &PgConnection) -> Vec<Categoria> {
+    categoria
+        .order(id.asc())
+        .load::<Categoria>(conexao)
+        .expect("Erro ao carregar categorias")
+}
+
+/// Retorna os dados de uma categoria, caso existente.
+pub fn get_categoria(conexao: &PgConnection, cat_id: i32) -> Option<Categoria> {
+    categoria
+        .find(cat_id)
+        .first::<Categoria>(conexao)
+        .optional()
+        .expect("Erro ao carregar categoria")
+}
+
+/// Retorna os ids de uma categoria e de todas as suas subcategorias, em
+/// qualquer nível.
+pub fn descendentes(conexao: &PgConnection, cat_id: i32) -> Vec<i32> {
+    bo::categorias::descendentes(&lista_categorias(conexao), cat_id)
+}
+
+/// Registra uma nova categoria no banco de dados.
+///
+/// Esta função assume que os dados da categoria sejam válidos. Caso a
+/// categoria seja cadastrada, será retornado seu id. Caso contrário, será
+/// retornada uma mensagem de erro em String.
+pub fn registra_categoria(
+    conexao: &PgConnection,
+    dados: NovaCategoria,
+    usuario: &str,
+) -> Result<i32, String> {
+    match diesel::insert_into(categoria)
+        .values(&dados)
+        .get_result::<Categoria>(conexao)
+    {
+        Ok(cat) => {
+            let _ = registra_log(
+                conexao,
+                String::from("CATEGORIA"),
+                usuario.to_owned(),
+                DBOperacao::Insercao,
+                Some(format!("Categoria {}", cat.id)),
+            );
+            Ok(cat.id)
+        }
+        Err(e) => {
+            if let diesel::result::Error::DatabaseError(_, _) = &e {
+                Err(format!("{}", e))
+            } else {
+                Err(String::from(
+                    "Erro interno ao cadastrar categoria. \
+                     Contate o suporte para mais informações.",
+                ))
+            }
+        }
+    }
+}
+
+/// Altera a descrição e a categoria pai de uma categoria.
+///
+/// Esta função assume que os dados da categoria sejam válidos, e que a
+/// categoria exista. Caso a categoria seja alterada, serão retornados seus
+/// dados atualizados. Caso contrário, será retornada uma mensagem de erro em
+/// String.
+pub fn altera_categoria(
+    conexao: &PgConnection,
+    cat_id: i32,
+    dados: NovaCategoria,
+    usuario: &str,
+) -> Result<Categoria, String> {
+    match diesel::update(categoria.find(cat_id))
+        .set(&dados)
+        .get_result::<Categoria>(conexao)
+    {
+        Ok(cat) => {
+            let _ = registra_log(
+                conexao,
+                String::from("CATEGORIA"),
+                usuario.to_owned(),
+                DBOperacao::Alteracao,
+                Some(format!("Categoria {}", cat.id)),
+            );
+            Ok(cat)
+        }
+        Err(e) => {
+            if let diesel::result::Error::DatabaseError(_, _) = &e {
+                Err(format!("{}", e))
+            } else {
+                Err(String::from(
+                    "Erro interno ao alterar categoria. \
+                     Contate o suporte para mais informações.",
+                ))
+            }
+        }
+    }
+}
+
+/// Deleta uma categoria do banco de dados.
+///
+/// Caso a categoria não exista, será retornado um erro 404. Caso a categoria
+/// possua subcategorias ou produtos associados, será retornado um erro 409.
+pub fn deleta_categoria(conexao: &PgConnection, cat_id: i32, usuario: &str) -> Resposta {
+    if get_categoria(conexao, cat_id).is_none() {
+        return Resposta::NaoEncontrado(
+            json!({
+                "mensagem": "Categoria não encontrada"
+            })
+            .to_string(),
+        );
+    }
+
+    match diesel::delete(categoria.find(cat_id)).execute(conexao) {
+        Ok(_) => {
+            let _ = registra_log(
+                conexao,
+                String::from("CATEGORIA"),
+                usuario.to_owned(),
+                DBOperacao::Remocao,
+                Some(format!("Categoria {}", cat_id)),
+            );
+            Resposta::Ok(json!({ "id": cat_id }).to_string())
+        }
+        Err(diesel::result::Error::DatabaseError(
+            diesel::result::DatabaseErrorKind::ForeignKeyViolation,
+            _,
+        )) => Resposta::Conflito(
+            json!({
+                "mensagem": format!(
+                    "A categoria {} possui subcategorias ou produtos e não pode ser removida",
+                    cat_id
+                )
+            })
+            .to_string(),
+        ),
+        Err(_) => Resposta::ErroInterno(
+            json!({
+                "mensagem":
+                    "Erro interno ao remover categoria. \
+                     Contate o suporte para mais informações."
+            })
+            .to_string(),
+        ),
+    }
+}
@@ -20,30 +20,52 @@
 //! As ferramentas deste módulo realizam o tráfego entre os dados recebidos
 //! através das rotas de clientes e a tabela `cliente` e relacionadas. Para as
 //! regras de negócio da aplicação, veja [`bo::clientes`][`crate::bo::clientes`].
+//!
+//! Os documentos e endereços dos clientes são encriptados antes de serem
+//! armazenados, e decriptados ao serem recuperados. Para tanto, as funções que
+//! manipulam esses dados requerem o [`Chaveiro`] da aplicação.
+//!
+//! Os documentos dos clientes retornados também poderão ser mascarados, de
+//! acordo com o [perfil][`PerfilUsuario`] do usuário que os requisitou.
 
 use super::log::*;
+use crate::bo;
+use crate::bo::cripto::Chaveiro;
 use crate::model::cliente::*;
 use crate::model::endereco::*;
+use crate::model::logdb::LogDB;
 use crate::model::schema::{cliente, endereco};
+use crate::model::usuario::PerfilUsuario;
+use crate::routes::respostas::Resposta;
+use bigdecimal::{BigDecimal, Signed, Zero};
+use chrono::NaiveDate;
 use diesel::prelude::*;
+use serde_json::json;
+
+/// Texto exibido no lugar de um dado encriptado que não pôde ser decriptado
+/// (ex: por ter sido encriptado com uma chave que não está mais no chaveiro).
+pub const DADO_ILEGIVEL: &str = "[ilegível]";
 
 /// Lista uma quantidade limitada de clientes cadastrados no sistema.
 ///
 /// Retorna um Vec com estruturas que representam os dados de um cliente,
 /// incluindo os endereços cadastrados para o mesmo. A quantidade de clientes
-/// retornados não será superior à informada no argumento `limite`.
-pub fn lista_clientes(conexao: &PgConnection, limite: i64) -> Vec<ClienteRepr> {
+/// retornados não será superior à informada no argumento `limite`. Os
+/// documentos serão mascarados caso o `perfil` informado não permita vê-los.
+pub fn lista_clientes(
+    conexao: &PgConnection,
+    chaveiro: &Chaveiro,
+    perfil: PerfilUsuario,
+    limite: i64,
+) -> Vec<ClienteRepr> {
     let cli_req = cliente::table
         .limit(limite)
         .load::<Cliente>(conexao)
         .expect("Erro ao carregar clientes");
-    let mut clientes = Vec::new();
-    for c in cli_req {
-        let enderecos = carrega_enderecos_cliente(conexao, c.id);
-        let repr = ClienteRepr::from((&c, enderecos));
-        clientes.push(repr);
-    }
-    clientes
+    cli_req
+        .into_iter()
+        .map(|c| repr_cliente(conexao, chaveiro, perfil, c))
+        .collect()
 }
 
 /// Retorna os dados de um cliente cadastrado no sistema.
@@ -51,43 +73,195 @@ pub fn lista_clientes(conexao: &PgConnection, limite: i64) -> Vec<ClienteRepr> {
 /// Será retornado um `Option` que poderá conter uma estrutura que representa
 /// os dados de um único cliente, incluindo os endereços cadastrados para o
 /// mesmo. O cliente será procurado de acordo com o seu id repassado no
-/// argumento `userid`.
-pub fn get_cliente(conexao: &PgConnection, userid: i32) -> Option<ClienteRepr> {
+/// argumento `userid`. O documento será mascarado caso o `perfil` informado
+/// não permita vê-lo.
+pub fn get_cliente(
+    conexao: &PgConnection,
+    chaveiro: &Chaveiro,
+    perfil: PerfilUsuario,
+    userid: i32,
+) -> Option<ClienteRepr> {
     use crate::model::schema::cliente::dsl::*;
     let cli_req = cliente
         .filter(id.eq(&userid))
         .load::<Cliente>(conexao)
         .expect("Erro ao carregar cliente");
-    match cli_req.first() {
-        None => None,
-        Some(cl) => {
-            let enderecos = carrega_enderecos_cliente(conexao, cl.id);
-            Some(ClienteRepr::from((cl, enderecos)))
-        }
-    }
+    cli_req
+        .into_iter()
+        .next()
+        .map(|cl| repr_cliente(conexao, chaveiro, perfil, cl))
+}
+
+/// Informa se um cliente com o id informado existe no sistema.
+pub fn existe_cliente(conexao: &PgConnection, userid: i32) -> bool {
+    use crate::model::schema::cliente::dsl::*;
+    diesel::select(diesel::dsl::exists(cliente.filter(id.eq(&userid))))
+        .get_result(conexao)
+        .expect("Erro ao verificar cliente")
+}
+
+/// Retorna o tipo de um cliente, caso o cliente exista. Ver
+/// [`Cliente::tipo`].
+pub fn get_tipo_cliente(conexao: &PgConnection, userid: i32) -> Option<i16> {
+    use crate::model::schema::cliente::dsl::*;
+    cliente
+        .find(userid)
+        .select(tipo)
+        .first::<i16>(conexao)
+        .optional()
+        .expect("Erro ao carregar cliente")
+}
+
+/// Retorna os dados de um cliente cadastrado no sistema, através de seu
+/// documento (CPF ou CNPJ).
+///
+/// Como o documento é armazenado de forma encriptada, a busca será feita
+/// através do hash do documento informado. Será retornado um `Option` que
+/// poderá conter uma estrutura que representa os dados do cliente, incluindo
+/// os endereços cadastrados para o mesmo. O documento retornado será mascarado
+/// caso o `perfil` informado não permita vê-lo.
+pub fn encontra_cliente_por_docto(
+    conexao: &PgConnection,
+    chaveiro: &Chaveiro,
+    perfil: PerfilUsuario,
+    cl_docto: &str,
+) -> Option<ClienteRepr> {
+    use crate::model::schema::cliente::dsl::*;
+    let cli_req = cliente
+        .filter(docto_hash.eq(chaveiro.hash_docto(cl_docto)))
+        .load::<Cliente>(conexao)
+        .expect("Erro ao carregar cliente");
+    cli_req
+        .into_iter()
+        .next()
+        .map(|cl| repr_cliente(conexao, chaveiro, perfil, cl))
+}
+
+/// Decripta os dados de um cliente e une-os com seus endereços, mascarando o
+/// documento do cliente caso o perfil informado não permita vê-lo.
+///
+/// Caso o documento não possa ser decriptado (ex: por ter sido encriptado com
+/// uma chave que não está mais no chaveiro), ele será substituído por
+/// [`DADO_ILEGIVEL`], para que um único registro ilegível não impeça a
+/// consulta dos demais.
+fn repr_cliente(
+    conexao: &PgConnection,
+    chaveiro: &Chaveiro,
+    perfil: PerfilUsuario,
+    mut cl: Cliente,
+) -> ClienteRepr {
+    cl.docto = match chaveiro.decripta_opcional(&cl.docto, cl.chave_versao) {
+        Ok(docto) if perfil.ve_documentos() => docto,
+        Ok(docto) => bo::clientes::mascara_docto(&docto),
+        Err(_) => String::from(DADO_ILEGIVEL),
+    };
+    let enderecos = carrega_enderecos_cliente(conexao, chaveiro, cl.id);
+    ClienteRepr::from((&cl, enderecos))
 }
 
 /// Registra um novo cliente no banco de dados.
 ///
 /// Esta função toma os dados do cliente recebidos através de uma requisição
 /// POST, e cadastra-os no banco de dados. Os dados recebidos não são avaliados
-/// quanto à sua validade, sendo inseridos diretamente no banco de dados.
+/// quanto à sua validade, sendo inseridos diretamente no banco de dados, após
+/// a encriptação dos dados sensíveis.
 /// Será retornado o id do cliente após ser cadastrado no banco de dados.
-pub fn registra_cliente(conexao: &PgConnection, dados: ClienteRecv) -> i32 {
-    let (cl_recv, end_recv) = dados.into();
+pub fn registra_cliente(conexao: &PgConnection, chaveiro: &Chaveiro, dados: ClienteRecv) -> i32 {
+    insere_cliente(conexao, chaveiro, dados, "TO-DO").expect("Erro ao inserir novo cliente")
+}
+
+/// Insere um novo cliente e seus endereços no banco de dados, após a
+/// encriptação dos dados sensíveis, registrando as operações no log em nome do
+/// usuário informado.
+fn insere_cliente(
+    conexao: &PgConnection,
+    chaveiro: &Chaveiro,
+    dados: ClienteRecv,
+    usuario: &str,
+) -> QueryResult<i32> {
+    let (mut cl_recv, end_recv): (NovoCliente, Vec<EnderecoRecv>) = dados.into();
+    encripta_cliente(chaveiro, &mut cl_recv);
     let c: Cliente = diesel::insert_into(cliente::table)
         .values(&cl_recv)
-        .get_result(conexao)
-        .expect("Erro ao inserir novo cliente");
+        .get_result(conexao)?;
     let _ = registra_log(
         conexao,
         String::from("CLIENTE"),
-        String::from("TO-DO"),
+        usuario.to_owned(),
         DBOperacao::Insercao,
         Some(format!("Cliente {}", c.id)),
     );
-    registra_enderecos_cliente(conexao, c.id, end_recv);
-    c.id
+    registra_enderecos_cliente(conexao, chaveiro, c.id, end_recv, usuario)?;
+    Ok(c.id)
+}
+
+/// Importa uma coleção de clientes no banco de dados.
+///
+/// Os clientes e seus endereços serão cadastrados em uma única transação, de
+/// forma que, caso algum cadastro falhe, nenhum cliente seja importado. Os
+/// dados recebidos não são avaliados quanto à sua validade; veja
+/// [`bo::clientes::valida_importacao`][`crate::bo::clientes::valida_importacao`].
+///
+/// As operações serão registradas no log em nome do usuário informado. Em
+/// caso de sucesso, serão retornados os ids dos clientes cadastrados, na mesma
+/// ordem em que foram informados. Caso contrário, será retornada uma mensagem
+/// de erro em String.
+pub fn importa_clientes(
+    conexao: &PgConnection,
+    chaveiro: &Chaveiro,
+    dados: Vec<ClienteRecv>,
+    usuario: &str,
+) -> Result<Vec<i32>, String> {
+    let resultado = conexao.transaction::<_, diesel::result::Error, _>(|| {
+        let ids = dados
+            .into_iter()
+            .map(|cl| insere_cliente(conexao, chaveiro, cl, usuario))
+            .collect::<QueryResult<Vec<i32>>>()?;
+        let _ = registra_log(
+            conexao,
+            String::from("CLIENTE"),
+            usuario.to_owned(),
+            DBOperacao::Insercao,
+            Some(format!("Importação de {} clientes", ids.len())),
+        );
+        Ok(ids)
+    });
+
+    resultado.map_err(|e| {
+        if let diesel::result::Error::DatabaseError(_, _) = &e {
+            format!("{}", e)
+        } else {
+            String::from(
+                "Erro interno ao importar clientes. \
+                 Contate o suporte para mais informações.",
+            )
+        }
+    })
+}
+
+/// Informa quais dos documentos (CPF ou CNPJ) informados já pertencem a
+/// clientes cadastrados no sistema.
+///
+/// A busca será feita através do hash dos documentos. Serão retornados os
+/// documentos já cadastrados, como foram informados.
+pub fn doctos_cadastrados(
+    conexao: &PgConnection,
+    chaveiro: &Chaveiro,
+    doctos: &[String],
+) -> Vec<String> {
+    use crate::model::schema::cliente::dsl::*;
+    let hashes: Vec<String> = doctos.iter().map(|d| chaveiro.hash_docto(d)).collect();
+    let encontrados = cliente
+        .select(docto_hash)
+        .filter(docto_hash.eq_any(&hashes))
+        .load::<Option<String>>(conexao)
+        .expect("Erro ao carregar clientes");
+    doctos
+        .iter()
+        .zip(hashes)
+        .filter(|(_, hash)| encontrados.contains(&Some(hash.clone())))
+        .map(|(d, _)| d.clone())
+        .collect()
 }
 
 /// Deleta um cliente em específico no banco de dados.
@@ -110,6 +284,11 @@ pub fn deleta_cliente(conexao: &PgConnection, cl: ClienteRepr) {
 /// retornando uma tuple contendo, respectivamente, as quantidades de registros
 /// de usuários e de endereços deletados neste processo.
 /// Utilize esta função com cuidado.
+///
+/// Esta função assume que não existam títulos registrados para os clientes,
+/// nem movimentações de estoque que os referenciem; veja
+/// [`existem_titulos`][`super::titulos::existem_titulos`] e
+/// [`existem_movimentos_clientes`][`super::estoque::existem_movimentos_clientes`].
 pub fn deleta_todos(conexao: &PgConnection) -> (usize, usize) {
     let num_end = diesel::delete(endereco::table)
         .execute(conexao)
@@ -134,43 +313,404 @@ pub fn deleta_todos(conexao: &PgConnection) -> (usize, usize) {
     (num_end, num_cl)
 }
 
+/// Calcula a situação de crédito de um cliente.
+///
+/// O saldo devedor do cliente será calculado a partir de seus títulos em
+/// aberto, e serão considerados vencidos os títulos em aberto cujo vencimento
+/// seja anterior à data informada. Será retornado um `Option` que conterá a
+/// situação de crédito, caso o cliente com o id informado exista.
+///
+/// Esta função apenas calcula a situação de crédito, sem modificar o cliente.
+/// Para aplicar as regras de bloqueio automático, veja
+/// [`aplica_regras_credito`].
+pub fn situacao_credito(
+    conexao: &PgConnection,
+    userid: i32,
+    data: NaiveDate,
+) -> Option<SituacaoCredito> {
+    use super::titulos;
+    let cl = {
+        use crate::model::schema::cliente::dsl::*;
+        cliente
+            .filter(id.eq(&userid))
+            .first::<Cliente>(conexao)
+            .optional()
+            .expect("Erro ao carregar cliente")?
+    };
+
+    let abertos = titulos::lista_titulos_abertos(conexao, cl.id);
+    let saldo_devedor: BigDecimal = abertos.iter().map(|t| t.valor_aberto()).sum();
+    let valor_vencido: BigDecimal = abertos
+        .iter()
+        .filter(|t| t.vencimento < data)
+        .map(|t| t.valor_aberto())
+        .sum();
+    let credito_disponivel = cl.limite_credito.clone() - saldo_devedor.clone();
+    let credito_disponivel = if credito_disponivel.is_negative() {
+        BigDecimal::zero()
+    } else {
+        credito_disponivel
+    };
+
+    Some(SituacaoCredito {
+        cliente_id: cl.id,
+        limite_credito: cl.limite_credito,
+        saldo_devedor,
+        credito_disponivel,
+        valor_vencido,
+        ativo: cl.ativo,
+        bloqueado: cl.bloqueado,
+        liberado_ate: cl.liberado_ate,
+    })
+}
+
+/// Usuário em nome do qual as operações automáticas do sistema serão
+/// registradas no log.
+const USUARIO_SISTEMA: &str = "SISTEMA";
+
+/// Calcula a situação de crédito de um cliente e aplica as regras de bloqueio
+/// automático.
+///
+/// Caso as regras de crédito exijam o bloqueio do cliente (veja
+/// [`motivo_bloqueio`][`bo::clientes::motivo_bloqueio`]), o mesmo será
+/// bloqueado, e o motivo do bloqueio será registrado no log em nome do
+/// sistema.
+///
+/// Será retornado um `Option` que conterá a situação de crédito atualizada,
+/// caso o cliente com o id informado exista. Caso o bloqueio falhe, será
+/// retornado o erro do banco de dados.
+pub fn aplica_regras_credito(
+    conexao: &PgConnection,
+    userid: i32,
+) -> QueryResult<Option<SituacaoCredito>> {
+    let hoje = chrono::Local::today().naive_local();
+    let mut situacao = match situacao_credito(conexao, userid, hoje) {
+        None => return Ok(None),
+        Some(situacao) => situacao,
+    };
+    if let Some(motivo) = bo::clientes::motivo_bloqueio(&situacao, hoje) {
+        bloqueia_cliente(conexao, userid, &motivo, USUARIO_SISTEMA)?;
+        situacao.bloqueado = true;
+    }
+    Ok(Some(situacao))
+}
+
+/// Verifica se um cliente pode realizar uma operação a prazo do valor
+/// informado.
+///
+/// Esta é a verificação a ser utilizada antes de qualquer operação que
+/// aumente o saldo devedor de um cliente (como uma venda a prazo). As regras
+/// de bloqueio automático serão aplicadas antes da verificação; veja
+/// [`aplica_regras_credito`] e
+/// [`verifica_credito`][`bo::clientes::verifica_credito`].
+///
+/// Em caso de sucesso, será retornada a situação de crédito do cliente antes
+/// da operação. Caso o cliente não exista, será retornado um erro 404; caso a
+/// operação não seja permitida, um erro 422; e caso o bloqueio automático
+/// falhe, um erro 500.
+pub fn verifica_credito(
+    conexao: &PgConnection,
+    userid: i32,
+    valor: &BigDecimal,
+) -> Result<SituacaoCredito, Resposta> {
+    let situacao = match aplica_regras_credito(conexao, userid) {
+        Err(_) => return Err(erro_regras_credito()),
+        Ok(None) => {
+            return Err(Resposta::NaoEncontrado(
+                json!({
+                    "mensagem": "Cliente não encontrado"
+                })
+                .to_string(),
+            ))
+        }
+        Ok(Some(situacao)) => situacao,
+    };
+    let hoje = chrono::Local::today().naive_local();
+    bo::clientes::verifica_credito(&situacao, valor, hoje).map_err(Resposta::ErroSemantico)?;
+    Ok(situacao)
+}
+
+/// Gera uma resposta padrão para falhas ao aplicar as regras de crédito de um
+/// cliente.
+pub fn erro_regras_credito() -> Resposta {
+    Resposta::ErroInterno(
+        json!({
+            "mensagem":
+                "Erro interno ao aplicar as regras de crédito do cliente. \
+                 Contate o suporte para mais informações."
+        })
+        .to_string(),
+    )
+}
+
+/// Bloqueia um cliente, registrando o motivo do bloqueio no log em nome do
+/// usuário informado.
+pub fn bloqueia_cliente(
+    conexao: &PgConnection,
+    userid: i32,
+    motivo: &str,
+    usuario: &str,
+) -> QueryResult<()> {
+    use crate::model::schema::cliente::dsl::*;
+    diesel::update(cliente.filter(id.eq(&userid)))
+        .set(bloqueado.eq(true))
+        .execute(conexao)?;
+    let _ = registra_log(
+        conexao,
+        String::from("CLIENTE"),
+        usuario.to_owned(),
+        DBOperacao::Alteracao,
+        Some(format!("Cliente {}: bloqueio: {}", userid, motivo)),
+    );
+    Ok(())
+}
+
+/// Altera o limite de crédito de um cliente, registrando a operação no log em
+/// nome do usuário informado.
+///
+/// O limite informado não é avaliado quanto à sua validade.
+pub fn altera_limite_credito(
+    conexao: &PgConnection,
+    userid: i32,
+    limite: &BigDecimal,
+    usuario: &str,
+) -> QueryResult<()> {
+    use crate::model::schema::cliente::dsl::*;
+    diesel::update(cliente.filter(id.eq(&userid)))
+        .set(limite_credito.eq(limite))
+        .execute(conexao)?;
+    let _ = registra_log(
+        conexao,
+        String::from("CLIENTE"),
+        usuario.to_owned(),
+        DBOperacao::Alteracao,
+        Some(format!(
+            "Cliente {}: limite de crédito alterado para {}",
+            userid, limite
+        )),
+    );
+    Ok(())
+}
+
+/// Libera manualmente o crédito de um cliente até a data informada.
+///
+/// O cliente será desbloqueado e, até a data de validade informada, não será
+/// bloqueado automaticamente pelas regras de crédito. O motivo da liberação
+/// será registrado no log em nome do usuário informado.
+pub fn libera_credito(
+    conexao: &PgConnection,
+    userid: i32,
+    recv: &LiberacaoCreditoRecv,
+    usuario: &str,
+) -> QueryResult<()> {
+    use crate::model::schema::cliente::dsl::*;
+    diesel::update(cliente.filter(id.eq(&userid)))
+        .set((bloqueado.eq(false), liberado_ate.eq(Some(recv.validade))))
+        .execute(conexao)?;
+    let _ = registra_log(
+        conexao,
+        String::from("CLIENTE"),
+        usuario.to_owned(),
+        DBOperacao::Alteracao,
+        Some(format!(
+            "Cliente {}: liberação de crédito até {}: {}",
+            userid, recv.validade, recv.motivo
+        )),
+    );
+    Ok(())
+}
+
+/// Texto utilizado em substituição aos dados pessoais de um cliente
+/// anonimizado.
+const TEXTO_ANONIMIZADO: &str = "ANONIMIZADO";
+
+/// Gera um documento de exportação com todos os dados mantidos a respeito de
+/// um cliente.
+///
+/// O documento envolve os dados cadastrais do cliente, seus endereços e todos
+/// os registros de log relacionados a ambos. Será retornado um `Option` que
+/// conterá o documento de exportação, caso o cliente com o id informado exista.
+/// Assim como nas demais consultas, o documento do cliente será mascarado caso
+/// o `perfil` informado não permita vê-lo.
+pub fn exporta_dados_cliente(
+    conexao: &PgConnection,
+    chaveiro: &Chaveiro,
+    perfil: PerfilUsuario,
+    userid: i32,
+) -> Option<ClienteExportacao> {
+    let cliente = get_cliente(conexao, chaveiro, perfil, userid)?;
+    let logs = recupera_log_cliente(conexao, &cliente);
+    Some(ClienteExportacao {
+        datahora: chrono::offset::Utc::now(),
+        cliente,
+        logs,
+    })
+}
+
+/// Anonimiza irreversivelmente os dados pessoais de um cliente.
+///
+/// O registro do cliente e de seus endereços será mantido no banco de dados,
+/// para que os registros que os referenciem continuem consistentes, mas os
+/// campos que identificam o cliente serão sobrescritos. A UF e a cidade dos
+/// endereços serão mantidas, por não identificarem o titular dos dados. O
+/// cliente também será marcado como inativo e bloqueado.
+///
+/// Os campos anonimizados não possuem dados pessoais, e portanto serão
+/// armazenados em texto-plano.
+///
+/// A operação é realizada em uma única transação e será registrada no log em
+/// nome do usuário informado. Esta função assume que o cliente informado
+/// exista e ainda não tenha sido anonimizado. Em caso de erro, será retornada
+/// uma mensagem de erro em String.
+pub fn anonimiza_cliente(
+    conexao: &PgConnection,
+    cl: ClienteRepr,
+    usuario: &str,
+) -> Result<i32, String> {
+    let resultado = conexao.transaction::<_, diesel::result::Error, _>(|| {
+        {
+            use crate::model::schema::cliente::dsl::*;
+            diesel::update(cliente.filter(id.eq(&cl.id)))
+                .set((
+                    nome.eq(TEXTO_ANONIMIZADO),
+                    docto.eq(format!("{}-{}", TEXTO_ANONIMIZADO, cl.id)),
+                    docto_hash.eq(None::<String>),
+                    chave_versao.eq(None::<i16>),
+                    ativo.eq(false),
+                    bloqueado.eq(true),
+                    anonimizado.eq(true),
+                ))
+                .execute(conexao)?;
+        }
+
+        {
+            use crate::model::schema::endereco::dsl::*;
+            diesel::update(endereco.filter(cliente_id.eq(&cl.id)))
+                .set((
+                    logradouro.eq(TEXTO_ANONIMIZADO),
+                    numero.eq(""),
+                    complemento.eq(None::<String>),
+                    bairro.eq(TEXTO_ANONIMIZADO),
+                    chave_versao.eq(None::<i16>),
+                ))
+                .execute(conexao)?;
+        }
+
+        let _ = registra_log(
+            conexao,
+            String::from("CLIENTE"),
+            usuario.to_owned(),
+            DBOperacao::Alteracao,
+            Some(format!("Cliente {}: anonimização de dados pessoais", cl.id)),
+        );
+        Ok(cl.id)
+    });
+
+    resultado.map_err(|e| {
+        if let diesel::result::Error::DatabaseError(_, _) = &e {
+            format!("{}", e)
+        } else {
+            String::from(
+                "Erro interno ao anonimizar cliente. \
+                 Contate o suporte para mais informações.",
+            )
+        }
+    })
+}
+
+/// Recupera os registros de log relacionados a um cliente e aos seus
+/// endereços, em ordem crescente de data.
+///
+/// Serão considerados os registros da tabela `CLIENTE` que descrevam o
+/// cliente, e os registros da tabela `ENDERECO` que descrevam algum de seus
+/// endereços.
+fn recupera_log_cliente(conexao: &PgConnection, cl: &ClienteRepr) -> Vec<LogDB> {
+    use crate::model::schema::logdb::dsl::*;
+    let desc_cliente = format!("Cliente {}", cl.id);
+    let desc_enderecos: Vec<String> = cl
+        .enderecos
+        .iter()
+        .map(|e| format!("Endereço {}", e.id))
+        .collect();
+
+    logdb
+        .filter(
+            tabela
+                .eq("CLIENTE")
+                .and(
+                    descricao
+                        .eq(&desc_cliente)
+                        .or(descricao.like(format!("{}: %", desc_cliente))),
+                )
+                .or(tabela.eq("ENDERECO").and(descricao.eq_any(desc_enderecos))),
+        )
+        .order(datahora.asc())
+        .load::<LogDB>(conexao)
+        .expect("Erro ao recuperar logs do cliente")
+}
+
 /// Registra os dados de endereços para um cliente em específico.
 ///
 /// Esta função assume que os dados de endereços recebidos estejam corretos,
 /// e também assume que o cliente, cujo id tenha sido informado via parâmetro,
-/// já tenha sido inserido no banco de dados.
+/// já tenha sido inserido no banco de dados. As operações serão registradas no
+/// log em nome do usuário informado.
 fn registra_enderecos_cliente(
     conexao: &PgConnection,
+    chaveiro: &Chaveiro,
     cliente_id: i32,
     enderecos: Vec<EnderecoRecv>,
-) {
+    usuario: &str,
+) -> QueryResult<()> {
     for e_recv in enderecos {
         let mut e: NovoEndereco = e_recv.into();
         e.cliente_id = cliente_id;
+        encripta_endereco(chaveiro, &mut e);
         let e_ins: Endereco = diesel::insert_into(endereco::table)
             .values(&e)
-            .get_result(conexao)
-            .expect("Erro ao salvar endereco");
+            .get_result(conexao)?;
         let _ = registra_log(
             conexao,
             String::from("ENDERECO"),
-            String::from("TO-DO"),
+            usuario.to_owned(),
             DBOperacao::Insercao,
             Some(format!("Endereço {}", e_ins.id)),
         );
     }
+    Ok(())
 }
 
 /// Recupera uma coleção de endereços para um cliente em específico.
 ///
 /// Esta função procurará pelos endereços que apontem para o cliente cujo id
-/// foi informado via parâmetro.
-fn carrega_enderecos_cliente(conexao: &PgConnection, userid: i32) -> Vec<Endereco> {
+/// foi informado via parâmetro. Os endereços serão retornados decriptados;
+/// os dados de endereços que não possam ser decriptados serão substituídos por
+/// [`DADO_ILEGIVEL`].
+fn carrega_enderecos_cliente(
+    conexao: &PgConnection,
+    chaveiro: &Chaveiro,
+    userid: i32,
+) -> Vec<Endereco> {
     use crate::model::schema::endereco::dsl::*;
     endereco
         .filter(cliente_id.eq(&userid))
         .load::<Endereco>(conexao)
         .expect("Erro ao carregar endereços")
+        .into_iter()
+        .map(|mut e| {
+            let versao = e.chave_versao;
+            let decripta = |texto: &str| {
+                chaveiro
+                    .decripta_opcional(texto, versao)
+                    .unwrap_or_else(|_| String::from(DADO_ILEGIVEL))
+            };
+            e.logradouro = decripta(&e.logradouro);
+            e.numero = decripta(&e.numero);
+            e.complemento = e.complemento.as_deref().map(decripta);
+            e.bairro = decripta(&e.bairro);
+            e
+        })
+        .collect()
 }
 
 /// Deleta todos os endereços referenciados.
@@ -192,3 +732,137 @@ fn deleta_enderecos(conexao: &PgConnection, enderecos: Vec<Endereco>) {
         );
     }
 }
+
+/// Encripta o documento de um cliente a ser cadastrado, calculando também o
+/// hash do documento para buscas exatas.
+fn encripta_cliente(chaveiro: &Chaveiro, cl: &mut NovoCliente) {
+    let (docto, versao) = chaveiro.encripta(&cl.docto);
+    cl.docto_hash = Some(chaveiro.hash_docto(&cl.docto));
+    cl.docto = docto;
+    cl.chave_versao = Some(versao);
+}
+
+/// Decripta o documento de um cliente recuperado do banco de dados.
+///
+/// Caso o documento não possa ser decriptado, será retornada uma mensagem de
+/// erro em String.
+fn decripta_cliente(chaveiro: &Chaveiro, mut cl: Cliente) -> Result<Cliente, String> {
+    cl.docto = chaveiro
+        .decripta_opcional(&cl.docto, cl.chave_versao)
+        .map_err(|e| format!("Erro ao decriptar documento do cliente {}: {}", cl.id, e))?;
+    Ok(cl)
+}
+
+/// Encripta os dados sensíveis de um endereço a ser cadastrado.
+fn encripta_endereco(chaveiro: &Chaveiro, e: &mut NovoEndereco) {
+    e.logradouro = chaveiro.encripta(&e.logradouro).0;
+    e.numero = chaveiro.encripta(&e.numero).0;
+    e.complemento = e.complemento.as_ref().map(|c| chaveiro.encripta(c).0);
+    e.bairro = chaveiro.encripta(&e.bairro).0;
+    e.chave_versao = Some(chaveiro.versao_atual());
+}
+
+/// Decripta os dados sensíveis de um endereço recuperado do banco de dados.
+///
+/// Caso algum dos dados não possa ser decriptado, será retornada uma mensagem
+/// de erro em String.
+fn decripta_endereco(chaveiro: &Chaveiro, mut e: Endereco) -> Result<Endereco, String> {
+    let versao = e.chave_versao;
+    let id_endereco = e.id;
+    let decripta = |texto: &str| {
+        chaveiro
+            .decripta_opcional(texto, versao)
+            .map_err(|erro| format!("Erro ao decriptar endereço {}: {}", id_endereco, erro))
+    };
+    e.logradouro = decripta(&e.logradouro)?;
+    e.numero = decripta(&e.numero)?;
+    e.complemento = e.complemento.as_deref().map(decripta).transpose()?;
+    e.bairro = decripta(&e.bairro)?;
+    Ok(e)
+}
+
+/// Encripta os dados de clientes e endereços que não estejam encriptados com a
+/// chave atual.
+///
+/// Esta função deve ser utilizada para encriptar registros anteriores à
+/// encriptação de dados, bem como para reencriptar registros após a rotação
+/// da chave de encriptação. Clientes anonimizados não serão modificados.
+///
+/// Toda a operação será realizada em uma única transação. Em caso de sucesso,
+/// será retornada uma tuple contendo, respectivamente, as quantidades de
+/// clientes e de endereços encriptados. Caso contrário (inclusive caso algum
+/// registro não possa ser decriptado com as chaves do chaveiro), nenhum
+/// registro será modificado, e será retornada uma mensagem de erro em String.
+pub fn encripta_registros(
+    conexao: &PgConnection,
+    chaveiro: &Chaveiro,
+) -> Result<(usize, usize), String> {
+    let versao_atual = Some(chaveiro.versao_atual());
+    let mut erro_decriptacao = None;
+    conexao
+        .transaction::<_, diesel::result::Error, _>(|| {
+            let clientes = {
+                use crate::model::schema::cliente::dsl::*;
+                cliente
+                    .filter(anonimizado.eq(false))
+                    .filter(chave_versao.is_null().or(chave_versao.ne(versao_atual)))
+                    .load::<Cliente>(conexao)?
+            };
+
+            let num_clientes = clientes.len();
+            for cl in clientes {
+                use crate::model::schema::cliente::dsl::*;
+                let texto = match decripta_cliente(chaveiro, cl) {
+                    Ok(texto) => texto,
+                    Err(erro) => {
+                        erro_decriptacao = Some(erro);
+                        return Err(diesel::result::Error::RollbackTransaction);
+                    }
+                };
+                let (novo_docto, versao) = chaveiro.encripta(&texto.docto);
+                diesel::update(cliente.filter(id.eq(&texto.id)))
+                    .set((
+                        docto.eq(novo_docto),
+                        docto_hash.eq(chaveiro.hash_docto(&texto.docto)),
+                        chave_versao.eq(versao),
+                    ))
+                    .execute(conexao)?;
+            }
+
+            let enderecos = {
+                use crate::model::schema::cliente::dsl::{anonimizado, cliente, id as cl_id};
+                use crate::model::schema::endereco::dsl::*;
+                endereco
+                    .filter(chave_versao.is_null().or(chave_versao.ne(versao_atual)))
+                    .filter(cliente_id.eq_any(cliente.select(cl_id).filter(anonimizado.eq(false))))
+                    .load::<Endereco>(conexao)?
+            };
+
+            let num_enderecos = enderecos.len();
+            for e in enderecos {
+                use crate::model::schema::endereco::dsl::*;
+                let texto = match decripta_endereco(chaveiro, e) {
+                    Ok(texto) => texto,
+                    Err(erro) => {
+                        erro_decriptacao = Some(erro);
+                        return Err(diesel::result::Error::RollbackTransaction);
+                    }
+                };
+                diesel::update(endereco.filter(id.eq(&texto.id)))
+                    .set((
+                        logradouro.eq(chaveiro.encripta(&texto.logradouro).0),
+                        numero.eq(chaveiro.encripta(&texto.numero).0),
+                        complemento.eq(texto.complemento.as_ref().map(|c| chaveiro.encripta(c).0)),
+                        bairro.eq(chaveiro.encripta(&texto.bairro).0),
+                        chave_versao.eq(chaveiro.versao_atual()),
+                    ))
+                    .execute(conexao)?;
+            }
+
+            Ok((num_clientes, num_enderecos))
+        })
+        .map_err(|e| match erro_decriptacao.take() {
+            Some(erro) => format!("Erro ao encriptar registros: {}", erro),
+            None => format!("Erro ao encriptar registros: {}", e),
+        })
+}
@@ -0,0 +1,178 @@
+// controller/depositos.rs -- Uma parte de Minerva.rs
+// Copyright (C) 2021 Lucas S. Vieira
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with this program.  If not, see <https://www.gnu.org/licenses/>.
+
+//! Ferramentas para tráfego de dados entre as rotas de depósitos e o banco de
+//! dados.
+//!
+//! As ferramentas deste módulo realizam o tráfego de dados entre as rotas de
+//! depósitos e a tabela `deposito` do banco de dados.
+
+use super::log::*;
+use crate::model::deposito::{Deposito, NovoDeposito, DEPOSITO_PADRAO};
+use crate::model::schema::deposito::dsl::*;
+use crate::routes::respostas::Resposta;
+use diesel::prelude::*;
+use serde_json::json;
+
+/// Lista todos os depósitos cadastrados no sistema, em ordem de id.
+pub fn lista_depositos(conexao: &PgConnection) -> Vec<Deposito> {
+    deposito
+        .order(id.asc())
+        .load::<Deposito>(conexao)
+        .expect("Erro ao carregar depósitos")
+}
+
+/// Retorna os dados de um depósito, caso existente.
+pub fn get_deposito(conexao: &PgConnection, dep_id: i32) -> Option<Deposito> {
+    deposito
+        .find(dep_id)
+        .first::<Deposito>(conexao)
+        .optional()
+        .expect("Erro ao carregar depósito")
+}
+
+/// Registra um novo depósito no banco de dados.
+///
+/// Esta função assume que os dados do depósito sejam válidos. Caso o depósito
+/// seja cadastrado, será retornado seu id. Caso contrário, será retornada uma
+/// mensagem de erro em String.
+pub fn registra_deposito(
+    conexao: &PgConnection,
+    dados: NovoDeposito,
+    usuario: &str,
+) -> Result<i32, String> {
+    match diesel::insert_into(deposito)
+        .values(&dados)
+        .get_result::<Deposito>(conexao)
+    {
+        Ok(dep) => {
+            let _ = registra_log(
+                conexao,
+                String::from("DEPOSITO"),
+                usuario.to_owned(),
+                DBOperacao::Insercao,
+                Some(format!("Depósito {}", dep.id)),
+            );
+            Ok(dep.id)
+        }
+        Err(e) => {
+            if let diesel::result::Error::DatabaseError(_, _) = &e {
+                Err(format!("{}", e))
+            } else {
+                Err(String::from(
+                    "Erro interno ao cadastrar depósito. \
+                     Contate o suporte para mais informações.",
+                ))
+            }
+        }
+    }
+}
+
+/// Altera a descrição e a situação de um depósito.
+///
+/// Esta função assume que os dados do depósito sejam válidos, e que o depósito
+/// exista. Caso o depósito seja alterado, serão retornados seus dados
+/// atualizados. Caso contrário, será retornada uma mensagem de erro em String.
+pub fn altera_deposito(
+    conexao: &PgConnection,
+    dep_id: i32,
+    dados: NovoDeposito,
+    usuario: &str,
+) -> Result<Deposito, String> {
+    match diesel::update(deposito.find(dep_id))
+        .set(&dados)
+        .get_result::<Deposito>(conexao)
+    {
+        Ok(dep) => {
+            let _ = registra_log(
+                conexao,
+                String::from("DEPOSITO"),
+                usuario.to_owned(),
+                DBOperacao::Alteracao,
+                Some(format!("Depósito {}", dep.id)),
+            );
+            Ok(dep)
+        }
+        Err(e) => {
+            if let diesel::result::Error::DatabaseError(_, _) = &e {
+                Err(format!("{}", e))
+            } else {
+                Err(String::from(
+                    "Erro interno ao alterar depósito. \
+                     Contate o suporte para mais informações.",
+                ))
+            }
+        }
+    }
+}
+
+/// Deleta um depósito do banco de dados.
+///
+/// Caso o depósito não exista, será retornado um erro 404. Caso o depósito
+/// seja o depósito padrão, ou possua posições ou movimentações de estoque,
+/// será retornado um erro 409.
+pub fn deleta_deposito(conexao: &PgConnection, dep_id: i32, usuario: &str) -> Resposta {
+    if get_deposito(conexao, dep_id).is_none() {
+        return Resposta::NaoEncontrado(
+            json!({
+                "mensagem": "Depósito não encontrado"
+            })
+            .to_string(),
+        );
+    }
+
+    if dep_id == DEPOSITO_PADRAO {
+        return Resposta::Conflito(
+            json!({
+                "mensagem": "O depósito padrão não pode ser removido"
+            })
+            .to_string(),
+        );
+    }
+
+    match diesel::delete(deposito.find(dep_id)).execute(conexao) {
+        Ok(_) => {
+            let _ = registra_log(
+                conexao,
+                String::from("DEPOSITO"),
+                usuario.to_owned(),
+                DBOperacao::Remocao,
+                Some(format!("Depósito {}", dep_id)),
+            );
+            Resposta::Ok(json!({ "id": dep_id }).to_string())
+        }
+        Err(diesel::result::Error::DatabaseError(
+            diesel::result::DatabaseErrorKind::ForeignKeyViolation,
+            _,
+        )) => Resposta::Conflito(
+            json!({
+                "mensagem": format!(
+                    "O depósito {} possui estoque ou movimentações e não pode ser removido",
+                    dep_id
+                )
+            })
+            .to_string(),
+        ),
+        Err(_) => Resposta::ErroInterno(
+            json!({
+                "mensagem":
+                    "Erro interno ao remover depósito. \
+                     Contate o suporte para mais informações."
+            })
+            .to_string(),
+        ),
+    }
+}
@@ -0,0 +1,70 @@
+// controller/documentos.rs -- Uma parte de Minerva.rs
+// Copyright (C) 2021 Lucas S. Vieira
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with this program.  If not, see <https://www.gnu.org/licenses/>.
+
+//! Ferramentas para tráfego de dados entre as rotas de documentos de estoque e
+//! o banco de dados.
+//!
+//! As ferramentas deste módulo realizam o tráfego de dados entre as rotas de
+//! documentos de estoque e a tabela `documento_estoque` do banco de dados. O
+//! registro de um documento ocorre junto das movimentações de estoque de seus
+//! itens; veja
+//! [`registra_documento`][`super::estoque::registra_documento`].
+
+use crate::model::documento::*;
+use crate::model::estoque::MovEstoque;
+use crate::model::schema::{documento_estoque, mov_estoque};
+use diesel::prelude::*;
+
+/// Retorna um documento de estoque junto das movimentações de estoque de seus
+/// itens, caso exista.
+pub fn get_documento(conexao: &PgConnection, documento_id: i32) -> Option<DocumentoEstoqueRepr> {
+    let documento = documento_estoque::table
+        .find(documento_id)
+        .first::<DocumentoEstoque>(conexao)
+        .optional()
+        .expect("Erro ao carregar documento de estoque")?;
+    let itens = mov_estoque::table
+        .filter(mov_estoque::documento_estoque_id.eq(documento.id))
+        .order(mov_estoque::id)
+        .load::<MovEstoque>(conexao)
+        .expect("Erro ao carregar itens do documento de estoque");
+    Some(DocumentoEstoqueRepr { documento, itens })
+}
+
+/// Lista os cabeçalhos dos documentos de estoque, de um número de documento e
+/// de um fornecedor, caso informados.
+///
+/// Os documentos são ordenados do mais recente para o mais antigo, e são
+/// retornados no máximo `limite` documentos.
+pub fn lista_documentos(
+    conexao: &PgConnection,
+    docto: Option<String>,
+    fornecedor: Option<i32>,
+    limite: i64,
+) -> Vec<DocumentoEstoque> {
+    let mut query = documento_estoque::table.into_boxed();
+    if let Some(docto) = docto {
+        query = query.filter(documento_estoque::docto.eq(docto));
+    }
+    if let Some(fornecedor) = fornecedor {
+        query = query.filter(documento_estoque::fornecedor_id.eq(fornecedor));
+    }
+    query
+        .order(documento_estoque::id.desc())
+        .limit(limite)
+        .load::<DocumentoEstoque>(conexao)
+        .expect("Erro ao carregar documentos de estoque")
+}
@@ -22,45 +22,100 @@
 //! operações.
 
 use super::log::*;
+use super::{lotes, series};
+use crate::model::categoria::TotalCategoria;
+use crate::model::documento::DocumentoEstoqueRecv;
 use crate::model::estoque::*;
+use crate::model::lote::LoteRecv;
+use crate::model::produto::{MetodoCusto, Produto, ProdutoComponente};
+use crate::model::reserva::ConversaoReservaRecv;
 use crate::routes::respostas::Resposta;
+use bigdecimal::BigDecimal;
 use comfy_table::Table;
 use diesel::prelude::*;
 use serde_json::json;
+use std::collections::BTreeMap;
 
 /// Realiza início de estoque.
 ///
-/// Esta função realiza um início de estoque, caso já não tenha sido feito. A
-/// função realizará verificações para avaliar se o produto está cadastrado no
-/// sistema, se o estoque já não foi iniciado, e se os dados iniciais recebidos
-/// são válidos.
+/// Esta função realiza um início de estoque de um produto em um depósito,
+/// caso já não tenha sido feito. A função realizará verificações para avaliar
+/// se o produto está cadastrado no sistema e ativo, se o depósito existe e
+/// está ativo, se o estoque já não foi iniciado no depósito, e se os dados
+/// iniciais recebidos são válidos.
 ///
 /// Caso o produto não exista, será retornado um erro 404. Do contrário, caso a
 /// posição inicial de estoque possua um erro em sua validação, será retornado
 /// um erro 412, dada a invalidade semântica dos dados.
-pub fn inicia_estoque(conexao: &PgConnection, recv: Estoque) -> Resposta {
-    use super::produtos;
-    use crate::model::schema::estoque;
+///
+/// O custo médio inicial da posição de estoque será o custo unitário
+/// informado, ou o preço unitário, caso o custo não seja informado. Caso a
+/// quantidade inicial seja positiva, a posição de estoque também receberá sua
+/// primeira camada de custo, com a quantidade inicial e o custo médio inicial,
+/// e, caso o produto possua controle de lotes, o saldo inicial do lote
+/// informado. Da mesma forma, produtos com controle de números de série terão
+/// os números de série informados colocados em estoque no depósito.
+///
+/// O início de estoque também é registrado como uma movimentação de entrada da
+/// quantidade inicial (mesmo que nula), com documento `INICIO`, o preço
+/// unitário informado e o custo médio inicial, à qual são vinculados a camada
+/// de custo, o lote e os números de série iniciais. Essa movimentação não pode
+/// ser estornada, e permite reconstruir a posição de estoque em datas passadas
+/// (veja [`posicoes_na_data`]).
+pub fn inicia_estoque(conexao: &PgConnection, recv: InicioEstoqueRecv) -> Resposta {
+    use super::{produtos, unidades};
+    use crate::model::schema::{estoque, mov_estoque};
     use bigdecimal::{Signed, Zero};
 
-    // 1. Verifica se o produto existe.
-    if produtos::get_produto(conexao, recv.produto_id).is_none() {
-        return Resposta::NaoEncontrado(
+    // 1. Verifica se o produto existe e está ativo.
+    let produto = match produtos::get_produto(conexao, recv.produto_id) {
+        None => {
+            return Resposta::NaoEncontrado(
+                json!({
+                    "mensagem": "Produto não encontrado."
+                })
+                .to_string(),
+            )
+        }
+        Some(p) if !p.ativo => {
+            return Resposta::ErroSemantico(
+                json!({
+                    "mensagem": format!("O produto {} está inativo", p.id)
+                })
+                .to_string(),
+            )
+        }
+        Some(p) => p,
+    };
+
+    if let Err(resposta) = verifica_variantes(conexao, &produto) {
+        return resposta;
+    }
+
+    if !produtos::lista_componentes(conexao, produto.id).is_empty() {
+        return Resposta::ErroSemantico(
             json!({
-                "mensagem": "Produto não encontrado."
+                "mensagem": format!(
+                    "O produto {} é um kit e não possui estoque próprio",
+                    produto.id
+                )
             })
             .to_string(),
         );
     }
 
-    // 2. Verifica se já não houve início de estoque.
-    if get_estoque(conexao, recv.produto_id).is_some() {
+    if let Err(resposta) = verifica_deposito(conexao, recv.deposito_id) {
+        return resposta;
+    }
+
+    // 2. Verifica se já não houve início de estoque no depósito.
+    if get_estoque(conexao, recv.produto_id, recv.deposito_id).is_some() {
         return Resposta::ErroSemantico(
             json!({
                 "mensagem":
                     format!(
-                        "Já foi realizado início de estoque para o produto {}.",
-                        recv.produto_id
+                        "Já foi realizado início de estoque para o produto {} no depósito {}.",
+                        recv.produto_id, recv.deposito_id
                     )
             })
             .to_string(),
@@ -86,21 +141,124 @@ pub fn inicia_estoque(conexao: &PgConnection, recv: Estoque) -> Resposta {
         );
     }
 
-    // 4. Realiza início de estoque.
-    match diesel::insert_into(estoque::table)
-        .values(&recv)
-        .get_result::<Estoque>(conexao)
+    if recv
+        .custo_unitario
+        .as_ref()
+        .is_some_and(|c| c.is_negative())
+    {
+        return Resposta::ErroSemantico(
+            json!({
+                "mensagem": "O custo unitário não pode ser negativo."
+            })
+            .to_string(),
+        );
+    }
+
+    let unidade_saida = unidades::get_unidade(conexao, &produto.unidsaida)
+        .expect("Unidade de saída do produto não cadastrada");
+    if let Err(s) = crate::bo::produtos::valida_quantidade(&recv.quantidade, &unidade_saida) {
+        return Resposta::ErroSemantico(s);
+    }
+
+    if let Err(s) = crate::bo::estoque::valida_lote(&produto, &recv.quantidade, recv.lote.as_ref())
+    {
+        return Resposta::ErroSemantico(s);
+    }
+
+    if let Err(s) =
+        crate::bo::estoque::valida_numeros_serie(&produto, &recv.quantidade, &recv.numeros_serie)
     {
+        return Resposta::ErroSemantico(s);
+    }
+
+    // 4. Realiza início de estoque.
+    let lote = recv.lote;
+    let numeros_serie = recv.numeros_serie;
+    let custo_medio = match recv.custo_unitario {
+        Some(custo) => custo,
+        None => recv.precounitario.clone(),
+    };
+    let posicao = Estoque {
+        produto_id: recv.produto_id,
+        quantidade: recv.quantidade,
+        custo_medio,
+        precounitario: recv.precounitario,
+        deposito_id: recv.deposito_id,
+    };
+    let mut erro_rastreio = None;
+    let resultado = conexao.transaction::<_, diesel::result::Error, _>(|| {
+        let est = diesel::insert_into(estoque::table)
+            .values(&posicao)
+            .get_result::<Estoque>(conexao)?;
+        let movimento = diesel::insert_into(mov_estoque::table)
+            .values(&NovoMovEstoque {
+                produto_id: est.produto_id,
+                docto: String::from("INICIO"),
+                quantidade: est.quantidade.clone(),
+                preco_unitario: est.precounitario.clone(),
+                preco_frete: BigDecimal::zero(),
+                datahora: chrono::Utc::now(),
+                deposito_id: est.deposito_id,
+                custo_unitario: est.custo_medio.clone(),
+                cliente_id: None,
+                estorno_de_id: None,
+                documento_estoque_id: None,
+                inventario_id: None,
+                inicio: true,
+                transferencia_de_id: None,
+            })
+            .get_result::<MovEstoque>(conexao)?;
+        if est.quantidade.is_positive() {
+            cria_camada(
+                conexao,
+                est.produto_id,
+                est.deposito_id,
+                Some(movimento.id),
+                &est.quantidade,
+                &est.custo_medio,
+            )?;
+            if let Err(s) = lotes::movimenta_lotes(
+                conexao,
+                &produto,
+                est.deposito_id,
+                Some(movimento.id),
+                &est.quantidade,
+                lote.as_ref(),
+            )? {
+                erro_rastreio = Some(s);
+                return Err(diesel::result::Error::RollbackTransaction);
+            }
+            if let Err(s) = series::movimenta_series(
+                conexao,
+                &produto,
+                est.deposito_id,
+                Some(movimento.id),
+                &est.quantidade,
+                &numeros_serie,
+            )? {
+                erro_rastreio = Some(s);
+                return Err(diesel::result::Error::RollbackTransaction);
+            }
+        }
+        Ok(est)
+    });
+    match resultado {
         Ok(est) => {
             let _ = registra_log(
                 conexao,
                 String::from("ESTOQUE"),
                 String::from("TO-DO"),
                 DBOperacao::Insercao,
-                Some(format!("Início de estoque do produto {}", est.produto_id)),
+                Some(format!(
+                    "Início de estoque do produto {} no depósito {}",
+                    est.produto_id, est.deposito_id
+                )),
             );
             Resposta::Ok(serde_json::to_string(&est).unwrap())
         }
+        Err(diesel::result::Error::RollbackTransaction) if erro_rastreio.is_some() => {
+            Resposta::ErroSemantico(erro_rastreio.unwrap())
+        }
         Err(e) => {
             if let diesel::result::Error::DatabaseError(_, _) = &e {
                 Resposta::ErroSemantico(
@@ -123,41 +281,209 @@ pub fn inicia_estoque(conexao: &PgConnection, recv: Estoque) -> Resposta {
     }
 }
 
+/// Verifica se o estoque de um produto pode ser controlado diretamente.
+///
+/// O estoque de produtos que possuam variantes é controlado apenas através de
+/// suas variantes, e portanto será retornado um erro 422 para estes produtos.
+fn verifica_variantes(conexao: &PgConnection, produto: &Produto) -> Result<(), Resposta> {
+    if super::produtos::possui_variantes(conexao, produto.id) {
+        return Err(Resposta::ErroSemantico(
+            json!({
+                "mensagem": format!(
+                    "O produto {} possui variantes; movimente o estoque de suas variantes",
+                    produto.id
+                )
+            })
+            .to_string(),
+        ));
+    }
+    Ok(())
+}
+
+/// Verifica se o estoque de um depósito pode ser movimentado.
+///
+/// Caso o depósito não exista ou esteja inativo, será retornado um erro 422.
+fn verifica_deposito(conexao: &PgConnection, dep_id: i32) -> Result<(), Resposta> {
+    let mensagem = match super::depositos::get_deposito(conexao, dep_id) {
+        Some(d) if d.ativo => return Ok(()),
+        Some(d) => format!("O depósito {} está inativo", d.id),
+        None => format!("Depósito {} não encontrado", dep_id),
+    };
+    Err(Resposta::ErroSemantico(
+        json!({ "mensagem": mensagem }).to_string(),
+    ))
+}
+
+/// Converte a quantidade e o preço unitário de uma movimentação de estoque para
+/// a unidade de saída do produto.
+///
+/// Caso a unidade da movimentação não tenha sido informada, será considerada a
+/// unidade de saída do produto. Do contrário, a quantidade será multiplicada
+/// pelo fator de conversão da unidade informada, e o preço unitário será
+/// dividido pelo mesmo fator, sendo arredondado para quatro casas decimais.
+/// A quantidade deve respeitar as casas decimais tanto da unidade informada
+/// quanto da unidade de saída do produto.
+fn converte_unidade(
+    conexao: &PgConnection,
+    produto: &Produto,
+    recv: &mut MovEstoqueRecv,
+) -> Result<(), Resposta> {
+    use super::unidades;
+    use crate::bo::produtos::{arredonda, valida_quantidade};
+
+    let unidade = recv
+        .unidade
+        .take()
+        .map(|u| u.trim().to_uppercase())
+        .unwrap_or_else(|| produto.unidsaida.clone());
+
+    if unidade != produto.unidsaida {
+        let unidade_mov = unidades::get_unidade(conexao, &unidade).ok_or_else(|| {
+            Resposta::ErroSemantico(
+                json!({
+                    "mensagem": format!("Unidade de medida {} não cadastrada", unidade)
+                })
+                .to_string(),
+            )
+        })?;
+
+        let conversao =
+            unidades::get_conversao(conexao, produto.id, &unidade).ok_or_else(|| {
+                Resposta::ErroSemantico(
+                    json!({
+                        "mensagem": format!(
+                            "O produto {} não possui fator de conversão para a unidade {}",
+                            produto.id, unidade
+                        )
+                    })
+                    .to_string(),
+                )
+            })?;
+
+        valida_quantidade(&recv.quantidade, &unidade_mov).map_err(Resposta::ErroSemantico)?;
+        recv.quantidade = &recv.quantidade * &conversao.fator;
+        recv.preco_unitario = arredonda(&(&recv.preco_unitario / &conversao.fator), 4);
+    }
+
+    let unidade_saida = unidades::get_unidade(conexao, &produto.unidsaida)
+        .expect("Unidade de saída do produto não cadastrada");
+    valida_quantidade(&recv.quantidade, &unidade_saida).map_err(Resposta::ErroSemantico)
+}
+
 /// Realiza uma movimentação de estoque de um produto.
 ///
-/// Esta função realiza uma movimentação de estoque do referido produto. A
-/// função também efetua validações para garantir que o produto exista, e que
-/// sua posição de estoque também exista, do contrário, será retornado um erro
-/// 404.
+/// Esta função realiza uma movimentação de estoque do referido produto, no
+/// depósito informado. A função também efetua validações para garantir que o
+/// produto exista, e que sua posição de estoque no depósito também exista, do
+/// contrário, será retornado um erro 404.
 ///
-/// Além disso, a função verificará se o preço unitário foi informado como
-/// negativo ou zero, se o preço do frete, caso informado, tenha sido informado
-/// como negativo, e se a movimentação a ser registrada colocará o estoque como
+/// Além disso, a função verificará se o produto está inativo, se o depósito
+/// não existe ou está inativo, se o preço unitário foi informado como negativo
+/// ou zero, se o preço do frete, caso informado, tenha sido informado como
+/// negativo, e se a movimentação a ser registrada colocará o estoque como
 /// negativo. Qualquer uma dessas situações classifica-se como erro semântico,
 /// retornando um erro 412.
+///
+/// Caso a movimentação tenha sido informada em uma unidade diferente da
+/// unidade de saída do produto, a quantidade e o preço unitário serão
+/// convertidos através do fator de conversão cadastrado para o produto. Será
+/// retornado um erro 422 caso não haja fator de conversão para a unidade, ou
+/// caso a quantidade não respeite as casas decimais das unidades envolvidas.
+///
+/// O registro da movimentação e a alteração da posição de estoque ocorrem em
+/// uma única transação, e a verificação de estoque negativo é feita pela
+/// própria alteração da posição de estoque (veja [`aplica_movimento`]). Dessa
+/// forma, movimentações concorrentes não podem tornar o estoque negativo, e
+/// uma falha não deixará movimentações registradas sem a respectiva alteração
+/// de estoque.
+///
+/// A movimentação não altera o preço de venda do produto. Entradas recalculam
+/// o custo médio do produto no depósito, ponderando o custo atual pela
+/// quantidade em estoque e o custo da entrada pela quantidade movimentada (veja
+/// [`custo_entrada`][`crate::bo::estoque::custo_entrada`]). Saídas não alteram
+/// o custo médio, e são valoradas segundo o método de valoração do produto
+/// (veja [`valora_saida`]). O custo pelo qual a movimentação foi valorada é
+/// registrado junto da movimentação.
+///
+/// Para produtos com controle de lotes, os saldos dos lotes são movimentados
+/// na mesma transação (veja
+/// [`movimenta_lotes`][`super::lotes::movimenta_lotes`]); caso a
+/// movimentação do lote não seja possível, será retornado um erro 422. O mesmo
+/// ocorre com os números de série de produtos com controle de números de série
+/// (veja [`movimenta_series`][`super::series::movimenta_series`]). Caso a
+/// movimentação informe um cliente inexistente, também será retornado um erro
+/// 422.
+///
+/// Saídas não podem consumir a quantidade reservada do produto no depósito
+/// (veja [`aplica_movimento`]); para consumi-la, a reserva deve ser convertida
+/// em uma saída (veja [`converte_reserva`]).
+///
+/// Caso o produto seja um kit, a movimentação será repassada aos seus
+/// componentes. Para mais informações, veja [`movimenta_kit`].
 pub fn movimenta_estoque(conexao: &PgConnection, recv: MovEstoqueRecv) -> Resposta {
+    movimenta(conexao, recv, None, None)
+}
+
+/// Realiza uma movimentação de estoque de um produto, como descrito em
+/// [`movimenta_estoque`].
+///
+/// Caso seja informada uma reserva, a reserva será convertida na mesma
+/// transação que registra a movimentação, antes da aplicação da movimentação
+/// sobre a posição de estoque, de forma que a quantidade reservada possa ser
+/// consumida pela movimentação. Caso a reserva não esteja mais ativa, ou tenha
+/// expirado, nada será registrado, e será retornado um erro 422.
+///
+/// Caso seja informado um documento de estoque, a movimentação será
+/// registrada como item do documento.
+fn movimenta(
+    conexao: &PgConnection,
+    mut recv: MovEstoqueRecv,
+    reserva: Option<i32>,
+    documento: Option<i32>,
+) -> Resposta {
     use super::produtos;
     use bigdecimal::{Signed, Zero};
 
-    // 1. Verifica se o produto existe.
-    if produtos::get_produto(conexao, recv.produto_id).is_none() {
-        return Resposta::NaoEncontrado(
-            json!({
-                "mensagem": "Produto não encontrado"
-            })
-            .to_string(),
-        );
+    // 1. Verifica se o produto existe e está ativo.
+    let produto = match produtos::get_produto(conexao, recv.produto_id) {
+        None => {
+            return Resposta::NaoEncontrado(
+                json!({
+                    "mensagem": "Produto não encontrado"
+                })
+                .to_string(),
+            )
+        }
+        Some(p) if !p.ativo => {
+            return Resposta::ErroSemantico(
+                json!({
+                    "mensagem": format!("O produto {} está inativo", p.id)
+                })
+                .to_string(),
+            )
+        }
+        Some(p) => p,
+    };
+
+    if let Err(resposta) = verifica_variantes(conexao, &produto) {
+        return resposta;
+    }
+
+    if let Err(resposta) = verifica_deposito(conexao, recv.deposito_id) {
+        return resposta;
     }
 
-    // 2. Verifica se foi feito início de estoque.
-    let estoque_atual = get_estoque(conexao, recv.produto_id);
-    if estoque_atual.is_none() {
+    // 2. Verifica se foi feito início de estoque no depósito. Kits não
+    //    possuem estoque próprio.
+    let componentes = produtos::lista_componentes(conexao, produto.id);
+    let estoque_atual = get_estoque(conexao, recv.produto_id, recv.deposito_id);
+    if estoque_atual.is_none() && componentes.is_empty() {
         return Resposta::NaoEncontrado(
             json!({
                 "mensagem":
                     format!(
-                        "Necessário efetuar início de estoque para o produto {}",
-                        recv.produto_id
+                        "Necessário efetuar início de estoque para o produto {} no depósito {}",
+                        recv.produto_id, recv.deposito_id
                     )
             })
             .to_string(),
@@ -187,104 +513,201 @@ pub fn movimenta_estoque(conexao: &PgConnection, recv: MovEstoqueRecv) -> Respos
         }
     }
 
-    // 3.3. Verifica se a movimentação vai colocar o estoque como negativo
-    //      ou zerar o preço.
-    let estoque_atual = estoque_atual.unwrap();
-    let nova_qtd_estoque = estoque_atual.quantidade.clone() + recv.quantidade.clone();
-    if nova_qtd_estoque.is_negative() {
-        return Resposta::ErroSemantico(
-            json!({
-                "mensagem":
-                    format!(
-                        "Movimentações de estoque não podem torná-lo \
-		 negativo! Estoque atual: {}",
-                        estoque_atual.quantidade
-                    )
-            })
-            .to_string(),
-        );
+    // 3.3. Converte quantidade e preço unitário para a unidade de saída do
+    //      produto, verificando as casas decimais permitidas.
+    if let Err(resposta) = converte_unidade(conexao, &produto, &mut recv) {
+        return resposta;
     }
 
-    // 4.1. Cadastra o movimento.
-    let novo_movimento = NovoMovEstoque::from(recv);
-    let movimento = {
-        use crate::model::schema::mov_estoque;
-        match diesel::insert_into(mov_estoque::table)
-            .values(&novo_movimento)
-            .get_result::<MovEstoque>(conexao)
-        {
-            Ok(movimen) => {
-                let _ = registra_log(
-                    conexao,
-                    String::from("MOV_ESTOQUE"),
-                    String::from("TO-DO"),
-                    DBOperacao::Insercao,
-                    Some(format!("Movimento de estoque {}", movimen.id)),
-                );
-                movimen
-            }
-            Err(e) => {
-                if let diesel::result::Error::DatabaseError(_, _) = &e {
-                    return Resposta::ErroSemantico(
-                        json!({
-                        "mensagem": e.to_string()
-                        })
-                        .to_string(),
-                    );
-                } else {
-                    return Resposta::ErroSemantico(
-                        json!({
-                        "mensagem":
-                                    "Erro interno ao realizar movimentação de estoque. \
-                                     Contate o suporte para mais informações."
-                        })
-                        .to_string(),
-                    );
-                }
-            }
+    // 3.4. Verifica o lote e os números de série informados, para produtos
+    //      com controle de lotes ou de números de série.
+    if let Err(s) = crate::bo::estoque::valida_lote(&produto, &recv.quantidade, recv.lote.as_ref())
+    {
+        return Resposta::ErroSemantico(s);
+    }
+
+    if let Err(s) =
+        crate::bo::estoque::valida_numeros_serie(&produto, &recv.quantidade, &recv.numeros_serie)
+    {
+        return Resposta::ErroSemantico(s);
+    }
+
+    // 3.5. Verifica se o cliente, quando informado, existe.
+    if let Some(cliente_id) = recv.cliente_id {
+        if !super::clientes::existe_cliente(conexao, cliente_id) {
+            return Resposta::ErroSemantico(
+                json!({
+                    "mensagem": format!("Cliente {} não encontrado", cliente_id)
+                })
+                .to_string(),
+            );
         }
-    };
+    }
 
-    // 4.2. Modifica o estoque.
-    let mod_estoque = {
-        use crate::model::schema::estoque::dsl::*;
-        diesel::update(estoque.filter(produto_id.eq(&novo_movimento.produto_id)))
-            .set((
-                quantidade.eq(&nova_qtd_estoque),
-                precounitario.eq(&novo_movimento.preco_unitario),
-            ))
-            .get_result::<Estoque>(conexao)
+    // 3.6. Saídas de kits movimentam o estoque de seus componentes.
+    if !componentes.is_empty() {
+        return movimenta_kit(conexao, &produto, &componentes, recv);
+    }
+
+    // 4. Registra o movimento e altera o estoque em uma única transação. A
+    //    alteração do estoque é condicional, e não ocorrerá caso o estoque
+    //    se torne negativo, mesmo que por conta de movimentações
+    //    concorrentes; neste caso, o movimento também não será registrado.
+    //    Entradas criam uma camada de custo, e saídas consomem as camadas
+    //    mais antigas, na mesma transação; o mesmo ocorre com os lotes e os
+    //    números de série de produtos com controle de lotes ou de números de
+    //    série.
+    let lote = recv.lote.take();
+    let numeros_serie = std::mem::take(&mut recv.numeros_serie);
+    let mut novo_movimento = NovoMovEstoque::from(recv);
+    novo_movimento.documento_estoque_id = documento;
+    let custo = if novo_movimento.quantidade.is_positive() {
+        Some(crate::bo::estoque::custo_entrada(
+            &novo_movimento.preco_unitario,
+            &novo_movimento.preco_frete,
+            &novo_movimento.quantidade,
+        ))
+    } else {
+        None
     };
+    let mut erro_rastreio = None;
+    let resultado = conexao.transaction::<_, diesel::result::Error, _>(|| {
+        use crate::model::reserva::SituacaoReserva;
+        use crate::model::schema::{mov_estoque, reserva as tabela_reserva};
+        if let Some(reserva_id) = reserva {
+            let hoje = chrono::Local::today().naive_local();
+            let convertidas = diesel::update(
+                tabela_reserva::table
+                    .find(reserva_id)
+                    .filter(tabela_reserva::situacao.eq(SituacaoReserva::Ativa))
+                    .filter(tabela_reserva::validade.ge(hoje)),
+            )
+            .set(tabela_reserva::situacao.eq(SituacaoReserva::Convertida))
+            .execute(conexao)?;
+            if convertidas == 0 {
+                erro_rastreio = Some(
+                    json!({
+                        "mensagem": format!(
+                            "A reserva {} não está ativa ou está expirada",
+                            reserva_id
+                        )
+                    })
+                    .to_string(),
+                );
+                return Err(diesel::result::Error::RollbackTransaction);
+            }
+        }
+        let posicao = aplica_movimento(
+            conexao,
+            novo_movimento.produto_id,
+            novo_movimento.deposito_id,
+            &novo_movimento.quantidade,
+            custo.as_ref(),
+        )?
+        .ok_or(diesel::result::Error::RollbackTransaction)?;
+        novo_movimento.custo_unitario = match &custo {
+            Some(custo) => custo.clone(),
+            None => valora_saida(
+                conexao,
+                &produto,
+                &posicao,
+                &-novo_movimento.quantidade.clone(),
+            )?,
+        };
+        let movimento = diesel::insert_into(mov_estoque::table)
+            .values(&novo_movimento)
+            .get_result::<MovEstoque>(conexao)?;
+        if custo.is_some() {
+            cria_camada(
+                conexao,
+                movimento.produto_id,
+                movimento.deposito_id,
+                Some(movimento.id),
+                &movimento.quantidade,
+                &movimento.custo_unitario,
+            )?;
+        }
+        if let Err(s) = lotes::movimenta_lotes(
+            conexao,
+            &produto,
+            movimento.deposito_id,
+            Some(movimento.id),
+            &movimento.quantidade,
+            lote.as_ref(),
+        )? {
+            erro_rastreio = Some(s);
+            return Err(diesel::result::Error::RollbackTransaction);
+        }
+        if let Err(s) = series::movimenta_series(
+            conexao,
+            &produto,
+            movimento.deposito_id,
+            Some(movimento.id),
+            &movimento.quantidade,
+            &numeros_serie,
+        )? {
+            erro_rastreio = Some(s);
+            return Err(diesel::result::Error::RollbackTransaction);
+        }
+        if let Some(reserva_id) = reserva {
+            diesel::update(tabela_reserva::table.find(reserva_id))
+                .set(tabela_reserva::mov_estoque_id.eq(movimento.id))
+                .execute(conexao)?;
+        }
+        Ok(movimento)
+    });
 
-    match mod_estoque {
-        Ok(est) => {
+    match resultado {
+        Ok(movimento) => {
+            let _ = registra_log(
+                conexao,
+                String::from("MOV_ESTOQUE"),
+                String::from("TO-DO"),
+                DBOperacao::Insercao,
+                Some(format!("Movimento de estoque {}", movimento.id)),
+            );
             let _ = registra_log(
                 conexao,
                 String::from("ESTOQUE"),
                 String::from("TO-DO"),
                 DBOperacao::Alteracao,
-                Some(format!("Altera estoque do produto {}", est.produto_id)),
+                Some(format!(
+                    "Altera estoque do produto {}",
+                    movimento.produto_id
+                )),
             );
-
-            // 5.1. Retorna o movimento
             Resposta::Ok(serde_json::to_string(&movimento).unwrap())
         }
+        Err(diesel::result::Error::RollbackTransaction) if erro_rastreio.is_some() => {
+            Resposta::ErroSemantico(erro_rastreio.unwrap())
+        }
+        Err(diesel::result::Error::RollbackTransaction) => {
+            let estoque_atual = get_estoque(
+                conexao,
+                novo_movimento.produto_id,
+                novo_movimento.deposito_id,
+            )
+            .map(|e| e.quantidade)
+            .unwrap_or_else(BigDecimal::zero);
+            let reservado = super::reservas::total_reservado(
+                conexao,
+                novo_movimento.produto_id,
+                novo_movimento.deposito_id,
+            )
+            .unwrap_or_else(|_| BigDecimal::zero());
+            Resposta::ErroSemantico(
+                json!({
+                    "mensagem":
+                        format!(
+                            "Movimentações de estoque não podem torná-lo \
+		 negativo! Estoque atual: {}, reservado: {}",
+                            estoque_atual, reservado
+                        )
+                })
+                .to_string(),
+            )
+        }
         Err(e) => {
-            // 5.2. Em caso de erro, realiza rollback da movimentação
-            {
-                use crate::model::schema::mov_estoque::dsl::*;
-                diesel::delete(mov_estoque.filter(id.eq(&movimento.id)))
-                    .execute(conexao)
-                    .expect("Erro no rollback de movimentação de estoque!");
-                let _ = registra_log(
-                    conexao,
-                    String::from("MOV_ESTOQUE"),
-                    String::from("TO-DO"),
-                    DBOperacao::Remocao,
-                    Some(format!("Rollback de movimento de estoque {}", movimento.id)),
-                );
-            }
-
             if let diesel::result::Error::DatabaseError(_, _) = &e {
                 Resposta::ErroSemantico(
                     json!({
@@ -296,8 +719,8 @@ pub fn movimenta_estoque(conexao: &PgConnection, recv: MovEstoqueRecv) -> Respos
                 Resposta::ErroSemantico(
                     json!({
                         "mensagem":
-                                "Erro interno ao atualizar estoque. \
-                                 Contate o suporte para mais informações."
+                            "Erro interno ao realizar movimentação de estoque. \
+                             Contate o suporte para mais informações."
                     })
                     .to_string(),
                 )
@@ -306,77 +729,1959 @@ pub fn movimenta_estoque(conexao: &PgConnection, recv: MovEstoqueRecv) -> Respos
     }
 }
 
-/// Retorna a posição de estoque de um produto.
+/// Converte uma reserva de estoque em uma saída de estoque.
 ///
-/// Esta função retorna um Option que poderá conter a posição de estoque de um
-/// produto com o id informado. Esta função verifica apenas se houve início de
-/// estoque do produto, mas não verifica se o produto existe.
-pub fn get_estoque(conexao: &PgConnection, prod_id: i32) -> Option<Estoque> {
-    use crate::model::schema::estoque::dsl::*;
-    let estoque_req = estoque
-        .filter(produto_id.eq(&prod_id))
-        .load::<Estoque>(conexao)
-        .expect("Erro ao carregar estoque");
-    estoque_req.first().cloned()
-}
+/// A saída terá o produto, o depósito, o documento e a quantidade da reserva,
+/// e o preço unitário informado, ou o preço de venda atual da posição de
+/// estoque, caso o preço não seja informado. Os demais dados da conversão
+/// seguem as mesmas regras de uma movimentação de estoque comum (veja
+/// [`movimenta_estoque`]), sendo que a saída pode consumir a quantidade da
+/// própria reserva. A saída e a conversão da reserva ocorrem em uma única
+/// transação, e a reserva passa a referenciar a movimentação gerada.
+///
+/// Caso a reserva não exista, será retornado um erro 404; caso já tenha sido
+/// liberada ou convertida, será retornado um erro 409. Reservas expiradas não
+/// podem ser convertidas (erro 422). A conversão será registrada no log em
+/// nome do usuário informado.
+pub fn converte_reserva(
+    conexao: &PgConnection,
+    reserva_id: i32,
+    recv: ConversaoReservaRecv,
+    usuario: &str,
+) -> Resposta {
+    use crate::model::reserva::SituacaoReserva;
 
-/// Une as informações de uma posição de estoque de um produto com os dados do
-/// produto referenciado.
-fn transforma_estoque_retorno(conexao: &PgConnection, e: &Estoque) -> EstoqueRepr {
-    use super::produtos;
-    let p = produtos::get_produto(conexao, e.produto_id).unwrap();
-    EstoqueRepr {
-        id: p.id,
-        descricao: p.descricao.clone(),
-        unidsaida: p.unidsaida,
-        quantidade: e.quantidade.clone(),
-        preco_unitario: e.precounitario.clone(),
+    let reserva = match super::reservas::get_reserva(conexao, reserva_id) {
+        None => {
+            return Resposta::NaoEncontrado(
+                json!({
+                    "mensagem": "Reserva não encontrada"
+                })
+                .to_string(),
+            )
+        }
+        Some(r) if r.situacao != SituacaoReserva::Ativa => {
+            return Resposta::Conflito(
+                json!({
+                    "mensagem": format!("A reserva {} não está ativa", r.id)
+                })
+                .to_string(),
+            )
+        }
+        Some(r) if r.validade < chrono::Local::today().naive_local() => {
+            return Resposta::ErroSemantico(
+                json!({
+                    "mensagem": format!("A reserva {} expirou em {}", r.id, r.validade)
+                })
+                .to_string(),
+            )
+        }
+        Some(r) => r,
+    };
+
+    let preco_unitario = match recv.preco_unitario {
+        Some(preco) => preco,
+        None => match get_estoque(conexao, reserva.produto_id, reserva.deposito_id) {
+            Some(e) => e.precounitario,
+            None => {
+                return Resposta::NaoEncontrado(
+                    json!({
+                        "mensagem": "Posição de estoque da reserva não encontrada"
+                    })
+                    .to_string(),
+                )
+            }
+        },
+    };
+
+    let movimento = MovEstoqueRecv {
+        produto_id: reserva.produto_id,
+        docto: reserva.docto.clone(),
+        quantidade: -reserva.quantidade.clone(),
+        preco_unitario,
+        preco_frete: None,
+        unidade: None,
+        deposito_id: reserva.deposito_id,
+        lote: recv.lote,
+        numeros_serie: recv.numeros_serie,
+        cliente_id: recv.cliente_id,
+    };
+    let resposta = movimenta(conexao, movimento, Some(reserva.id), None);
+    if let Resposta::Ok(_) = &resposta {
+        let _ = registra_log(
+            conexao,
+            String::from("RESERVA"),
+            usuario.to_owned(),
+            DBOperacao::Alteracao,
+            Some(format!(
+                "Reserva {}: conversão em saída de estoque",
+                reserva.id
+            )),
+        );
     }
+    resposta
 }
 
-/// Lista uma quantidade limitada de posições de estoque com dados de produto.
+/// Registra um documento de estoque (ex: uma nota fiscal de compra) e as
+/// entradas de estoque de seus itens.
 ///
-/// Retorna um Vec com estruturas que representam a união entre dados de um
-/// produto e de sua posição de estoque. A quantidade de estruturas retornadas
-/// não será superior a `limite`.
-pub fn lista_estoque(conexao: &PgConnection, limite: i64) -> Vec<EstoqueRepr> {
-    use crate::model::schema::estoque;
-    estoque::table
-        .limit(limite)
-        .load::<Estoque>(conexao)
-        .expect("Erro ao carregar estoque")
-        .iter()
-        .map(|e| transforma_estoque_retorno(conexao, e))
-        .collect()
-}
-
-/// Mostra a posição de estoque de um produto com seus respectivos dados.
+/// Cada item é registrado como uma entrada de estoque no depósito do
+/// documento, com o número do documento, e tendo o fornecedor do documento
+/// como cliente, seguindo as mesmas regras de uma movimentação de estoque
+/// comum (veja [`movimenta_estoque`]). O frete total do documento é rateado
+/// entre os itens por valor ou por peso, como descrito em
+/// [`rateia_frete`][`crate::bo::estoque::rateia_frete`], e compõe o custo de
+/// entrada de cada item.
 ///
-/// Retorna um Option que poderá conter os dados de posição de estoque de um
-/// produto, junto com seus dados de cadastro. Os dados só serão retornados se
-/// o sistema encontrar a posição de estoque do produto e seus dados
-/// correspondentes, respectivamente.
-pub fn mostra_estoque(conexao: &PgConnection, prod_id: i32) -> Option<EstoqueRepr> {
-    get_estoque(conexao, prod_id)
-        .as_ref()
-        .map(|e| transforma_estoque_retorno(conexao, e))
-}
+/// O documento e todas as suas entradas são registrados em uma única
+/// transação: caso qualquer item não possa ser registrado, nada será
+/// registrado, e será retornado o erro do item, identificado por sua posição
+/// no documento. Caso os dados do documento sejam inválidos, ou o fornecedor
+/// ou o depósito não existam, será retornado um erro 422. Em caso de sucesso,
+/// será retornado o documento junto das movimentações de seus itens, e o
+/// registro será feito no log em nome do usuário informado.
+pub fn registra_documento(
+    conexao: &PgConnection,
+    recv: DocumentoEstoqueRecv,
+    usuario: &str,
+) -> Resposta {
+    use crate::model::documento::{DocumentoEstoque, NovoDocumentoEstoque, RateioFrete};
+    use crate::model::schema::documento_estoque;
+    use bigdecimal::Zero;
 
-/// Lista uma quantidade limitada de posições de estoque com dados de produto,
-/// em uma tabela escrita como texto-plano.
-///
-/// O retorno será uma string em texto-plano, formatada para assemelhar-se a uma
-/// tabela. A tabela não terá mais linhas do que a quantidade descrita pelo
-/// parâmetro `limite`.
-pub fn lista_movimentos_texto(conexao: &PgConnection, limite: i64) -> String {
-    let movimentos = recupera_movimentos(conexao, limite);
-    let mut table = Table::new();
-    prepara_tabela(&mut table);
-    processa_tabela(conexao, &movimentos, &mut table);
-    format!("{}\n", table)
-}
+    if let Err(s) = crate::bo::estoque::valida_documento(&recv) {
+        return Resposta::ErroSemantico(s);
+    }
 
-/// Prepara o cabeçalho da tabela de movimentações de estoque.
+    if let Some(fornecedor_id) = recv.fornecedor_id {
+        if !super::clientes::existe_cliente(conexao, fornecedor_id) {
+            return Resposta::ErroSemantico(
+                json!({
+                    "mensagem": format!("Fornecedor {} não encontrado", fornecedor_id)
+                })
+                .to_string(),
+            );
+        }
+    }
+
+    if let Err(resposta) = verifica_deposito(conexao, recv.deposito_id) {
+        return resposta;
+    }
+
+    let frete = recv.frete.clone().unwrap_or_else(BigDecimal::zero);
+    let bases: Vec<BigDecimal> = recv
+        .itens
+        .iter()
+        .map(|item| match recv.rateio_frete {
+            RateioFrete::Valor => &item.quantidade * &item.preco_unitario,
+            RateioFrete::Peso => item.peso.clone().unwrap_or_else(BigDecimal::zero),
+        })
+        .collect();
+    let fretes = crate::bo::estoque::rateia_frete(&frete, &bases);
+
+    let identifica_item = |posicao: usize, resposta: Resposta| {
+        let prefixa = |s: String| {
+            let mensagem = serde_json::from_str::<serde_json::Value>(&s)
+                .ok()
+                .and_then(|v| v["mensagem"].as_str().map(String::from))
+                .unwrap_or(s);
+            json!({
+                "mensagem": format!("Item {}: {}", posicao, mensagem)
+            })
+            .to_string()
+        };
+        match resposta {
+            Resposta::NaoEncontrado(s) => Resposta::NaoEncontrado(prefixa(s)),
+            Resposta::ErroSemantico(s) => Resposta::ErroSemantico(prefixa(s)),
+            resposta => resposta,
+        }
+    };
+
+    let mut erro_item = None;
+    let resultado = conexao.transaction::<_, diesel::result::Error, _>(|| {
+        let documento = diesel::insert_into(documento_estoque::table)
+            .values(&NovoDocumentoEstoque {
+                docto: recv.docto.trim().to_owned(),
+                fornecedor_id: recv.fornecedor_id,
+                data: recv.data,
+                deposito_id: recv.deposito_id,
+                frete: frete.clone(),
+                rateio_frete: recv.rateio_frete,
+            })
+            .get_result::<DocumentoEstoque>(conexao)?;
+        for (i, (item, frete_item)) in recv.itens.iter().zip(fretes).enumerate() {
+            let movimento = MovEstoqueRecv {
+                produto_id: item.produto_id,
+                docto: documento.docto.clone(),
+                quantidade: item.quantidade.clone(),
+                preco_unitario: item.preco_unitario.clone(),
+                preco_frete: Some(frete_item),
+                unidade: item.unidade.clone(),
+                deposito_id: documento.deposito_id,
+                lote: item.lote.clone(),
+                numeros_serie: item.numeros_serie.clone(),
+                cliente_id: documento.fornecedor_id,
+            };
+            match movimenta(conexao, movimento, None, Some(documento.id)) {
+                Resposta::Ok(_) => {}
+                resposta => {
+                    erro_item = Some(identifica_item(i + 1, resposta));
+                    return Err(diesel::result::Error::RollbackTransaction);
+                }
+            }
+        }
+        Ok(documento)
+    });
+
+    match resultado {
+        Ok(documento) => {
+            let _ = registra_log(
+                conexao,
+                String::from("DOCUMENTO_ESTOQUE"),
+                usuario.to_owned(),
+                DBOperacao::Insercao,
+                Some(format!(
+                    "Documento de estoque {} ({}), com {} itens",
+                    documento.id,
+                    documento.docto,
+                    recv.itens.len()
+                )),
+            );
+            let documento = super::documentos::get_documento(conexao, documento.id).unwrap();
+            Resposta::Ok(serde_json::to_string(&documento).unwrap())
+        }
+        Err(diesel::result::Error::RollbackTransaction) if erro_item.is_some() => {
+            erro_item.unwrap()
+        }
+        Err(e) => Resposta::ErroSemantico(
+            json!({
+                "mensagem": e.to_string()
+            })
+            .to_string(),
+        ),
+    }
+}
+
+/// Fecha uma sessão de inventário, gerando as movimentações de ajuste de suas
+/// diferenças.
+///
+/// Cada item contado cuja quantidade contada difira da quantidade esperada
+/// (veja [`revisa_inventario`][`super::inventarios::revisa_inventario`]) gera
+/// uma movimentação de ajuste da diferença, com o documento
+/// `INVENTARIO-<id>`, referenciando a sessão de inventário. Como a diferença é
+/// calculada sobre a quantidade esperada na abertura da sessão, movimentações
+/// registradas durante a contagem são preservadas. Itens não contados não são
+/// ajustados.
+///
+/// Sobras entram no estoque pelo custo médio da posição de estoque, sem
+/// alterá-lo, e criam uma camada de custo; faltas são valoradas como qualquer
+/// saída (veja [`valora_saida`]). O preço unitário dos ajustes é o preço de
+/// venda da posição de estoque.
+///
+/// Os ajustes e o fechamento da sessão ocorrem em uma única transação. Caso a
+/// sessão não exista, será retornado um erro 404; caso já tenha sido fechada,
+/// será retornado um erro 409. Caso algum item possua contagens divergentes,
+/// ou caso algum ajuste torne o estoque negativo ou consuma a quantidade
+/// reservada, nada será registrado, e será retornado um erro 422. Em caso de
+/// sucesso, serão retornados a sessão fechada e os ajustes, e o fechamento
+/// será registrado no log em nome do usuário informado.
+pub fn fecha_inventario(conexao: &PgConnection, inventario_id: i32, usuario: &str) -> Resposta {
+    use super::{inventarios, produtos};
+    use crate::model::inventario::{FechamentoInventario, Inventario, SituacaoInventario};
+    use crate::model::schema::{estoque, inventario, mov_estoque};
+    use bigdecimal::{Signed, Zero};
+
+    let erro =
+        |mensagem: String| Resposta::ErroSemantico(json!({ "mensagem": mensagem }).to_string());
+
+    if inventarios::get_inventario(conexao, inventario_id).is_none() {
+        return Resposta::NaoEncontrado(
+            json!({
+                "mensagem": "Inventário não encontrado"
+            })
+            .to_string(),
+        );
+    }
+
+    let mut falha = None;
+    let resultado = conexao.transaction::<_, diesel::result::Error, _>(|| {
+        let sessao = inventario::table
+            .find(inventario_id)
+            .for_update()
+            .first::<Inventario>(conexao)?;
+        if sessao.situacao != SituacaoInventario::Aberto {
+            falha = Some(Resposta::Conflito(
+                json!({
+                    "mensagem": format!("O inventário {} não está aberto", sessao.id)
+                })
+                .to_string(),
+            ));
+            return Err(diesel::result::Error::RollbackTransaction);
+        }
+
+        let itens = inventarios::revisa_itens(conexao, sessao.id)?;
+        let divergentes: Vec<String> = itens
+            .iter()
+            .filter(|item| item.divergente)
+            .map(|item| item.produto_id.to_string())
+            .collect();
+        if !divergentes.is_empty() {
+            falha = Some(erro(format!(
+                "Os produtos {} possuem contagens divergentes e devem ser recontados",
+                divergentes.join(", ")
+            )));
+            return Err(diesel::result::Error::RollbackTransaction);
+        }
+
+        let mut ajustes = Vec::new();
+        for item in itens {
+            let diferenca = match item.diferenca {
+                Some(d) if !d.is_zero() => d,
+                _ => continue,
+            };
+            let produto = produtos::get_produto(conexao, item.produto_id)
+                .expect("Produto do inventário não encontrado");
+            let sobra = diferenca.is_positive();
+            let custo_medio = estoque::table
+                .find((produto.id, sessao.deposito_id))
+                .select(estoque::custo_medio)
+                .first::<BigDecimal>(conexao)?;
+            let posicao = match aplica_movimento(
+                conexao,
+                produto.id,
+                sessao.deposito_id,
+                &diferenca,
+                if sobra { Some(&custo_medio) } else { None },
+            )? {
+                Some(posicao) => posicao,
+                None => {
+                    falha = Some(erro(format!(
+                        "O ajuste de {} do produto {} tornaria o estoque negativo ou \
+                         consumiria a quantidade reservada",
+                        diferenca, produto.id
+                    )));
+                    return Err(diesel::result::Error::RollbackTransaction);
+                }
+            };
+            let custo_unitario = if sobra {
+                custo_medio
+            } else {
+                valora_saida(conexao, &produto, &posicao, &-diferenca.clone())?
+            };
+            let movimento = diesel::insert_into(mov_estoque::table)
+                .values(&NovoMovEstoque {
+                    produto_id: produto.id,
+                    docto: format!("INVENTARIO-{}", sessao.id),
+                    quantidade: diferenca,
+                    preco_unitario: posicao.precounitario.clone(),
+                    preco_frete: BigDecimal::zero(),
+                    datahora: chrono::Utc::now(),
+                    deposito_id: sessao.deposito_id,
+                    custo_unitario,
+                    cliente_id: None,
+                    estorno_de_id: None,
+                    documento_estoque_id: None,
+                    inventario_id: Some(sessao.id),
+                    inicio: false,
+                    transferencia_de_id: None,
+                })
+                .get_result::<MovEstoque>(conexao)?;
+            if sobra {
+                cria_camada(
+                    conexao,
+                    movimento.produto_id,
+                    movimento.deposito_id,
+                    Some(movimento.id),
+                    &movimento.quantidade,
+                    &movimento.custo_unitario,
+                )?;
+            }
+            ajustes.push(movimento);
+        }
+
+        let sessao = diesel::update(inventario::table.find(sessao.id))
+            .set((
+                inventario::situacao.eq(SituacaoInventario::Fechado),
+                inventario::fechamento.eq(chrono::Utc::now()),
+            ))
+            .get_result::<Inventario>(conexao)?;
+        Ok(FechamentoInventario {
+            inventario: sessao,
+            ajustes,
+        })
+    });
+
+    match resultado {
+        Ok(fechamento) => {
+            for movimento in &fechamento.ajustes {
+                let _ = registra_log(
+                    conexao,
+                    String::from("MOV_ESTOQUE"),
+                    usuario.to_owned(),
+                    DBOperacao::Insercao,
+                    Some(format!(
+                        "Movimento de estoque {}: ajuste do inventário {}",
+                        movimento.id, fechamento.inventario.id
+                    )),
+                );
+            }
+            let _ = registra_log(
+                conexao,
+                String::from("INVENTARIO"),
+                usuario.to_owned(),
+                DBOperacao::Alteracao,
+                Some(format!(
+                    "Fechamento do inventário {}, com {} ajustes",
+                    fechamento.inventario.id,
+                    fechamento.ajustes.len()
+                )),
+            );
+            Resposta::Ok(serde_json::to_string(&fechamento).unwrap())
+        }
+        Err(diesel::result::Error::RollbackTransaction) if falha.is_some() => falha.unwrap(),
+        Err(e) => erro(e.to_string()),
+    }
+}
+
+/// Estorna uma movimentação de estoque.
+///
+/// O estorno é realizado através de uma movimentação compensatória, com o
+/// mesmo produto, depósito, documento, preço unitário e cliente da
+/// movimentação original, e a quantidade inversa, que passa a referenciar a
+/// movimentação estornada. A movimentação original não é alterada.
+///
+/// O estorno de uma saída devolve a quantidade ao estoque pelo custo unitário
+/// da saída, recalculando o custo médio como em uma entrada e criando uma nova
+/// camada de custo. O estorno de uma entrada retira a quantidade do estoque
+/// pelo custo unitário da entrada, retirando o valor da entrada do custo médio
+/// (veja
+/// [`custo_estorno_entrada`][`crate::bo::estoque::custo_estorno_entrada`]) e
+/// consumindo primeiramente a camada de custo criada pela entrada. Os lotes e
+/// os números de série movimentados pela movimentação original também são
+/// movimentados de forma inversa. Como em qualquer saída, o estorno de uma
+/// entrada não pode tornar o estoque negativo nem consumir a quantidade
+/// reservada.
+///
+/// Caso a movimentação seja a saída ou a entrada de uma transferência entre
+/// depósitos (veja [`transfere_estoque`]), ambas as movimentações da
+/// transferência serão estornadas na mesma transação (veja
+/// [`movimentos_estorno`][`crate::bo::estoque::movimentos_estorno`]), e será
+/// retornado o estorno da movimentação informada.
+///
+/// O motivo do estorno é obrigatório (erro 422), e será registrado no log em
+/// nome do usuário informado. Caso a movimentação não exista, será retornado
+/// um erro 404. Movimentações que já foram estornadas, ou que sejam estornos
+/// de outras movimentações, não podem ser estornadas (erro 409), assim como
+/// inícios de estoque. Em caso de sucesso, será retornada a movimentação de
+/// estorno.
+pub fn estorna_movimento(
+    conexao: &PgConnection,
+    mov_id: i32,
+    recv: EstornoRecv,
+    usuario: &str,
+) -> Resposta {
+    use crate::model::schema::mov_estoque;
+    use bigdecimal::Zero;
+
+    if let Err(s) = crate::bo::estoque::valida_estorno(&recv) {
+        return Resposta::ErroSemantico(s);
+    }
+
+    let original = match get_movimento(conexao, mov_id) {
+        None => {
+            return Resposta::NaoEncontrado(
+                json!({
+                    "mensagem": "Movimentação de estoque não encontrada"
+                })
+                .to_string(),
+            )
+        }
+        Some(m) if m.inicio => {
+            return Resposta::Conflito(
+                json!({
+                    "mensagem": format!(
+                        "A movimentação {} é um início de estoque e não pode ser estornada",
+                        m.id
+                    )
+                })
+                .to_string(),
+            )
+        }
+        Some(m) if m.estorno_de_id.is_some() => {
+            return Resposta::Conflito(
+                json!({
+                    "mensagem": format!(
+                        "A movimentação {} é um estorno e não pode ser estornada",
+                        m.id
+                    )
+                })
+                .to_string(),
+            )
+        }
+        Some(m) => m,
+    };
+
+    // A outra movimentação da transferência, caso a movimentação seja parte
+    // de uma transferência entre depósitos.
+    let par = match original.transferencia_de_id {
+        Some(saida_id) => get_movimento(conexao, saida_id),
+        None => mov_estoque::table
+            .filter(mov_estoque::transferencia_de_id.eq(original.id))
+            .first::<MovEstoque>(conexao)
+            .optional()
+            .expect("Erro ao verificar transferência da movimentação"),
+    };
+    let movimentos = match crate::bo::estoque::movimentos_estorno(&original, par.as_ref()) {
+        Ok(movimentos) => movimentos,
+        Err(s) => return Resposta::ErroSemantico(s),
+    };
+
+    let estorno_de = |movimento_id: i32| {
+        mov_estoque::table
+            .filter(mov_estoque::estorno_de_id.eq(movimento_id))
+            .select(mov_estoque::id)
+            .first::<i32>(conexao)
+            .optional()
+            .expect("Erro ao verificar estornos da movimentação")
+    };
+    let conflito = |movimento_id: i32, estorno_id: i32| {
+        Resposta::Conflito(
+            json!({
+                "mensagem": format!(
+                    "A movimentação {} já foi estornada pela movimentação {}",
+                    movimento_id, estorno_id
+                )
+            })
+            .to_string(),
+        )
+    };
+
+    for movimento in &movimentos {
+        if let Some(estorno_id) = estorno_de(movimento.id) {
+            return conflito(movimento.id, estorno_id);
+        }
+    }
+
+    let mut erro_rastreio = None;
+    let mut posicao_atual = (original.produto_id, original.deposito_id);
+    let resultado = conexao.transaction::<_, diesel::result::Error, _>(|| {
+        let mut estornos = Vec::new();
+        for movimento in &movimentos {
+            posicao_atual = (movimento.produto_id, movimento.deposito_id);
+            match aplica_estorno(conexao, movimento)? {
+                Ok(estorno) => estornos.push(estorno),
+                Err(s) => {
+                    erro_rastreio = Some(s);
+                    return Err(diesel::result::Error::RollbackTransaction);
+                }
+            }
+        }
+        Ok(estornos)
+    });
+
+    match resultado {
+        Ok(estornos) => {
+            for estorno in &estornos {
+                let _ = registra_log(
+                    conexao,
+                    String::from("MOV_ESTOQUE"),
+                    usuario.to_owned(),
+                    DBOperacao::Insercao,
+                    Some(format!(
+                        "Movimento de estoque {}: estorno do movimento {}. Motivo: {}",
+                        estorno.id,
+                        estorno.estorno_de_id.unwrap_or_default(),
+                        recv.motivo.trim()
+                    )),
+                );
+                let _ = registra_log(
+                    conexao,
+                    String::from("ESTOQUE"),
+                    usuario.to_owned(),
+                    DBOperacao::Alteracao,
+                    Some(format!(
+                        "Altera estoque do produto {} no depósito {}",
+                        estorno.produto_id, estorno.deposito_id
+                    )),
+                );
+            }
+            let estorno = estornos
+                .into_iter()
+                .find(|e| e.estorno_de_id == Some(original.id))
+                .unwrap();
+            Resposta::Ok(serde_json::to_string(&estorno).unwrap())
+        }
+        Err(diesel::result::Error::RollbackTransaction) if erro_rastreio.is_some() => {
+            Resposta::ErroSemantico(erro_rastreio.unwrap())
+        }
+        Err(diesel::result::Error::RollbackTransaction) => {
+            let (produto_id, deposito_id) = posicao_atual;
+            let estoque_atual = get_estoque(conexao, produto_id, deposito_id)
+                .map(|e| e.quantidade)
+                .unwrap_or_else(BigDecimal::zero);
+            let reservado = super::reservas::total_reservado(conexao, produto_id, deposito_id)
+                .unwrap_or_else(|_| BigDecimal::zero());
+            Resposta::ErroSemantico(
+                json!({
+                    "mensagem": format!(
+                        "Movimentações de estoque não podem torná-lo negativo! \
+                         Estoque atual: {}, reservado: {}",
+                        estoque_atual, reservado
+                    )
+                })
+                .to_string(),
+            )
+        }
+        Err(diesel::result::Error::DatabaseError(
+            diesel::result::DatabaseErrorKind::UniqueViolation,
+            _,
+        )) => conflito(mov_id, estorno_de(mov_id).unwrap_or_default()),
+        Err(e) => Resposta::ErroSemantico(
+            json!({
+                "mensagem": e.to_string()
+            })
+            .to_string(),
+        ),
+    }
+}
+
+/// Registra o estorno de uma única movimentação de estoque, como descrito em
+/// [`estorna_movimento`]. Esta função deve ser executada dentro de uma
+/// transação.
+///
+/// Caso o estorno torne o estoque negativo, será retornado o erro
+/// `RollbackTransaction`. Caso os lotes ou os números de série da
+/// movimentação original não possam ser movimentados de forma inversa, será
+/// retornada uma mensagem de erro em JSON. Em caso de sucesso, será retornada
+/// a movimentação de estorno.
+fn aplica_estorno(
+    conexao: &PgConnection,
+    original: &MovEstoque,
+) -> QueryResult<Result<MovEstoque, String>> {
+    use super::produtos;
+    use crate::model::schema::{camada_custo, estoque, mov_estoque};
+    use bigdecimal::{Signed, Zero};
+
+    let produto = produtos::get_produto(conexao, original.produto_id)
+        .expect("Produto da movimentação não encontrado");
+    let lotes_original = lotes::lotes_movimento(conexao, original.id);
+    let series_original = series::series_movimento(conexao, original.id);
+
+    let novo_movimento = NovoMovEstoque {
+        produto_id: original.produto_id,
+        docto: original.docto.clone(),
+        quantidade: -original.quantidade.clone(),
+        preco_unitario: original.preco_unitario.clone(),
+        preco_frete: BigDecimal::zero(),
+        datahora: chrono::Utc::now(),
+        deposito_id: original.deposito_id,
+        custo_unitario: original.custo_unitario.clone(),
+        cliente_id: original.cliente_id,
+        estorno_de_id: Some(original.id),
+        documento_estoque_id: None,
+        inventario_id: None,
+        inicio: false,
+        transferencia_de_id: None,
+    };
+    let entrada = novo_movimento.quantidade.is_positive();
+
+    let posicao = aplica_movimento(
+        conexao,
+        novo_movimento.produto_id,
+        novo_movimento.deposito_id,
+        &novo_movimento.quantidade,
+        if entrada {
+            Some(&novo_movimento.custo_unitario)
+        } else {
+            None
+        },
+    )?
+    .ok_or(diesel::result::Error::RollbackTransaction)?;
+    let movimento = diesel::insert_into(mov_estoque::table)
+        .values(&novo_movimento)
+        .get_result::<MovEstoque>(conexao)?;
+
+    if entrada {
+        cria_camada(
+            conexao,
+            movimento.produto_id,
+            movimento.deposito_id,
+            Some(movimento.id),
+            &movimento.quantidade,
+            &movimento.custo_unitario,
+        )?;
+    } else {
+        diesel::update(estoque::table.find((posicao.produto_id, posicao.deposito_id)))
+            .set(
+                estoque::custo_medio.eq(crate::bo::estoque::custo_estorno_entrada(
+                    &posicao.quantidade,
+                    &posicao.custo_medio,
+                    &original.quantidade,
+                    &original.custo_unitario,
+                )),
+            )
+            .execute(conexao)?;
+        let (mut camadas, demais): (Vec<_>, Vec<_>) = camada_custo::table
+            .filter(camada_custo::produto_id.eq(posicao.produto_id))
+            .filter(camada_custo::deposito_id.eq(posicao.deposito_id))
+            .filter(camada_custo::restante.gt(BigDecimal::zero()))
+            .order(camada_custo::id)
+            .for_update()
+            .load::<CamadaCusto>(conexao)?
+            .into_iter()
+            .partition(|c| c.mov_estoque_id == Some(original.id));
+        camadas.extend(demais);
+        let (consumos, _) = crate::bo::estoque::consome_camadas(
+            &camadas,
+            &original.quantidade,
+            &posicao.custo_medio,
+        );
+        for (camada_id, consumo) in consumos {
+            diesel::update(camada_custo::table.find(camada_id))
+                .set(camada_custo::restante.eq(camada_custo::restante - consumo))
+                .execute(conexao)?;
+        }
+    }
+
+    for lote in &lotes_original {
+        let recv_lote = LoteRecv {
+            numero: lote.numero.clone(),
+            fabricacao: None,
+            validade: None,
+        };
+        if let Err(s) = lotes::movimenta_lotes(
+            conexao,
+            &produto,
+            movimento.deposito_id,
+            Some(movimento.id),
+            &-lote.quantidade.clone(),
+            Some(&recv_lote),
+        )? {
+            return Ok(Err(s));
+        }
+    }
+
+    if let Err(s) = series::movimenta_series(
+        conexao,
+        &produto,
+        movimento.deposito_id,
+        Some(movimento.id),
+        &movimento.quantidade,
+        &series_original,
+    )? {
+        return Ok(Err(s));
+    }
+    Ok(Ok(movimento))
+}
+
+/// Aplica uma movimentação sobre a posição de estoque de um produto em um
+/// depósito.
+///
+/// A quantidade informada é somada à quantidade em estoque através de uma
+/// alteração condicional, que só ocorrerá caso o estoque resultante não seja
+/// negativo. Como a alteração trava a linha da posição de estoque até o fim da
+/// transação corrente, movimentações concorrentes do mesmo produto são
+/// avaliadas sobre a quantidade já atualizada, e não podem tornar o estoque
+/// negativo.
+///
+/// Saídas também não podem consumir a quantidade reservada da posição de
+/// estoque (veja [`total_reservado`][`super::reservas::total_reservado`]).
+/// Para tanto, a posição de estoque é travada antes do cálculo da quantidade
+/// reservada, assim como ocorre no registro de reservas, e a saída só ocorrerá
+/// caso o estoque resultante não seja inferior à quantidade reservada.
+///
+/// Caso seja informado o custo unitário de uma entrada, o custo médio da
+/// posição de estoque será recalculado na mesma alteração, como a média entre
+/// o custo médio atual e o custo da entrada, ponderada pelas respectivas
+/// quantidades.
+///
+/// Retorna a posição de estoque atualizada, ou `None` caso a movimentação
+/// tornasse o estoque negativo, consumisse a quantidade reservada, ou não
+/// houvesse posição de estoque. Deve ser
+/// utilizada dentro de uma transação que também registre a movimentação.
+fn aplica_movimento(
+    conexao: &PgConnection,
+    prod_id: i32,
+    dep_id: i32,
+    qtd: &BigDecimal,
+    custo: Option<&BigDecimal>,
+) -> QueryResult<Option<Estoque>> {
+    use crate::model::schema::estoque::dsl::*;
+    use bigdecimal::{Signed, Zero};
+
+    let minimo = if qtd.is_negative() {
+        let travada = estoque
+            .find((prod_id, dep_id))
+            .select(quantidade)
+            .for_update()
+            .first::<BigDecimal>(conexao)
+            .optional()?;
+        if travada.is_none() {
+            return Ok(None);
+        }
+        super::reservas::total_reservado(conexao, prod_id, dep_id)?
+    } else {
+        BigDecimal::zero()
+    };
+    let alvo = estoque
+        .filter(produto_id.eq(prod_id))
+        .filter(deposito_id.eq(dep_id))
+        .filter((quantidade + qtd).ge(minimo));
+    match custo {
+        Some(custo) => diesel::update(alvo)
+            .set((
+                quantidade.eq(quantidade + qtd),
+                custo_medio.eq((quantidade * custo_medio + qtd * custo) / (quantidade + qtd)),
+            ))
+            .get_result::<Estoque>(conexao)
+            .optional(),
+        None => diesel::update(alvo)
+            .set(quantidade.eq(quantidade + qtd))
+            .get_result::<Estoque>(conexao)
+            .optional(),
+    }
+}
+
+/// Cria uma camada de custo para uma entrada de estoque de um produto em um
+/// depósito, com a quantidade e o custo unitário da entrada.
+///
+/// Deve ser utilizada dentro da transação que registra a entrada.
+fn cria_camada(
+    conexao: &PgConnection,
+    prod_id: i32,
+    dep_id: i32,
+    mov_id: Option<i32>,
+    qtd: &BigDecimal,
+    custo: &BigDecimal,
+) -> QueryResult<CamadaCusto> {
+    use crate::model::schema::camada_custo;
+    diesel::insert_into(camada_custo::table)
+        .values(&NovaCamadaCusto {
+            produto_id: prod_id,
+            deposito_id: dep_id,
+            mov_estoque_id: mov_id,
+            quantidade: qtd.clone(),
+            restante: qtd.clone(),
+            custo_unitario: custo.clone(),
+        })
+        .get_result::<CamadaCusto>(conexao)
+}
+
+/// Consome as camadas de custo de uma posição de estoque por uma saída de
+/// `qtd` unidades, informada como um valor positivo, e retorna o custo
+/// unitário pelo qual a saída deverá ser valorada.
+///
+/// As camadas são consumidas da mais antiga para a mais recente, como descrito
+/// em [`consome_camadas`][`crate::bo::estoque::consome_camadas`],
+/// independentemente do método de valoração do produto. A saída será valorada
+/// pelo custo das camadas consumidas caso o produto seja valorado por PEPS, ou
+/// pelo custo médio da posição de estoque, do contrário.
+///
+/// Deve ser utilizada dentro da transação que registra a saída, após a
+/// aplicação da saída sobre a posição de estoque.
+fn valora_saida(
+    conexao: &PgConnection,
+    produto: &Produto,
+    posicao: &Estoque,
+    qtd: &BigDecimal,
+) -> QueryResult<BigDecimal> {
+    use crate::model::schema::camada_custo::dsl::*;
+    use bigdecimal::Zero;
+
+    let camadas = camada_custo
+        .filter(produto_id.eq(posicao.produto_id))
+        .filter(deposito_id.eq(posicao.deposito_id))
+        .filter(restante.gt(BigDecimal::zero()))
+        .order(id)
+        .for_update()
+        .load::<CamadaCusto>(conexao)?;
+    let (consumos, custo_peps) =
+        crate::bo::estoque::consome_camadas(&camadas, qtd, &posicao.custo_medio);
+    for (camada_id, consumo) in consumos {
+        diesel::update(camada_custo.find(camada_id))
+            .set(restante.eq(restante - consumo))
+            .execute(conexao)?;
+    }
+    Ok(match produto.metodo_custo {
+        MetodoCusto::Medio => posicao.custo_medio.clone(),
+        MetodoCusto::Peps => custo_peps,
+    })
+}
+
+/// Realiza a saída de estoque de um kit, através da saída de estoque de cada um
+/// de seus componentes.
+///
+/// Kits não possuem estoque próprio, e portanto apenas saídas (quantidades
+/// negativas) podem ser registradas; entradas resultarão em um erro 422. Cada
+/// componente terá uma movimentação registrada com o documento informado, a
+/// quantidade do componente no kit multiplicada pela quantidade movimentada, o
+/// preço unitário atual do componente, e valorada segundo o método de
+/// valoração do componente (veja [`valora_saida`]).
+///
+/// Os componentes são movimentados no depósito informado na movimentação do
+/// kit. Todos os componentes devem possuir início de estoque neste depósito e
+/// estar ativos, e nenhum deles pode ter seu estoque tornado negativo; do
+/// contrário, será retornado um erro 422 e nenhuma movimentação será
+/// registrada. As movimentações dos componentes são registradas em uma única
+/// transação, e são retornadas em caso de sucesso.
+///
+/// Saídas de kits não podem informar lotes. Componentes com controle de lotes
+/// terão seus lotes consumidos por FEFO (veja
+/// [`movimenta_lotes`][`super::lotes::movimenta_lotes`]). Como os números de
+/// série das unidades de cada componente não podem ser informados, kits com
+/// componentes com controle de números de série não podem ser movimentados.
+/// O cliente informado na saída do kit é registrado nas movimentações dos
+/// componentes.
+fn movimenta_kit(
+    conexao: &PgConnection,
+    kit: &Produto,
+    componentes: &[ProdutoComponente],
+    recv: MovEstoqueRecv,
+) -> Resposta {
+    use super::{produtos, unidades};
+    use crate::bo::produtos::valida_quantidade;
+    use crate::model::schema::mov_estoque;
+    use bigdecimal::{Signed, Zero};
+
+    let erro =
+        |mensagem: String| Resposta::ErroSemantico(json!({ "mensagem": mensagem }).to_string());
+
+    if !recv.quantidade.is_negative() {
+        return erro(format!(
+            "O produto {} é um kit e não possui estoque próprio; apenas saídas \
+             podem ser registradas",
+            kit.id
+        ));
+    }
+
+    if recv.lote.is_some() {
+        return erro(format!(
+            "O produto {} é um kit; saídas de kits não podem informar lote",
+            kit.id
+        ));
+    }
+
+    if !recv.numeros_serie.is_empty() {
+        return erro(format!(
+            "O produto {} é um kit; saídas de kits não podem informar números de série",
+            kit.id
+        ));
+    }
+
+    // 1. Recupera o estoque de cada componente.
+    let mut posicoes = Vec::new();
+    for componente in componentes {
+        let produto = produtos::get_produto(conexao, componente.componente_id).unwrap();
+        if !produto.ativo {
+            return erro(format!("O componente {} está inativo", produto.id));
+        }
+        if produto.controla_serie {
+            return erro(format!(
+                "O componente {} possui controle de números de série e não pode \
+                 ser movimentado através do kit {}",
+                produto.id, kit.id
+            ));
+        }
+        match get_estoque(conexao, produto.id, recv.deposito_id) {
+            None => {
+                return erro(format!(
+                    "Necessário efetuar início de estoque para o componente {} no depósito {}",
+                    produto.id, recv.deposito_id
+                ))
+            }
+            Some(e) => posicoes.push((componente.clone(), e, produto)),
+        }
+    }
+
+    // 2. Calcula as movimentações, verificando se algum estoque se tornará
+    //    negativo e se as quantidades respeitam as unidades dos componentes.
+    let pares: Vec<_> = posicoes
+        .iter()
+        .map(|(c, e, _)| (c.clone(), e.clone()))
+        .collect();
+    let movimentos = match crate::bo::estoque::movimentos_componentes(&recv.quantidade, &pares) {
+        Ok(m) => m,
+        Err(s) => return Resposta::ErroSemantico(s),
+    };
+    for ((_, _, produto), (_, quantidade)) in posicoes.iter().zip(movimentos.iter()) {
+        let unidade = unidades::get_unidade(conexao, &produto.unidsaida)
+            .expect("Unidade de saída do produto não cadastrada");
+        if let Err(s) = valida_quantidade(quantidade, &unidade) {
+            return Resposta::ErroSemantico(s);
+        }
+    }
+
+    // 3. Registra as movimentações e altera os estoques em uma transação.
+    //    Assim como em movimentações comuns, as alterações de estoque são
+    //    condicionais, e nenhuma movimentação será registrada caso o estoque
+    //    de algum componente tenha se tornado insuficiente no processo.
+    let datahora = chrono::offset::Utc::now();
+    let mut sem_estoque = None;
+    let mut erro_rastreio = None;
+    let resultado = conexao.transaction::<_, diesel::result::Error, _>(|| {
+        let mut registrados = Vec::new();
+        for ((_, _, produto), (componente_id, quantidade)) in posicoes.iter().zip(movimentos) {
+            let posicao = match aplica_movimento(
+                conexao,
+                componente_id,
+                recv.deposito_id,
+                &quantidade,
+                None,
+            )? {
+                Some(p) => p,
+                None => {
+                    sem_estoque = Some(componente_id);
+                    return Err(diesel::result::Error::RollbackTransaction);
+                }
+            };
+            let custo = valora_saida(conexao, produto, &posicao, &-quantidade.clone())?;
+            let movimento = diesel::insert_into(mov_estoque::table)
+                .values(&NovoMovEstoque {
+                    produto_id: componente_id,
+                    docto: recv.docto.clone(),
+                    quantidade,
+                    preco_unitario: posicao.precounitario,
+                    preco_frete: BigDecimal::zero(),
+                    datahora,
+                    deposito_id: recv.deposito_id,
+                    custo_unitario: custo,
+                    cliente_id: recv.cliente_id,
+                    estorno_de_id: None,
+                    documento_estoque_id: None,
+                    inventario_id: None,
+                    inicio: false,
+                    transferencia_de_id: None,
+                })
+                .get_result::<MovEstoque>(conexao)?;
+            if let Err(s) = lotes::movimenta_lotes(
+                conexao,
+                produto,
+                recv.deposito_id,
+                Some(movimento.id),
+                &movimento.quantidade,
+                None,
+            )? {
+                erro_rastreio = Some(s);
+                return Err(diesel::result::Error::RollbackTransaction);
+            }
+            registrados.push(movimento);
+        }
+        Ok(registrados)
+    });
+
+    match resultado {
+        Ok(registrados) => {
+            for movimento in &registrados {
+                let _ = registra_log(
+                    conexao,
+                    String::from("MOV_ESTOQUE"),
+                    String::from("TO-DO"),
+                    DBOperacao::Insercao,
+                    Some(format!(
+                        "Movimento de estoque {}: saída do kit {}",
+                        movimento.id, kit.id
+                    )),
+                );
+                let _ = registra_log(
+                    conexao,
+                    String::from("ESTOQUE"),
+                    String::from("TO-DO"),
+                    DBOperacao::Alteracao,
+                    Some(format!(
+                        "Altera estoque do produto {}",
+                        movimento.produto_id
+                    )),
+                );
+            }
+            Resposta::Ok(serde_json::to_string(&registrados).unwrap())
+        }
+        Err(diesel::result::Error::RollbackTransaction) if erro_rastreio.is_some() => {
+            Resposta::ErroSemantico(erro_rastreio.unwrap())
+        }
+        Err(diesel::result::Error::RollbackTransaction) => {
+            let componente_id = sem_estoque.unwrap_or_default();
+            let estoque_atual = get_estoque(conexao, componente_id, recv.deposito_id)
+                .map(|e| e.quantidade)
+                .unwrap_or_else(BigDecimal::zero);
+            let reservado =
+                super::reservas::total_reservado(conexao, componente_id, recv.deposito_id)
+                    .unwrap_or_else(|_| BigDecimal::zero());
+            erro(format!(
+                "Movimentações de estoque não podem torná-lo negativo! \
+                 Estoque atual do componente {}: {}, reservado: {}",
+                componente_id, estoque_atual, reservado
+            ))
+        }
+        Err(e) => Resposta::ErroSemantico(
+            json!({
+                "mensagem": e.to_string()
+            })
+            .to_string(),
+        ),
+    }
+}
+
+/// Transfere estoque de um produto entre dois depósitos.
+///
+/// A transferência é registrada como uma saída de estoque no depósito de
+/// origem e uma entrada de estoque no depósito de destino, com o mesmo
+/// documento, data e hora, e com o preço unitário do produto no depósito de
+/// origem. Ambas são valoradas pelo custo da saída no depósito de origem (veja
+/// [`valora_saida`]), que é incorporado ao custo médio do depósito de destino
+/// e origina uma nova camada de custo neste depósito. Caso o
+/// produto não possua início de estoque no depósito de destino, sua posição de
+/// estoque será criada neste depósito, com o preço de venda do depósito de
+/// origem.
+///
+/// O produto deve existir, do contrário será retornado um erro 404; o mesmo
+/// ocorrerá caso não haja início de estoque no depósito de origem. Será
+/// retornado um erro 422 caso os dados da transferência sejam inválidos (veja
+/// [`valida_transferencia`][`crate::bo::estoque::valida_transferencia`]), caso
+/// o produto ou algum dos depósitos esteja inativo, caso o produto seja um kit
+/// ou possua variantes, ou caso o estoque do depósito de origem seja
+/// insuficiente.
+///
+/// Para produtos com controle de lotes, o lote informado, ou os lotes que
+/// vencem primeiro, são transferidos para o depósito de destino, com o mesmo
+/// número e as mesmas datas (veja
+/// [`movimenta_lotes`][`super::lotes::movimenta_lotes`]). Para produtos com
+/// controle de números de série, os números de série informados devem estar em
+/// estoque no depósito de origem, e passam a estar em estoque no depósito de
+/// destino.
+///
+/// Ambas as movimentações e as alterações de estoque ocorrem em uma única
+/// transação, e são retornadas em caso de sucesso, na ordem saída e entrada.
+/// A entrada referencia a saída (veja [`MovEstoque::transferencia_de_id`]),
+/// e ambas só podem ser estornadas em conjunto (veja [`estorna_movimento`]).
+pub fn transfere_estoque(
+    conexao: &PgConnection,
+    recv: TransferenciaRecv,
+    usuario: &str,
+) -> Resposta {
+    use super::{produtos, unidades};
+    use crate::model::schema::{estoque, mov_estoque};
+    use bigdecimal::Zero;
+
+    let erro =
+        |mensagem: String| Resposta::ErroSemantico(json!({ "mensagem": mensagem }).to_string());
+
+    if let Err(s) = crate::bo::estoque::valida_transferencia(&recv) {
+        return Resposta::ErroSemantico(s);
+    }
+
+    // 1. Verifica o produto e os depósitos envolvidos.
+    let produto = match produtos::get_produto(conexao, recv.produto_id) {
+        None => {
+            return Resposta::NaoEncontrado(
+                json!({
+                    "mensagem": "Produto não encontrado"
+                })
+                .to_string(),
+            )
+        }
+        Some(p) if !p.ativo => return erro(format!("O produto {} está inativo", p.id)),
+        Some(p) => p,
+    };
+
+    if let Err(resposta) = verifica_variantes(conexao, &produto) {
+        return resposta;
+    }
+
+    if !produtos::lista_componentes(conexao, produto.id).is_empty() {
+        return erro(format!(
+            "O produto {} é um kit e não possui estoque próprio",
+            produto.id
+        ));
+    }
+
+    for dep_id in &[recv.origem_id, recv.destino_id] {
+        if let Err(resposta) = verifica_deposito(conexao, *dep_id) {
+            return resposta;
+        }
+    }
+
+    let unidade_saida = unidades::get_unidade(conexao, &produto.unidsaida)
+        .expect("Unidade de saída do produto não cadastrada");
+    if let Err(s) = crate::bo::produtos::valida_quantidade(&recv.quantidade, &unidade_saida) {
+        return Resposta::ErroSemantico(s);
+    }
+
+    if get_estoque(conexao, produto.id, recv.origem_id).is_none() {
+        return Resposta::NaoEncontrado(
+            json!({
+                "mensagem": format!(
+                    "Necessário efetuar início de estoque para o produto {} no depósito {}",
+                    produto.id, recv.origem_id
+                )
+            })
+            .to_string(),
+        );
+    }
+
+    let lote = recv.lote.as_ref().map(|numero| LoteRecv {
+        numero: numero.clone(),
+        fabricacao: None,
+        validade: None,
+    });
+    if let Err(s) =
+        crate::bo::estoque::valida_lote(&produto, &-recv.quantidade.clone(), lote.as_ref())
+    {
+        return Resposta::ErroSemantico(s);
+    }
+
+    if let Err(s) =
+        crate::bo::estoque::valida_numeros_serie(&produto, &recv.quantidade, &recv.numeros_serie)
+    {
+        return Resposta::ErroSemantico(s);
+    }
+
+    // 2. Registra a saída e a entrada em uma única transação. A saída é
+    //    aplicada primeiro, e de forma condicional; caso o estoque de origem
+    //    seja insuficiente, nada será registrado. Os lotes e os números de
+    //    série transferidos são movimentados na mesma transação.
+    let datahora = chrono::offset::Utc::now();
+    let mut erro_rastreio = None;
+    let resultado = conexao.transaction::<_, diesel::result::Error, _>(|| {
+        let origem = aplica_movimento(
+            conexao,
+            produto.id,
+            recv.origem_id,
+            &-recv.quantidade.clone(),
+            None,
+        )?
+        .ok_or(diesel::result::Error::RollbackTransaction)?;
+        let custo = valora_saida(conexao, &produto, &origem, &recv.quantidade)?;
+
+        diesel::insert_into(estoque::table)
+            .values(&Estoque {
+                produto_id: produto.id,
+                quantidade: BigDecimal::zero(),
+                precounitario: origem.precounitario.clone(),
+                deposito_id: recv.destino_id,
+                custo_medio: custo.clone(),
+            })
+            .on_conflict_do_nothing()
+            .execute(conexao)?;
+        aplica_movimento(
+            conexao,
+            produto.id,
+            recv.destino_id,
+            &recv.quantidade,
+            Some(&custo),
+        )?
+        .ok_or(diesel::result::Error::RollbackTransaction)?;
+
+        let movimento = |quantidade: BigDecimal, deposito_id, transferencia_de_id| NovoMovEstoque {
+            produto_id: produto.id,
+            docto: recv.docto.clone(),
+            quantidade,
+            preco_unitario: origem.precounitario.clone(),
+            preco_frete: BigDecimal::zero(),
+            datahora,
+            deposito_id,
+            custo_unitario: custo.clone(),
+            cliente_id: None,
+            estorno_de_id: None,
+            documento_estoque_id: None,
+            inventario_id: None,
+            inicio: false,
+            transferencia_de_id,
+        };
+        let saida = diesel::insert_into(mov_estoque::table)
+            .values(&movimento(-recv.quantidade.clone(), recv.origem_id, None))
+            .get_result::<MovEstoque>(conexao)?;
+        let entrada = diesel::insert_into(mov_estoque::table)
+            .values(&movimento(
+                recv.quantidade.clone(),
+                recv.destino_id,
+                Some(saida.id),
+            ))
+            .get_result::<MovEstoque>(conexao)?;
+        cria_camada(
+            conexao,
+            produto.id,
+            recv.destino_id,
+            Some(entrada.id),
+            &entrada.quantidade,
+            &custo,
+        )?;
+
+        let transferidos = match lotes::movimenta_lotes(
+            conexao,
+            &produto,
+            recv.origem_id,
+            Some(saida.id),
+            &saida.quantidade,
+            lote.as_ref(),
+        )? {
+            Ok(t) => t,
+            Err(s) => {
+                erro_rastreio = Some(s);
+                return Err(diesel::result::Error::RollbackTransaction);
+            }
+        };
+        for (transferido, quantidade) in transferidos {
+            let destino = LoteRecv {
+                numero: transferido.numero,
+                fabricacao: transferido.fabricacao,
+                validade: transferido.validade,
+            };
+            if let Err(s) = lotes::movimenta_lotes(
+                conexao,
+                &produto,
+                recv.destino_id,
+                Some(entrada.id),
+                &-quantidade,
+                Some(&destino),
+            )? {
+                erro_rastreio = Some(s);
+                return Err(diesel::result::Error::RollbackTransaction);
+            }
+        }
+        for (movimento, deposito_id) in &[(&saida, recv.origem_id), (&entrada, recv.destino_id)] {
+            if let Err(s) = series::movimenta_series(
+                conexao,
+                &produto,
+                *deposito_id,
+                Some(movimento.id),
+                &movimento.quantidade,
+                &recv.numeros_serie,
+            )? {
+                erro_rastreio = Some(s);
+                return Err(diesel::result::Error::RollbackTransaction);
+            }
+        }
+        Ok(vec![saida, entrada])
+    });
+
+    match resultado {
+        Ok(registrados) => {
+            for movimento in &registrados {
+                let _ = registra_log(
+                    conexao,
+                    String::from("MOV_ESTOQUE"),
+                    usuario.to_owned(),
+                    DBOperacao::Insercao,
+                    Some(format!(
+                        "Movimento de estoque {}: transferência do depósito {} para o depósito {}",
+                        movimento.id, recv.origem_id, recv.destino_id
+                    )),
+                );
+                let _ = registra_log(
+                    conexao,
+                    String::from("ESTOQUE"),
+                    usuario.to_owned(),
+                    DBOperacao::Alteracao,
+                    Some(format!(
+                        "Altera estoque do produto {} no depósito {}",
+                        movimento.produto_id, movimento.deposito_id
+                    )),
+                );
+            }
+            Resposta::Ok(serde_json::to_string(&registrados).unwrap())
+        }
+        Err(diesel::result::Error::RollbackTransaction) if erro_rastreio.is_some() => {
+            Resposta::ErroSemantico(erro_rastreio.unwrap())
+        }
+        Err(diesel::result::Error::RollbackTransaction) => {
+            let estoque_atual = get_estoque(conexao, produto.id, recv.origem_id)
+                .map(|e| e.quantidade)
+                .unwrap_or_else(BigDecimal::zero);
+            let reservado = super::reservas::total_reservado(conexao, produto.id, recv.origem_id)
+                .unwrap_or_else(|_| BigDecimal::zero());
+            erro(format!(
+                "Movimentações de estoque não podem torná-lo negativo! \
+                 Estoque atual no depósito {}: {}, reservado: {}",
+                recv.origem_id, estoque_atual, reservado
+            ))
+        }
+        Err(e) => erro(e.to_string()),
+    }
+}
+
+/// Altera o preço de venda de um produto.
+///
+/// O preço de venda será alterado na posição de estoque do produto no depósito
+/// informado, ou em todas as posições de estoque do produto, caso nenhum
+/// depósito seja informado. O custo médio do produto não é alterado.
+///
+/// Caso o produto não possua posição de estoque nos depósitos em questão, será
+/// retornado um erro 404. Caso o preço seja menor ou igual a zero, será
+/// retornado um erro 422. Em caso de sucesso, será retornada a posição de
+/// estoque atualizada, como em [`mostra_estoque`].
+pub fn altera_preco_venda(
+    conexao: &PgConnection,
+    prod_id: i32,
+    recv: PrecoVendaRecv,
+    usuario: &str,
+) -> Resposta {
+    use crate::model::schema::estoque::dsl::*;
+    use bigdecimal::Signed;
+
+    if !recv.preco_unitario.is_positive() {
+        return Resposta::ErroSemantico(
+            json!({
+                "mensagem": "Preço unitário deve ser maior que zero"
+            })
+            .to_string(),
+        );
+    }
+
+    let mut query = diesel::update(estoque)
+        .filter(produto_id.eq(prod_id))
+        .into_boxed();
+    if let Some(dep_id) = recv.deposito_id {
+        query = query.filter(deposito_id.eq(dep_id));
+    }
+    match query
+        .set(precounitario.eq(&recv.preco_unitario))
+        .execute(conexao)
+    {
+        Ok(0) => Resposta::NaoEncontrado(
+            json!({
+                "mensagem": format!(
+                    "Necessário efetuar início de estoque para o produto {}",
+                    prod_id
+                )
+            })
+            .to_string(),
+        ),
+        Ok(_) => {
+            let _ = registra_log(
+                conexao,
+                String::from("ESTOQUE"),
+                usuario.to_owned(),
+                DBOperacao::Alteracao,
+                Some(format!("Altera preço de venda do produto {}", prod_id)),
+            );
+            let posicao = mostra_estoque(conexao, prod_id, recv.deposito_id);
+            Resposta::Ok(serde_json::to_string(&posicao).unwrap())
+        }
+        Err(e) => Resposta::ErroSemantico(
+            json!({
+                "mensagem": e.to_string()
+            })
+            .to_string(),
+        ),
+    }
+}
+
+/// Retorna a posição de estoque de um produto em um depósito.
+///
+/// Esta função retorna um Option que poderá conter a posição de estoque de um
+/// produto com o id informado, no depósito informado. Esta função verifica
+/// apenas se houve início de estoque do produto no depósito, mas não verifica
+/// se o produto ou o depósito existem.
+pub fn get_estoque(conexao: &PgConnection, prod_id: i32, dep_id: i32) -> Option<Estoque> {
+    use crate::model::schema::estoque::dsl::*;
+    estoque
+        .find((prod_id, dep_id))
+        .first::<Estoque>(conexao)
+        .optional()
+        .expect("Erro ao carregar estoque")
+}
+
+/// Informa se há movimentações de estoque referentes a qualquer cliente.
+pub fn existem_movimentos_clientes(conexao: &PgConnection) -> bool {
+    use crate::model::schema::mov_estoque::dsl::*;
+    diesel::select(diesel::dsl::exists(
+        mov_estoque.select(id).filter(cliente_id.is_not_null()),
+    ))
+    .get_result(conexao)
+    .expect("Erro ao verificar movimentações de clientes")
+}
+
+/// Informa se há movimentações de estoque referentes ao cliente de id
+/// informado.
+pub fn possui_movimentos_cliente(conexao: &PgConnection, cl_id: i32) -> bool {
+    use crate::model::schema::mov_estoque::dsl::*;
+    diesel::select(diesel::dsl::exists(
+        mov_estoque.filter(cliente_id.eq(&cl_id)),
+    ))
+    .get_result(conexao)
+    .expect("Erro ao verificar movimentações do cliente")
+}
+
+/// Retorna as posições de estoque de um produto em todos os depósitos, em
+/// ordem de depósito. Caso um depósito seja informado, apenas a posição de
+/// estoque neste depósito será retornada, caso exista.
+pub fn posicoes_produto(
+    conexao: &PgConnection,
+    prod_id: i32,
+    deposito: Option<i32>,
+) -> Vec<Estoque> {
+    use crate::model::schema::estoque::dsl::*;
+    let mut query = estoque.filter(produto_id.eq(prod_id)).into_boxed();
+    if let Some(deposito) = deposito {
+        query = query.filter(deposito_id.eq(deposito));
+    }
+    query
+        .order(deposito_id.asc())
+        .load::<Estoque>(conexao)
+        .expect("Erro ao carregar estoque")
+}
+
+/// Retorna a quantidade e o preço unitário de um produto, consolidados a partir
+/// de suas posições de estoque em todos os depósitos, caso haja início de
+/// estoque em algum depósito. Para mais informações, veja
+/// [`consolida_posicoes`][`crate::bo::estoque::consolida_posicoes`].
+pub fn estoque_consolidado(
+    conexao: &PgConnection,
+    prod_id: i32,
+) -> Option<(BigDecimal, BigDecimal)> {
+    let posicoes = posicoes_produto(conexao, prod_id, None);
+    if posicoes.is_empty() {
+        return None;
+    }
+    let pares: Vec<_> = posicoes
+        .iter()
+        .map(|e| (&e.quantidade, &e.precounitario))
+        .collect();
+    Some(crate::bo::estoque::consolida_posicoes(&pares))
+}
+
+/// Une as informações de uma posição de estoque de um produto em um depósito
+/// com os dados do produto referenciado e com a quantidade reservada da
+/// posição.
+fn transforma_estoque_retorno(conexao: &PgConnection, e: &Estoque) -> EstoqueRepr {
+    use super::produtos;
+    let p = produtos::get_produto(conexao, e.produto_id).unwrap();
+    let reservado = super::reservas::total_reservado(conexao, e.produto_id, e.deposito_id)
+        .expect("Erro ao carregar reservas");
+    EstoqueRepr {
+        id: p.id,
+        descricao: p.descricao.clone(),
+        unidsaida: p.unidsaida,
+        quantidade: e.quantidade.clone(),
+        disponivel: &e.quantidade - &reservado,
+        reservado,
+        preco_unitario: e.precounitario.clone(),
+        custo_medio: e.custo_medio.clone(),
+        produto_pai_id: p.produto_pai_id,
+        deposito_id: Some(e.deposito_id),
+        depositos: None,
+        variantes: None,
+    }
+}
+
+/// Une as informações das posições de estoque de um produto em todos os
+/// depósitos em uma posição consolidada, com os dados do produto referenciado.
+/// As posições informadas devem pertencer a um mesmo produto, e não podem
+/// estar vazias.
+fn consolida_estoque_retorno(conexao: &PgConnection, posicoes: &[Estoque]) -> EstoqueRepr {
+    let pares: Vec<_> = posicoes
+        .iter()
+        .map(|e| (&e.quantidade, &e.precounitario))
+        .collect();
+    let (quantidade, preco_unitario) = crate::bo::estoque::consolida_posicoes(&pares);
+    let pares: Vec<_> = posicoes
+        .iter()
+        .map(|e| (&e.quantidade, &e.custo_medio))
+        .collect();
+    let (_, custo_medio) = crate::bo::estoque::consolida_posicoes(&pares);
+    let depositos: Vec<_> = posicoes
+        .iter()
+        .map(|e| {
+            let reservado = super::reservas::total_reservado(conexao, e.produto_id, e.deposito_id)
+                .expect("Erro ao carregar reservas");
+            PosicaoDeposito {
+                deposito_id: e.deposito_id,
+                quantidade: e.quantidade.clone(),
+                disponivel: &e.quantidade - &reservado,
+                reservado,
+                preco_unitario: e.precounitario.clone(),
+                custo_medio: e.custo_medio.clone(),
+            }
+        })
+        .collect();
+    EstoqueRepr {
+        quantidade,
+        reservado: depositos.iter().map(|d| &d.reservado).sum(),
+        disponivel: depositos.iter().map(|d| &d.disponivel).sum(),
+        preco_unitario,
+        custo_medio,
+        deposito_id: None,
+        depositos: Some(depositos),
+        ..transforma_estoque_retorno(conexao, &posicoes[0])
+    }
+}
+
+/// Transforma posições de estoque em suas representações com dados de
+/// produto, em ordem de produto.
+///
+/// Caso um depósito seja informado, assume-se que as posições pertençam a este
+/// depósito, e cada uma será representada individualmente. Do contrário, as
+/// posições de cada produto serão consolidadas, como descrito em
+/// [`consolida_estoque_retorno`].
+fn representa_posicoes(
+    conexao: &PgConnection,
+    posicoes: Vec<Estoque>,
+    deposito: Option<i32>,
+) -> Vec<EstoqueRepr> {
+    if deposito.is_some() {
+        return posicoes
+            .iter()
+            .map(|e| transforma_estoque_retorno(conexao, e))
+            .collect();
+    }
+    let mut por_produto: BTreeMap<i32, Vec<Estoque>> = BTreeMap::new();
+    for posicao in posicoes {
+        por_produto
+            .entry(posicao.produto_id)
+            .or_default()
+            .push(posicao);
+    }
+    por_produto
+        .values()
+        .map(|p| consolida_estoque_retorno(conexao, p))
+        .collect()
+}
+
+/// Lista uma quantidade limitada de posições de estoque com dados de produto.
+///
+/// Retorna um Vec com estruturas que representam a união entre dados de um
+/// produto e de sua posição de estoque. A quantidade de estruturas retornadas
+/// não será superior a `limite`. Caso uma categoria seja informada, serão
+/// retornadas apenas posições de estoque de produtos desta categoria ou de suas
+/// subcategorias.
+///
+/// Caso um depósito seja informado, serão retornadas as posições de estoque
+/// dos produtos neste depósito. Do contrário, será retornada a posição
+/// consolidada de cada produto em todos os depósitos, contendo as posições de
+/// cada depósito.
+///
+/// Caso `agrupa` seja verdadeiro, as posições de estoque de variantes serão
+/// substituídas por uma posição consolidada de seu produto pai, contendo as
+/// posições das variantes. Para mais informações, veja
+/// [`consolida_variantes`][`crate::bo::estoque::consolida_variantes`].
+pub fn lista_estoque(
+    conexao: &PgConnection,
+    limite: i64,
+    categoria: Option<i32>,
+    deposito: Option<i32>,
+    agrupa: bool,
+) -> Vec<EstoqueRepr> {
+    use crate::model::schema::{estoque, produto};
+    let mut query = estoque::table.into_boxed();
+    if let Some(deposito) = deposito {
+        query = query.filter(estoque::deposito_id.eq(deposito));
+    }
+    if let Some(categoria) = categoria {
+        let categorias = super::categorias::descendentes(conexao, categoria);
+        query = query.filter(
+            estoque::produto_id.eq_any(
+                produto::table
+                    .select(produto::id)
+                    .filter(produto::categoria_id.eq_any(categorias)),
+            ),
+        );
+    }
+    if !agrupa && deposito.is_some() {
+        query = query.limit(limite);
+    }
+    let posicoes = query
+        .order((estoque::produto_id.asc(), estoque::deposito_id.asc()))
+        .load::<Estoque>(conexao)
+        .expect("Erro ao carregar estoque");
+    let mut posicoes = representa_posicoes(conexao, posicoes, deposito);
+
+    if !agrupa {
+        posicoes.truncate(limite as usize);
+        return posicoes;
+    }
+
+    let mut agrupadas: Vec<EstoqueRepr> = Vec::new();
+    let mut variantes: BTreeMap<i32, Vec<EstoqueRepr>> = BTreeMap::new();
+    for posicao in posicoes {
+        match posicao.produto_pai_id {
+            Some(pai) => variantes.entry(pai).or_default().push(posicao),
+            None => agrupadas.push(posicao),
+        }
+    }
+    for (pai, variantes) in variantes {
+        let pai = super::produtos::get_produto(conexao, pai).unwrap();
+        agrupadas.push(crate::bo::estoque::consolida_variantes(&pai, variantes));
+    }
+    agrupadas.sort_by_key(|e| e.id);
+    agrupadas.truncate(limite as usize);
+    agrupadas
+}
+
+/// Lista as camadas de custo com quantidade restante, de um produto e de um
+/// depósito, caso informados.
+///
+/// As camadas são ordenadas por produto, depósito e ordem de consumo, da mais
+/// antiga para a mais recente.
+pub fn lista_camadas(
+    conexao: &PgConnection,
+    produto: Option<i32>,
+    deposito: Option<i32>,
+) -> Vec<CamadaCusto> {
+    use crate::model::schema::camada_custo::dsl::*;
+    use bigdecimal::Zero;
+    let mut query = camada_custo
+        .filter(restante.gt(BigDecimal::zero()))
+        .into_boxed();
+    if let Some(produto) = produto {
+        query = query.filter(produto_id.eq(produto));
+    }
+    if let Some(deposito) = deposito {
+        query = query.filter(deposito_id.eq(deposito));
+    }
+    query
+        .order((produto_id, deposito_id, id))
+        .load::<CamadaCusto>(conexao)
+        .expect("Erro ao carregar camadas de custo")
+}
+
+/// Valora o estoque de um depósito, ou de todos os depósitos, caso nenhum
+/// depósito seja informado, pelo custo médio e por PEPS.
+///
+/// Para mais informações, veja
+/// [`valoriza_produto`][`crate::bo::estoque::valoriza_produto`].
+pub fn valoriza_estoque(conexao: &PgConnection, deposito: Option<i32>) -> ValorizacaoEstoque {
+    use crate::model::schema::{estoque, produto};
+    let mut query = estoque::table
+        .inner_join(produto::table.on(produto::id.eq(estoque::produto_id)))
+        .select((produto::all_columns, estoque::all_columns))
+        .into_boxed();
+    if let Some(deposito) = deposito {
+        query = query.filter(estoque::deposito_id.eq(deposito));
+    }
+    let posicoes = query
+        .order((estoque::produto_id, estoque::deposito_id))
+        .load::<(Produto, Estoque)>(conexao)
+        .expect("Erro ao carregar posições de estoque");
+
+    let mut camadas: BTreeMap<i32, Vec<CamadaCusto>> = BTreeMap::new();
+    for camada in lista_camadas(conexao, None, deposito) {
+        camadas.entry(camada.produto_id).or_default().push(camada);
+    }
+
+    let mut produtos: Vec<(Produto, Vec<Estoque>)> = Vec::new();
+    for (prod, posicao) in posicoes {
+        match produtos.last_mut() {
+            Some((p, lista)) if p.id == prod.id => lista.push(posicao),
+            _ => produtos.push((prod, vec![posicao])),
+        }
+    }
+
+    let valores = produtos
+        .iter()
+        .map(|(prod, lista)| {
+            let camadas = camadas.get(&prod.id).map(Vec::as_slice).unwrap_or(&[]);
+            crate::bo::estoque::valoriza_produto(prod, lista, camadas)
+        })
+        .collect();
+    crate::bo::estoque::valoriza_estoque(deposito, valores)
+}
+
+/// Totaliza o estoque por categoria de produto.
+///
+/// O estoque de cada produto é valorado pelo custo, da mesma forma que em
+/// [`valoriza_estoque`]. Os totais de cada categoria incluem os produtos de
+/// todas as suas subcategorias. Produtos sem categoria são totalizados em um
+/// item à parte, sem id de categoria. Para mais informações, veja
+/// [`totaliza_categorias`][`crate::bo::categorias::totaliza_categorias`].
+pub fn totais_por_categoria(conexao: &PgConnection) -> Vec<TotalCategoria> {
+    use super::categorias;
+    use crate::model::schema::produto;
+    let categorias_produtos: BTreeMap<i32, Option<i32>> = produto::table
+        .select((produto::id, produto::categoria_id))
+        .load::<(i32, Option<i32>)>(conexao)
+        .expect("Erro ao carregar categorias dos produtos")
+        .into_iter()
+        .collect();
+    let produtos: Vec<(Option<i32>, BigDecimal, BigDecimal)> = valoriza_estoque(conexao, None)
+        .produtos
+        .into_iter()
+        .map(|p| {
+            let categoria_id = categorias_produtos.get(&p.produto_id).copied().flatten();
+            (categoria_id, p.quantidade, p.valor)
+        })
+        .collect();
+    crate::bo::categorias::totaliza_categorias(&categorias::lista_categorias(conexao), &produtos)
+}
+
+/// Mostra a posição de estoque de um produto com seus respectivos dados.
+///
+/// Retorna um Option que poderá conter os dados de posição de estoque de um
+/// produto, junto com seus dados de cadastro. Os dados só serão retornados se
+/// o sistema encontrar a posição de estoque do produto e seus dados
+/// correspondentes, respectivamente.
+///
+/// Caso um depósito seja informado, será retornada a posição de estoque do
+/// produto neste depósito. Do contrário, será retornada a posição consolidada
+/// do produto em todos os depósitos, contendo as posições de cada depósito.
+///
+/// Caso o produto possua variantes, será retornada a posição consolidada das
+/// variantes que possuam início de estoque, contendo as posições de cada uma
+/// delas.
+pub fn mostra_estoque(
+    conexao: &PgConnection,
+    prod_id: i32,
+    deposito: Option<i32>,
+) -> Option<EstoqueRepr> {
+    use super::produtos;
+    let posicao = |prod_id| {
+        representa_posicoes(
+            conexao,
+            posicoes_produto(conexao, prod_id, deposito),
+            deposito,
+        )
+        .pop()
+    };
+
+    if produtos::possui_variantes(conexao, prod_id) {
+        let pai = produtos::get_produto(conexao, prod_id)?;
+        let variantes = produtos::lista_variantes(conexao, prod_id)
+            .iter()
+            .filter_map(|v| posicao(v.produto.id))
+            .collect();
+        return Some(crate::bo::estoque::consolida_variantes(&pai, variantes));
+    }
+
+    posicao(prod_id)
+}
+
+/// Retorna o instante em que termina uma data, isto é, a meia-noite do dia
+/// seguinte no fuso horário local. Caso a meia-noite não exista no fuso
+/// horário local (ex: no início do horário de verão), será considerada a
+/// primeira hora seguinte.
+fn fim_da_data(data: chrono::NaiveDate) -> chrono::DateTime<chrono::Utc> {
+    use chrono::TimeZone;
+    let meia_noite = data.succ().and_hms(0, 0, 0);
+    chrono::Local
+        .from_local_datetime(&meia_noite)
+        .earliest()
+        .unwrap_or_else(|| {
+            chrono::Local
+                .from_local_datetime(&(meia_noite + chrono::Duration::hours(1)))
+                .unwrap()
+        })
+        .with_timezone(&chrono::Utc)
+}
+
+/// Lista uma quantidade limitada de posições de estoque em uma data passada,
+/// com dados de produto, em ordem de produto.
+///
+/// As posições são reconstruídas a partir das movimentações de estoque
+/// registradas até o fim da data, no fuso horário local, incluindo os inícios
+/// de estoque; posições cujo estoque ainda não havia sido iniciado na data não
+/// são retornadas. Para mais informações sobre o cálculo, veja
+/// [`reconstroi_saldo`][`crate::bo::estoque::reconstroi_saldo`].
+///
+/// Caso um produto seja informado, apenas suas posições serão retornadas; caso
+/// uma categoria seja informada, apenas posições de produtos desta categoria
+/// ou de suas subcategorias. Caso um depósito seja informado, serão retornadas
+/// as posições dos produtos neste depósito. Do contrário, será retornada a
+/// posição consolidada de cada produto em todos os depósitos, contendo as
+/// posições de cada depósito.
+pub fn posicoes_na_data(
+    conexao: &PgConnection,
+    data: chrono::NaiveDate,
+    produto_id: Option<i32>,
+    categoria: Option<i32>,
+    deposito: Option<i32>,
+    limite: i64,
+) -> Vec<EstoqueNaDataRepr> {
+    use crate::model::schema::{mov_estoque, produto};
+    let mut query = mov_estoque::table
+        .filter(mov_estoque::datahora.lt(fim_da_data(data)))
+        .into_boxed();
+    if let Some(produto_id) = produto_id {
+        query = query.filter(mov_estoque::produto_id.eq(produto_id));
+    }
+    if let Some(deposito) = deposito {
+        query = query.filter(mov_estoque::deposito_id.eq(deposito));
+    }
+    if let Some(categoria) = categoria {
+        let categorias = super::categorias::descendentes(conexao, categoria);
+        query = query.filter(
+            mov_estoque::produto_id.eq_any(
+                produto::table
+                    .select(produto::id)
+                    .filter(produto::categoria_id.eq_any(categorias)),
+            ),
+        );
+    }
+    let movimentos = query
+        .order((mov_estoque::produto_id, mov_estoque::deposito_id))
+        .select((
+            mov_estoque::produto_id,
+            mov_estoque::deposito_id,
+            mov_estoque::quantidade,
+            mov_estoque::custo_unitario,
+        ))
+        .load::<(i32, i32, BigDecimal, BigDecimal)>(conexao)
+        .expect("Erro ao carregar movimentações de estoque");
+
+    let mut por_produto: BTreeMap<i32, BTreeMap<i32, Vec<(BigDecimal, BigDecimal)>>> =
+        BTreeMap::new();
+    for (prod_id, dep_id, quantidade, custo) in movimentos {
+        por_produto
+            .entry(prod_id)
+            .or_default()
+            .entry(dep_id)
+            .or_default()
+            .push((quantidade, custo));
+    }
+
+    por_produto
+        .into_iter()
+        .take(limite as usize)
+        .map(|(prod_id, depositos)| {
+            let prod = super::produtos::get_produto(conexao, prod_id).unwrap();
+            let pares: Vec<_> = depositos.values().flatten().map(|(q, c)| (q, c)).collect();
+            let (quantidade, custo_medio, valor) = crate::bo::estoque::reconstroi_saldo(&pares);
+            let saldos = depositos
+                .iter()
+                .map(|(dep_id, movimentos)| {
+                    let pares: Vec<_> = movimentos.iter().map(|(q, c)| (q, c)).collect();
+                    let (quantidade, custo_medio, valor) =
+                        crate::bo::estoque::reconstroi_saldo(&pares);
+                    SaldoDeposito {
+                        deposito_id: *dep_id,
+                        quantidade,
+                        custo_medio,
+                        valor,
+                    }
+                })
+                .collect();
+            EstoqueNaDataRepr {
+                id: prod.id,
+                descricao: prod.descricao,
+                unidsaida: prod.unidsaida,
+                data,
+                quantidade,
+                custo_medio,
+                valor,
+                deposito_id: deposito,
+                depositos: if deposito.is_some() {
+                    None
+                } else {
+                    Some(saldos)
+                },
+            }
+        })
+        .collect()
+}
+
+/// Lista uma quantidade limitada de posições de estoque com dados de produto,
+/// em uma tabela escrita como texto-plano.
+///
+/// O retorno será uma string em texto-plano, formatada para assemelhar-se a uma
+/// tabela. A tabela não terá mais linhas do que a quantidade descrita pelo
+/// parâmetro `limite`.
+pub fn lista_movimentos_texto(conexao: &PgConnection, limite: i64) -> String {
+    let movimentos = recupera_movimentos(conexao, limite);
+    let mut table = Table::new();
+    prepara_tabela(&mut table);
+    processa_tabela(conexao, &movimentos, &mut table);
+    format!("{}\n", table)
+}
+
+/// Prepara o cabeçalho da tabela de movimentações de estoque.
 fn prepara_tabela(table: &mut Table) {
     use comfy_table::presets::ASCII_BORDERS_ONLY_CONDENSED;
     table
@@ -387,6 +2692,7 @@ fn prepara_tabela(table: &mut Table) {
             "Tipo",
             "Quantidade",
             "Preço Unit.",
+            "Custo Unit.",
             "Frete",
             "Data/Hora",
         ]);
@@ -415,6 +2721,7 @@ fn processa_tabela(conexao: &PgConnection, movimentos: &[MovEstoque], table: &mu
             tipo_movimento,
             mov.quantidade.abs().to_string(),
             mov.preco_unitario.to_string(),
+            mov.custo_unitario.to_string(),
             mov.preco_frete.to_string(),
             mov.datahora.to_string(),
         ]);
@@ -433,6 +2740,16 @@ pub fn recupera_movimentos(conexao: &PgConnection, limite: i64) -> Vec<MovEstoqu
         .expect("Erro ao recuperar movimentações de estoque")
 }
 
+/// Retorna uma movimentação de estoque em específico, caso existente.
+pub fn get_movimento(conexao: &PgConnection, movid: i32) -> Option<MovEstoque> {
+    use crate::model::schema::mov_estoque::dsl::*;
+    mov_estoque
+        .find(movid)
+        .first::<MovEstoque>(conexao)
+        .optional()
+        .expect("Erro ao recuperar movimentação de estoque")
+}
+
 /// Recupera movimentações de estoque a partir do banco de dados, em ordem
 /// decrescente de data, impressas em texto-plano formatado para parecer uma
 /// tabela.
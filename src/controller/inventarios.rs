@@ -0,0 +1,297 @@
+// controller/inventarios.rs -- Uma parte de Minerva.rs
+// Copyright (C) 2021 Lucas S. Vieira
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with this program.  If not, see <https://www.gnu.org/licenses/>.
+
+//! Ferramentas para tráfego de dados entre as rotas de sessões de inventário e
+//! o banco de dados.
+//!
+//! As ferramentas deste módulo realizam o tráfego de dados entre as rotas de
+//! inventário e as tabelas `inventario`, `inventario_item` e
+//! `inventario_contagem` do banco de dados. O fechamento de uma sessão de
+//! inventário ocorre junto das movimentações de ajuste correspondentes; veja
+//! [`fecha_inventario`][`super::estoque::fecha_inventario`].
+
+use super::log::*;
+use crate::model::inventario::*;
+use crate::model::schema::{estoque, inventario, inventario_contagem, inventario_item, produto};
+use crate::routes::respostas::Resposta;
+use bigdecimal::BigDecimal;
+use diesel::prelude::*;
+use serde_json::json;
+
+/// Retorna uma sessão de inventário, caso exista.
+pub fn get_inventario(conexao: &PgConnection, inventario_id: i32) -> Option<Inventario> {
+    inventario::table
+        .find(inventario_id)
+        .first::<Inventario>(conexao)
+        .optional()
+        .expect("Erro ao carregar inventário")
+}
+
+/// Lista as sessões de inventário de um depósito, caso informado.
+///
+/// As sessões são ordenadas da mais recente para a mais antiga.
+pub fn lista_inventarios(conexao: &PgConnection, deposito: Option<i32>) -> Vec<Inventario> {
+    let mut query = inventario::table.into_boxed();
+    if let Some(deposito) = deposito {
+        query = query.filter(inventario::deposito_id.eq(deposito));
+    }
+    query
+        .order(inventario::id.desc())
+        .load::<Inventario>(conexao)
+        .expect("Erro ao carregar inventários")
+}
+
+/// Revisa os itens de uma sessão de inventário, retornando a quantidade
+/// esperada, as contagens e a diferença de cada item, como descrito em
+/// [`revisa_item_inventario`][`crate::bo::estoque::revisa_item_inventario`].
+///
+/// Retorna `None` caso a sessão não exista.
+pub fn revisa_inventario(conexao: &PgConnection, inventario_id: i32) -> Option<RevisaoInventario> {
+    let inventario = get_inventario(conexao, inventario_id)?;
+    let itens = revisa_itens(conexao, inventario.id).expect("Erro ao revisar inventário");
+    Some(RevisaoInventario { inventario, itens })
+}
+
+/// Revisa os itens de uma sessão de inventário, como em
+/// [`revisa_inventario`]. Os itens são ordenados por produto.
+pub fn revisa_itens(
+    conexao: &PgConnection,
+    inventario_id: i32,
+) -> QueryResult<Vec<DiferencaInventario>> {
+    let itens = inventario_item::table
+        .filter(inventario_item::inventario_id.eq(inventario_id))
+        .order(inventario_item::produto_id)
+        .load::<ItemInventario>(conexao)?;
+    let contagens = inventario_contagem::table
+        .filter(inventario_contagem::inventario_id.eq(inventario_id))
+        .order(inventario_contagem::id)
+        .load::<ContagemInventario>(conexao)?;
+    Ok(itens
+        .iter()
+        .map(|item| crate::bo::estoque::revisa_item_inventario(item, &contagens))
+        .collect())
+}
+
+/// Abre uma sessão de inventário em um depósito.
+///
+/// Na abertura, a quantidade atual de cada posição de estoque do depósito é
+/// registrada como a quantidade esperada do respectivo item.
+///
+/// Sessões de inventário não suportam produtos com controle de lotes ou de
+/// números de série, já que seus ajustes exigem a identificação dos lotes e
+/// das unidades. Caso o depósito possua posições de estoque de tais produtos,
+/// a abertura será recusada com um erro 422, e os mesmos deverão ser ajustados
+/// através de movimentações de estoque comuns.
+///
+/// Caso a descrição esteja em branco, ou o depósito não exista ou esteja
+/// inativo, também será retornado um erro 422. Apenas uma sessão pode estar
+/// aberta em cada depósito; do contrário, será retornado um erro 409. Em caso
+/// de sucesso, será retornada a revisão da sessão aberta, e a abertura será
+/// registrada no log em nome do usuário informado.
+pub fn abre_inventario(conexao: &PgConnection, recv: InventarioRecv, usuario: &str) -> Resposta {
+    let erro =
+        |mensagem: String| Resposta::ErroSemantico(json!({ "mensagem": mensagem }).to_string());
+    if let Err(s) = crate::bo::estoque::valida_inventario(&recv) {
+        return Resposta::ErroSemantico(s);
+    }
+
+    match super::depositos::get_deposito(conexao, recv.deposito_id) {
+        None => return erro(format!("Depósito {} não encontrado", recv.deposito_id)),
+        Some(d) if !d.ativo => return erro(format!("O depósito {} está inativo", d.id)),
+        Some(_) => {}
+    }
+
+    let mut falha = None;
+    let resultado = conexao.transaction::<_, diesel::result::Error, _>(|| {
+        let controlados = estoque::table
+            .inner_join(produto::table.on(produto::id.eq(estoque::produto_id)))
+            .filter(estoque::deposito_id.eq(recv.deposito_id))
+            .filter(produto::controla_lote.or(produto::controla_serie))
+            .order(estoque::produto_id)
+            .select(estoque::produto_id)
+            .load::<i32>(conexao)?;
+        if !controlados.is_empty() {
+            let lista: Vec<String> = controlados.iter().map(|id| id.to_string()).collect();
+            falha = Some(erro(format!(
+                "O depósito {} possui produtos com controle de lotes ou de \
+                 números de série, que não são suportados em inventários: {}",
+                recv.deposito_id,
+                lista.join(", ")
+            )));
+            return Err(diesel::result::Error::RollbackTransaction);
+        }
+        let inventario = diesel::insert_into(inventario::table)
+            .values(&NovoInventario {
+                deposito_id: recv.deposito_id,
+                descricao: recv.descricao.trim().to_owned(),
+            })
+            .get_result::<Inventario>(conexao)?;
+        let itens: Vec<ItemInventario> = estoque::table
+            .inner_join(produto::table.on(produto::id.eq(estoque::produto_id)))
+            .filter(estoque::deposito_id.eq(inventario.deposito_id))
+            .order(estoque::produto_id)
+            .select((estoque::produto_id, estoque::quantidade))
+            .load::<(i32, BigDecimal)>(conexao)?
+            .into_iter()
+            .map(|(produto_id, esperado)| ItemInventario {
+                inventario_id: inventario.id,
+                produto_id,
+                esperado,
+            })
+            .collect();
+        diesel::insert_into(inventario_item::table)
+            .values(&itens)
+            .execute(conexao)?;
+        Ok(inventario)
+    });
+
+    match resultado {
+        Ok(inventario) => {
+            let _ = registra_log(
+                conexao,
+                String::from("INVENTARIO"),
+                usuario.to_owned(),
+                DBOperacao::Insercao,
+                Some(format!(
+                    "Abertura do inventário {} no depósito {}",
+                    inventario.id, inventario.deposito_id
+                )),
+            );
+            let revisao = revisa_inventario(conexao, inventario.id).unwrap();
+            Resposta::Ok(serde_json::to_string(&revisao).unwrap())
+        }
+        Err(diesel::result::Error::DatabaseError(
+            diesel::result::DatabaseErrorKind::UniqueViolation,
+            _,
+        )) => Resposta::Conflito(
+            json!({
+                "mensagem": format!(
+                    "Já existe um inventário aberto no depósito {}",
+                    recv.deposito_id
+                )
+            })
+            .to_string(),
+        ),
+        Err(diesel::result::Error::RollbackTransaction) if falha.is_some() => falha.unwrap(),
+        Err(e) => erro(e.to_string()),
+    }
+}
+
+/// Registra uma contagem de um item de uma sessão de inventário.
+///
+/// Um mesmo item pode ser contado várias vezes, por um ou mais contadores;
+/// uma nova contagem de um contador substitui suas contagens anteriores na
+/// revisão da sessão, mas todas as contagens são mantidas. Caso o contador
+/// não seja informado, será considerado o usuário informado.
+///
+/// Caso a sessão não exista, será retornado um erro 404; caso já tenha sido
+/// fechada, será retornado um erro 409. Caso o produto não faça parte da
+/// sessão, ou a quantidade seja inválida para a unidade de saída do produto,
+/// será retornado um erro 422. Em caso de sucesso, será retornada a contagem
+/// registrada.
+pub fn registra_contagem(
+    conexao: &PgConnection,
+    inventario_id: i32,
+    recv: ContagemRecv,
+    usuario: &str,
+) -> Resposta {
+    use super::{produtos, unidades};
+
+    let erro =
+        |mensagem: String| Resposta::ErroSemantico(json!({ "mensagem": mensagem }).to_string());
+    if let Err(s) = crate::bo::estoque::valida_contagem(&recv) {
+        return Resposta::ErroSemantico(s);
+    }
+
+    if get_inventario(conexao, inventario_id).is_none() {
+        return Resposta::NaoEncontrado(
+            json!({
+                "mensagem": "Inventário não encontrado"
+            })
+            .to_string(),
+        );
+    }
+
+    let produto = match produtos::get_produto(conexao, recv.produto_id) {
+        None => return erro(format!("Produto {} não encontrado", recv.produto_id)),
+        Some(p) => p,
+    };
+    let unidade_saida = unidades::get_unidade(conexao, &produto.unidsaida)
+        .expect("Unidade de saída do produto não cadastrada");
+    if let Err(s) = crate::bo::produtos::valida_quantidade(&recv.quantidade, &unidade_saida) {
+        return Resposta::ErroSemantico(s);
+    }
+
+    let mut falha = None;
+    let resultado = conexao.transaction::<_, diesel::result::Error, _>(|| {
+        let situacao = inventario::table
+            .find(inventario_id)
+            .select(inventario::situacao)
+            .for_update()
+            .first::<SituacaoInventario>(conexao)?;
+        if situacao != SituacaoInventario::Aberto {
+            falha = Some(Resposta::Conflito(
+                json!({
+                    "mensagem": format!("O inventário {} não está aberto", inventario_id)
+                })
+                .to_string(),
+            ));
+            return Err(diesel::result::Error::RollbackTransaction);
+        }
+        let item = inventario_item::table
+            .find((inventario_id, produto.id))
+            .first::<ItemInventario>(conexao)
+            .optional()?;
+        if item.is_none() {
+            falha = Some(erro(format!(
+                "O produto {} não faz parte do inventário {}",
+                produto.id, inventario_id
+            )));
+            return Err(diesel::result::Error::RollbackTransaction);
+        }
+        diesel::insert_into(inventario_contagem::table)
+            .values(&NovaContagemInventario {
+                inventario_id,
+                produto_id: produto.id,
+                contador: recv
+                    .contador
+                    .as_deref()
+                    .unwrap_or(usuario)
+                    .trim()
+                    .to_owned(),
+                quantidade: recv.quantidade.clone(),
+            })
+            .get_result::<ContagemInventario>(conexao)
+    });
+
+    match resultado {
+        Ok(contagem) => {
+            let _ = registra_log(
+                conexao,
+                String::from("INVENTARIO_CONTAGEM"),
+                usuario.to_owned(),
+                DBOperacao::Insercao,
+                Some(format!(
+                    "Contagem {} do produto {} no inventário {}",
+                    contagem.id, contagem.produto_id, contagem.inventario_id
+                )),
+            );
+            Resposta::Ok(serde_json::to_string(&contagem).unwrap())
+        }
+        Err(diesel::result::Error::RollbackTransaction) if falha.is_some() => falha.unwrap(),
+        Err(e) => erro(e.to_string()),
+    }
+}
@@ -0,0 +1,245 @@
+// controller/lotes.rs -- Uma parte de Minerva.rs
+// Copyright (C) 2021 Lucas S. Vieira
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with this program.  If not, see <https://www.gnu.org/licenses/>.
+
+//! Ferramentas para tráfego de dados entre as rotas de lotes e o banco de
+//! dados.
+//!
+//! As ferramentas deste módulo realizam o tráfego de dados entre as rotas de
+//! estoque e as tabelas `lote` e `mov_estoque_lote` do banco de dados. A
+//! movimentação dos saldos dos lotes ocorre sempre junto da movimentação de
+//! estoque correspondente; veja o módulo [`estoque`][`super::estoque`].
+
+use crate::model::lote::*;
+use crate::model::produto::Produto;
+use crate::model::schema::{lote, mov_estoque_lote};
+use bigdecimal::{BigDecimal, Signed, Zero};
+use diesel::prelude::*;
+use serde_json::json;
+
+/// Lista os lotes com saldo, de um produto e de um depósito, caso informados.
+///
+/// Os lotes são ordenados por produto, depósito e data de validade, sendo que
+/// lotes sem data de validade são listados por último.
+pub fn lista_lotes(
+    conexao: &PgConnection,
+    produto: Option<i32>,
+    deposito: Option<i32>,
+) -> Vec<Lote> {
+    let mut query = lote::table
+        .filter(lote::quantidade.gt(BigDecimal::zero()))
+        .into_boxed();
+    if let Some(produto) = produto {
+        query = query.filter(lote::produto_id.eq(produto));
+    }
+    if let Some(deposito) = deposito {
+        query = query.filter(lote::deposito_id.eq(deposito));
+    }
+    query
+        .order((
+            lote::produto_id,
+            lote::deposito_id,
+            lote::validade.asc(),
+            lote::id,
+        ))
+        .load::<Lote>(conexao)
+        .expect("Erro ao carregar lotes")
+}
+
+/// Lista os lotes com saldo que vencem em até `dias` dias a partir da data
+/// atual, inclusive os lotes já vencidos, de um depósito, caso informado.
+///
+/// Os lotes são ordenados por data de validade, e acompanhados da quantidade
+/// de dias até seu vencimento.
+pub fn lotes_vencendo(
+    conexao: &PgConnection,
+    dias: i64,
+    deposito: Option<i32>,
+) -> Vec<LoteVencimento> {
+    let hoje = chrono::Local::today().naive_local();
+    let limite = hoje + chrono::Duration::days(dias);
+    let mut query = lote::table
+        .filter(lote::quantidade.gt(BigDecimal::zero()))
+        .filter(lote::validade.le(limite))
+        .into_boxed();
+    if let Some(deposito) = deposito {
+        query = query.filter(lote::deposito_id.eq(deposito));
+    }
+    query
+        .order((
+            lote::validade,
+            lote::produto_id,
+            lote::deposito_id,
+            lote::id,
+        ))
+        .load::<Lote>(conexao)
+        .expect("Erro ao carregar lotes")
+        .into_iter()
+        .map(|l| LoteVencimento {
+            dias_para_vencer: l
+                .validade
+                .map(|v| (v - hoje).num_days())
+                .unwrap_or_default(),
+            lote: l,
+        })
+        .collect()
+}
+
+/// Lista os lotes movimentados por uma movimentação de estoque, junto das
+/// quantidades movimentadas de cada lote.
+pub fn lotes_movimento(conexao: &PgConnection, mov_id: i32) -> Vec<LoteMovimentado> {
+    mov_estoque_lote::table
+        .inner_join(lote::table)
+        .filter(mov_estoque_lote::mov_estoque_id.eq(mov_id))
+        .order(lote::id)
+        .select((
+            lote::id,
+            lote::numero,
+            lote::fabricacao,
+            lote::validade,
+            mov_estoque_lote::quantidade,
+        ))
+        .load::<(i32, String, _, _, BigDecimal)>(conexao)
+        .expect("Erro ao carregar lotes da movimentação")
+        .into_iter()
+        .map(
+            |(lote_id, numero, fabricacao, validade, quantidade)| LoteMovimentado {
+                lote_id,
+                numero,
+                fabricacao,
+                validade,
+                quantidade,
+            },
+        )
+        .collect()
+}
+
+/// Movimenta os saldos dos lotes de um produto em um depósito, de acordo com
+/// uma movimentação de `qtd` unidades do produto.
+///
+/// Produtos sem controle de lotes não possuem lotes a serem movimentados. Em
+/// entradas, o lote informado será criado no depósito caso ainda não exista,
+/// com as datas informadas; do contrário, a entrada deve respeitar as regras
+/// de [`valida_entrada_lote`][`crate::bo::estoque::valida_entrada_lote`]. Em
+/// saídas, será consumido o lote informado, caso exista e possua saldo
+/// suficiente, ou os lotes que vencem primeiro, como descrito em
+/// [`consome_lotes`][`crate::bo::estoque::consome_lotes`].
+///
+/// Caso seja informada a movimentação de estoque correspondente, as quantidades
+/// movimentadas de cada lote serão registradas junto da mesma. Retorna os lotes
+/// movimentados e as respectivas quantidades, ou uma mensagem de erro, caso a
+/// movimentação dos lotes não seja possível. Deve ser utilizada dentro da
+/// transação que registra a movimentação de estoque, que deverá ser desfeita
+/// em caso de erro.
+pub fn movimenta_lotes(
+    conexao: &PgConnection,
+    produto: &Produto,
+    dep_id: i32,
+    mov_id: Option<i32>,
+    qtd: &BigDecimal,
+    recv: Option<&LoteRecv>,
+) -> QueryResult<Result<Vec<(Lote, BigDecimal)>, String>> {
+    let erro = |mensagem: String| Ok(Err(json!({ "mensagem": mensagem }).to_string()));
+    if !produto.controla_lote || qtd.is_zero() {
+        return Ok(Ok(vec![]));
+    }
+
+    let hoje = chrono::Local::today().naive_local();
+    let do_lote = |numero: &str| {
+        lote::table
+            .filter(lote::produto_id.eq(produto.id))
+            .filter(lote::deposito_id.eq(dep_id))
+            .filter(lote::numero.eq(numero.trim().to_owned()))
+            .for_update()
+    };
+
+    let movimentos = if qtd.is_positive() {
+        let recv = match recv {
+            None => {
+                return erro(format!(
+                    "Entradas de estoque do produto {} devem informar o lote",
+                    produto.id
+                ))
+            }
+            Some(recv) => recv,
+        };
+        diesel::insert_into(lote::table)
+            .values(&NovoLote {
+                produto_id: produto.id,
+                deposito_id: dep_id,
+                numero: recv.numero.trim().to_owned(),
+                fabricacao: recv.fabricacao,
+                validade: recv.validade,
+                quantidade: BigDecimal::zero(),
+            })
+            .on_conflict_do_nothing()
+            .execute(conexao)?;
+        let existente = do_lote(&recv.numero).first::<Lote>(conexao)?;
+        if let Err(s) = crate::bo::estoque::valida_entrada_lote(&existente, recv, hoje) {
+            return Ok(Err(s));
+        }
+        vec![(existente, qtd.clone())]
+    } else if let Some(recv) = recv {
+        match do_lote(&recv.numero).first::<Lote>(conexao).optional()? {
+            None => {
+                return erro(format!(
+                    "O lote {} não existe no depósito {}",
+                    recv.numero, dep_id
+                ))
+            }
+            Some(l) if l.quantidade < -qtd => {
+                return erro(format!(
+                    "Saldo insuficiente no lote {}! Saldo atual: {}",
+                    l.numero, l.quantidade
+                ))
+            }
+            Some(l) => vec![(l, qtd.clone())],
+        }
+    } else {
+        let lotes = lote::table
+            .filter(lote::produto_id.eq(produto.id))
+            .filter(lote::deposito_id.eq(dep_id))
+            .filter(lote::quantidade.gt(BigDecimal::zero()))
+            .order(lote::id)
+            .for_update()
+            .load::<Lote>(conexao)?;
+        match crate::bo::estoque::consome_lotes(&lotes, &-qtd, hoje) {
+            Err(s) => return Ok(Err(s)),
+            Ok(consumos) => consumos
+                .into_iter()
+                .map(|(lote_id, consumo)| {
+                    let l = lotes.iter().find(|l| l.id == lote_id).unwrap().clone();
+                    (l, -consumo)
+                })
+                .collect(),
+        }
+    };
+
+    for (l, quantidade) in &movimentos {
+        diesel::update(lote::table.find(l.id))
+            .set(lote::quantidade.eq(lote::quantidade + quantidade))
+            .execute(conexao)?;
+        if let Some(mov_id) = mov_id {
+            diesel::insert_into(mov_estoque_lote::table)
+                .values(&MovEstoqueLote {
+                    mov_estoque_id: mov_id,
+                    lote_id: l.id,
+                    quantidade: quantidade.clone(),
+                })
+                .execute(conexao)?;
+        }
+    }
+    Ok(Ok(movimentos))
+}
@@ -23,9 +23,20 @@
 //! Operações de inserção, alteração e inclusão no banco de dados gerarão uma
 //! gravação na tabela de log do mesmo.
 
+pub mod arquivos;
+pub mod categorias;
 pub mod clientes;
+pub mod depositos;
+pub mod documentos;
 pub mod estoque;
+pub mod inventarios;
 pub mod log;
 pub mod login;
+pub mod lotes;
+pub mod precos;
 pub mod produtos;
+pub mod reservas;
+pub mod series;
+pub mod titulos;
+pub mod unidades;
 pub mod usuarios;
@@ -0,0 +1,301 @@
+// controller/precos.rs -- Uma parte de Minerva.rs
+// Copyright (C) 2021 Lucas S. Vieira
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with this program.  If not, see <https://www.gnu.org/licenses/>.
+
+//! Ferramentas para tráfego de dados entre as rotas de tabelas de preço e o
+//! banco de dados.
+//!
+//! As ferramentas deste módulo realizam o tráfego de dados entre as rotas de
+//! tabelas de preço e as tabelas `tabela_preco` e `preco_produto` do banco de
+//! dados, além da resolução do preço efetivo de um produto para um cliente.
+
+use super::log::*;
+use crate::bo;
+use crate::model::preco::{
+    NovaTabelaPreco, NovoPrecoProduto, PrecoEfetivo, PrecoProduto, PrecoProdutoRecv, TabelaPreco,
+};
+use crate::model::schema::{preco_produto, tabela_preco};
+use crate::routes::respostas::Resposta;
+use chrono::NaiveDate;
+use diesel::prelude::*;
+use serde_json::json;
+
+/// Lista todas as tabelas de preço cadastradas no sistema, em ordem de id.
+pub fn lista_tabelas(conexao: &PgConnection) -> Vec<TabelaPreco> {
+    tabela_preco::table
+        .order(tabela_preco::id.asc())
+        .load::<TabelaPreco>(conexao)
+        .expect("Erro ao carregar tabelas de preço")
+}
+
+/// Retorna os dados de uma tabela de preço, caso existente.
+pub fn get_tabela(conexao: &PgConnection, tabela_id: i32) -> Option<TabelaPreco> {
+    tabela_preco::table
+        .find(tabela_id)
+        .first::<TabelaPreco>(conexao)
+        .optional()
+        .expect("Erro ao carregar tabela de preço")
+}
+
+/// Registra uma nova tabela de preço no banco de dados.
+///
+/// Esta função assume que os dados da tabela de preço sejam válidos. Caso a
+/// tabela seja cadastrada, será retornado seu id. Caso contrário, será
+/// retornada uma mensagem de erro em String.
+pub fn registra_tabela(
+    conexao: &PgConnection,
+    dados: NovaTabelaPreco,
+    usuario: &str,
+) -> Result<i32, String> {
+    match diesel::insert_into(tabela_preco::table)
+        .values(&dados)
+        .get_result::<TabelaPreco>(conexao)
+    {
+        Ok(tabela) => {
+            let _ = registra_log(
+                conexao,
+                String::from("TABELA_PRECO"),
+                usuario.to_owned(),
+                DBOperacao::Insercao,
+                Some(format!("Tabela de preço {}", tabela.id)),
+            );
+            Ok(tabela.id)
+        }
+        Err(e) => {
+            if let diesel::result::Error::DatabaseError(_, _) = &e {
+                Err(format!("{}", e))
+            } else {
+                Err(String::from(
+                    "Erro interno ao cadastrar tabela de preço. \
+                     Contate o suporte para mais informações.",
+                ))
+            }
+        }
+    }
+}
+
+/// Altera os dados de uma tabela de preço.
+///
+/// Esta função assume que os dados da tabela de preço sejam válidos, e que a
+/// tabela exista. Caso a tabela seja alterada, serão retornados seus dados
+/// atualizados. Caso contrário, será retornada uma mensagem de erro em String.
+pub fn altera_tabela(
+    conexao: &PgConnection,
+    tabela_id: i32,
+    dados: NovaTabelaPreco,
+    usuario: &str,
+) -> Result<TabelaPreco, String> {
+    match diesel::update(tabela_preco::table.find(tabela_id))
+        .set(&dados)
+        .get_result::<TabelaPreco>(conexao)
+    {
+        Ok(tabela) => {
+            let _ = registra_log(
+                conexao,
+                String::from("TABELA_PRECO"),
+                usuario.to_owned(),
+                DBOperacao::Alteracao,
+                Some(format!("Tabela de preço {}", tabela.id)),
+            );
+            Ok(tabela)
+        }
+        Err(e) => {
+            if let diesel::result::Error::DatabaseError(_, _) = &e {
+                Err(format!("{}", e))
+            } else {
+                Err(String::from(
+                    "Erro interno ao alterar tabela de preço. \
+                     Contate o suporte para mais informações.",
+                ))
+            }
+        }
+    }
+}
+
+/// Deleta uma tabela de preço do banco de dados, junto de todos os seus
+/// preços.
+///
+/// Será retornado `true` caso a tabela de preço existisse e tenha sido
+/// removida.
+pub fn deleta_tabela(conexao: &PgConnection, tabela_id: i32, usuario: &str) -> bool {
+    let num = diesel::delete(tabela_preco::table.find(tabela_id))
+        .execute(conexao)
+        .expect("Erro ao remover tabela de preço");
+    if num > 0 {
+        let _ = registra_log(
+            conexao,
+            String::from("TABELA_PRECO"),
+            usuario.to_owned(),
+            DBOperacao::Remocao,
+            Some(format!("Tabela de preço {}", tabela_id)),
+        );
+    }
+    num > 0
+}
+
+/// Lista os preços de uma tabela de preço, em ordem de produto e de início de
+/// vigência.
+pub fn lista_precos(conexao: &PgConnection, tabela_id: i32) -> Vec<PrecoProduto> {
+    preco_produto::table
+        .filter(preco_produto::tabela_preco_id.eq(&tabela_id))
+        .order((
+            preco_produto::produto_id.asc(),
+            preco_produto::vigencia_inicio.asc(),
+        ))
+        .load::<PrecoProduto>(conexao)
+        .expect("Erro ao carregar preços da tabela de preço")
+}
+
+/// Lista os preços de um produto em todas as tabelas de preço.
+pub fn lista_precos_produto(conexao: &PgConnection, prodid: i32) -> Vec<PrecoProduto> {
+    preco_produto::table
+        .filter(preco_produto::produto_id.eq(&prodid))
+        .order(preco_produto::id.asc())
+        .load::<PrecoProduto>(conexao)
+        .expect("Erro ao carregar preços do produto")
+}
+
+/// Registra o preço de um produto em uma tabela de preço.
+///
+/// Esta função assume que os dados do preço tenham sido validados, e que a
+/// tabela de preço exista. Caso o produto não exista, será retornado um erro
+/// 422. Caso a vigência do preço se sobreponha à vigência de outro preço do
+/// mesmo produto na mesma tabela, será retornado um erro 409. Do contrário,
+/// serão retornados os dados do preço cadastrado.
+pub fn registra_preco(
+    conexao: &PgConnection,
+    tabela_id: i32,
+    dados: PrecoProdutoRecv,
+    usuario: &str,
+) -> Resposta {
+    if super::produtos::get_produto(conexao, dados.produto_id).is_none() {
+        return Resposta::ErroSemantico(
+            json!({
+                "mensagem": format!("O produto {} não existe", dados.produto_id)
+            })
+            .to_string(),
+        );
+    }
+
+    if let Some(existente) = lista_precos(conexao, tabela_id)
+        .iter()
+        .filter(|p| p.produto_id == dados.produto_id)
+        .find(|p| bo::precos::sobrepoe(p, &dados))
+    {
+        return Resposta::Conflito(
+            json!({
+                "mensagem": format!(
+                    "A vigência do preço se sobrepõe à vigência do preço {} do produto {}",
+                    existente.id, existente.produto_id
+                )
+            })
+            .to_string(),
+        );
+    }
+
+    let novo = NovoPrecoProduto {
+        tabela_preco_id: tabela_id,
+        produto_id: dados.produto_id,
+        preco: dados.preco,
+        vigencia_inicio: dados.vigencia_inicio,
+        vigencia_fim: dados.vigencia_fim,
+    };
+
+    match diesel::insert_into(preco_produto::table)
+        .values(&novo)
+        .get_result::<PrecoProduto>(conexao)
+    {
+        Ok(p) => {
+            let _ = registra_log(
+                conexao,
+                String::from("PRECO_PRODUTO"),
+                usuario.to_owned(),
+                DBOperacao::Insercao,
+                Some(format!(
+                    "Tabela de preço {}: preço {} do produto {}",
+                    p.tabela_preco_id, p.id, p.produto_id
+                )),
+            );
+            Resposta::Ok(serde_json::to_string(&p).unwrap())
+        }
+        Err(_) => Resposta::ErroInterno(
+            json!({
+                "mensagem":
+                    "Erro interno ao cadastrar preço. \
+                     Contate o suporte para mais informações."
+            })
+            .to_string(),
+        ),
+    }
+}
+
+/// Remove um preço de uma tabela de preço.
+///
+/// Será retornado `true` caso o preço pertencesse à tabela de preço e tenha
+/// sido removido.
+pub fn deleta_preco(conexao: &PgConnection, tabela_id: i32, preco_id: i32, usuario: &str) -> bool {
+    let num = diesel::delete(
+        preco_produto::table
+            .filter(preco_produto::id.eq(&preco_id))
+            .filter(preco_produto::tabela_preco_id.eq(&tabela_id)),
+    )
+    .execute(conexao)
+    .expect("Erro ao remover preço");
+    if num > 0 {
+        let _ = registra_log(
+            conexao,
+            String::from("PRECO_PRODUTO"),
+            usuario.to_owned(),
+            DBOperacao::Remocao,
+            Some(format!("Tabela de preço {}: preço {}", tabela_id, preco_id)),
+        );
+    }
+    num > 0
+}
+
+/// Resolve o preço efetivo de um produto em uma data, opcionalmente para um
+/// cliente.
+///
+/// Esta função assume que o produto e o cliente existam. O cliente deve ser
+/// informado através de seu id e de seu tipo. Para as regras de escolha da
+/// tabela de preço, veja
+/// [`resolve_preco`][`crate::bo::precos::resolve_preco`]. Caso nenhuma tabela
+/// possua preço aplicável, será utilizado o preço unitário consolidado das
+/// posições de estoque do produto em todos os depósitos. Caso o produto também
+/// não possua posição de estoque, será retornado `None`.
+pub fn preco_efetivo(
+    conexao: &PgConnection,
+    prodid: i32,
+    cliente: Option<(i32, i16)>,
+    data: NaiveDate,
+) -> Option<PrecoEfetivo> {
+    let tabelas = lista_tabelas(conexao);
+    let precos = lista_precos_produto(conexao, prodid);
+    let (preco, tabela) = match bo::precos::resolve_preco(&tabelas, &precos, cliente, data) {
+        Some((tabela, preco)) => (preco.preco.clone(), Some(tabela.clone())),
+        None => (
+            super::estoque::estoque_consolidado(conexao, prodid)?.1,
+            None,
+        ),
+    };
+    Some(PrecoEfetivo {
+        produto_id: prodid,
+        cliente_id: cliente.map(|(id, _)| id),
+        data,
+        preco,
+        tabela_preco_id: tabela.as_ref().map(|t| t.id),
+        tabela_preco: tabela.map(|t| t.descricao),
+    })
+}
@@ -22,18 +22,56 @@
 //! banco de dados e relacionadas.
 
 use super::log::*;
-use crate::model::produto::{NovoProduto, Produto};
-use crate::model::schema::produto;
+use crate::bo::produtos::remove_pontuacao;
+use crate::model::produto::{
+    AlteracaoProduto, ComponenteRecv, NovoProduto, Produto, ProdutoAtributo, ProdutoComponente,
+    ProdutoGtin, VarianteRecv, VarianteRepr,
+};
 use crate::model::schema::produto::dsl::*;
+use crate::model::schema::{
+    estoque, mov_estoque, produto, produto_atributo, produto_componente, produto_gtin,
+    produto_unidade,
+};
+use crate::routes::respostas::Resposta;
 use diesel::prelude::*;
+use diesel::sql_types::{Integer, Text};
+use serde_json::json;
+use std::collections::BTreeMap;
+
+sql_function!(
+    /// Preenche um texto à esquerda até o tamanho informado.
+    fn lpad(texto: Text, tamanho: Integer, preenchimento: Text) -> Text
+);
 
 /// Lista uma quantidade limitada de produtos cadastrados no sistema.
 ///
 /// Retorna um Vec com estruturas que representam os dados de um produto. A
 /// quantidade de produtos retornada não deverá exceder a informada no
-/// parâmetro `limite`.
-pub fn lista_produtos(conexao: &PgConnection, limite: i64) -> Vec<Produto> {
-    produto::table
+/// parâmetro `limite`. Produtos inativos só serão retornados caso `inativos`
+/// seja verdadeiro. Caso um capítulo NCM seja informado (os dois primeiros
+/// dígitos do NCM), apenas produtos deste capítulo serão retornados. Caso uma
+/// categoria seja informada, apenas produtos desta categoria ou de suas
+/// subcategorias serão retornados.
+pub fn lista_produtos(
+    conexao: &PgConnection,
+    limite: i64,
+    inativos: bool,
+    capitulo: Option<&str>,
+    categoria: Option<i32>,
+) -> Vec<Produto> {
+    let mut query = produto::table.into_boxed();
+    if !inativos {
+        query = query.filter(ativo.eq(true));
+    }
+    if let Some(capitulo) = capitulo {
+        query = query.filter(ncm.like(format!("{}%", capitulo)));
+    }
+    if let Some(categoria) = categoria {
+        let categorias = super::categorias::descendentes(conexao, categoria);
+        query = query.filter(categoria_id.eq_any(categorias));
+    }
+    query
+        .order(id.asc())
         .limit(limite)
         .load::<Produto>(conexao)
         .expect("Erro ao carregar produtos")
@@ -52,30 +90,125 @@ pub fn get_produto(conexao: &PgConnection, prod_id: i32) -> Option<Produto> {
     prod_req.first().cloned()
 }
 
+/// Informa se um produto possui histórico de estoque, isto é, se há posição
+/// de estoque ou movimentações de estoque que o referenciem.
+pub fn possui_historico(conexao: &PgConnection, prodid: i32) -> bool {
+    diesel::select(
+        diesel::dsl::exists(estoque::table.filter(estoque::produto_id.eq(&prodid))).or(
+            diesel::dsl::exists(mov_estoque::table.filter(mov_estoque::produto_id.eq(&prodid))),
+        ),
+    )
+    .get_result(conexao)
+    .expect("Erro ao verificar histórico do produto")
+}
+
 /// Deleta um produto em específico do banco de dados.
 ///
 /// O produto a ser deletado deverá ter seu id informado através do parâmetro
-/// `prodid`. Esta função assume que o produto de id informada exista no banco
-/// de dados.
-pub fn deleta_produto(conexao: &PgConnection, prodid: i32) {
-    diesel::delete(produto.filter(id.eq(&prodid)))
-        .execute(conexao)
-        .expect("Erro ao deletar produto");
-    let _ = registra_log(
-        conexao,
-        String::from("PRODUTO"),
-        String::from("TO-DO"),
-        DBOperacao::Remocao,
-        Some(format!("Produto {}", prodid)),
-    );
+/// `prodid`. Caso o produto não exista, será retornado um erro 404.
+///
+/// Produtos que possuam histórico de estoque não podem ser removidos, e serão
+/// apenas inativados. Caso o produto já esteja inativo, ou caso a remoção
+/// conflite com algum registro que referencie o produto, será retornado um
+/// erro 409. A operação será registrada no log em nome do usuário informado.
+pub fn deleta_produto(conexao: &PgConnection, prodid: i32, usuario: &str) -> Resposta {
+    let prod = match get_produto(conexao, prodid) {
+        None => {
+            return Resposta::NaoEncontrado(
+                json!({
+                    "mensagem": "Produto não encontrado"
+                })
+                .to_string(),
+            )
+        }
+        Some(p) => p,
+    };
+
+    let resultado = if possui_historico(conexao, prodid) {
+        if !prod.ativo {
+            return Resposta::Conflito(
+                json!({
+                    "mensagem": format!(
+                        "O produto {} possui histórico de estoque e já está inativo",
+                        prodid
+                    )
+                })
+                .to_string(),
+            );
+        }
+        diesel::update(produto.filter(id.eq(&prodid)))
+            .set(ativo.eq(false))
+            .execute(conexao)
+            .map(|_| (DBOperacao::Alteracao, false))
+    } else {
+        diesel::delete(produto.filter(id.eq(&prodid)))
+            .execute(conexao)
+            .map(|_| (DBOperacao::Remocao, true))
+    };
+
+    match resultado {
+        Ok((operacao, removido)) => {
+            let _ = registra_log(
+                conexao,
+                String::from("PRODUTO"),
+                usuario.to_owned(),
+                operacao,
+                Some(if removido {
+                    format!("Produto {}", prodid)
+                } else {
+                    format!("Produto {}: inativação", prodid)
+                }),
+            );
+            Resposta::Ok(json!({ "id": prodid, "removido": removido }).to_string())
+        }
+        Err(diesel::result::Error::DatabaseError(
+            diesel::result::DatabaseErrorKind::ForeignKeyViolation,
+            _,
+        )) => Resposta::Conflito(
+            json!({
+                "mensagem": format!(
+                    "O produto {} é referenciado por outros registros e não pode ser removido",
+                    prodid
+                )
+            })
+            .to_string(),
+        ),
+        Err(_) => Resposta::ErroInterno(
+            json!({
+                "mensagem":
+                    "Erro interno ao remover produto. \
+                     Contate o suporte para mais informações."
+            })
+            .to_string(),
+        ),
+    }
 }
 
 /// Deleta todos os produtos cadastrados no banco de dados.
 ///
-/// Será retornada a quantidade de registros removidos no processo. Utilize
-/// esta função com cuidado.
-pub fn deleta_todos(conexao: &PgConnection) -> usize {
-    let num_deletados = diesel::delete(produto::table)
+/// Produtos que possuam histórico de estoque não serão removidos, e serão
+/// apenas inativados, assim como os produtos pais de variantes que possuam
+/// histórico de estoque. Será retornada uma tuple contendo, respectivamente, a
+/// quantidade de produtos removidos e a quantidade de produtos inativados no
+/// processo, que também serão registradas separadamente no log. Utilize esta
+/// função com cuidado.
+pub fn deleta_todos(conexao: &PgConnection) -> (usize, usize) {
+    let com_historico: Vec<i32> = produto
+        .select((id, produto_pai_id))
+        .filter(
+            id.eq_any(estoque::table.select(estoque::produto_id))
+                .or(id.eq_any(mov_estoque::table.select(mov_estoque::produto_id))),
+        )
+        .load::<(i32, Option<i32>)>(conexao)
+        .expect("Erro ao carregar produtos com histórico")
+        .into_iter()
+        .flat_map(|(prodid, paiid)| std::iter::once(prodid).chain(paiid))
+        .collect();
+    let num_inativados = diesel::update(produto.filter(id.eq_any(&com_historico)))
+        .set(ativo.eq(false))
+        .execute(conexao)
+        .expect("Erro ao inativar produtos");
+    let num_deletados = diesel::delete(produto.filter(diesel::dsl::not(id.eq_any(&com_historico))))
         .execute(conexao)
         .expect("Erro ao deletar produtos");
     let _ = registra_log(
@@ -83,9 +216,78 @@ pub fn deleta_todos(conexao: &PgConnection) -> usize {
         String::from("PRODUTO"),
         String::from("TO-DO"),
         DBOperacao::Remocao,
-        Some(String::from("Removendo todos os produtos")),
+        Some(format!(
+            "Removendo {} produtos sem histórico",
+            num_deletados
+        )),
     );
-    num_deletados
+    let _ = registra_log(
+        conexao,
+        String::from("PRODUTO"),
+        String::from("TO-DO"),
+        DBOperacao::Alteracao,
+        Some(format!(
+            "Inativando {} produtos com histórico",
+            num_inativados
+        )),
+    );
+    (num_deletados, num_inativados)
+}
+
+/// Altera os dados de um produto no banco de dados.
+///
+/// Esta função assume que os dados de alteração sejam válidos, e que o produto
+/// de id informado exista. Apenas os campos informados serão alterados. A
+/// operação será registrada no log em nome do usuário informado. Caso o
+/// produto seja alterado, serão retornados seus dados atualizados. Caso
+/// contrário, será retornada uma mensagem de erro em String.
+///
+/// Caso a unidade de saída do produto seja alterada, seus fatores de conversão
+/// de unidades serão removidos, posto que são relativos à unidade de saída.
+pub fn altera_produto(
+    conexao: &PgConnection,
+    prodid: i32,
+    mut dados: AlteracaoProduto,
+    usuario: &str,
+) -> Result<Produto, String> {
+    dados.unidsaida = dados.unidsaida.map(|u| u.to_uppercase());
+    dados.ncm = dados.ncm.map(|n| remove_pontuacao(&n));
+    dados.cest = dados
+        .cest
+        .map(|c| c.map(|c| remove_pontuacao(&c)).filter(|c| !c.is_empty()));
+    let resultado = conexao.transaction(|| {
+        let anterior = produto.find(prodid).first::<Produto>(conexao)?;
+        let prod = diesel::update(produto.filter(id.eq(&prodid)))
+            .set(&dados)
+            .get_result::<Produto>(conexao)?;
+        if prod.unidsaida != anterior.unidsaida {
+            diesel::delete(produto_unidade::table.filter(produto_unidade::produto_id.eq(&prodid)))
+                .execute(conexao)?;
+        }
+        Ok(prod)
+    });
+    match resultado {
+        Ok(prod) => {
+            let _ = registra_log(
+                conexao,
+                String::from("PRODUTO"),
+                usuario.to_owned(),
+                DBOperacao::Alteracao,
+                Some(format!("Produto {}", prod.id)),
+            );
+            Ok(prod)
+        }
+        Err(e) => {
+            if let diesel::result::Error::DatabaseError(_, _) = &e {
+                Err(format!("{}", e))
+            } else {
+                Err(String::from(
+                    "Erro interno ao alterar produto. \
+                     Contate o suporte para mais informações.",
+                ))
+            }
+        }
+    }
 }
 
 /// Registra um novo produto no banco de dados.
@@ -95,6 +297,11 @@ pub fn deleta_todos(conexao: &PgConnection) -> usize {
 /// Caso contrário, será retornada uma mensagem de erro em String.
 pub fn registra_produto(conexao: &PgConnection, mut dados: NovoProduto) -> Result<i32, String> {
     dados.unidsaida = dados.unidsaida.to_uppercase();
+    dados.ncm = remove_pontuacao(&dados.ncm);
+    dados.cest = dados
+        .cest
+        .map(|c| remove_pontuacao(&c))
+        .filter(|c| !c.is_empty());
     match diesel::insert_into(produto::table)
         .values(&dados)
         .get_result::<Produto>(conexao)
@@ -121,3 +328,458 @@ pub fn registra_produto(conexao: &PgConnection, mut dados: NovoProduto) -> Resul
         }
     }
 }
+
+/// Lista os códigos GTIN associados a um produto.
+pub fn lista_gtins(conexao: &PgConnection, prodid: i32) -> Vec<ProdutoGtin> {
+    produto_gtin::table
+        .filter(produto_gtin::produto_id.eq(&prodid))
+        .order(produto_gtin::gtin.asc())
+        .load::<ProdutoGtin>(conexao)
+        .expect("Erro ao carregar códigos GTIN")
+}
+
+/// Encontra um código GTIN cadastrado.
+///
+/// A busca é feita pela forma normalizada do código, de forma que, por
+/// exemplo, um GTIN-12 possa ser encontrado quando informado como GTIN-13
+/// preenchido com zero à esquerda.
+pub fn get_gtin(conexao: &PgConnection, codigo: &str) -> Option<ProdutoGtin> {
+    use crate::bo::produtos::normaliza_gtin;
+    produto_gtin::table
+        .filter(lpad(produto_gtin::gtin, 14, "0").eq(normaliza_gtin(codigo)))
+        .first::<ProdutoGtin>(conexao)
+        .optional()
+        .expect("Erro ao carregar código GTIN")
+}
+
+/// Retorna os dados de um produto através de um de seus códigos GTIN, caso
+/// existente.
+pub fn get_produto_por_gtin(conexao: &PgConnection, codigo: &str) -> Option<Produto> {
+    get_gtin(conexao, codigo).and_then(|g| get_produto(conexao, g.produto_id))
+}
+
+/// Associa um código GTIN a um produto.
+///
+/// Esta função assume que o código GTIN seja válido, e que o produto exista.
+/// Caso o código já esteja associado a algum produto, será retornado um erro
+/// 409. Caso contrário, serão retornados os dados do código cadastrado.
+pub fn registra_gtin(conexao: &PgConnection, prodid: i32, codigo: &str, usuario: &str) -> Resposta {
+    if let Some(existente) = get_gtin(conexao, codigo) {
+        return Resposta::Conflito(
+            json!({
+                "mensagem": format!(
+                    "O código GTIN {} já está associado ao produto {}",
+                    existente.gtin, existente.produto_id
+                )
+            })
+            .to_string(),
+        );
+    }
+
+    let novo = ProdutoGtin {
+        gtin: codigo.to_owned(),
+        produto_id: prodid,
+    };
+
+    match diesel::insert_into(produto_gtin::table)
+        .values(&novo)
+        .get_result::<ProdutoGtin>(conexao)
+    {
+        Ok(g) => {
+            let _ = registra_log(
+                conexao,
+                String::from("PRODUTO_GTIN"),
+                usuario.to_owned(),
+                DBOperacao::Insercao,
+                Some(format!("Produto {}: GTIN {}", g.produto_id, g.gtin)),
+            );
+            Resposta::Ok(serde_json::to_string(&g).unwrap())
+        }
+        Err(diesel::result::Error::DatabaseError(
+            diesel::result::DatabaseErrorKind::UniqueViolation,
+            _,
+        )) => Resposta::Conflito(
+            json!({
+                "mensagem": format!("O código GTIN {} já está cadastrado", codigo)
+            })
+            .to_string(),
+        ),
+        Err(_) => Resposta::ErroInterno(
+            json!({
+                "mensagem":
+                    "Erro interno ao cadastrar código GTIN. \
+                     Contate o suporte para mais informações."
+            })
+            .to_string(),
+        ),
+    }
+}
+
+/// Remove a associação de um código GTIN a um produto.
+///
+/// Será retornado `true` caso o código estivesse associado ao produto e tenha
+/// sido removido.
+pub fn deleta_gtin(conexao: &PgConnection, prodid: i32, codigo: &str, usuario: &str) -> bool {
+    let g = match get_gtin(conexao, codigo) {
+        Some(g) if g.produto_id == prodid => g,
+        _ => return false,
+    };
+    diesel::delete(produto_gtin::table.find(&g.gtin))
+        .execute(conexao)
+        .expect("Erro ao remover código GTIN");
+    let _ = registra_log(
+        conexao,
+        String::from("PRODUTO_GTIN"),
+        usuario.to_owned(),
+        DBOperacao::Remocao,
+        Some(format!("Produto {}: GTIN {}", g.produto_id, g.gtin)),
+    );
+    true
+}
+
+/// Retorna os atributos de uma variante de produto, indexados pelo nome do
+/// atributo. Produtos que não sejam variantes não possuem atributos.
+pub fn atributos(conexao: &PgConnection, prodid: i32) -> BTreeMap<String, String> {
+    produto_atributo::table
+        .filter(produto_atributo::produto_id.eq(&prodid))
+        .load::<ProdutoAtributo>(conexao)
+        .expect("Erro ao carregar atributos do produto")
+        .into_iter()
+        .map(|a| (a.atributo, a.valor))
+        .collect()
+}
+
+/// Verifica se um produto possui variantes cadastradas.
+pub fn possui_variantes(conexao: &PgConnection, prodid: i32) -> bool {
+    diesel::select(diesel::dsl::exists(
+        produto.filter(produto_pai_id.eq(&prodid)),
+    ))
+    .get_result(conexao)
+    .expect("Erro ao verificar variantes do produto")
+}
+
+/// Lista as variantes de um produto, junto de seus atributos, em ordem de id.
+/// Variantes inativas também serão retornadas.
+pub fn lista_variantes(conexao: &PgConnection, prodid: i32) -> Vec<VarianteRepr> {
+    produto
+        .filter(produto_pai_id.eq(&prodid))
+        .order(id.asc())
+        .load::<Produto>(conexao)
+        .expect("Erro ao carregar variantes do produto")
+        .into_iter()
+        .map(|p| VarianteRepr {
+            atributos: atributos(conexao, p.id),
+            produto: p,
+        })
+        .collect()
+}
+
+/// Registra uma variante de um produto.
+///
+/// Esta função assume que os dados da variante tenham sido validados. A
+/// variante herda a unidade de saída, os dados fiscais e a categoria do
+/// produto pai, e é cadastrada junto de seus atributos em uma única transação.
+///
+/// Caso o produto pai seja ele próprio uma variante, seja um kit ou componente
+/// de kit, ou não possua NCM, será retornado um erro 422. Caso o produto pai
+/// possua histórico de estoque, ou caso já exista uma variante com a mesma
+/// combinação de atributos, será retornado um erro 409. Do contrário, serão
+/// retornados os dados da variante cadastrada.
+pub fn registra_variante(
+    conexao: &PgConnection,
+    pai: &Produto,
+    dados: VarianteRecv,
+    usuario: &str,
+) -> Resposta {
+    use crate::bo::produtos::{descricao_variante, normaliza_atributos};
+
+    if let Some(avo) = pai.produto_pai_id {
+        return Resposta::ErroSemantico(
+            json!({
+                "mensagem": format!(
+                    "O produto {} é uma variante do produto {} e não pode possuir variantes",
+                    pai.id, avo
+                )
+            })
+            .to_string(),
+        );
+    }
+
+    if !lista_componentes(conexao, pai.id).is_empty() || eh_componente(conexao, pai.id) {
+        return Resposta::ErroSemantico(
+            json!({
+                "mensagem": format!(
+                    "O produto {} compõe um kit e não pode possuir variantes",
+                    pai.id
+                )
+            })
+            .to_string(),
+        );
+    }
+
+    let pai_ncm = match &pai.ncm {
+        Some(n) => n.clone(),
+        None => {
+            return Resposta::ErroSemantico(
+                json!({
+                    "mensagem": format!(
+                        "O produto {} não possui NCM, que é necessário para suas variantes",
+                        pai.id
+                    )
+                })
+                .to_string(),
+            )
+        }
+    };
+
+    if possui_historico(conexao, pai.id) {
+        return Resposta::Conflito(
+            json!({
+                "mensagem": format!(
+                    "O produto {} possui histórico de estoque e não pode receber variantes",
+                    pai.id
+                )
+            })
+            .to_string(),
+        );
+    }
+
+    let atribs = normaliza_atributos(&dados.atributos);
+    if let Some(existente) = lista_variantes(conexao, pai.id)
+        .into_iter()
+        .find(|v| v.atributos == atribs)
+    {
+        return Resposta::Conflito(
+            json!({
+                "mensagem": format!(
+                    "A variante {} já possui os atributos informados",
+                    existente.produto.id
+                )
+            })
+            .to_string(),
+        );
+    }
+
+    let nova = NovoProduto {
+        descricao: dados
+            .descricao
+            .map(|d| d.trim().to_owned())
+            .unwrap_or_else(|| descricao_variante(&pai.descricao, &atribs)),
+        unidsaida: pai.unidsaida.clone(),
+        ncm: pai_ncm,
+        cest: pai.cest.clone(),
+        origem: pai.origem,
+        categoria_id: pai.categoria_id,
+        produto_pai_id: Some(pai.id),
+        metodo_custo: pai.metodo_custo,
+        controla_lote: pai.controla_lote,
+        controla_serie: pai.controla_serie,
+    };
+
+    let resultado = conexao.transaction::<_, diesel::result::Error, _>(|| {
+        let variante = diesel::insert_into(produto::table)
+            .values(&nova)
+            .get_result::<Produto>(conexao)?;
+        let linhas: Vec<ProdutoAtributo> = atribs
+            .iter()
+            .map(|(atributo, valor)| ProdutoAtributo {
+                produto_id: variante.id,
+                atributo: atributo.clone(),
+                valor: valor.clone(),
+            })
+            .collect();
+        diesel::insert_into(produto_atributo::table)
+            .values(&linhas)
+            .execute(conexao)?;
+        Ok(variante)
+    });
+
+    match resultado {
+        Ok(variante) => {
+            let _ = registra_log(
+                conexao,
+                String::from("PRODUTO"),
+                usuario.to_owned(),
+                DBOperacao::Insercao,
+                Some(format!("Produto {}: variante de {}", variante.id, pai.id)),
+            );
+            let _ = registra_log(
+                conexao,
+                String::from("PRODUTO_ATRIBUTO"),
+                usuario.to_owned(),
+                DBOperacao::Insercao,
+                Some(format!(
+                    "Produto {}: {} atributo(s)",
+                    variante.id,
+                    atribs.len()
+                )),
+            );
+            Resposta::Ok(
+                serde_json::to_string(&VarianteRepr {
+                    produto: variante,
+                    atributos: atribs,
+                })
+                .unwrap(),
+            )
+        }
+        Err(_) => Resposta::ErroInterno(
+            json!({
+                "mensagem":
+                    "Erro interno ao cadastrar variante. \
+                     Contate o suporte para mais informações."
+            })
+            .to_string(),
+        ),
+    }
+}
+
+/// Lista os componentes de um kit, em ordem de id do componente. Produtos que
+/// não sejam kits não possuem componentes.
+pub fn lista_componentes(conexao: &PgConnection, kitid: i32) -> Vec<ProdutoComponente> {
+    produto_componente::table
+        .filter(produto_componente::kit_id.eq(&kitid))
+        .order(produto_componente::componente_id.asc())
+        .load::<ProdutoComponente>(conexao)
+        .expect("Erro ao carregar componentes do kit")
+}
+
+/// Verifica se um produto é componente de algum kit.
+pub fn eh_componente(conexao: &PgConnection, prodid: i32) -> bool {
+    diesel::select(diesel::dsl::exists(
+        produto_componente::table.filter(produto_componente::componente_id.eq(&prodid)),
+    ))
+    .get_result(conexao)
+    .expect("Erro ao verificar composições do produto")
+}
+
+/// Cadastra um componente de um kit, ou altera sua quantidade caso o
+/// componente já esteja cadastrado no kit.
+///
+/// Esta função assume que os dados do componente tenham sido validados. Kits
+/// possuem apenas um nível de composição: um kit não pode ser componente de
+/// outro kit, e produtos com variantes não podem compor kits nem ser kits.
+/// Nestes casos, ou caso o componente não exista ou sua quantidade não
+/// respeite as casas decimais de sua unidade de saída, será retornado um erro
+/// 422. Caso o kit possua histórico de estoque próprio, será retornado um erro
+/// 409.
+pub fn registra_componente(
+    conexao: &PgConnection,
+    kit: &Produto,
+    dados: ComponenteRecv,
+    usuario: &str,
+) -> Resposta {
+    use super::unidades;
+    use crate::bo::produtos::valida_quantidade;
+
+    let erro =
+        |mensagem: String| Resposta::ErroSemantico(json!({ "mensagem": mensagem }).to_string());
+
+    if dados.componente_id == kit.id {
+        return erro(String::from("Um kit não pode ser componente de si mesmo"));
+    }
+
+    let componente = match get_produto(conexao, dados.componente_id) {
+        Some(p) => p,
+        None => return erro(format!("O produto {} não existe", dados.componente_id)),
+    };
+
+    if eh_componente(conexao, kit.id) {
+        return erro(format!(
+            "O produto {} é componente de outro kit e não pode possuir componentes",
+            kit.id
+        ));
+    }
+
+    if !lista_componentes(conexao, componente.id).is_empty() {
+        return erro(format!(
+            "O produto {} é um kit e não pode ser componente de outro kit",
+            componente.id
+        ));
+    }
+
+    if possui_variantes(conexao, kit.id) || possui_variantes(conexao, componente.id) {
+        return erro(String::from(
+            "Produtos com variantes não podem ser kits nem componentes de kits; \
+             utilize suas variantes",
+        ));
+    }
+
+    let unidade = unidades::get_unidade(conexao, &componente.unidsaida)
+        .expect("Unidade de saída do produto não cadastrada");
+    if let Err(s) = valida_quantidade(&dados.quantidade, &unidade) {
+        return Resposta::ErroSemantico(s);
+    }
+
+    if possui_historico(conexao, kit.id) {
+        return Resposta::Conflito(
+            json!({
+                "mensagem": format!(
+                    "O produto {} possui histórico de estoque e não pode ser um kit",
+                    kit.id
+                )
+            })
+            .to_string(),
+        );
+    }
+
+    let resultado = diesel::insert_into(produto_componente::table)
+        .values(&ProdutoComponente {
+            kit_id: kit.id,
+            componente_id: componente.id,
+            quantidade: dados.quantidade,
+        })
+        .on_conflict((
+            produto_componente::kit_id,
+            produto_componente::componente_id,
+        ))
+        .do_update()
+        .set(
+            produto_componente::quantidade
+                .eq(diesel::pg::upsert::excluded(produto_componente::quantidade)),
+        )
+        .get_result::<ProdutoComponente>(conexao);
+
+    match resultado {
+        Ok(c) => {
+            let _ = registra_log(
+                conexao,
+                String::from("PRODUTO_COMPONENTE"),
+                usuario.to_owned(),
+                DBOperacao::Insercao,
+                Some(format!(
+                    "Kit {}: componente {} com quantidade {}",
+                    c.kit_id, c.componente_id, c.quantidade
+                )),
+            );
+            Resposta::Ok(serde_json::to_string(&c).unwrap())
+        }
+        Err(_) => Resposta::ErroInterno(
+            json!({
+                "mensagem":
+                    "Erro interno ao cadastrar componente do kit. \
+                     Contate o suporte para mais informações."
+            })
+            .to_string(),
+        ),
+    }
+}
+
+/// Remove um componente de um kit.
+///
+/// Será retornado `true` caso o produto fosse componente do kit e tenha sido
+/// removido.
+pub fn deleta_componente(conexao: &PgConnection, kitid: i32, compid: i32, usuario: &str) -> bool {
+    let num = diesel::delete(produto_componente::table.find((kitid, compid)))
+        .execute(conexao)
+        .expect("Erro ao remover componente do kit");
+    if num > 0 {
+        let _ = registra_log(
+            conexao,
+            String::from("PRODUTO_COMPONENTE"),
+            usuario.to_owned(),
+            DBOperacao::Remocao,
+            Some(format!("Kit {}: componente {}", kitid, compid)),
+        );
+    }
+    num > 0
+}
@@ -0,0 +1,248 @@
+// controller/reservas.rs -- Uma parte de Minerva.rs
+// Copyright (C) 2021 Lucas S. Vieira
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with this program.  If not, see <https://www.gnu.org/licenses/>.
+
+//! Ferramentas para tráfego de dados entre as rotas de reservas de estoque e o
+//! banco de dados.
+//!
+//! As ferramentas deste módulo realizam o tráfego de dados entre as rotas de
+//! reservas e a tabela `reserva` do banco de dados. A conversão de uma reserva
+//! em uma saída de estoque ocorre junto da movimentação de estoque
+//! correspondente; veja
+//! [`converte_reserva`][`super::estoque::converte_reserva`].
+
+use super::log::*;
+use crate::model::reserva::*;
+use crate::model::schema::{estoque, reserva};
+use crate::routes::respostas::Resposta;
+use bigdecimal::{BigDecimal, Zero};
+use diesel::prelude::*;
+use serde_json::json;
+
+/// Retorna a quantidade reservada de um produto em um depósito, isto é, a soma
+/// das quantidades das reservas ativas e não expiradas da posição de estoque.
+///
+/// Caso utilizada dentro de uma transação que tenha travado a posição de
+/// estoque, a quantidade reservada não poderá ser alterada por novas reservas
+/// até o fim da transação, já que a criação de reservas também trava a posição
+/// de estoque.
+pub fn total_reservado(
+    conexao: &PgConnection,
+    prod_id: i32,
+    dep_id: i32,
+) -> QueryResult<BigDecimal> {
+    let hoje = chrono::Local::today().naive_local();
+    reserva::table
+        .filter(reserva::produto_id.eq(prod_id))
+        .filter(reserva::deposito_id.eq(dep_id))
+        .filter(reserva::situacao.eq(SituacaoReserva::Ativa))
+        .filter(reserva::validade.ge(hoje))
+        .select(diesel::dsl::sum(reserva::quantidade))
+        .first::<Option<BigDecimal>>(conexao)
+        .map(|total| total.unwrap_or_else(|| BigDecimal::zero().with_scale(3)))
+}
+
+/// Retorna uma reserva de estoque, caso exista.
+pub fn get_reserva(conexao: &PgConnection, reserva_id: i32) -> Option<Reserva> {
+    reserva::table
+        .find(reserva_id)
+        .first::<Reserva>(conexao)
+        .optional()
+        .expect("Erro ao carregar reserva")
+}
+
+/// Lista as reservas ativas e não expiradas, de um produto, de um depósito e
+/// de um documento de referência, caso informados.
+///
+/// As reservas são ordenadas por produto, depósito e data de validade.
+pub fn lista_reservas(
+    conexao: &PgConnection,
+    produto: Option<i32>,
+    deposito: Option<i32>,
+    docto: Option<String>,
+) -> Vec<Reserva> {
+    let hoje = chrono::Local::today().naive_local();
+    let mut query = reserva::table
+        .filter(reserva::situacao.eq(SituacaoReserva::Ativa))
+        .filter(reserva::validade.ge(hoje))
+        .into_boxed();
+    if let Some(produto) = produto {
+        query = query.filter(reserva::produto_id.eq(produto));
+    }
+    if let Some(deposito) = deposito {
+        query = query.filter(reserva::deposito_id.eq(deposito));
+    }
+    if let Some(docto) = docto {
+        query = query.filter(reserva::docto.eq(docto));
+    }
+    query
+        .order((
+            reserva::produto_id,
+            reserva::deposito_id,
+            reserva::validade,
+            reserva::id,
+        ))
+        .load::<Reserva>(conexao)
+        .expect("Erro ao carregar reservas")
+}
+
+/// Registra uma reserva de estoque.
+///
+/// O produto deve existir e possuir início de estoque no depósito informado,
+/// do contrário será retornado um erro 404. Será retornado um erro 422 caso os
+/// dados da reserva sejam inválidos (veja
+/// [`valida_reserva`][`crate::bo::estoque::valida_reserva`]), caso o produto
+/// esteja inativo, caso a quantidade não respeite as casas decimais da unidade
+/// de saída do produto, ou caso a quantidade disponível da posição de estoque
+/// seja insuficiente.
+///
+/// A posição de estoque é travada durante a verificação da quantidade
+/// disponível e o registro da reserva, de forma que reservas e movimentações
+/// concorrentes não possam reservar ou movimentar as mesmas unidades. A
+/// operação será registrada no log em nome do usuário informado.
+pub fn registra_reserva(conexao: &PgConnection, recv: ReservaRecv, usuario: &str) -> Resposta {
+    use super::{produtos, unidades};
+
+    let erro =
+        |mensagem: String| Resposta::ErroSemantico(json!({ "mensagem": mensagem }).to_string());
+    let hoje = chrono::Local::today().naive_local();
+    if let Err(s) = crate::bo::estoque::valida_reserva(&recv, hoje) {
+        return Resposta::ErroSemantico(s);
+    }
+
+    let produto = match produtos::get_produto(conexao, recv.produto_id) {
+        None => {
+            return Resposta::NaoEncontrado(
+                json!({
+                    "mensagem": "Produto não encontrado"
+                })
+                .to_string(),
+            )
+        }
+        Some(p) if !p.ativo => return erro(format!("O produto {} está inativo", p.id)),
+        Some(p) => p,
+    };
+
+    if super::estoque::get_estoque(conexao, produto.id, recv.deposito_id).is_none() {
+        return Resposta::NaoEncontrado(
+            json!({
+                "mensagem": format!(
+                    "Necessário efetuar início de estoque para o produto {} no depósito {}",
+                    produto.id, recv.deposito_id
+                )
+            })
+            .to_string(),
+        );
+    }
+
+    let unidade_saida = unidades::get_unidade(conexao, &produto.unidsaida)
+        .expect("Unidade de saída do produto não cadastrada");
+    if let Err(s) = crate::bo::produtos::valida_quantidade(&recv.quantidade, &unidade_saida) {
+        return Resposta::ErroSemantico(s);
+    }
+
+    let mut disponivel = None;
+    let resultado = conexao.transaction::<_, diesel::result::Error, _>(|| {
+        let quantidade = estoque::table
+            .find((recv.produto_id, recv.deposito_id))
+            .select(estoque::quantidade)
+            .for_update()
+            .first::<BigDecimal>(conexao)?;
+        let livre = quantidade - total_reservado(conexao, recv.produto_id, recv.deposito_id)?;
+        if livre < recv.quantidade {
+            disponivel = Some(livre);
+            return Err(diesel::result::Error::RollbackTransaction);
+        }
+        diesel::insert_into(reserva::table)
+            .values(&NovaReserva {
+                produto_id: recv.produto_id,
+                deposito_id: recv.deposito_id,
+                docto: recv.docto.trim().to_owned(),
+                quantidade: recv.quantidade.clone(),
+                validade: recv.validade,
+            })
+            .get_result::<Reserva>(conexao)
+    });
+
+    match resultado {
+        Ok(reserva) => {
+            let _ = registra_log(
+                conexao,
+                String::from("RESERVA"),
+                usuario.to_owned(),
+                DBOperacao::Insercao,
+                Some(format!(
+                    "Reserva {}: produto {} no depósito {}, documento {}",
+                    reserva.id, reserva.produto_id, reserva.deposito_id, reserva.docto
+                )),
+            );
+            Resposta::Ok(serde_json::to_string(&reserva).unwrap())
+        }
+        Err(diesel::result::Error::RollbackTransaction) if disponivel.is_some() => erro(format!(
+            "Quantidade insuficiente para reserva! Disponível: {}",
+            disponivel.unwrap()
+        )),
+        Err(e) => erro(e.to_string()),
+    }
+}
+
+/// Libera uma reserva de estoque ativa, de forma que a quantidade reservada
+/// volte a estar disponível.
+///
+/// Caso a reserva não exista, será retornado um erro 404; caso já tenha sido
+/// liberada ou convertida, será retornado um erro 409. Reservas expiradas
+/// também podem ser liberadas. A operação será registrada no log em nome do
+/// usuário informado.
+pub fn libera_reserva(conexao: &PgConnection, reserva_id: i32, usuario: &str) -> Resposta {
+    let resultado = diesel::update(
+        reserva::table
+            .find(reserva_id)
+            .filter(reserva::situacao.eq(SituacaoReserva::Ativa)),
+    )
+    .set(reserva::situacao.eq(SituacaoReserva::Liberada))
+    .get_result::<Reserva>(conexao)
+    .optional();
+
+    match resultado {
+        Ok(Some(reserva)) => {
+            let _ = registra_log(
+                conexao,
+                String::from("RESERVA"),
+                usuario.to_owned(),
+                DBOperacao::Alteracao,
+                Some(format!("Reserva {}: liberação", reserva.id)),
+            );
+            Resposta::Ok(serde_json::to_string(&reserva).unwrap())
+        }
+        Ok(None) if get_reserva(conexao, reserva_id).is_none() => Resposta::NaoEncontrado(
+            json!({
+                "mensagem": "Reserva não encontrada"
+            })
+            .to_string(),
+        ),
+        Ok(None) => Resposta::Conflito(
+            json!({
+                "mensagem": format!("A reserva {} não está ativa", reserva_id)
+            })
+            .to_string(),
+        ),
+        Err(e) => Resposta::ErroSemantico(
+            json!({
+                "mensagem": e.to_string()
+            })
+            .to_string(),
+        ),
+    }
+}
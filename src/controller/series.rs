@@ -0,0 +1,186 @@
+// controller/series.rs -- Uma parte de Minerva.rs
+// Copyright (C) 2021 Lucas S. Vieira
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with this program.  If not, see <https://www.gnu.org/licenses/>.
+
+//! Ferramentas para tráfego de dados entre as rotas de números de série e o
+//! banco de dados.
+//!
+//! As ferramentas deste módulo realizam o tráfego de dados entre as rotas de
+//! estoque e as tabelas `numero_serie` e `mov_estoque_serie` do banco de
+//! dados. A movimentação dos números de série ocorre sempre junto da
+//! movimentação de estoque correspondente; veja o módulo
+//! [`estoque`][`super::estoque`].
+
+use crate::model::estoque::MovEstoque;
+use crate::model::produto::Produto;
+use crate::model::schema::{mov_estoque, mov_estoque_serie, numero_serie};
+use crate::model::serie::*;
+use bigdecimal::{BigDecimal, Signed, Zero};
+use diesel::prelude::*;
+use serde_json::json;
+
+/// Lista os números de série em estoque, de um produto e de um depósito, caso
+/// informados.
+///
+/// Os números de série são ordenados por produto, depósito e número.
+pub fn lista_series(
+    conexao: &PgConnection,
+    produto: Option<i32>,
+    deposito: Option<i32>,
+) -> Vec<NumeroSerie> {
+    let mut query = numero_serie::table
+        .filter(numero_serie::deposito_id.is_not_null())
+        .into_boxed();
+    if let Some(produto) = produto {
+        query = query.filter(numero_serie::produto_id.eq(produto));
+    }
+    if let Some(deposito) = deposito {
+        query = query.filter(numero_serie::deposito_id.eq(deposito));
+    }
+    query
+        .order((
+            numero_serie::produto_id,
+            numero_serie::deposito_id,
+            numero_serie::numero,
+        ))
+        .load::<NumeroSerie>(conexao)
+        .expect("Erro ao carregar números de série")
+}
+
+/// Retorna o histórico de um número de série de um produto, com todas as
+/// movimentações de estoque da unidade em ordem cronológica, caso o número de
+/// série exista.
+pub fn historico_serie(
+    conexao: &PgConnection,
+    produto: i32,
+    numero: &str,
+) -> Option<HistoricoSerie> {
+    let serie = numero_serie::table
+        .filter(numero_serie::produto_id.eq(produto))
+        .filter(numero_serie::numero.eq(numero.trim()))
+        .first::<NumeroSerie>(conexao)
+        .optional()
+        .expect("Erro ao carregar número de série")?;
+    let movimentos = mov_estoque_serie::table
+        .inner_join(mov_estoque::table)
+        .filter(mov_estoque_serie::numero_serie_id.eq(serie.id))
+        .order((mov_estoque::datahora, mov_estoque::id))
+        .select(mov_estoque::all_columns)
+        .load::<MovEstoque>(conexao)
+        .expect("Erro ao carregar movimentações do número de série");
+    Some(HistoricoSerie {
+        numero_serie: serie,
+        movimentos,
+    })
+}
+
+/// Lista os números de série movimentados por uma movimentação de estoque.
+pub fn series_movimento(conexao: &PgConnection, mov_id: i32) -> Vec<String> {
+    mov_estoque_serie::table
+        .inner_join(numero_serie::table)
+        .filter(mov_estoque_serie::mov_estoque_id.eq(mov_id))
+        .order(numero_serie::numero)
+        .select(numero_serie::numero)
+        .load::<String>(conexao)
+        .expect("Erro ao carregar números de série da movimentação")
+}
+
+/// Movimenta os números de série de um produto em um depósito, de acordo com
+/// uma movimentação de `qtd` unidades do produto.
+///
+/// Produtos sem controle de números de série não possuem números de série a
+/// serem movimentados. Em entradas, os números de série informados serão
+/// cadastrados para o produto caso ainda não existam, e passarão a estar em
+/// estoque no depósito; números de série que já estejam em estoque, em
+/// qualquer depósito, não podem dar entrada novamente. Em saídas, os números
+/// de série informados devem estar em estoque no depósito, e deixarão de estar
+/// em estoque. Espera-se que os números de série já tenham sido validados
+/// através de
+/// [`valida_numeros_serie`][`crate::bo::estoque::valida_numeros_serie`].
+///
+/// Caso seja informada a movimentação de estoque correspondente, os números de
+/// série serão registrados junto da mesma, compondo seu histórico. Retorna uma
+/// mensagem de erro caso a movimentação dos números de série não seja
+/// possível. Deve ser utilizada dentro da transação que registra a
+/// movimentação de estoque, que deverá ser desfeita em caso de erro.
+pub fn movimenta_series(
+    conexao: &PgConnection,
+    produto: &Produto,
+    dep_id: i32,
+    mov_id: Option<i32>,
+    qtd: &BigDecimal,
+    numeros: &[String],
+) -> QueryResult<Result<(), String>> {
+    let erro = |mensagem: String| Ok(Err(json!({ "mensagem": mensagem }).to_string()));
+    if !produto.controla_serie || qtd.is_zero() {
+        return Ok(Ok(()));
+    }
+
+    let entrada = qtd.is_positive();
+    for numero in numeros {
+        let numero = numero.trim().to_owned();
+        if entrada {
+            diesel::insert_into(numero_serie::table)
+                .values(&NovoNumeroSerie {
+                    produto_id: produto.id,
+                    numero: numero.clone(),
+                    deposito_id: None,
+                })
+                .on_conflict_do_nothing()
+                .execute(conexao)?;
+        }
+        let serie = numero_serie::table
+            .filter(numero_serie::produto_id.eq(produto.id))
+            .filter(numero_serie::numero.eq(&numero))
+            .for_update()
+            .first::<NumeroSerie>(conexao)
+            .optional()?;
+        let serie = match serie {
+            Some(s) if entrada && s.deposito_id.is_some() => {
+                return erro(format!(
+                    "O número de série {} já está em estoque no depósito {}",
+                    numero,
+                    s.deposito_id.unwrap()
+                ))
+            }
+            Some(s) if !entrada && s.deposito_id != Some(dep_id) => {
+                return erro(format!(
+                    "O número de série {} não está em estoque no depósito {}",
+                    numero, dep_id
+                ))
+            }
+            None => {
+                return erro(format!(
+                    "O número de série {} não está em estoque no depósito {}",
+                    numero, dep_id
+                ))
+            }
+            Some(s) => s,
+        };
+
+        diesel::update(numero_serie::table.find(serie.id))
+            .set(numero_serie::deposito_id.eq(if entrada { Some(dep_id) } else { None }))
+            .execute(conexao)?;
+        if let Some(mov_id) = mov_id {
+            diesel::insert_into(mov_estoque_serie::table)
+                .values(&MovEstoqueSerie {
+                    mov_estoque_id: mov_id,
+                    numero_serie_id: serie.id,
+                })
+                .execute(conexao)?;
+        }
+    }
+    Ok(Ok(()))
+}
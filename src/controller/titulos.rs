@@ -0,0 +1,130 @@
+// controller/titulos.rs -- Uma parte de Minerva.rs
+// Copyright (C) 2021 Lucas S. Vieira
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with this program.  If not, see <https://www.gnu.org/licenses/>.
+
+//! Ferramentas para tráfego de dados entre as rotas de títulos a receber de
+//! clientes e o banco de dados.
+//!
+//! As ferramentas deste módulo realizam o tráfego de dados entre as rotas de
+//! títulos e a tabela `titulo`. As regras de crédito dos clientes, que
+//! dependem dos títulos em aberto, podem ser encontradas em
+//! [`bo::clientes`][`crate::bo::clientes`].
+
+use super::log::*;
+use crate::model::schema::titulo;
+use crate::model::titulo::*;
+use diesel::prelude::*;
+
+/// Registra um novo título a receber para um cliente.
+///
+/// Os dados recebidos não são avaliados quanto à sua validade, e assume-se que
+/// o cliente exista. A operação será registrada no log em nome do usuário
+/// informado. Será retornado o título cadastrado.
+pub fn registra_titulo(
+    conexao: &PgConnection,
+    cliente_id: i32,
+    recv: TituloRecv,
+    usuario: &str,
+) -> QueryResult<Titulo> {
+    let titulo: Titulo = diesel::insert_into(titulo::table)
+        .values(&NovoTitulo::from((cliente_id, recv)))
+        .get_result(conexao)?;
+    let _ = registra_log(
+        conexao,
+        String::from("TITULO"),
+        usuario.to_owned(),
+        DBOperacao::Insercao,
+        Some(format!("Título {}", titulo.id)),
+    );
+    Ok(titulo)
+}
+
+/// Retorna um título de um cliente.
+///
+/// Será retornado um `Option` que poderá conter o título com o id informado,
+/// caso o mesmo exista e pertença ao cliente informado.
+pub fn get_titulo(conexao: &PgConnection, cl_id: i32, titulo_id: i32) -> Option<Titulo> {
+    use crate::model::schema::titulo::dsl::*;
+    titulo
+        .filter(id.eq(&titulo_id))
+        .filter(cliente_id.eq(&cl_id))
+        .first::<Titulo>(conexao)
+        .optional()
+        .expect("Erro ao carregar título")
+}
+
+/// Lista todos os títulos de um cliente, em ordem de vencimento.
+pub fn lista_titulos_cliente(conexao: &PgConnection, cl_id: i32) -> Vec<Titulo> {
+    use crate::model::schema::titulo::dsl::*;
+    titulo
+        .filter(cliente_id.eq(&cl_id))
+        .order((vencimento.asc(), id.asc()))
+        .load::<Titulo>(conexao)
+        .expect("Erro ao carregar títulos")
+}
+
+/// Lista os títulos de um cliente que ainda possuam algum valor em aberto, em
+/// ordem de vencimento.
+pub fn lista_titulos_abertos(conexao: &PgConnection, cl_id: i32) -> Vec<Titulo> {
+    use crate::model::schema::titulo::dsl::*;
+    titulo
+        .filter(cliente_id.eq(&cl_id))
+        .filter(valor_pago.lt(valor))
+        .order((vencimento.asc(), id.asc()))
+        .load::<Titulo>(conexao)
+        .expect("Erro ao carregar títulos")
+}
+
+/// Registra um pagamento, parcial ou total, de um título.
+///
+/// O valor pago não é avaliado quanto à sua validade. A operação será
+/// registrada no log em nome do usuário informado. Será retornado o título
+/// com o valor pago atualizado.
+pub fn registra_pagamento(
+    conexao: &PgConnection,
+    tit: &Titulo,
+    recv: PagamentoTituloRecv,
+    usuario: &str,
+) -> QueryResult<Titulo> {
+    use crate::model::schema::titulo::dsl::*;
+    let atualizado = diesel::update(titulo.filter(id.eq(&tit.id)))
+        .set(valor_pago.eq(valor_pago + recv.valor.clone()))
+        .get_result::<Titulo>(conexao)?;
+    let _ = registra_log(
+        conexao,
+        String::from("TITULO"),
+        usuario.to_owned(),
+        DBOperacao::Alteracao,
+        Some(format!("Título {}: pagamento de {}", tit.id, recv.valor)),
+    );
+    Ok(atualizado)
+}
+
+/// Informa se há algum título registrado para qualquer cliente, em aberto ou
+/// não.
+pub fn existem_titulos(conexao: &PgConnection) -> bool {
+    use crate::model::schema::titulo::dsl::*;
+    diesel::select(diesel::dsl::exists(titulo.select(id)))
+        .get_result(conexao)
+        .expect("Erro ao verificar títulos")
+}
+
+/// Informa se um cliente possui algum título registrado, em aberto ou não.
+pub fn possui_titulos(conexao: &PgConnection, cl_id: i32) -> bool {
+    use crate::model::schema::titulo::dsl::*;
+    diesel::select(diesel::dsl::exists(titulo.filter(cliente_id.eq(&cl_id))))
+        .get_result(conexao)
+        .expect("Erro ao verificar títulos")
+}
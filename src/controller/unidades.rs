@@ -0,0 +1,220 @@
+// controller/unidades.rs -- Uma parte de Minerva.rs
+// Copyright (C) 2021 Lucas S. Vieira
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with this program.  If not, see <https://www.gnu.org/licenses/>.
+
+//! Ferramentas para tráfego de dados entre as rotas de unidades de medida e o
+//! banco de dados.
+//!
+//! As ferramentas deste módulo realizam o tráfego de dados entre as rotas de
+//! unidades de medida e de fatores de conversão de unidades de produtos, e as
+//! tabelas `unidade_medida` e `produto_unidade`.
+
+use super::log::*;
+use crate::model::schema::{produto_unidade, unidade_medida};
+use crate::model::unidade::*;
+use crate::routes::respostas::Resposta;
+use diesel::prelude::*;
+use serde_json::json;
+
+/// Lista todas as unidades de medida cadastradas no sistema, em ordem de
+/// código.
+pub fn lista_unidades(conexao: &PgConnection) -> Vec<UnidadeMedida> {
+    unidade_medida::table
+        .order(unidade_medida::codigo.asc())
+        .load::<UnidadeMedida>(conexao)
+        .expect("Erro ao carregar unidades de medida")
+}
+
+/// Retorna os dados de uma unidade de medida, caso existente.
+///
+/// O código da unidade será procurado em uppercase.
+pub fn get_unidade(conexao: &PgConnection, codigo: &str) -> Option<UnidadeMedida> {
+    unidade_medida::table
+        .find(codigo.to_uppercase())
+        .first::<UnidadeMedida>(conexao)
+        .optional()
+        .expect("Erro ao carregar unidade de medida")
+}
+
+/// Registra uma nova unidade de medida no banco de dados.
+///
+/// Esta função assume que os dados da unidade de medida sejam válidos. O
+/// código da unidade será armazenado em uppercase. Caso a unidade seja
+/// cadastrada, será retornado seu código. Caso contrário, será retornada uma
+/// mensagem de erro em String.
+pub fn registra_unidade(
+    conexao: &PgConnection,
+    mut dados: UnidadeMedida,
+    usuario: &str,
+) -> Result<String, String> {
+    dados.codigo = dados.codigo.to_uppercase();
+    match diesel::insert_into(unidade_medida::table)
+        .values(&dados)
+        .get_result::<UnidadeMedida>(conexao)
+    {
+        Ok(unidade) => {
+            let _ = registra_log(
+                conexao,
+                String::from("UNIDADE_MEDIDA"),
+                usuario.to_owned(),
+                DBOperacao::Insercao,
+                Some(format!("Unidade de medida {}", unidade.codigo)),
+            );
+            Ok(unidade.codigo)
+        }
+        Err(e) => {
+            if let diesel::result::Error::DatabaseError(_, _) = &e {
+                Err(format!("{}", e))
+            } else {
+                Err(String::from(
+                    "Erro interno ao cadastrar unidade de medida. \
+                     Contate o suporte para mais informações.",
+                ))
+            }
+        }
+    }
+}
+
+/// Deleta uma unidade de medida do banco de dados.
+///
+/// Caso a unidade não exista, será retornado um erro 404. Caso a unidade
+/// esteja em uso por algum produto, como unidade de saída ou como unidade de
+/// conversão, será retornado um erro 409.
+pub fn deleta_unidade(conexao: &PgConnection, codigo: &str, usuario: &str) -> Resposta {
+    let unidade = match get_unidade(conexao, codigo) {
+        None => {
+            return Resposta::NaoEncontrado(
+                json!({
+                    "mensagem": "Unidade de medida não encontrada"
+                })
+                .to_string(),
+            )
+        }
+        Some(u) => u,
+    };
+
+    match diesel::delete(unidade_medida::table.find(&unidade.codigo)).execute(conexao) {
+        Ok(_) => {
+            let _ = registra_log(
+                conexao,
+                String::from("UNIDADE_MEDIDA"),
+                usuario.to_owned(),
+                DBOperacao::Remocao,
+                Some(format!("Unidade de medida {}", unidade.codigo)),
+            );
+            Resposta::Ok(json!({ "codigo": unidade.codigo }).to_string())
+        }
+        Err(diesel::result::Error::DatabaseError(
+            diesel::result::DatabaseErrorKind::ForeignKeyViolation,
+            _,
+        )) => Resposta::Conflito(
+            json!({
+                "mensagem": format!(
+                    "A unidade de medida {} está em uso e não pode ser removida",
+                    unidade.codigo
+                )
+            })
+            .to_string(),
+        ),
+        Err(_) => Resposta::ErroInterno(
+            json!({
+                "mensagem":
+                    "Erro interno ao remover unidade de medida. \
+                     Contate o suporte para mais informações."
+            })
+            .to_string(),
+        ),
+    }
+}
+
+/// Lista os fatores de conversão de unidades de um produto.
+pub fn lista_conversoes(conexao: &PgConnection, prod_id: i32) -> Vec<ProdutoUnidade> {
+    use crate::model::schema::produto_unidade::dsl::*;
+    produto_unidade
+        .filter(produto_id.eq(&prod_id))
+        .order(unidade.asc())
+        .load::<ProdutoUnidade>(conexao)
+        .expect("Erro ao carregar conversões de unidades")
+}
+
+/// Retorna o fator de conversão de uma unidade de medida para a unidade de
+/// saída de um produto, caso cadastrado.
+pub fn get_conversao(conexao: &PgConnection, prod_id: i32, codigo: &str) -> Option<ProdutoUnidade> {
+    produto_unidade::table
+        .find((prod_id, codigo.to_uppercase()))
+        .first::<ProdutoUnidade>(conexao)
+        .optional()
+        .expect("Erro ao carregar conversão de unidade")
+}
+
+/// Registra ou atualiza o fator de conversão de uma unidade de medida para a
+/// unidade de saída de um produto.
+///
+/// Esta função assume que os dados sejam válidos, e que o produto e a unidade
+/// de medida existam. A operação será registrada no log em nome do usuário
+/// informado. Será retornado o fator de conversão cadastrado.
+pub fn registra_conversao(
+    conexao: &PgConnection,
+    prod_id: i32,
+    dados: ProdutoUnidadeRecv,
+    usuario: &str,
+) -> QueryResult<ProdutoUnidade> {
+    use crate::model::schema::produto_unidade::dsl::*;
+    let conversao = diesel::insert_into(produto_unidade)
+        .values(&ProdutoUnidade {
+            produto_id: prod_id,
+            unidade: dados.unidade.to_uppercase(),
+            fator: dados.fator,
+        })
+        .on_conflict((produto_id, unidade))
+        .do_update()
+        .set(fator.eq(diesel::pg::upsert::excluded(fator)))
+        .get_result::<ProdutoUnidade>(conexao)?;
+    let _ = registra_log(
+        conexao,
+        String::from("PRODUTO_UNIDADE"),
+        usuario.to_owned(),
+        DBOperacao::Insercao,
+        Some(format!(
+            "Produto {}: conversão de {} com fator {}",
+            conversao.produto_id, conversao.unidade, conversao.fator
+        )),
+    );
+    Ok(conversao)
+}
+
+/// Deleta o fator de conversão de uma unidade de medida de um produto.
+///
+/// Será retornado `true` caso o fator de conversão existisse e tenha sido
+/// removido.
+pub fn deleta_conversao(conexao: &PgConnection, prod_id: i32, codigo: &str, usuario: &str) -> bool {
+    let num = diesel::delete(produto_unidade::table.find((prod_id, codigo.to_uppercase())))
+        .execute(conexao)
+        .expect("Erro ao remover conversão de unidade");
+    if num > 0 {
+        let _ = registra_log(
+            conexao,
+            String::from("PRODUTO_UNIDADE"),
+            usuario.to_owned(),
+            DBOperacao::Remocao,
+            Some(format!(
+                "Produto {}: conversão de {}",
+                prod_id,
+                codigo.to_uppercase()
+            )),
+        );
+    }
+    num > 0
+}
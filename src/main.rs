@@ -36,18 +36,27 @@ fn launch() -> _ {
     bo::db::garante_usuario_inicial(&pool);
 
     let redis_pool = bo::redis::cria_pool_redis();
+    let chaveiro = bo::cripto::cria_chaveiro();
+    let armazenamento = bo::arquivos::cria_file_store();
 
     //let twilio = bo::twilio::cria_conexao_twilio();
 
     rocket::build()
         .manage(pool)
         .manage(redis_pool)
+        .manage(chaveiro)
+        .manage(armazenamento)
         //.manage(twilio.ok())
         .mount("/", routes![routes::index])
         .mount("/login", routes::login::constroi_rotas())
         .mount("/clientes", routes::clientes::constroi_rotas())
         .mount("/produtos", routes::produtos::constroi_rotas())
+        .mount("/categorias", routes::categorias::constroi_rotas())
+        .mount("/depositos", routes::depositos::constroi_rotas())
+        .mount("/precos", routes::precos::constroi_rotas())
         .mount("/estoque", routes::estoque::constroi_rotas())
+        .mount("/unidades", routes::unidades::constroi_rotas())
+        .mount("/arquivos", routes::arquivos::constroi_rotas())
         .mount("/log", routes::log::constroi_rotas())
         .mount("/usuarios", routes::usuarios::constroi_rotas())
 }
@@ -0,0 +1,97 @@
+// model/arquivo.rs -- Uma parte de Minerva.rs
+// Copyright (C) 2021 Lucas S. Vieira
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with this program.  If not, see <https://www.gnu.org/licenses/>.
+
+//! Utilitários de modelagem de arquivos anexos para banco de dados e regras de
+//! negócio.
+//!
+//! Este módulo define estruturas para o tráfego de metadados de arquivos
+//! anexos (imagens de produtos e documentos de movimentações de estoque). O
+//! conteúdo dos arquivos não é armazenado no banco de dados, e sim em um
+//! [`FileStore`][`crate::bo::arquivos::FileStore`], sob a chave registrada nos
+//! metadados.
+
+use super::schema::arquivo;
+use chrono::DateTime;
+use rocket::data::Capped;
+use rocket::fs::TempFile;
+use serde::Serialize;
+
+/// Representa os metadados de um arquivo anexo, da forma como são armazenados
+/// na tabela `arquivo`.
+///
+/// Um arquivo pertence a exatamente um produto ou a exatamente uma
+/// movimentação de estoque.
+#[derive(Queryable, Identifiable, Serialize, Debug, Clone)]
+#[table_name = "arquivo"]
+pub struct Arquivo {
+    /// Id do arquivo no banco de dados.
+    pub id: i32,
+    /// Chave sob a qual o conteúdo do arquivo foi gravado no armazenamento.
+    #[serde(skip_serializing)]
+    pub chave: String,
+    /// Nome original do arquivo, como informado no envio.
+    pub nome: String,
+    /// Tipo de conteúdo (MIME) do arquivo (ex: `image/png`).
+    pub content_type: String,
+    /// Tamanho do arquivo, em bytes.
+    pub tamanho: i32,
+    /// Hash SHA-256 do conteúdo do arquivo, em hexadecimal.
+    pub sha256: String,
+    /// Id do produto ao qual o arquivo pertence, caso seja uma imagem de
+    /// produto.
+    pub produto_id: Option<i32>,
+    /// Id da movimentação de estoque à qual o arquivo pertence, caso seja um
+    /// documento de movimentação.
+    pub mov_estoque_id: Option<i32>,
+    /// Login do usuário que enviou o arquivo.
+    pub usuario: String,
+    /// Data e hora de envio do arquivo.
+    pub datahora: DateTime<chrono::Utc>,
+}
+
+/// Representa os metadados de um arquivo anexo a serem inseridos no banco de
+/// dados. A data e hora de envio são preenchidas pelo próprio banco de dados.
+#[derive(Insertable, Debug, Clone)]
+#[table_name = "arquivo"]
+pub struct NovoArquivo {
+    /// Chave do conteúdo no armazenamento. Ver [`Arquivo::chave`].
+    pub chave: String,
+    /// Nome original do arquivo. Ver [`Arquivo::nome`].
+    pub nome: String,
+    /// Tipo de conteúdo do arquivo. Ver [`Arquivo::content_type`].
+    pub content_type: String,
+    /// Tamanho do arquivo. Ver [`Arquivo::tamanho`].
+    pub tamanho: i32,
+    /// Hash do conteúdo do arquivo. Ver [`Arquivo::sha256`].
+    pub sha256: String,
+    /// Produto dono do arquivo. Ver [`Arquivo::produto_id`].
+    pub produto_id: Option<i32>,
+    /// Movimentação dona do arquivo. Ver [`Arquivo::mov_estoque_id`].
+    pub mov_estoque_id: Option<i32>,
+    /// Usuário que enviou o arquivo. Ver [`Arquivo::usuario`].
+    pub usuario: String,
+}
+
+/// Representa um arquivo anexo recebido através de um formulário
+/// `multipart/form-data`, no campo `arquivo`.
+///
+/// O conteúdo recebido é limitado pelo limite `file` da configuração do
+/// Rocket; caso o arquivo exceda este limite, o conteúdo estará incompleto.
+#[derive(FromForm)]
+pub struct ArquivoRecv<'r> {
+    /// Arquivo enviado, armazenado temporariamente pelo Rocket.
+    pub arquivo: Capped<TempFile<'r>>,
+}
@@ -0,0 +1,97 @@
+// model/categoria.rs -- Uma parte de Minerva.rs
+// Copyright (C) 2021 Lucas S. Vieira
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with this program.  If not, see <https://www.gnu.org/licenses/>.
+
+//! Utilitários de modelagem de categorias de produtos para banco de dados e
+//! regras de negócio.
+//!
+//! Este módulo define estruturas para o tráfego de dados de categorias de
+//! produtos entre as partes respectivas do sistema. As categorias formam uma
+//! árvore de até três níveis (grupo, subgrupo e família), onde cada categoria
+//! pode possuir uma categoria pai.
+
+use super::schema::categoria;
+use bigdecimal::BigDecimal;
+use serde::{Deserialize, Serialize};
+
+/// Representa uma categoria de produtos, da forma como é armazenada na tabela
+/// `categoria`.
+#[derive(Queryable, Identifiable, Serialize, Debug, Clone)]
+#[table_name = "categoria"]
+pub struct Categoria {
+    /// Id da categoria no banco de dados.
+    pub id: i32,
+    /// Descrição textual da categoria.
+    pub descricao: String,
+    /// Id da categoria pai, na tabela `categoria`. Categorias sem categoria
+    /// pai são grupos, no topo da árvore de categorias.
+    pub categoria_pai_id: Option<i32>,
+}
+
+/// Representa os dados de inserção ou alteração de uma categoria, recebidos
+/// como corpo de uma requisição, similar ao exemplo a seguir, em JSON:
+///
+/// ```json
+/// {
+///   "descricao": "Bebidas",
+///   "categoria_pai_id": 1
+/// }
+/// ```
+///
+/// A categoria pai pode ser omitida ou declarada com valor `null`, fazendo com
+/// que a categoria seja um grupo, no topo da árvore de categorias.
+#[derive(Insertable, AsChangeset, Deserialize, Clone, Debug)]
+#[table_name = "categoria"]
+#[changeset_options(treat_none_as_null = "true")]
+pub struct NovaCategoria {
+    /// Descrição textual da categoria.
+    /// Ver [`Categoria::descricao`].
+    pub descricao: String,
+    /// Id da categoria pai.
+    /// Ver [`Categoria::categoria_pai_id`].
+    #[serde(default)]
+    pub categoria_pai_id: Option<i32>,
+}
+
+/// Representa uma categoria e suas subcategorias, para exibição da árvore de
+/// categorias.
+#[derive(Serialize, Debug, Clone)]
+pub struct CategoriaArvore {
+    /// Id da categoria. Ver [`Categoria::id`].
+    pub id: i32,
+    /// Descrição da categoria. Ver [`Categoria::descricao`].
+    pub descricao: String,
+    /// Subcategorias imediatas da categoria, com suas respectivas
+    /// subcategorias.
+    pub subcategorias: Vec<CategoriaArvore>,
+}
+
+/// Representa os totais de posição de estoque de uma categoria, considerando
+/// os produtos da categoria e de todas as suas subcategorias.
+#[derive(Serialize, Debug, Clone)]
+pub struct TotalCategoria {
+    /// Id da categoria. Será nulo para o total de produtos sem categoria.
+    pub categoria_id: Option<i32>,
+    /// Descrição da categoria.
+    pub descricao: String,
+    /// Quantidade de produtos com posição de estoque na categoria.
+    pub produtos: i64,
+    /// Soma das quantidades em estoque dos produtos da categoria.
+    pub quantidade: BigDecimal,
+    /// Soma dos valores em estoque dos produtos da categoria, pelo custo, de
+    /// acordo com o método de valoração de cada produto.
+    /// Ver [`ValorizacaoProduto::valor`][`super::estoque::ValorizacaoProduto::valor`].
+    pub valor: BigDecimal,
+}
@@ -20,7 +20,10 @@
 //! partes respectivas do sistema.
 
 use super::endereco::{Endereco, EnderecoRecv};
+use super::logdb::LogDB;
 use crate::model::schema::cliente;
+use bigdecimal::BigDecimal;
+use chrono::{DateTime, NaiveDate};
 use serde::{Deserialize, Serialize};
 
 /// Representa a estrutura de um elemento da tabela `cliente` do banco de dados.
@@ -38,6 +41,9 @@ pub struct Cliente {
     /// Documento do cliente. Caso seja pessoa física, será seu CPF com onze
     /// dígitos, pontos e hífens. Caso seja pessoa jurídica, será seu CNPJ
     /// com catorze dígitos, pontos, hífens e barras.
+    ///
+    /// O documento será armazenado encriptado com a chave de versão
+    /// [`Cliente::chave_versao`]. Ver [`Chaveiro`][`crate::bo::cripto::Chaveiro`].
     pub docto: String,
     /// Determina se o cliente está ativo. Um cliente pode ser definido como
     /// inativo se sua remoção não for conveniente.
@@ -45,6 +51,23 @@ pub struct Cliente {
     /// Determina se o cliente está bloqueado. Um cliente bloqueado não poderá
     /// ter operações feitas em seu nome.
     pub bloqueado: bool,
+    /// Determina se os dados pessoais do cliente foram anonimizados. Um cliente
+    /// anonimizado mantém seu registro no banco de dados, mas seus dados
+    /// pessoais foram irreversivelmente removidos.
+    pub anonimizado: bool,
+    /// Hash determinístico do documento do cliente, utilizado para buscas
+    /// exatas e para garantir a unicidade do documento.
+    pub docto_hash: Option<String>,
+    /// Versão da chave utilizada para encriptar os dados do cliente. Caso não
+    /// seja informada, os dados estarão armazenados em texto-plano.
+    pub chave_versao: Option<i16>,
+    /// Limite de crédito do cliente, isto é, o valor máximo que o cliente pode
+    /// possuir em títulos em aberto. Admite até duas casas decimais.
+    /// Valor máximo: `99999999999.99`.
+    pub limite_credito: BigDecimal,
+    /// Data até a qual o bloqueio automático do cliente por regras de crédito
+    /// foi suspenso por um usuário privilegiado, inclusive.
+    pub liberado_ate: Option<NaiveDate>,
 }
 
 /// Representa os dados de um cliente a serem inseridos na criação de um novo
@@ -64,6 +87,16 @@ pub struct NovoCliente {
     pub ativo: bool,
     /// Determina se o cliente está bloqueado. Ver [`Cliente::bloqueado`].
     pub bloqueado: bool,
+    /// Determina se o cliente foi anonimizado. Ver [`Cliente::anonimizado`].
+    pub anonimizado: bool,
+    /// Hash do documento do cliente. Ver [`Cliente::docto_hash`].
+    pub docto_hash: Option<String>,
+    /// Versão da chave de encriptação. Ver [`Cliente::chave_versao`].
+    pub chave_versao: Option<i16>,
+    /// Limite de crédito do cliente. Ver [`Cliente::limite_credito`].
+    pub limite_credito: BigDecimal,
+    /// Data de liberação do crédito do cliente. Ver [`Cliente::liberado_ate`].
+    pub liberado_ate: Option<NaiveDate>,
 }
 
 /// Representa os dados de um cliente a serem retornados como resposta a uma
@@ -80,6 +113,8 @@ pub struct NovoCliente {
 ///   "docto": "999.999.999-99",
 ///   "ativo": true,
 ///   "bloqueado": false,
+///   "anonimizado": false,
+///   "limite_credito": "1500.00",
 ///   "enderecos": [
 ///     {
 ///       "logradouro": "Rua dos Tolos",
@@ -115,6 +150,10 @@ pub struct ClienteRepr {
     pub ativo: bool,
     /// Determina se o cliente está bloqueado. Ver [`Cliente::bloqueado`].
     pub bloqueado: bool,
+    /// Determina se o cliente foi anonimizado. Ver [`Cliente::anonimizado`].
+    pub anonimizado: bool,
+    /// Limite de crédito do cliente. Ver [`Cliente::limite_credito`].
+    pub limite_credito: BigDecimal,
     /// Lista de endereços registrados para o cliente.
     /// Ver [`Endereco`][`super::endereco::Endereco`].
     pub enderecos: Vec<Endereco>,
@@ -161,6 +200,147 @@ pub struct ClienteRecv {
     pub enderecos: Vec<EnderecoRecv>,
 }
 
+/// Representa o documento de exportação de todos os dados mantidos pelo
+/// sistema a respeito de um cliente, para atendimento de requisições de
+/// titulares de dados, como previsto pela LGPD.
+///
+/// O documento será gerado com uma estrutura similar à seguir, em JSON:
+///
+/// ```json
+/// {
+///   "datahora": "2021-09-07T02:36:22.000000Z",
+///   "cliente": { ... },
+///   "logs": [ ... ]
+/// }
+/// ```
+///
+/// O campo `cliente` possui a mesma estrutura de [`ClienteRepr`], incluindo
+/// os endereços do cliente. O campo `logs` é uma lista de registros de log
+/// relacionados ao cliente e aos seus endereços, com a mesma estrutura de
+/// [`LogDB`][`super::logdb::LogDB`].
+#[derive(Serialize)]
+pub struct ClienteExportacao {
+    /// Data e hora de geração do documento de exportação.
+    pub datahora: DateTime<chrono::Utc>,
+    /// Dados cadastrais do cliente, incluindo seus endereços.
+    pub cliente: ClienteRepr,
+    /// Registros de log relacionados ao cliente e aos seus endereços.
+    pub logs: Vec<LogDB>,
+}
+
+/// Representa um erro encontrado em uma linha de um arquivo de importação de
+/// clientes.
+#[derive(Serialize, Debug, PartialEq)]
+pub struct ErroImportacao {
+    /// Número da linha do arquivo onde o erro foi encontrado, começando em 1.
+    /// A linha 1 corresponde ao cabeçalho do arquivo.
+    pub linha: usize,
+    /// Mensagem descrevendo o erro encontrado.
+    pub mensagem: String,
+}
+
+/// Representa o relatório de uma importação de clientes a partir de um
+/// arquivo CSV.
+///
+/// O relatório será gerado com uma estrutura similar à seguir, em JSON:
+///
+/// ```json
+/// {
+///   "simulacao": false,
+///   "total": 2,
+///   "importados": [12, 13],
+///   "erros": []
+/// }
+/// ```
+///
+/// Caso alguma linha possua erros, nenhum cliente será importado, e o campo
+/// `erros` listará os erros encontrados em cada linha, com a estrutura de
+/// [`ErroImportacao`].
+#[derive(Serialize)]
+pub struct RelatorioImportacao {
+    /// Determina se a importação foi apenas uma simulação, sem efetivar o
+    /// cadastro dos clientes.
+    pub simulacao: bool,
+    /// Quantidade de clientes encontrados no arquivo.
+    pub total: usize,
+    /// Ids dos clientes cadastrados. Estará vazio em caso de simulação ou de
+    /// erros.
+    pub importados: Vec<i32>,
+    /// Erros encontrados no arquivo, por linha.
+    pub erros: Vec<ErroImportacao>,
+}
+
+/// Representa a situação de crédito de um cliente, calculada a partir de seu
+/// limite de crédito e dos títulos em aberto registrados para o mesmo.
+///
+/// A situação será retornada com uma estrutura similar à seguir, em JSON:
+///
+/// ```json
+/// {
+///   "cliente_id": 7,
+///   "limite_credito": "1500.00",
+///   "saldo_devedor": "1200.00",
+///   "credito_disponivel": "300.00",
+///   "valor_vencido": "0.00",
+///   "ativo": true,
+///   "bloqueado": false,
+///   "liberado_ate": null
+/// }
+/// ```
+#[derive(Serialize, Clone)]
+pub struct SituacaoCredito {
+    /// Id do cliente.
+    pub cliente_id: i32,
+    /// Limite de crédito do cliente. Ver [`Cliente::limite_credito`].
+    pub limite_credito: BigDecimal,
+    /// Soma dos valores em aberto de todos os títulos do cliente.
+    pub saldo_devedor: BigDecimal,
+    /// Crédito ainda disponível para o cliente. Nunca será negativo.
+    pub credito_disponivel: BigDecimal,
+    /// Soma dos valores em aberto dos títulos vencidos do cliente.
+    pub valor_vencido: BigDecimal,
+    /// Determina se o cliente está ativo. Ver [`Cliente::ativo`].
+    pub ativo: bool,
+    /// Determina se o cliente está bloqueado. Ver [`Cliente::bloqueado`].
+    pub bloqueado: bool,
+    /// Data de liberação do crédito do cliente. Ver [`Cliente::liberado_ate`].
+    pub liberado_ate: Option<NaiveDate>,
+}
+
+/// Representa os dados para alteração do limite de crédito de um cliente,
+/// recebidos como corpo de uma requisição.
+///
+/// ```json
+/// {
+///   "limite_credito": 1500.00
+/// }
+/// ```
+#[derive(Deserialize, Clone)]
+pub struct LimiteCreditoRecv {
+    /// Novo limite de crédito do cliente. Ver [`Cliente::limite_credito`].
+    pub limite_credito: BigDecimal,
+}
+
+/// Representa os dados para liberação manual do crédito de um cliente,
+/// recebidos como corpo de uma requisição.
+///
+/// ```json
+/// {
+///   "motivo": "Pagamento confirmado por telefone",
+///   "validade": "2021-09-30"
+/// }
+/// ```
+///
+/// Enquanto a liberação for válida, o cliente não será bloqueado
+/// automaticamente pelas regras de crédito.
+#[derive(Deserialize, Clone)]
+pub struct LiberacaoCreditoRecv {
+    /// Motivo da liberação. Será registrado no log.
+    pub motivo: String,
+    /// Data até a qual a liberação será válida, inclusive.
+    pub validade: NaiveDate,
+}
+
 impl NovoCliente {
     /// Cria uma estrutura de cadastro de cliente sem dados significativos.
     /// A estrutura não será criada automaticamente no banco de dados.
@@ -172,7 +352,12 @@ impl NovoCliente {
     /// - PJ: `false`;
     /// - Documento: vazio;
     /// - Ativo: `true`;
-    /// - Bloqueado: `false`.
+    /// - Bloqueado: `false`;
+    /// - Anonimizado: `false`;
+    /// - Hash do documento: `None`;
+    /// - Versão da chave de encriptação: `None`;
+    /// - Limite de crédito: 0;
+    /// - Data de liberação do crédito: `None`.
     pub fn new() -> Self {
         Self {
             tipo: 0,
@@ -181,6 +366,11 @@ impl NovoCliente {
             docto: String::new(),
             ativo: true,
             bloqueado: false,
+            anonimizado: false,
+            docto_hash: None,
+            chave_versao: None,
+            limite_credito: BigDecimal::default(),
+            liberado_ate: None,
         }
     }
 }
@@ -201,8 +391,10 @@ impl From<(&Cliente, Vec<Endereco>)> for ClienteRepr {
             nome: cl.nome.clone(),
             pj: cl.pj,
             docto: cl.docto.clone(),
-            ativo: true,
-            bloqueado: false,
+            ativo: cl.ativo,
+            bloqueado: cl.bloqueado,
+            anonimizado: cl.anonimizado,
+            limite_credito: cl.limite_credito.clone(),
             enderecos: enderec,
         }
     }
@@ -224,6 +416,11 @@ impl Into<(NovoCliente, Vec<EnderecoRecv>)> for ClienteRecv {
                 docto: self.docto.clone(),
                 ativo: true,
                 bloqueado: false,
+                anonimizado: false,
+                docto_hash: None,
+                chave_versao: None,
+                limite_credito: BigDecimal::default(),
+                liberado_ate: None,
             },
             self.enderecos,
         )
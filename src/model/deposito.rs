@@ -0,0 +1,75 @@
+// model/deposito.rs -- Uma parte de Minerva.rs
+// Copyright (C) 2021 Lucas S. Vieira
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with this program.  If not, see <https://www.gnu.org/licenses/>.
+
+//! Utilitários de modelagem de depósitos para banco de dados e regras de
+//! negócio.
+//!
+//! Este módulo define estruturas para o tráfego de dados de depósitos entre as
+//! partes respectivas do sistema. Um depósito é um local físico de
+//! armazenamento de produtos (ex: um armazém ou a loja), e cada produto possui
+//! uma posição de estoque por depósito.
+
+use super::schema::deposito;
+use serde::{Deserialize, Serialize};
+
+/// Id do depósito padrão, criado junto da tabela `deposito`.
+///
+/// Posições e movimentações de estoque que não informem um depósito serão
+/// consideradas como pertencentes a este depósito.
+pub const DEPOSITO_PADRAO: i32 = 1;
+
+/// Retorna o id do depósito padrão. Utilizado como valor padrão de campos de
+/// depósito em estruturas recebidas via requisição.
+pub fn deposito_padrao() -> i32 {
+    DEPOSITO_PADRAO
+}
+
+/// Representa um depósito, da forma como é armazenado na tabela `deposito`.
+#[derive(Queryable, Identifiable, Serialize, Debug, Clone)]
+#[table_name = "deposito"]
+pub struct Deposito {
+    /// Id do depósito no banco de dados.
+    pub id: i32,
+    /// Descrição textual do depósito (ex: `Loja`, `Armazém 2`).
+    pub descricao: String,
+    /// Informa se o depósito está ativo. Depósitos inativos não podem ter seus
+    /// estoques movimentados.
+    pub ativo: bool,
+}
+
+/// Representa os dados de inserção ou alteração de um depósito, recebidos como
+/// corpo de uma requisição, similar ao exemplo a seguir, em JSON:
+///
+/// ```json
+/// {
+///   "descricao": "Armazém 2",
+///   "ativo": true
+/// }
+/// ```
+///
+/// O campo `ativo` pode ser omitido. Nesse caso, um novo depósito será
+/// considerado ativo, e a alteração de um depósito não modificará o campo.
+#[derive(Insertable, AsChangeset, Deserialize, Clone, Debug)]
+#[table_name = "deposito"]
+pub struct NovoDeposito {
+    /// Descrição textual do depósito.
+    /// Ver [`Deposito::descricao`].
+    pub descricao: String,
+    /// Informa se o depósito está ativo.
+    /// Ver [`Deposito::ativo`].
+    #[serde(default)]
+    pub ativo: Option<bool>,
+}
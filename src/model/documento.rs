@@ -0,0 +1,196 @@
+// model/documento.rs -- Uma parte de Minerva.rs
+// Copyright (C) 2021 Lucas S. Vieira
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with this program.  If not, see <https://www.gnu.org/licenses/>.
+
+//! Utilitários de modelagem de documentos de estoque para banco de dados e
+//! regras de negócio.
+//!
+//! Este módulo define estruturas para o tráfego de dados de documentos de
+//! estoque entre as partes respectivas do sistema. Um documento de estoque
+//! (ex: uma nota fiscal de compra) possui um cabeçalho, com o número do
+//! documento, o fornecedor, a data e o frete total, e um conjunto de itens,
+//! registrados como entradas de estoque em uma única transação.
+
+use super::deposito::deposito_padrao;
+use super::enum_error::EnumError;
+use super::estoque::MovEstoque;
+use super::lote::LoteRecv;
+use super::schema::documento_estoque;
+use bigdecimal::BigDecimal;
+use chrono::{DateTime, NaiveDate};
+use diesel::sql_types::SmallInt;
+use diesel_enum::DbEnum;
+use num_derive::FromPrimitive;
+use serde::{Deserialize, Serialize};
+use serde_repr::{Deserialize_repr, Serialize_repr};
+
+/// Representa o critério de rateio do frete de um documento de estoque entre
+/// seus itens.
+#[derive(
+    FromPrimitive,
+    ToPrimitive,
+    Debug,
+    Clone,
+    Copy,
+    PartialEq,
+    Eq,
+    AsExpression,
+    FromSqlRow,
+    DbEnum,
+    Serialize_repr,
+    Deserialize_repr,
+    Default,
+)]
+#[sql_type = "SmallInt"]
+#[error_fn = "EnumError::nao_encontrado"]
+#[error_type = "EnumError"]
+#[repr(i16)]
+pub enum RateioFrete {
+    /// O frete é rateado proporcionalmente ao valor de cada item (quantidade
+    /// multiplicada pelo preço unitário).
+    #[default]
+    Valor = 0,
+    /// O frete é rateado proporcionalmente ao peso informado em cada item.
+    Peso = 1,
+}
+
+/// Representa o cabeçalho de um documento de estoque, da forma como é
+/// armazenado na tabela `documento_estoque`.
+#[derive(Queryable, Identifiable, Serialize, Debug, Clone)]
+#[table_name = "documento_estoque"]
+pub struct DocumentoEstoque {
+    /// Id do documento no banco de dados.
+    pub id: i32,
+    /// Número do documento (ex: número da nota fiscal). Também é o documento
+    /// das movimentações de estoque dos itens.
+    pub docto: String,
+    /// Id do fornecedor do documento, na tabela `cliente`, caso informado.
+    pub fornecedor_id: Option<i32>,
+    /// Data do documento (ex: data de emissão da nota fiscal).
+    pub data: NaiveDate,
+    /// Id do depósito movimentado pelos itens do documento, na tabela
+    /// `deposito`.
+    pub deposito_id: i32,
+    /// Frete total do documento, rateado entre os itens. Não pode ser
+    /// negativo. Admite até quatro casas decimais.
+    pub frete: BigDecimal,
+    /// Critério de rateio do frete entre os itens.
+    pub rateio_frete: RateioFrete,
+    /// Data e hora de registro do documento.
+    pub datahora: DateTime<chrono::Utc>,
+}
+
+/// Representa os dados de um novo documento de estoque a ser inserido no banco
+/// de dados. Ver [`DocumentoEstoque`].
+#[derive(Insertable, Clone)]
+#[table_name = "documento_estoque"]
+pub struct NovoDocumentoEstoque {
+    /// Ver [`DocumentoEstoque::docto`].
+    pub docto: String,
+    /// Ver [`DocumentoEstoque::fornecedor_id`].
+    pub fornecedor_id: Option<i32>,
+    /// Ver [`DocumentoEstoque::data`].
+    pub data: NaiveDate,
+    /// Ver [`DocumentoEstoque::deposito_id`].
+    pub deposito_id: i32,
+    /// Ver [`DocumentoEstoque::frete`].
+    pub frete: BigDecimal,
+    /// Ver [`DocumentoEstoque::rateio_frete`].
+    pub rateio_frete: RateioFrete,
+}
+
+/// Representa os dados de um item de um documento de estoque a serem
+/// recebidos como parte de um [`DocumentoEstoqueRecv`].
+///
+/// Cada item será registrado como uma entrada de estoque, e segue as mesmas
+/// regras de uma movimentação de estoque comum (veja
+/// [`MovEstoqueRecv`][`super::estoque::MovEstoqueRecv`]). O frete de cada item
+/// é calculado a partir do frete total do documento.
+#[derive(Deserialize, Clone)]
+pub struct ItemDocumentoRecv {
+    /// Id do produto do item.
+    pub produto_id: i32,
+    /// Quantidade do item. Deve ser positiva.
+    pub quantidade: BigDecimal,
+    /// Preço unitário do item.
+    pub preco_unitario: BigDecimal,
+    /// Unidade de medida da quantidade e do preço unitário. Opcional; caso
+    /// omitida, será considerada a unidade de saída do produto.
+    #[serde(default)]
+    pub unidade: Option<String>,
+    /// Peso total do item, utilizado no rateio do frete por peso. Obrigatório
+    /// caso o frete seja rateado por peso.
+    #[serde(default)]
+    pub peso: Option<BigDecimal>,
+    /// Lote do item, para produtos com controle de lotes.
+    #[serde(default)]
+    pub lote: Option<LoteRecv>,
+    /// Números de série das unidades do item, para produtos com controle de
+    /// números de série.
+    #[serde(default)]
+    pub numeros_serie: Vec<String>,
+}
+
+/// Representa os dados de um documento de estoque a serem recebidos como
+/// corpo de uma requisição, similar ao exemplo a seguir, em JSON:
+///
+/// ```json
+/// {
+///   "docto": "NF-000123",
+///   "fornecedor_id": 12,
+///   "data": "2026-10-18",
+///   "frete": 30.00,
+///   "rateio_frete": 0,
+///   "itens": [
+///     { "produto_id": 8, "quantidade": 10, "preco_unitario": 1.70 },
+///     { "produto_id": 9, "quantidade": 5, "preco_unitario": 4.00 }
+///   ]
+/// }
+/// ```
+///
+/// O fornecedor, o frete, o critério de rateio (por padrão, por valor) e o
+/// depósito (por padrão, o
+/// [depósito padrão][`super::deposito::DEPOSITO_PADRAO`]) podem ser omitidos.
+#[derive(Deserialize, Clone)]
+pub struct DocumentoEstoqueRecv {
+    /// Ver [`DocumentoEstoque::docto`].
+    pub docto: String,
+    /// Ver [`DocumentoEstoque::fornecedor_id`].
+    #[serde(default)]
+    pub fornecedor_id: Option<i32>,
+    /// Ver [`DocumentoEstoque::data`].
+    pub data: NaiveDate,
+    /// Ver [`DocumentoEstoque::frete`].
+    #[serde(default)]
+    pub frete: Option<BigDecimal>,
+    /// Ver [`DocumentoEstoque::rateio_frete`].
+    #[serde(default)]
+    pub rateio_frete: RateioFrete,
+    /// Ver [`DocumentoEstoque::deposito_id`].
+    #[serde(default = "deposito_padrao")]
+    pub deposito_id: i32,
+    /// Itens do documento. Deve haver ao menos um item.
+    pub itens: Vec<ItemDocumentoRecv>,
+}
+
+/// Representa um documento de estoque junto das movimentações de estoque de
+/// seus itens, para ser retornado como resposta de uma requisição.
+#[derive(Serialize, Clone)]
+pub struct DocumentoEstoqueRepr {
+    /// Cabeçalho do documento.
+    pub documento: DocumentoEstoque,
+    /// Movimentações de estoque dos itens do documento, na ordem dos itens.
+    pub itens: Vec<MovEstoque>,
+}
@@ -58,6 +58,12 @@ pub struct Endereco {
     pub uf: String,
     /// Cidade.
     pub cidade: String,
+    /// Versão da chave utilizada para encriptar o logradouro, o número, o
+    /// complemento e o bairro do endereço. Caso não seja informada, os dados
+    /// estarão armazenados em texto-plano.
+    /// Este campo não será mostrado na resposta a uma requisição.
+    #[serde(skip_serializing)]
+    pub chave_versao: Option<i16>,
 }
 
 /// Representa os dados de um endereço a serem inseridos na criação de um novo
@@ -82,6 +88,8 @@ pub struct NovoEndereco {
     pub uf: String,
     /// Cidade. Ver [`Endereco::cidade`].
     pub cidade: String,
+    /// Versão da chave de encriptação. Ver [`Endereco::chave_versao`].
+    pub chave_versao: Option<i16>,
 }
 
 /// Representa os dados de um endereço a ser cadastrado, recebidos como corpo de
@@ -125,7 +133,8 @@ impl NovoEndereco {
     /// - Complemento: `None`;
     /// - Bairro: vazio;
     /// - UF: vazio;
-    /// - cidade: vazio.
+    /// - cidade: vazio;
+    /// - Versão da chave de encriptação: `None`.
     pub fn new() -> Self {
         Self {
             cliente_id: -1,
@@ -136,6 +145,7 @@ impl NovoEndereco {
             bairro: String::new(),
             uf: String::new(),
             cidade: String::new(),
+            chave_versao: None,
         }
     }
 }
@@ -162,6 +172,7 @@ impl From<EnderecoRecv> for NovoEndereco {
             bairro: recv.bairro.clone(),
             uf: recv.uf.clone(),
             cidade: recv.cidade,
+            chave_versao: None,
         }
     }
 }
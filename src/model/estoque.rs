@@ -21,27 +21,109 @@
 //! movimentação de estoque entre as partes respectivas do sistema.
 
 use bigdecimal::BigDecimal;
-use chrono::DateTime;
+use chrono::{DateTime, NaiveDate};
 use serde::Deserialize;
 use serde::Serialize;
 use std::str::FromStr;
 
-use super::schema::{estoque, mov_estoque};
+use super::deposito::deposito_padrao;
+use super::lote::LoteRecv;
+use super::produto::MetodoCusto;
+use super::schema::{camada_custo, estoque, mov_estoque};
 
-/// Representa a posição de estoque de um produto, como armazenada no banco de
-/// dados, na tabela `estoque`.
-#[derive(Queryable, Insertable, Clone, Identifiable, Serialize, Deserialize)]
+/// Representa a posição de estoque de um produto em um depósito, como
+/// armazenada no banco de dados, na tabela `estoque`.
+///
+/// A posição de estoque armazena separadamente o preço de venda e o custo
+/// médio do produto. O preço de venda não é alterado pelas movimentações de
+/// estoque, enquanto o custo médio é recalculado a cada entrada.
+#[derive(Queryable, Insertable, Clone, Identifiable, Serialize)]
 #[table_name = "estoque"]
-#[primary_key(produto_id)]
+#[primary_key(produto_id, deposito_id)]
 pub struct Estoque {
     /// Id do produto associado à posição de estoque, na tabela `produto`.
     pub produto_id: i32,
     /// Quantidade do produto em estoque. Admite até três casas decimais.
     /// Valor máximo: `999999999.999`.
     pub quantidade: BigDecimal,
-    /// Preço unitário atual do produto. Não poderá ser menor ou igual a zero.
-    /// Admite até quatro casas decimais. Valor máximo: `999999999.9999`.
+    /// Preço de venda unitário atual do produto. Não poderá ser menor ou igual
+    /// a zero. Admite até quatro casas decimais. Valor máximo:
+    /// `999999999.9999`.
+    pub precounitario: BigDecimal,
+    /// Id do depósito da posição de estoque, na tabela `deposito`.
+    pub deposito_id: i32,
+    /// Custo médio unitário do produto no depósito, ponderado pelas
+    /// quantidades das entradas de estoque. Admite até quatro casas decimais.
+    /// Valor máximo: `999999999.9999`.
+    pub custo_medio: BigDecimal,
+}
+
+/// Representa os dados de um início de estoque a serem recebidos como corpo de
+/// uma requisição, similar ao exemplo a seguir, em JSON:
+///
+/// ```json
+/// {
+///   "produto_id": 8,
+///   "quantidade": 500.0,
+///   "precounitario": 1.50,
+///   "custo_unitario": 0.90,
+///   "deposito_id": 1
+/// }
+/// ```
+///
+/// O custo unitário pode ser omitido, e nesse caso será considerado igual ao
+/// preço unitário. O depósito também pode ser omitido, e nesse caso será
+/// considerado o [depósito padrão][`super::deposito::DEPOSITO_PADRAO`].
+///
+/// Produtos com controle de lotes que possuam quantidade inicial deverão
+/// informar também o lote desta quantidade, no campo `lote`
+/// (veja [`LoteRecv`]). Da mesma forma, produtos com controle de números de
+/// série deverão informar os números de série das unidades iniciais, no campo
+/// `numeros_serie`.
+#[derive(Deserialize, Clone)]
+pub struct InicioEstoqueRecv {
+    /// Id do produto. Ver [`Estoque::produto_id`].
+    pub produto_id: i32,
+    /// Quantidade inicial do produto. Ver [`Estoque::quantidade`].
+    pub quantidade: BigDecimal,
+    /// Preço de venda unitário do produto. Ver [`Estoque::precounitario`].
     pub precounitario: BigDecimal,
+    /// Custo unitário inicial do produto. Não poderá ser negativo. Opcional.
+    /// Ver [`Estoque::custo_medio`].
+    #[serde(default)]
+    pub custo_unitario: Option<BigDecimal>,
+    /// Id do depósito da posição de estoque. Ver [`Estoque::deposito_id`].
+    #[serde(default = "deposito_padrao")]
+    pub deposito_id: i32,
+    /// Lote da quantidade inicial, para produtos com controle de lotes.
+    #[serde(default)]
+    pub lote: Option<LoteRecv>,
+    /// Números de série das unidades iniciais, para produtos com controle de
+    /// números de série.
+    #[serde(default)]
+    pub numeros_serie: Vec<String>,
+}
+
+/// Representa os dados de alteração do preço de venda de um produto,
+/// recebidos como corpo de uma requisição, similar ao exemplo a seguir, em
+/// JSON:
+///
+/// ```json
+/// {
+///   "preco_unitario": 2.10,
+///   "deposito_id": 1
+/// }
+/// ```
+///
+/// Caso o depósito seja omitido, o preço de venda será alterado em todas as
+/// posições de estoque do produto.
+#[derive(Deserialize, Clone)]
+pub struct PrecoVendaRecv {
+    /// Novo preço de venda unitário do produto. Ver [`Estoque::precounitario`].
+    pub preco_unitario: BigDecimal,
+    /// Depósito cuja posição de estoque terá o preço alterado. Opcional.
+    #[serde(default)]
+    pub deposito_id: Option<i32>,
 }
 
 /// Representa uma movimentação no estoque de um produto, da forma como é
@@ -66,10 +148,43 @@ pub struct MovEstoque {
     pub preco_frete: BigDecimal,
     /// Data e hora de registro da movimentação de estoque no sistema.
     pub datahora: DateTime<chrono::Utc>,
-    /// Novo preço unitário atribuído ao produto pela movimentação. Não poderá
-    /// ser menor ou igual a zero. Admite até quatro casas decimais.
-    /// Valor máximo: `999999999.9999`.
+    /// Preço unitário informado no documento da movimentação (ex: preço de
+    /// compra em uma entrada, ou preço de venda em uma saída). Não altera o
+    /// preço de venda da posição de estoque. Não poderá ser menor ou igual a
+    /// zero. Admite até quatro casas decimais. Valor máximo: `999999999.9999`.
     pub preco_unitario: BigDecimal,
+    /// Id do depósito cujo estoque foi movimentado, na tabela `deposito`.
+    pub deposito_id: i32,
+    /// Custo unitário pelo qual a movimentação foi valorada. Em entradas,
+    /// corresponde ao preço unitário acrescido do frete rateado pela
+    /// quantidade; em saídas, corresponde ao custo médio do produto no momento
+    /// da saída, ou ao custo médio das camadas de custo consumidas, caso o
+    /// produto seja valorado por PEPS. Admite até quatro casas decimais.
+    pub custo_unitario: BigDecimal,
+    /// Id do cliente ao qual a movimentação se refere, na tabela `cliente`,
+    /// caso informado (ex: o cliente de uma venda).
+    pub cliente_id: Option<i32>,
+    /// Id da movimentação estornada por esta movimentação, na tabela
+    /// `mov_estoque`, caso esta seja uma movimentação de estorno. Cada
+    /// movimentação pode ser estornada uma única vez.
+    pub estorno_de_id: Option<i32>,
+    /// Id do documento de estoque ao qual a movimentação pertence, na tabela
+    /// `documento_estoque`, caso a movimentação tenha sido registrada como
+    /// item de um documento.
+    pub documento_estoque_id: Option<i32>,
+    /// Id da sessão de inventário cujo fechamento gerou a movimentação, na
+    /// tabela `inventario`, caso esta seja uma movimentação de ajuste de
+    /// inventário.
+    pub inventario_id: Option<i32>,
+    /// Informa se a movimentação é o início de estoque da posição, isto é, a
+    /// entrada da quantidade inicial registrada pelo início de estoque. Cada
+    /// posição de estoque possui um único início de estoque.
+    pub inicio: bool,
+    /// Id da movimentação de saída de uma transferência entre depósitos, na
+    /// tabela `mov_estoque`, caso esta seja a movimentação de entrada da mesma
+    /// transferência. As duas movimentações de uma transferência são sempre
+    /// estornadas em conjunto.
+    pub transferencia_de_id: Option<i32>,
 }
 
 /// Representa os dados de uma movimentação de estoque a ser inserida no banco
@@ -86,7 +201,7 @@ pub struct NovoMovEstoque {
     /// Quantidade de produto movimentada. Pode ser positivo ou negativo.
     /// Ver [`MovEstoque::quantidade`].
     pub quantidade: BigDecimal,
-    /// Novo preço unitário do produto.
+    /// Preço unitário do documento da movimentação.
     /// Ver [`MovEstoque::preco_unitario`].
     pub preco_unitario: BigDecimal,
     /// Preço do frete da quantidade de produto a ser movimentada, se aplicável.
@@ -95,6 +210,33 @@ pub struct NovoMovEstoque {
     /// Data e hora de registro da movimentação de estoque.
     /// Ver [`MovEstoque::datahora`].
     pub datahora: DateTime<chrono::Utc>,
+    /// Depósito cujo estoque será movimentado.
+    /// Ver [`MovEstoque::deposito_id`].
+    pub deposito_id: i32,
+    /// Custo unitário da movimentação.
+    /// Ver [`MovEstoque::custo_unitario`].
+    pub custo_unitario: BigDecimal,
+    /// Cliente ao qual a movimentação se refere, caso informado.
+    /// Ver [`MovEstoque::cliente_id`].
+    pub cliente_id: Option<i32>,
+    /// Movimentação estornada, caso esta seja uma movimentação de estorno.
+    /// Ver [`MovEstoque::estorno_de_id`].
+    pub estorno_de_id: Option<i32>,
+    /// Documento de estoque ao qual a movimentação pertence, caso seja um
+    /// item de um documento.
+    /// Ver [`MovEstoque::documento_estoque_id`].
+    pub documento_estoque_id: Option<i32>,
+    /// Sessão de inventário que gerou a movimentação, caso seja um ajuste de
+    /// inventário.
+    /// Ver [`MovEstoque::inventario_id`].
+    pub inventario_id: Option<i32>,
+    /// Indica se a movimentação é um início de estoque.
+    /// Ver [`MovEstoque::inicio`].
+    pub inicio: bool,
+    /// Saída da transferência, caso esta seja a entrada de uma transferência
+    /// entre depósitos.
+    /// Ver [`MovEstoque::transferencia_de_id`].
+    pub transferencia_de_id: Option<i32>,
 }
 
 /// Representa os dados de uma movimentação de estoque a serem recebidos como
@@ -109,12 +251,33 @@ pub struct NovoMovEstoque {
 ///   "docto": "00000000",
 ///   "quantidade": 200.0,
 ///   "preco_frete": 15.00,
-///   "preco_unitario": 1.70
+///   "preco_unitario": 1.70,
+///   "unidade": "CX",
+///   "deposito_id": 2
 /// }
 /// ```
 ///
-/// Note que, por mais que esteja presente no exemplo, o preço do frete poderá
-/// ser omitido ou declarado com valor `null`.
+/// Note que, por mais que estejam presentes no exemplo, o preço do frete e a
+/// unidade poderão ser omitidos ou declarados com valor `null`. O depósito
+/// também poderá ser omitido, e nesse caso será considerado o
+/// [depósito padrão][`super::deposito::DEPOSITO_PADRAO`].
+///
+/// Produtos com controle de lotes devem informar o lote nas entradas, no campo
+/// `lote` (veja [`LoteRecv`]). Nas saídas, o lote é opcional; caso não seja
+/// informado, serão consumidos os lotes não vencidos com a validade mais
+/// próxima (FEFO).
+///
+/// Produtos com controle de números de série devem informar, no campo
+/// `numeros_serie`, o número de série de cada unidade movimentada, de forma que
+/// a quantidade de números de série seja igual à quantidade movimentada. A
+/// movimentação também pode informar o cliente ao qual se refere, no campo
+/// `cliente_id`, para que as unidades vendidas possam ser rastreadas até o
+/// cliente.
+///
+/// Caso a unidade seja informada e seja diferente da unidade de saída do
+/// produto, a quantidade e o preço unitário serão considerados nesta unidade,
+/// e serão convertidos para a unidade de saída do produto através do fator de
+/// conversão cadastrado para o produto antes da movimentação.
 ///
 /// A data e a hora da movimentação de estoque são registradas no momento em que
 /// estes dados são tratados para a inserção no banco de dados, caso não haja
@@ -134,12 +297,98 @@ pub struct MovEstoqueRecv {
     /// Quantidade de produto movimentada. Pode ser positivo ou negativo.
     /// Ver [`MovEstoque::quantidade`].
     pub quantidade: BigDecimal,
-    /// Novo preço unitário do produto.
+    /// Preço unitário do documento da movimentação.
     /// Ver [`MovEstoque::preco_unitario`].
     pub preco_unitario: BigDecimal,
     /// Preço do frete da quantidade de produto a ser movimentada, se aplicável.
     /// Opcional. Ver [`MovEstoque::preco_frete`].
     pub preco_frete: Option<BigDecimal>,
+    /// Unidade de medida na qual a quantidade e o preço unitário foram
+    /// informados. Opcional; caso omitida, será considerada a unidade de saída
+    /// do produto.
+    #[serde(default)]
+    pub unidade: Option<String>,
+    /// Depósito cujo estoque será movimentado.
+    /// Ver [`MovEstoque::deposito_id`].
+    #[serde(default = "deposito_padrao")]
+    pub deposito_id: i32,
+    /// Lote movimentado, para produtos com controle de lotes. Obrigatório em
+    /// entradas; em saídas, caso omitido, serão consumidos os lotes que
+    /// vencem primeiro (FEFO).
+    #[serde(default)]
+    pub lote: Option<LoteRecv>,
+    /// Números de série das unidades movimentadas, para produtos com controle
+    /// de números de série.
+    #[serde(default)]
+    pub numeros_serie: Vec<String>,
+    /// Cliente ao qual a movimentação se refere. Opcional.
+    /// Ver [`MovEstoque::cliente_id`].
+    #[serde(default)]
+    pub cliente_id: Option<i32>,
+}
+
+/// Representa os dados de uma movimentação de estoque a serem recebidos como
+/// corpo de uma requisição, identificando o produto por um de seus códigos
+/// GTIN ao invés de seu id.
+///
+/// ```json
+/// {
+///   "gtin": "7891234567895",
+///   "docto": "00000000",
+///   "quantidade": 200.0,
+///   "preco_frete": 15.00,
+///   "preco_unitario": 1.70
+/// }
+/// ```
+///
+/// Os demais campos seguem as mesmas regras de [`MovEstoqueRecv`].
+#[derive(Deserialize, Clone)]
+pub struct MovEstoqueGtinRecv {
+    /// Código GTIN do produto cujo estoque será movimentado.
+    /// Ver [`ProdutoGtin::gtin`][`super::produto::ProdutoGtin::gtin`].
+    pub gtin: String,
+    /// Ver [`MovEstoqueRecv::docto`].
+    pub docto: String,
+    /// Ver [`MovEstoqueRecv::quantidade`].
+    pub quantidade: BigDecimal,
+    /// Ver [`MovEstoqueRecv::preco_unitario`].
+    pub preco_unitario: BigDecimal,
+    /// Ver [`MovEstoqueRecv::preco_frete`].
+    pub preco_frete: Option<BigDecimal>,
+    /// Ver [`MovEstoqueRecv::unidade`].
+    #[serde(default)]
+    pub unidade: Option<String>,
+    /// Ver [`MovEstoqueRecv::deposito_id`].
+    #[serde(default = "deposito_padrao")]
+    pub deposito_id: i32,
+    /// Ver [`MovEstoqueRecv::lote`].
+    #[serde(default)]
+    pub lote: Option<LoteRecv>,
+    /// Ver [`MovEstoqueRecv::numeros_serie`].
+    #[serde(default)]
+    pub numeros_serie: Vec<String>,
+    /// Ver [`MovEstoqueRecv::cliente_id`].
+    #[serde(default)]
+    pub cliente_id: Option<i32>,
+}
+
+impl MovEstoqueGtinRecv {
+    /// Converte os dados de movimentação por GTIN em dados de movimentação
+    /// comuns, para o produto de id informado.
+    pub fn para_produto(self, produto_id: i32) -> MovEstoqueRecv {
+        MovEstoqueRecv {
+            produto_id,
+            docto: self.docto,
+            quantidade: self.quantidade,
+            preco_unitario: self.preco_unitario,
+            preco_frete: self.preco_frete,
+            unidade: self.unidade,
+            deposito_id: self.deposito_id,
+            lote: self.lote,
+            numeros_serie: self.numeros_serie,
+            cliente_id: self.cliente_id,
+        }
+    }
 }
 
 /// Representa uma união entre os dados de um produto e os dados de estoque do
@@ -160,9 +409,40 @@ pub struct EstoqueRepr {
     /// Quantidade em estoque do produto.
     /// Ver [`Estoque::quantidade`].
     pub quantidade: BigDecimal,
-    /// Preço unitário do produto.
+    /// Quantidade do produto reservada por reservas ativas e não expiradas.
+    /// Ver [`Reserva`][`super::reserva::Reserva`].
+    pub reservado: BigDecimal,
+    /// Quantidade do produto disponível para novas saídas e reservas, isto é,
+    /// a quantidade em estoque que não está reservada.
+    pub disponivel: BigDecimal,
+    /// Preço de venda unitário do produto.
     /// Ver [`Estoque::precounitario`].
     pub preco_unitario: BigDecimal,
+    /// Custo médio unitário do produto.
+    /// Ver [`Estoque::custo_medio`].
+    pub custo_medio: BigDecimal,
+    /// Id do produto pai, caso o produto seja uma variante.
+    /// Ver [`Produto::produto_pai_id`][`super::produto::Produto::produto_pai_id`].
+    pub produto_pai_id: Option<i32>,
+    /// Id do depósito da posição de estoque, caso a posição seja de um único
+    /// depósito. Ver [`Estoque::deposito_id`].
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub deposito_id: Option<i32>,
+    /// Posições de estoque do produto em cada depósito, caso a posição de
+    /// estoque seja a consolidação das posições de todos os depósitos. Nesse
+    /// caso, a quantidade e as quantidades reservada e disponível serão as
+    /// somas das respectivas quantidades dos depósitos, e o preço unitário e o
+    /// custo médio serão as médias dos respectivos valores dos depósitos,
+    /// ponderadas pelas quantidades.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub depositos: Option<Vec<PosicaoDeposito>>,
+    /// Posições de estoque das variantes do produto, caso a posição de estoque
+    /// seja a consolidação das posições de suas variantes. Nesse caso, a
+    /// quantidade será a soma das quantidades das variantes, e o preço unitário
+    /// e o custo médio serão as médias dos respectivos valores das variantes,
+    /// ponderadas pelas quantidades.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub variantes: Option<Vec<EstoqueRepr>>,
 }
 
 impl From<MovEstoqueRecv> for NovoMovEstoque {
@@ -172,7 +452,8 @@ impl From<MovEstoqueRecv> for NovoMovEstoque {
     ///
     /// Caso nenhum preço de frete tenha sido informado, o valor será definido
     /// como zero. A data e hora do movimento serão atribuídas no momento desse
-    /// processo de conversão.
+    /// processo de conversão. O custo unitário será inicialmente igual ao
+    /// preço unitário, devendo ser valorado antes da inserção.
     fn from(recv: MovEstoqueRecv) -> Self {
         Self {
             produto_id: recv.produto_id,
@@ -184,6 +465,265 @@ impl From<MovEstoqueRecv> for NovoMovEstoque {
                 None => BigDecimal::from_str("0.0000").unwrap(),
             },
             datahora: chrono::offset::Utc::now(),
+            deposito_id: recv.deposito_id,
+            custo_unitario: recv.preco_unitario,
+            cliente_id: recv.cliente_id,
+            estorno_de_id: None,
+            documento_estoque_id: None,
+            inventario_id: None,
+            inicio: false,
+            transferencia_de_id: None,
         }
     }
 }
+
+/// Representa a posição de estoque de um produto em um depósito, como parte de
+/// uma posição de estoque consolidada. Ver [`EstoqueRepr::depositos`].
+#[derive(Serialize, Clone)]
+pub struct PosicaoDeposito {
+    /// Id do depósito. Ver [`Estoque::deposito_id`].
+    pub deposito_id: i32,
+    /// Quantidade do produto no depósito. Ver [`Estoque::quantidade`].
+    pub quantidade: BigDecimal,
+    /// Quantidade reservada do produto no depósito.
+    /// Ver [`EstoqueRepr::reservado`].
+    pub reservado: BigDecimal,
+    /// Quantidade disponível do produto no depósito.
+    /// Ver [`EstoqueRepr::disponivel`].
+    pub disponivel: BigDecimal,
+    /// Preço de venda unitário do produto no depósito.
+    /// Ver [`Estoque::precounitario`].
+    pub preco_unitario: BigDecimal,
+    /// Custo médio unitário do produto no depósito.
+    /// Ver [`Estoque::custo_medio`].
+    pub custo_medio: BigDecimal,
+}
+
+/// Representa a posição de estoque de um produto em uma data passada,
+/// reconstruída a partir das movimentações de estoque registradas até o fim
+/// daquela data, incluindo os inícios de estoque.
+///
+/// O valor da posição corresponde à soma das quantidades das movimentações
+/// multiplicadas por seus custos unitários, e o custo médio, ao valor dividido
+/// pela quantidade. Para mais informações, veja
+/// [`reconstroi_saldo`][`crate::bo::estoque::reconstroi_saldo`].
+#[derive(Serialize, Clone)]
+pub struct EstoqueNaDataRepr {
+    /// Id do produto na tabela `produto` no banco de dados.
+    pub id: i32,
+    /// Descrição do produto.
+    pub descricao: String,
+    /// Unidade de saída do produto.
+    pub unidsaida: String,
+    /// Data da posição de estoque.
+    pub data: NaiveDate,
+    /// Quantidade em estoque do produto ao fim da data.
+    pub quantidade: BigDecimal,
+    /// Custo médio unitário do produto ao fim da data.
+    pub custo_medio: BigDecimal,
+    /// Valor do estoque do produto ao fim da data. Arredondado para duas casas
+    /// decimais.
+    pub valor: BigDecimal,
+    /// Id do depósito da posição de estoque, caso a posição seja de um único
+    /// depósito.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub deposito_id: Option<i32>,
+    /// Posições do produto em cada depósito, caso a posição de estoque seja a
+    /// consolidação das posições de todos os depósitos.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub depositos: Option<Vec<SaldoDeposito>>,
+}
+
+/// Representa a posição de estoque de um produto em um depósito em uma data
+/// passada, como parte de uma posição consolidada.
+/// Ver [`EstoqueNaDataRepr::depositos`].
+#[derive(Serialize, Clone)]
+pub struct SaldoDeposito {
+    /// Id do depósito.
+    pub deposito_id: i32,
+    /// Quantidade do produto no depósito ao fim da data.
+    pub quantidade: BigDecimal,
+    /// Custo médio unitário do produto no depósito ao fim da data.
+    pub custo_medio: BigDecimal,
+    /// Valor do estoque do produto no depósito ao fim da data.
+    pub valor: BigDecimal,
+}
+
+/// Representa os dados de uma transferência de estoque entre depósitos, a
+/// serem recebidos como corpo de uma requisição, similar ao exemplo a seguir,
+/// em JSON:
+///
+/// ```json
+/// {
+///   "produto_id": 8,
+///   "docto": "TRANSF-0001",
+///   "quantidade": 20.0,
+///   "origem_id": 1,
+///   "destino_id": 2
+/// }
+/// ```
+///
+/// Uma transferência gera uma saída de estoque no depósito de origem e uma
+/// entrada de estoque no depósito de destino, ambas com o mesmo documento, e
+/// com o preço unitário e o custo do produto no depósito de origem. A
+/// quantidade deve ser positiva, e informada na unidade de saída do produto.
+///
+/// Para produtos com controle de lotes, pode ser informado o número do lote a
+/// ser transferido, no campo `lote`; caso omitido, serão transferidos os lotes
+/// não vencidos com a validade mais próxima (FEFO). Os lotes transferidos são
+/// criados no depósito de destino com o mesmo número e as mesmas datas.
+///
+/// Produtos com controle de números de série devem informar os números de
+/// série das unidades transferidas, no campo `numeros_serie`.
+#[derive(Deserialize, Clone)]
+pub struct TransferenciaRecv {
+    /// Id do produto a ser transferido, na tabela `produto`.
+    pub produto_id: i32,
+    /// Documento relacionado à transferência.
+    pub docto: String,
+    /// Quantidade de produto a ser transferida. Deve ser positiva.
+    pub quantidade: BigDecimal,
+    /// Id do depósito de origem, na tabela `deposito`.
+    pub origem_id: i32,
+    /// Id do depósito de destino, na tabela `deposito`.
+    pub destino_id: i32,
+    /// Número do lote a ser transferido, para produtos com controle de lotes.
+    /// Opcional.
+    #[serde(default)]
+    pub lote: Option<String>,
+    /// Números de série das unidades transferidas, para produtos com controle
+    /// de números de série.
+    #[serde(default)]
+    pub numeros_serie: Vec<String>,
+}
+
+/// Representa os dados do estorno de uma movimentação de estoque, a serem
+/// recebidos como corpo de uma requisição, similar ao exemplo a seguir, em
+/// JSON:
+///
+/// ```json
+/// {
+///   "motivo": "Quantidade digitada incorretamente"
+/// }
+/// ```
+///
+/// O motivo é obrigatório, e é registrado no log junto do estorno.
+#[derive(Deserialize, Clone)]
+pub struct EstornoRecv {
+    /// Motivo do estorno. Não pode estar em branco.
+    pub motivo: String,
+}
+
+/// Representa uma camada de custo de uma posição de estoque, como armazenada no
+/// banco de dados, na tabela `camada_custo`.
+///
+/// Cada entrada de estoque cria uma camada com a quantidade e o custo unitário
+/// da entrada, e cada saída consome as camadas mais antigas da posição de
+/// estoque, reduzindo sua quantidade restante. Desta forma, o estoque pode ser
+/// valorado por PEPS (primeiro a entrar, primeiro a sair), através das
+/// quantidades restantes e dos custos das camadas.
+#[derive(Queryable, Clone, Identifiable, Serialize)]
+#[table_name = "camada_custo"]
+pub struct CamadaCusto {
+    /// Id da camada de custo no banco de dados.
+    pub id: i32,
+    /// Id do produto da posição de estoque, na tabela `produto`.
+    pub produto_id: i32,
+    /// Id do depósito da posição de estoque, na tabela `deposito`.
+    pub deposito_id: i32,
+    /// Id da movimentação de entrada que originou a camada, na tabela
+    /// `mov_estoque`. Camadas originadas de inícios de estoque não possuem
+    /// movimentação.
+    pub mov_estoque_id: Option<i32>,
+    /// Quantidade que deu entrada na camada. Admite até três casas decimais.
+    pub quantidade: BigDecimal,
+    /// Quantidade ainda não consumida por saídas de estoque. Admite até três
+    /// casas decimais.
+    pub restante: BigDecimal,
+    /// Custo unitário da entrada que originou a camada. Admite até quatro
+    /// casas decimais.
+    pub custo_unitario: BigDecimal,
+    /// Data e hora de criação da camada.
+    pub datahora: DateTime<chrono::Utc>,
+}
+
+/// Representa os dados de uma camada de custo a ser inserida no banco de
+/// dados. Ver [`CamadaCusto`].
+#[derive(Insertable, Clone)]
+#[table_name = "camada_custo"]
+pub struct NovaCamadaCusto {
+    /// Ver [`CamadaCusto::produto_id`].
+    pub produto_id: i32,
+    /// Ver [`CamadaCusto::deposito_id`].
+    pub deposito_id: i32,
+    /// Ver [`CamadaCusto::mov_estoque_id`].
+    pub mov_estoque_id: Option<i32>,
+    /// Ver [`CamadaCusto::quantidade`].
+    pub quantidade: BigDecimal,
+    /// Quantidade restante da camada; na criação, igual à quantidade.
+    /// Ver [`CamadaCusto::restante`].
+    pub restante: BigDecimal,
+    /// Ver [`CamadaCusto::custo_unitario`].
+    pub custo_unitario: BigDecimal,
+}
+
+/// Representa a valoração do estoque de um produto, segundo cada método de
+/// valoração. Ver [`ValorizacaoEstoque`].
+#[derive(Serialize, Clone)]
+pub struct ValorizacaoProduto {
+    /// Id do produto na tabela `produto`.
+    pub produto_id: i32,
+    /// Descrição do produto.
+    pub descricao: String,
+    /// Método de valoração do produto.
+    /// Ver [`Produto::metodo_custo`][`super::produto::Produto::metodo_custo`].
+    pub metodo_custo: MetodoCusto,
+    /// Quantidade em estoque do produto.
+    pub quantidade: BigDecimal,
+    /// Valor do estoque pelo custo médio: a soma das quantidades de cada
+    /// posição multiplicadas por seus custos médios.
+    pub valor_medio: BigDecimal,
+    /// Valor do estoque por PEPS: a soma das quantidades restantes de cada
+    /// camada de custo multiplicadas por seus custos unitários.
+    pub valor_peps: BigDecimal,
+    /// Valor do estoque segundo o método de valoração do produto.
+    pub valor: BigDecimal,
+}
+
+/// Representa a valoração total do estoque, segundo cada método de valoração,
+/// retornada pela rota de valoração de estoque, similar ao exemplo a seguir,
+/// em JSON:
+///
+/// ```json
+/// {
+///   "deposito_id": 1,
+///   "valor_medio": 150.0,
+///   "valor_peps": 160.0,
+///   "valor": 160.0,
+///   "produtos": [
+///     {
+///       "produto_id": 8,
+///       "descricao": "Ovos brancos",
+///       "metodo_custo": 1,
+///       "quantidade": 50.0,
+///       "valor_medio": 150.0,
+///       "valor_peps": 160.0,
+///       "valor": 160.0
+///     }
+///   ]
+/// }
+/// ```
+#[derive(Serialize, Clone)]
+pub struct ValorizacaoEstoque {
+    /// Depósito valorado, ou `None`, caso a valoração considere todos os
+    /// depósitos.
+    pub deposito_id: Option<i32>,
+    /// Valor total do estoque pelo custo médio.
+    pub valor_medio: BigDecimal,
+    /// Valor total do estoque por PEPS.
+    pub valor_peps: BigDecimal,
+    /// Valor total do estoque segundo o método de valoração de cada produto.
+    pub valor: BigDecimal,
+    /// Valoração de cada produto em estoque.
+    pub produtos: Vec<ValorizacaoProduto>,
+}
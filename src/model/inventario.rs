@@ -0,0 +1,239 @@
+// model/inventario.rs -- Uma parte de Minerva.rs
+// Copyright (C) 2021 Lucas S. Vieira
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with this program.  If not, see <https://www.gnu.org/licenses/>.
+
+//! Utilitários de modelagem de sessões de inventário para banco de dados e
+//! regras de negócio.
+//!
+//! Este módulo define estruturas para o tráfego de dados de sessões de
+//! inventário (contagem física do estoque) entre as partes respectivas do
+//! sistema. Uma sessão de inventário registra as quantidades esperadas dos
+//! produtos de um depósito em sua abertura, recebe as contagens dos
+//! contadores, e gera movimentações de ajuste das diferenças em seu
+//! fechamento.
+
+use super::deposito::deposito_padrao;
+use super::enum_error::EnumError;
+use super::schema::{inventario, inventario_contagem, inventario_item};
+use bigdecimal::BigDecimal;
+use chrono::DateTime;
+use diesel::sql_types::SmallInt;
+use diesel_enum::DbEnum;
+use num_derive::FromPrimitive;
+use serde::{Deserialize, Serialize};
+use serde_repr::{Deserialize_repr, Serialize_repr};
+
+/// Representa a situação de uma sessão de inventário.
+#[derive(
+    FromPrimitive,
+    ToPrimitive,
+    Debug,
+    Clone,
+    Copy,
+    PartialEq,
+    Eq,
+    AsExpression,
+    FromSqlRow,
+    DbEnum,
+    Serialize_repr,
+    Deserialize_repr,
+)]
+#[sql_type = "SmallInt"]
+#[error_fn = "EnumError::nao_encontrado"]
+#[error_type = "EnumError"]
+#[repr(i16)]
+pub enum SituacaoInventario {
+    /// A sessão está aberta, e pode receber contagens.
+    Aberto = 0,
+    /// A sessão foi fechada, e suas diferenças foram ajustadas.
+    Fechado = 1,
+}
+
+/// Representa uma sessão de inventário, da forma como é armazenada na tabela
+/// `inventario`.
+#[derive(Queryable, Identifiable, Serialize, Debug, Clone)]
+#[table_name = "inventario"]
+pub struct Inventario {
+    /// Id da sessão de inventário no banco de dados.
+    pub id: i32,
+    /// Id do depósito inventariado, na tabela `deposito`.
+    pub deposito_id: i32,
+    /// Descrição da sessão de inventário (ex: "Inventário do 3º trimestre").
+    pub descricao: String,
+    /// Situação da sessão de inventário.
+    pub situacao: SituacaoInventario,
+    /// Data e hora de abertura da sessão, na qual as quantidades esperadas
+    /// foram registradas.
+    pub abertura: DateTime<chrono::Utc>,
+    /// Data e hora de fechamento da sessão, caso tenha sido fechada.
+    pub fechamento: Option<DateTime<chrono::Utc>>,
+}
+
+/// Representa os dados de uma nova sessão de inventário a ser inserida no
+/// banco de dados. Ver [`Inventario`].
+#[derive(Insertable, Clone)]
+#[table_name = "inventario"]
+pub struct NovoInventario {
+    /// Ver [`Inventario::deposito_id`].
+    pub deposito_id: i32,
+    /// Ver [`Inventario::descricao`].
+    pub descricao: String,
+}
+
+/// Representa um item de uma sessão de inventário, isto é, um produto a ser
+/// contado e sua quantidade esperada, da forma como é armazenado na tabela
+/// `inventario_item`.
+#[derive(Queryable, Insertable, Serialize, Debug, Clone)]
+#[table_name = "inventario_item"]
+pub struct ItemInventario {
+    /// Id da sessão de inventário, na tabela `inventario`.
+    pub inventario_id: i32,
+    /// Id do produto a ser contado, na tabela `produto`.
+    pub produto_id: i32,
+    /// Quantidade esperada do produto no depósito, na abertura da sessão.
+    pub esperado: BigDecimal,
+}
+
+/// Representa uma contagem de um item de uma sessão de inventário, da forma
+/// como é armazenada na tabela `inventario_contagem`.
+#[derive(Queryable, Identifiable, Serialize, Debug, Clone)]
+#[table_name = "inventario_contagem"]
+pub struct ContagemInventario {
+    /// Id da contagem no banco de dados.
+    pub id: i32,
+    /// Id da sessão de inventário, na tabela `inventario`.
+    pub inventario_id: i32,
+    /// Id do produto contado, na tabela `produto`.
+    pub produto_id: i32,
+    /// Identificação de quem realizou a contagem.
+    pub contador: String,
+    /// Quantidade contada, na unidade de saída do produto. Não pode ser
+    /// negativa. Admite até três casas decimais.
+    pub quantidade: BigDecimal,
+    /// Data e hora de registro da contagem.
+    pub datahora: DateTime<chrono::Utc>,
+}
+
+/// Representa os dados de uma nova contagem a ser inserida no banco de dados.
+/// Ver [`ContagemInventario`].
+#[derive(Insertable, Clone)]
+#[table_name = "inventario_contagem"]
+pub struct NovaContagemInventario {
+    /// Ver [`ContagemInventario::inventario_id`].
+    pub inventario_id: i32,
+    /// Ver [`ContagemInventario::produto_id`].
+    pub produto_id: i32,
+    /// Ver [`ContagemInventario::contador`].
+    pub contador: String,
+    /// Ver [`ContagemInventario::quantidade`].
+    pub quantidade: BigDecimal,
+}
+
+/// Representa os dados de abertura de uma sessão de inventário a serem
+/// recebidos como corpo de uma requisição, similar ao exemplo a seguir, em
+/// JSON:
+///
+/// ```json
+/// {
+///   "descricao": "Inventário do 3º trimestre",
+///   "deposito_id": 1
+/// }
+/// ```
+///
+/// O depósito pode ser omitido, e nesse caso será considerado o
+/// [depósito padrão][`super::deposito::DEPOSITO_PADRAO`].
+#[derive(Deserialize, Clone)]
+pub struct InventarioRecv {
+    /// Ver [`Inventario::descricao`].
+    pub descricao: String,
+    /// Ver [`Inventario::deposito_id`].
+    #[serde(default = "deposito_padrao")]
+    pub deposito_id: i32,
+}
+
+/// Representa os dados de uma contagem a serem recebidos como corpo de uma
+/// requisição, similar ao exemplo a seguir, em JSON:
+///
+/// ```json
+/// {
+///   "produto_id": 8,
+///   "quantidade": 42.0,
+///   "contador": "Equipe A"
+/// }
+/// ```
+///
+/// O contador pode ser omitido, e nesse caso será considerado o usuário que
+/// registrou a contagem.
+#[derive(Deserialize, Clone)]
+pub struct ContagemRecv {
+    /// Ver [`ContagemInventario::produto_id`].
+    pub produto_id: i32,
+    /// Ver [`ContagemInventario::quantidade`].
+    pub quantidade: BigDecimal,
+    /// Ver [`ContagemInventario::contador`].
+    #[serde(default)]
+    pub contador: Option<String>,
+}
+
+/// Representa a última contagem de um contador para um item de uma sessão de
+/// inventário.
+#[derive(Serialize, Debug, Clone, PartialEq)]
+pub struct ContagemContador {
+    /// Identificação do contador.
+    pub contador: String,
+    /// Última quantidade contada pelo contador.
+    pub quantidade: BigDecimal,
+}
+
+/// Representa a revisão de um item de uma sessão de inventário, com a
+/// quantidade esperada, as contagens e a diferença a ser ajustada.
+#[derive(Serialize, Debug, Clone, PartialEq)]
+pub struct DiferencaInventario {
+    /// Id do produto, na tabela `produto`.
+    pub produto_id: i32,
+    /// Quantidade esperada do produto na abertura da sessão.
+    pub esperado: BigDecimal,
+    /// Última contagem de cada contador do item.
+    pub contagens: Vec<ContagemContador>,
+    /// Quantidade contada, caso o item tenha sido contado e as últimas
+    /// contagens de todos os contadores coincidam.
+    pub contado: Option<BigDecimal>,
+    /// Diferença entre a quantidade contada e a esperada, caso haja quantidade
+    /// contada.
+    pub diferenca: Option<BigDecimal>,
+    /// Informa se as últimas contagens dos contadores divergem entre si, caso
+    /// em que o item deve ser recontado antes do fechamento da sessão.
+    pub divergente: bool,
+}
+
+/// Representa uma sessão de inventário junto da revisão de seus itens, para
+/// ser retornada como resposta de uma requisição.
+#[derive(Serialize, Clone)]
+pub struct RevisaoInventario {
+    /// Sessão de inventário.
+    pub inventario: Inventario,
+    /// Revisão de cada item da sessão, ordenada por produto.
+    pub itens: Vec<DiferencaInventario>,
+}
+
+/// Representa o resultado do fechamento de uma sessão de inventário, para ser
+/// retornado como resposta de uma requisição.
+#[derive(Serialize, Clone)]
+pub struct FechamentoInventario {
+    /// Sessão de inventário fechada.
+    pub inventario: Inventario,
+    /// Movimentações de ajuste geradas pelo fechamento, ordenadas por produto.
+    pub ajustes: Vec<super::estoque::MovEstoque>,
+}
@@ -0,0 +1,139 @@
+// model/lote.rs -- Uma parte de Minerva.rs
+// Copyright (C) 2021 Lucas S. Vieira
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with this program.  If not, see <https://www.gnu.org/licenses/>.
+
+//! Utilitários de modelagem de lotes de produtos para banco de dados e regras
+//! de negócio.
+//!
+//! Este módulo define estruturas para o tráfego de dados de lotes entre as
+//! partes respectivas do sistema. Produtos com controle de lotes possuem, em
+//! cada posição de estoque, um saldo por lote, movimentado junto das
+//! movimentações de estoque do produto.
+
+use super::schema::{lote, mov_estoque_lote};
+use bigdecimal::BigDecimal;
+use chrono::NaiveDate;
+use serde::{Deserialize, Serialize};
+
+/// Representa um lote de um produto em um depósito, da forma como é armazenado
+/// na tabela `lote`.
+#[derive(Queryable, Identifiable, Serialize, Debug, Clone)]
+#[table_name = "lote"]
+pub struct Lote {
+    /// Id do lote no banco de dados.
+    pub id: i32,
+    /// Id do produto do lote, na tabela `produto`.
+    pub produto_id: i32,
+    /// Id do depósito do lote, na tabela `deposito`.
+    pub deposito_id: i32,
+    /// Número do lote, conforme informado pelo fabricante. É único para cada
+    /// produto em cada depósito.
+    pub numero: String,
+    /// Data de fabricação do lote, caso informada.
+    pub fabricacao: Option<NaiveDate>,
+    /// Data de validade do lote, caso informada. Lotes com a data de validade
+    /// anterior à data atual estão vencidos.
+    pub validade: Option<NaiveDate>,
+    /// Saldo do lote no depósito. Admite até três casas decimais.
+    pub quantidade: BigDecimal,
+}
+
+/// Representa os dados de um novo lote a ser inserido no banco de dados.
+/// Ver [`Lote`].
+#[derive(Insertable, Clone)]
+#[table_name = "lote"]
+pub struct NovoLote {
+    /// Ver [`Lote::produto_id`].
+    pub produto_id: i32,
+    /// Ver [`Lote::deposito_id`].
+    pub deposito_id: i32,
+    /// Ver [`Lote::numero`].
+    pub numero: String,
+    /// Ver [`Lote::fabricacao`].
+    pub fabricacao: Option<NaiveDate>,
+    /// Ver [`Lote::validade`].
+    pub validade: Option<NaiveDate>,
+    /// Saldo inicial do lote. Ver [`Lote::quantidade`].
+    pub quantidade: BigDecimal,
+}
+
+/// Representa os dados de um lote informados em uma movimentação ou em um
+/// início de estoque, como no exemplo a seguir, em JSON:
+///
+/// ```json
+/// {
+///   "numero": "L2026-118",
+///   "fabricacao": "2026-10-01",
+///   "validade": "2026-12-31"
+/// }
+/// ```
+///
+/// As datas de fabricação e de validade são opcionais, e são consideradas
+/// apenas na primeira entrada do lote no depósito; em entradas posteriores,
+/// caso informadas, devem ser iguais às datas do lote. Em saídas, apenas o
+/// número do lote é considerado.
+#[derive(Deserialize, Clone)]
+pub struct LoteRecv {
+    /// Número do lote. Ver [`Lote::numero`].
+    pub numero: String,
+    /// Data de fabricação do lote. Ver [`Lote::fabricacao`].
+    #[serde(default)]
+    pub fabricacao: Option<NaiveDate>,
+    /// Data de validade do lote. Ver [`Lote::validade`].
+    #[serde(default)]
+    pub validade: Option<NaiveDate>,
+}
+
+/// Representa a quantidade de um lote movimentada por uma movimentação de
+/// estoque, como armazenada na tabela `mov_estoque_lote`.
+#[derive(Queryable, Insertable, Clone)]
+#[table_name = "mov_estoque_lote"]
+pub struct MovEstoqueLote {
+    /// Id da movimentação de estoque, na tabela `mov_estoque`.
+    pub mov_estoque_id: i32,
+    /// Id do lote movimentado, na tabela `lote`.
+    pub lote_id: i32,
+    /// Quantidade movimentada do lote. Positivo para entradas, negativo para
+    /// saídas.
+    pub quantidade: BigDecimal,
+}
+
+/// Representa um lote movimentado por uma movimentação de estoque, junto da
+/// quantidade movimentada do mesmo.
+#[derive(Serialize, Clone)]
+pub struct LoteMovimentado {
+    /// Id do lote. Ver [`Lote::id`].
+    pub lote_id: i32,
+    /// Número do lote. Ver [`Lote::numero`].
+    pub numero: String,
+    /// Data de fabricação do lote. Ver [`Lote::fabricacao`].
+    pub fabricacao: Option<NaiveDate>,
+    /// Data de validade do lote. Ver [`Lote::validade`].
+    pub validade: Option<NaiveDate>,
+    /// Quantidade movimentada do lote. Ver [`MovEstoqueLote::quantidade`].
+    pub quantidade: BigDecimal,
+}
+
+/// Representa um lote com saldo próximo do vencimento, ou já vencido, como
+/// retornado pelo relatório de lotes a vencer.
+#[derive(Serialize, Clone)]
+pub struct LoteVencimento {
+    /// Dados do lote.
+    #[serde(flatten)]
+    pub lote: Lote,
+    /// Quantidade de dias até o vencimento do lote. Negativo caso o lote já
+    /// esteja vencido.
+    pub dias_para_vencer: i64,
+}
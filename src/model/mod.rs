@@ -22,13 +22,24 @@
 //!
 //! [Diesel]: https://diesel.rs
 
+pub mod arquivo;
+pub mod categoria;
 pub mod cliente;
+pub mod deposito;
+pub mod documento;
 pub mod endereco;
 pub mod enum_error;
 pub mod estoque;
+pub mod inventario;
 pub mod logdb;
 pub mod login;
+pub mod lote;
+pub mod preco;
 pub mod produto;
+pub mod reserva;
 #[allow(missing_docs)]
 pub mod schema;
+pub mod serie;
+pub mod titulo;
+pub mod unidade;
 pub mod usuario;
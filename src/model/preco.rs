@@ -0,0 +1,162 @@
+// model/preco.rs -- Uma parte de Minerva.rs
+// Copyright (C) 2021 Lucas S. Vieira
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with this program.  If not, see <https://www.gnu.org/licenses/>.
+
+//! Utilitários de modelagem de tabelas de preço para banco de dados e regras
+//! de negócio.
+//!
+//! Este módulo define estruturas para o tráfego de dados de tabelas de preço
+//! e preços de produtos entre as partes respectivas do sistema. Uma tabela de
+//! preço pode ser geral, aplicável a um tipo de cliente (segmento), ou
+//! exclusiva de um cliente (contrato).
+
+use super::schema::{preco_produto, tabela_preco};
+use bigdecimal::BigDecimal;
+use chrono::NaiveDate;
+use serde::{Deserialize, Serialize};
+
+/// Representa uma tabela de preço, da forma como é armazenada na tabela
+/// `tabela_preco`.
+#[derive(Queryable, Identifiable, Serialize, Debug, Clone)]
+#[table_name = "tabela_preco"]
+pub struct TabelaPreco {
+    /// Id da tabela de preço no banco de dados.
+    pub id: i32,
+    /// Descrição textual da tabela de preço (ex: `Varejo`, `Atacado`).
+    pub descricao: String,
+    /// Id do cliente, na tabela `cliente`, caso a tabela de preço seja
+    /// exclusiva de um cliente.
+    pub cliente_id: Option<i32>,
+    /// Tipo de cliente ao qual a tabela de preço se aplica, caso a tabela seja
+    /// de um segmento de clientes. Ver
+    /// [`Cliente::tipo`][`super::cliente::Cliente::tipo`].
+    pub tipo_cliente: Option<i16>,
+}
+
+/// Representa os dados de inserção ou alteração de uma tabela de preço,
+/// recebidos como corpo de uma requisição, similar ao exemplo a seguir, em
+/// JSON:
+///
+/// ```json
+/// {
+///   "descricao": "Atacado",
+///   "tipo_cliente": 1
+/// }
+/// ```
+///
+/// O cliente e o tipo de cliente são opcionais e excludentes entre si. Caso
+/// nenhum dos dois seja informado, a tabela de preço será geral.
+#[derive(Insertable, AsChangeset, Deserialize, Clone, Debug)]
+#[table_name = "tabela_preco"]
+#[changeset_options(treat_none_as_null = "true")]
+pub struct NovaTabelaPreco {
+    /// Descrição textual da tabela de preço.
+    /// Ver [`TabelaPreco::descricao`].
+    pub descricao: String,
+    /// Id do cliente, para tabelas exclusivas.
+    /// Ver [`TabelaPreco::cliente_id`].
+    #[serde(default)]
+    pub cliente_id: Option<i32>,
+    /// Tipo de cliente, para tabelas de segmento.
+    /// Ver [`TabelaPreco::tipo_cliente`].
+    #[serde(default)]
+    pub tipo_cliente: Option<i16>,
+}
+
+/// Representa o preço de um produto em uma tabela de preço, da forma como é
+/// armazenado na tabela `preco_produto`.
+#[derive(Queryable, Identifiable, Serialize, Debug, Clone)]
+#[table_name = "preco_produto"]
+pub struct PrecoProduto {
+    /// Id do preço no banco de dados.
+    pub id: i32,
+    /// Id da tabela de preço, na tabela `tabela_preco`.
+    pub tabela_preco_id: i32,
+    /// Id do produto, na tabela `produto`.
+    pub produto_id: i32,
+    /// Preço unitário do produto, na unidade de saída do produto. Deve ser
+    /// maior que zero. Admite até quatro casas decimais.
+    pub preco: BigDecimal,
+    /// Data de início da vigência do preço, inclusive.
+    pub vigencia_inicio: NaiveDate,
+    /// Data de fim da vigência do preço, inclusive. Caso não seja informada,
+    /// o preço vigora indefinidamente.
+    pub vigencia_fim: Option<NaiveDate>,
+}
+
+/// Representa os dados de um preço de produto a serem recebidos como corpo de
+/// uma requisição, similar ao exemplo a seguir, em JSON:
+///
+/// ```json
+/// {
+///   "produto_id": 31,
+///   "preco": 4.99,
+///   "vigencia_inicio": "2021-10-01",
+///   "vigencia_fim": "2021-12-31"
+/// }
+/// ```
+///
+/// O fim da vigência é opcional. A tabela de preço será informada através da
+/// rota da requisição.
+#[derive(Deserialize, Clone, Debug)]
+pub struct PrecoProdutoRecv {
+    /// Id do produto. Ver [`PrecoProduto::produto_id`].
+    pub produto_id: i32,
+    /// Preço do produto. Ver [`PrecoProduto::preco`].
+    pub preco: BigDecimal,
+    /// Início da vigência. Ver [`PrecoProduto::vigencia_inicio`].
+    pub vigencia_inicio: NaiveDate,
+    /// Fim da vigência. Ver [`PrecoProduto::vigencia_fim`].
+    #[serde(default)]
+    pub vigencia_fim: Option<NaiveDate>,
+}
+
+/// Representa os dados de um preço de produto a ser inserido no banco de
+/// dados.
+#[derive(Insertable, Clone, Debug)]
+#[table_name = "preco_produto"]
+pub struct NovoPrecoProduto {
+    /// Id da tabela de preço. Ver [`PrecoProduto::tabela_preco_id`].
+    pub tabela_preco_id: i32,
+    /// Id do produto. Ver [`PrecoProduto::produto_id`].
+    pub produto_id: i32,
+    /// Preço do produto. Ver [`PrecoProduto::preco`].
+    pub preco: BigDecimal,
+    /// Início da vigência. Ver [`PrecoProduto::vigencia_inicio`].
+    pub vigencia_inicio: NaiveDate,
+    /// Fim da vigência. Ver [`PrecoProduto::vigencia_fim`].
+    pub vigencia_fim: Option<NaiveDate>,
+}
+
+/// Representa o preço efetivo de um produto para um cliente em uma data.
+///
+/// Caso nenhuma tabela de preço possua preço vigente para o produto, o preço
+/// efetivo será o preço unitário da posição de estoque do produto, e a tabela
+/// de preço não será informada.
+#[derive(Serialize, Debug, Clone)]
+pub struct PrecoEfetivo {
+    /// Id do produto.
+    pub produto_id: i32,
+    /// Id do cliente, caso informado.
+    pub cliente_id: Option<i32>,
+    /// Data de referência do preço.
+    pub data: NaiveDate,
+    /// Preço unitário efetivo do produto.
+    pub preco: BigDecimal,
+    /// Id da tabela de preço de onde o preço foi obtido, se houver.
+    pub tabela_preco_id: Option<i32>,
+    /// Descrição da tabela de preço de onde o preço foi obtido, se houver.
+    pub tabela_preco: Option<String>,
+}
@@ -22,8 +22,48 @@
 //! O model de produtos não compreende dados relacionados a controle de estoque.
 //! Para tanto, veja o módulo [`estoque`][`super::estoque`].
 
-use super::schema::produto;
-use serde::{Deserialize, Serialize};
+use super::enum_error::EnumError;
+use super::schema::{produto, produto_atributo, produto_componente, produto_gtin};
+use bigdecimal::BigDecimal;
+use diesel::sql_types::SmallInt;
+use diesel_enum::DbEnum;
+use num_derive::FromPrimitive;
+use serde::{Deserialize, Deserializer, Serialize};
+use serde_repr::{Deserialize_repr, Serialize_repr};
+use std::collections::BTreeMap;
+
+/// Representa o método de valoração do estoque de um produto.
+///
+/// Independente do método, o custo médio e as camadas de custo de cada
+/// posição de estoque são sempre mantidos; o método determina apenas por qual
+/// custo as saídas de estoque do produto serão valoradas.
+#[derive(
+    FromPrimitive,
+    ToPrimitive,
+    Debug,
+    Clone,
+    Copy,
+    PartialEq,
+    Eq,
+    AsExpression,
+    FromSqlRow,
+    DbEnum,
+    Serialize_repr,
+    Deserialize_repr,
+    Default,
+)]
+#[sql_type = "SmallInt"]
+#[error_fn = "EnumError::nao_encontrado"]
+#[error_type = "EnumError"]
+#[repr(i16)]
+pub enum MetodoCusto {
+    /// Custo médio ponderado móvel da posição de estoque.
+    #[default]
+    Medio = 0,
+    /// PEPS (primeiro a entrar, primeiro a sair): as saídas consomem as
+    /// camadas de custo mais antigas da posição de estoque.
+    Peps = 1,
+}
 
 /// Representa os dados de um produto armazenados no banco de dados.
 ///
@@ -39,6 +79,36 @@ pub struct Produto {
     /// Unidade de saída do produto. Deve ser armazenada em uppercase.
     /// Ex: UN/UNID (Unidade), KG (Quilograma), FD (Fardo), L (Litro), etc.
     pub unidsaida: String,
+    /// Determina se o produto está ativo. Produtos que possuam histórico de
+    /// estoque não podem ser removidos, e serão inativados em vez disso.
+    pub ativo: bool,
+    /// Código NCM (Nomenclatura Comum do Mercosul) do produto, com oito
+    /// dígitos e sem pontuação. Pode não existir em produtos cadastrados antes
+    /// da obrigatoriedade da classificação fiscal.
+    pub ncm: Option<String>,
+    /// Código CEST (Código Especificador da Substituição Tributária) do
+    /// produto, com sete dígitos e sem pontuação, quando aplicável.
+    pub cest: Option<String>,
+    /// Código de origem da mercadoria, entre 0 e 8, conforme a tabela de
+    /// origem da mercadoria do CST. Ex: 0 (Nacional), 1 (Estrangeira --
+    /// importação direta), 2 (Estrangeira -- adquirida no mercado interno).
+    pub origem: i16,
+    /// Id da categoria do produto, na tabela `categoria`, caso o produto
+    /// esteja categorizado.
+    pub categoria_id: Option<i32>,
+    /// Id do produto pai, na tabela `produto`, caso este produto seja uma
+    /// variante (ex: um tamanho ou cor específicos de uma peça de vestuário).
+    /// O estoque de produtos com variantes é controlado apenas nas variantes.
+    pub produto_pai_id: Option<i32>,
+    /// Método de valoração das saídas de estoque do produto.
+    pub metodo_custo: MetodoCusto,
+    /// Determina se o estoque do produto é controlado por lotes. Entradas de
+    /// estoque de produtos com controle de lotes devem informar o lote.
+    pub controla_lote: bool,
+    /// Determina se o estoque do produto é controlado por números de série.
+    /// Movimentações de estoque de produtos com controle de números de série
+    /// devem informar o número de série de cada unidade movimentada.
+    pub controla_serie: bool,
 }
 
 /// Representa os dados de inserção de um novo produto no banco de dados.
@@ -50,9 +120,22 @@ pub struct Produto {
 /// ```json
 /// {
 ///   "descricao": "Produto adicionado via requisição web",
-///   "unidsaida": "KG"
+///   "unidsaida": "KG",
+///   "ncm": "0409.00.00",
+///   "cest": "17.001.00",
+///   "origem": 0,
+///   "categoria_id": 3,
+///   "metodo_custo": 0,
+///   "controla_lote": false,
+///   "controla_serie": false
 /// }
 /// ```
+///
+/// O NCM é obrigatório, e pode ser informado com ou sem pontuação. O CEST, a
+/// origem, a categoria, o método de custo e os controles de lotes e de
+/// números de série são opcionais, sendo que a origem será considerada `0`
+/// (Nacional), o método de custo, `0` (custo médio), e o produto não terá
+/// controle de lotes nem de números de série, caso não sejam informados.
 #[derive(Debug, Insertable, Deserialize, Clone, Default)]
 #[table_name = "produto"]
 pub struct NovoProduto {
@@ -62,16 +145,270 @@ pub struct NovoProduto {
     /// Unidade de saída do produto.
     /// Ver [`Produto::unidsaida`].
     pub unidsaida: String,
+    /// Código NCM do produto.
+    /// Ver [`Produto::ncm`].
+    pub ncm: String,
+    /// Código CEST do produto, se aplicável.
+    /// Ver [`Produto::cest`].
+    #[serde(default)]
+    pub cest: Option<String>,
+    /// Código de origem da mercadoria.
+    /// Ver [`Produto::origem`].
+    #[serde(default)]
+    pub origem: i16,
+    /// Id da categoria do produto, se houver.
+    /// Ver [`Produto::categoria_id`].
+    #[serde(default)]
+    pub categoria_id: Option<i32>,
+    /// Id do produto pai, caso o produto seja uma variante. Não pode ser
+    /// informado no cadastro comum de produtos; variantes são cadastradas
+    /// através de [`VarianteRecv`].
+    /// Ver [`Produto::produto_pai_id`].
+    #[serde(skip)]
+    pub produto_pai_id: Option<i32>,
+    /// Método de valoração do estoque do produto.
+    /// Ver [`Produto::metodo_custo`].
+    #[serde(default)]
+    pub metodo_custo: MetodoCusto,
+    /// Determina se o estoque do produto é controlado por lotes.
+    /// Ver [`Produto::controla_lote`].
+    #[serde(default)]
+    pub controla_lote: bool,
+    /// Determina se o estoque do produto é controlado por números de série.
+    /// Ver [`Produto::controla_serie`].
+    #[serde(default)]
+    pub controla_serie: bool,
+}
+
+/// Representa os dados de alteração de um produto, recebidos como corpo de uma
+/// requisição PATCH na respectiva rota de produtos.
+///
+/// Todos os campos são opcionais, e apenas os campos informados serão
+/// alterados, como no exemplo a seguir, em JSON:
+///
+/// ```json
+/// {
+///   "descricao": "Nova descrição do produto"
+/// }
+/// ```
+///
+/// Um produto inativo pode ser reativado ao informar `"ativo": true`. O CEST
+/// de um produto pode ser removido ao informar `"cest": ""`, e sua categoria,
+/// ao informar `"categoria_id": null`.
+#[derive(Debug, AsChangeset, Deserialize, Clone, Default)]
+#[table_name = "produto"]
+pub struct AlteracaoProduto {
+    /// Nova descrição textual do produto.
+    /// Ver [`Produto::descricao`].
+    pub descricao: Option<String>,
+    /// Nova unidade de saída do produto.
+    /// Ver [`Produto::unidsaida`].
+    pub unidsaida: Option<String>,
+    /// Determina se o produto está ativo.
+    /// Ver [`Produto::ativo`].
+    pub ativo: Option<bool>,
+    /// Novo código NCM do produto.
+    /// Ver [`Produto::ncm`].
+    pub ncm: Option<String>,
+    /// Novo código CEST do produto. Caso seja `Some(None)`, o CEST do produto
+    /// será removido.
+    /// Ver [`Produto::cest`].
+    pub cest: Option<Option<String>>,
+    /// Novo código de origem da mercadoria.
+    /// Ver [`Produto::origem`].
+    pub origem: Option<i16>,
+    /// Nova categoria do produto. Caso seja `Some(None)` (ou `null`, quando
+    /// recebido via requisição), o produto deixará de possuir categoria.
+    /// Ver [`Produto::categoria_id`].
+    #[serde(default, deserialize_with = "deserializa_alteracao")]
+    pub categoria_id: Option<Option<i32>>,
+    /// Novo método de valoração do estoque do produto. A alteração vale para
+    /// as saídas posteriores; movimentações já realizadas não são revaloradas.
+    /// Ver [`Produto::metodo_custo`].
+    pub metodo_custo: Option<MetodoCusto>,
+    /// Determina se o estoque do produto é controlado por lotes. Não pode ser
+    /// alterado em produtos com estoque ou movimentações.
+    /// Ver [`Produto::controla_lote`].
+    pub controla_lote: Option<bool>,
+    /// Determina se o estoque do produto é controlado por números de série.
+    /// Não pode ser alterado em produtos com estoque ou movimentações.
+    /// Ver [`Produto::controla_serie`].
+    pub controla_serie: Option<bool>,
+}
+
+/// Deserializa um campo de alteração que admita remoção de valor, de forma que
+/// um campo omitido seja `None`, e um campo com valor `null` seja `Some(None)`.
+fn deserializa_alteracao<'de, D, T>(deserializer: D) -> Result<Option<Option<T>>, D::Error>
+where
+    D: Deserializer<'de>,
+    T: Deserialize<'de>,
+{
+    Option::deserialize(deserializer).map(Some)
+}
+
+/// Representa um código de barras GTIN (EAN/UPC) associado a um produto, da
+/// forma como é armazenado na tabela `produto_gtin`.
+///
+/// Um produto pode possuir vários códigos GTIN, mas um mesmo código não pode
+/// ser associado a mais de um produto.
+#[derive(Queryable, Insertable, Serialize, Debug, Clone)]
+#[table_name = "produto_gtin"]
+pub struct ProdutoGtin {
+    /// Código GTIN-8, GTIN-12, GTIN-13 ou GTIN-14, apenas com dígitos.
+    pub gtin: String,
+    /// Id do produto na tabela `produto`.
+    pub produto_id: i32,
+}
+
+/// Representa os dados de um código GTIN a ser associado a um produto,
+/// recebidos como corpo de uma requisição.
+///
+/// ```json
+/// {
+///   "gtin": "7891234567895"
+/// }
+/// ```
+///
+/// O produto será informado através da rota da requisição.
+#[derive(Deserialize, Clone)]
+pub struct GtinRecv {
+    /// Código GTIN. Ver [`ProdutoGtin::gtin`].
+    pub gtin: String,
+}
+
+/// Representa um atributo de uma variante de produto, da forma como é
+/// armazenado na tabela `produto_atributo`.
+///
+/// Ex: o atributo `tamanho` com valor `M`, ou o atributo `cor` com valor
+/// `Azul`.
+#[derive(Queryable, Insertable, Serialize, Debug, Clone)]
+#[table_name = "produto_atributo"]
+pub struct ProdutoAtributo {
+    /// Id da variante na tabela `produto`.
+    pub produto_id: i32,
+    /// Nome do atributo. Deve ser armazenado em lowercase.
+    pub atributo: String,
+    /// Valor do atributo para a variante.
+    pub valor: String,
+}
+
+/// Representa os dados de uma nova variante de um produto, recebidos como
+/// corpo de uma requisição, similar ao exemplo a seguir, em JSON:
+///
+/// ```json
+/// {
+///   "descricao": "Camiseta básica M azul",
+///   "atributos": {
+///     "tamanho": "M",
+///     "cor": "Azul"
+///   }
+/// }
+/// ```
+///
+/// A descrição é opcional; caso omitida, será formada pela descrição do
+/// produto pai seguida dos valores dos atributos. Os demais dados da variante
+/// (unidade de saída, dados fiscais e categoria) são copiados do produto pai,
+/// que será informado através da rota da requisição.
+#[derive(Deserialize, Clone, Debug)]
+pub struct VarianteRecv {
+    /// Descrição da variante. Opcional.
+    #[serde(default)]
+    pub descricao: Option<String>,
+    /// Atributos que diferenciam a variante das demais variantes do mesmo
+    /// produto. Ver [`ProdutoAtributo`].
+    pub atributos: BTreeMap<String, String>,
+}
+
+/// Representa os dados de uma variante de produto, junto de seus atributos.
+#[derive(Serialize, Debug, Clone)]
+pub struct VarianteRepr {
+    /// Dados de cadastro da variante.
+    #[serde(flatten)]
+    pub produto: Produto,
+    /// Atributos da variante. Ver [`ProdutoAtributo`].
+    pub atributos: BTreeMap<String, String>,
+}
+
+/// Representa um componente de um kit (composição de produtos), da forma como
+/// é armazenado na tabela `produto_componente`.
+///
+/// Um kit não possui estoque próprio: suas saídas de estoque movimentam o
+/// estoque de cada um de seus componentes.
+#[derive(Queryable, Insertable, Identifiable, Serialize, Debug, Clone)]
+#[table_name = "produto_componente"]
+#[primary_key(kit_id, componente_id)]
+pub struct ProdutoComponente {
+    /// Id do kit na tabela `produto`.
+    pub kit_id: i32,
+    /// Id do produto componente na tabela `produto`.
+    pub componente_id: i32,
+    /// Quantidade do componente, em sua unidade de saída, contida em uma
+    /// unidade do kit. Deve ser maior que zero. Admite até três casas
+    /// decimais.
+    pub quantidade: BigDecimal,
+}
+
+/// Representa os dados de um componente de kit, a serem recebidos como corpo
+/// de uma requisição.
+///
+/// ```json
+/// {
+///   "componente_id": 12,
+///   "quantidade": 2
+/// }
+/// ```
+///
+/// O kit será informado através da rota da requisição.
+#[derive(Deserialize, Clone)]
+pub struct ComponenteRecv {
+    /// Id do produto componente. Ver [`ProdutoComponente::componente_id`].
+    pub componente_id: i32,
+    /// Quantidade do componente. Ver [`ProdutoComponente::quantidade`].
+    pub quantidade: BigDecimal,
 }
 
 impl NovoProduto {
     /// Cria um novo produto com dados iniciais inválidos.
     ///
-    /// O produto retornado terá sua descrição e unidade de saída em branco.
+    /// O produto retornado terá sua descrição, unidade de saída e NCM em
+    /// branco, origem nacional, valoração pelo custo médio, e sem controle de
+    /// lotes.
     pub fn new() -> Self {
         Self {
             descricao: String::new(),
             unidsaida: String::new(),
+            ncm: String::new(),
+            cest: None,
+            origem: 0,
+            categoria_id: None,
+            produto_pai_id: None,
+            metodo_custo: MetodoCusto::Medio,
+            controla_lote: false,
+            controla_serie: false,
+        }
+    }
+}
+
+impl From<NovoProduto> for AlteracaoProduto {
+    /// Gera uma estrutura de alteração de produto a partir de dados completos
+    /// de um produto, como recebidos em uma requisição PUT. Todos os dados do
+    /// produto serão alterados, inclusive o CEST e a categoria, que serão
+    /// removidos caso não tenham sido informados, o método de custo, que
+    /// voltará a ser o custo médio, e os controles de lotes e de números de
+    /// série, que serão desativados.
+    /// O estado do produto será mantido.
+    fn from(dados: NovoProduto) -> Self {
+        Self {
+            descricao: Some(dados.descricao),
+            unidsaida: Some(dados.unidsaida),
+            ativo: None,
+            ncm: Some(dados.ncm),
+            cest: Some(dados.cest),
+            origem: Some(dados.origem),
+            categoria_id: Some(dados.categoria_id),
+            metodo_custo: Some(dados.metodo_custo),
+            controla_lote: Some(dados.controla_lote),
+            controla_serie: Some(dados.controla_serie),
         }
     }
 }
@@ -0,0 +1,174 @@
+// model/reserva.rs -- Uma parte de Minerva.rs
+// Copyright (C) 2021 Lucas S. Vieira
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with this program.  If not, see <https://www.gnu.org/licenses/>.
+
+//! Utilitários de modelagem de reservas de estoque para banco de dados e
+//! regras de negócio.
+//!
+//! Este módulo define estruturas para o tráfego de dados de reservas de
+//! estoque entre as partes respectivas do sistema. Uma reserva separa uma
+//! quantidade de uma posição de estoque para um documento de referência (ex:
+//! um pedido de venda), até sua data de validade, de forma que a quantidade
+//! reservada não possa ser movimentada por outros documentos.
+
+use super::deposito::deposito_padrao;
+use super::enum_error::EnumError;
+use super::lote::LoteRecv;
+use super::schema::reserva;
+use bigdecimal::BigDecimal;
+use chrono::{DateTime, NaiveDate};
+use diesel::sql_types::SmallInt;
+use diesel_enum::DbEnum;
+use num_derive::FromPrimitive;
+use serde::{Deserialize, Serialize};
+use serde_repr::{Deserialize_repr, Serialize_repr};
+
+/// Representa a situação de uma reserva de estoque.
+#[derive(
+    FromPrimitive,
+    ToPrimitive,
+    Debug,
+    Clone,
+    Copy,
+    PartialEq,
+    Eq,
+    AsExpression,
+    FromSqlRow,
+    DbEnum,
+    Serialize_repr,
+    Deserialize_repr,
+)]
+#[sql_type = "SmallInt"]
+#[error_fn = "EnumError::nao_encontrado"]
+#[error_type = "EnumError"]
+#[repr(i16)]
+pub enum SituacaoReserva {
+    /// A reserva está ativa, e reduz a quantidade disponível da posição de
+    /// estoque até sua data de validade.
+    Ativa = 0,
+    /// A reserva foi liberada, e não reduz mais a quantidade disponível.
+    Liberada = 1,
+    /// A reserva foi convertida em uma saída de estoque.
+    Convertida = 2,
+}
+
+/// Representa uma reserva de estoque, da forma como é armazenada na tabela
+/// `reserva`.
+#[derive(Queryable, Identifiable, Serialize, Debug, Clone)]
+#[table_name = "reserva"]
+pub struct Reserva {
+    /// Id da reserva no banco de dados.
+    pub id: i32,
+    /// Id do produto reservado, na tabela `produto`.
+    pub produto_id: i32,
+    /// Id do depósito da posição de estoque reservada, na tabela `deposito`.
+    pub deposito_id: i32,
+    /// Documento de referência da reserva (ex: número do pedido de venda).
+    pub docto: String,
+    /// Quantidade reservada, na unidade de saída do produto. Deve ser
+    /// positiva. Admite até três casas decimais.
+    pub quantidade: BigDecimal,
+    /// Data de validade da reserva. A reserva deixa de reduzir a quantidade
+    /// disponível após esta data, e não pode mais ser convertida.
+    pub validade: NaiveDate,
+    /// Situação da reserva.
+    pub situacao: SituacaoReserva,
+    /// Data e hora de registro da reserva.
+    pub datahora: DateTime<chrono::Utc>,
+    /// Id da movimentação de estoque gerada pela conversão da reserva, na
+    /// tabela `mov_estoque`, caso a reserva tenha sido convertida.
+    pub mov_estoque_id: Option<i32>,
+}
+
+/// Representa os dados de uma nova reserva a ser inserida no banco de dados.
+/// Ver [`Reserva`].
+#[derive(Insertable, Clone)]
+#[table_name = "reserva"]
+pub struct NovaReserva {
+    /// Ver [`Reserva::produto_id`].
+    pub produto_id: i32,
+    /// Ver [`Reserva::deposito_id`].
+    pub deposito_id: i32,
+    /// Ver [`Reserva::docto`].
+    pub docto: String,
+    /// Ver [`Reserva::quantidade`].
+    pub quantidade: BigDecimal,
+    /// Ver [`Reserva::validade`].
+    pub validade: NaiveDate,
+}
+
+/// Representa os dados de uma reserva de estoque a serem recebidos como corpo
+/// de uma requisição, similar ao exemplo a seguir, em JSON:
+///
+/// ```json
+/// {
+///   "produto_id": 8,
+///   "docto": "PED-0042",
+///   "quantidade": 3.0,
+///   "validade": "2026-10-25",
+///   "deposito_id": 1
+/// }
+/// ```
+///
+/// O depósito pode ser omitido, e nesse caso será considerado o
+/// [depósito padrão][`super::deposito::DEPOSITO_PADRAO`]. A quantidade deve
+/// estar disponível na posição de estoque no momento da reserva.
+#[derive(Deserialize, Clone)]
+pub struct ReservaRecv {
+    /// Ver [`Reserva::produto_id`].
+    pub produto_id: i32,
+    /// Ver [`Reserva::docto`].
+    pub docto: String,
+    /// Ver [`Reserva::quantidade`].
+    pub quantidade: BigDecimal,
+    /// Ver [`Reserva::validade`].
+    pub validade: NaiveDate,
+    /// Ver [`Reserva::deposito_id`].
+    #[serde(default = "deposito_padrao")]
+    pub deposito_id: i32,
+}
+
+/// Representa os dados da conversão de uma reserva em uma saída de estoque, a
+/// serem recebidos como corpo de uma requisição, similar ao exemplo a seguir,
+/// em JSON:
+///
+/// ```json
+/// {
+///   "preco_unitario": 2.10,
+///   "cliente_id": 12
+/// }
+/// ```
+///
+/// Todos os campos são opcionais. A saída terá o documento e a quantidade da
+/// reserva, e, caso o preço unitário seja omitido, o preço de venda atual da
+/// posição de estoque. O lote e os números de série seguem as mesmas regras
+/// de uma movimentação de estoque comum (veja
+/// [`MovEstoqueRecv`][`super::estoque::MovEstoqueRecv`]).
+#[derive(Deserialize, Clone, Default)]
+pub struct ConversaoReservaRecv {
+    /// Preço unitário da saída. Opcional.
+    #[serde(default)]
+    pub preco_unitario: Option<BigDecimal>,
+    /// Lote da saída, para produtos com controle de lotes. Opcional.
+    #[serde(default)]
+    pub lote: Option<LoteRecv>,
+    /// Números de série das unidades da saída, para produtos com controle de
+    /// números de série.
+    #[serde(default)]
+    pub numeros_serie: Vec<String>,
+    /// Cliente ao qual a saída se refere. Opcional.
+    #[serde(default)]
+    pub cliente_id: Option<i32>,
+}
@@ -1,3 +1,39 @@
+table! {
+    arquivo (id) {
+        id -> Int4,
+        chave -> Varchar,
+        nome -> Varchar,
+        content_type -> Varchar,
+        tamanho -> Int4,
+        sha256 -> Varchar,
+        produto_id -> Nullable<Int4>,
+        mov_estoque_id -> Nullable<Int4>,
+        usuario -> Varchar,
+        datahora -> Timestamptz,
+    }
+}
+
+table! {
+    camada_custo (id) {
+        id -> Int4,
+        produto_id -> Int4,
+        deposito_id -> Int4,
+        mov_estoque_id -> Nullable<Int4>,
+        quantidade -> Numeric,
+        restante -> Numeric,
+        custo_unitario -> Numeric,
+        datahora -> Timestamptz,
+    }
+}
+
+table! {
+    categoria (id) {
+        id -> Int4,
+        descricao -> Varchar,
+        categoria_pai_id -> Nullable<Int4>,
+    }
+}
+
 table! {
     cliente (id) {
         id -> Int4,
@@ -7,6 +43,32 @@ table! {
         docto -> Varchar,
         ativo -> Bool,
         bloqueado -> Bool,
+        anonimizado -> Bool,
+        docto_hash -> Nullable<Varchar>,
+        chave_versao -> Nullable<Int2>,
+        limite_credito -> Numeric,
+        liberado_ate -> Nullable<Date>,
+    }
+}
+
+table! {
+    deposito (id) {
+        id -> Int4,
+        descricao -> Varchar,
+        ativo -> Bool,
+    }
+}
+
+table! {
+    documento_estoque (id) {
+        id -> Int4,
+        docto -> Varchar,
+        fornecedor_id -> Nullable<Int4>,
+        data -> Date,
+        deposito_id -> Int4,
+        frete -> Numeric,
+        rateio_frete -> Int2,
+        datahora -> Timestamptz,
     }
 }
 
@@ -21,14 +83,47 @@ table! {
         bairro -> Varchar,
         uf -> Varchar,
         cidade -> Varchar,
+        chave_versao -> Nullable<Int2>,
     }
 }
 
 table! {
-    estoque (produto_id) {
+    estoque (produto_id, deposito_id) {
         produto_id -> Int4,
         quantidade -> Numeric,
         precounitario -> Numeric,
+        deposito_id -> Int4,
+        custo_medio -> Numeric,
+    }
+}
+
+table! {
+    inventario (id) {
+        id -> Int4,
+        deposito_id -> Int4,
+        descricao -> Varchar,
+        situacao -> Int2,
+        abertura -> Timestamptz,
+        fechamento -> Nullable<Timestamptz>,
+    }
+}
+
+table! {
+    inventario_contagem (id) {
+        id -> Int4,
+        inventario_id -> Int4,
+        produto_id -> Int4,
+        contador -> Varchar,
+        quantidade -> Numeric,
+        datahora -> Timestamptz,
+    }
+}
+
+table! {
+    inventario_item (inventario_id, produto_id) {
+        inventario_id -> Int4,
+        produto_id -> Int4,
+        esperado -> Numeric,
     }
 }
 
@@ -43,6 +138,18 @@ table! {
     }
 }
 
+table! {
+    lote (id) {
+        id -> Int4,
+        produto_id -> Int4,
+        deposito_id -> Int4,
+        numero -> Varchar,
+        fabricacao -> Nullable<Date>,
+        validade -> Nullable<Date>,
+        quantidade -> Numeric,
+    }
+}
+
 table! {
     mov_estoque (id) {
         id -> Int4,
@@ -52,6 +159,49 @@ table! {
         preco_frete -> Numeric,
         datahora -> Timestamptz,
         preco_unitario -> Numeric,
+        deposito_id -> Int4,
+        custo_unitario -> Numeric,
+        cliente_id -> Nullable<Int4>,
+        estorno_de_id -> Nullable<Int4>,
+        documento_estoque_id -> Nullable<Int4>,
+        inventario_id -> Nullable<Int4>,
+        inicio -> Bool,
+        transferencia_de_id -> Nullable<Int4>,
+    }
+}
+
+table! {
+    mov_estoque_lote (mov_estoque_id, lote_id) {
+        mov_estoque_id -> Int4,
+        lote_id -> Int4,
+        quantidade -> Numeric,
+    }
+}
+
+table! {
+    mov_estoque_serie (mov_estoque_id, numero_serie_id) {
+        mov_estoque_id -> Int4,
+        numero_serie_id -> Int4,
+    }
+}
+
+table! {
+    numero_serie (id) {
+        id -> Int4,
+        produto_id -> Int4,
+        numero -> Varchar,
+        deposito_id -> Nullable<Int4>,
+    }
+}
+
+table! {
+    preco_produto (id) {
+        id -> Int4,
+        tabela_preco_id -> Int4,
+        produto_id -> Int4,
+        preco -> Numeric,
+        vigencia_inicio -> Date,
+        vigencia_fim -> Nullable<Date>,
     }
 }
 
@@ -60,6 +210,89 @@ table! {
         id -> Int4,
         descricao -> Varchar,
         unidsaida -> Varchar,
+        ativo -> Bool,
+        ncm -> Nullable<Varchar>,
+        cest -> Nullable<Varchar>,
+        origem -> Int2,
+        categoria_id -> Nullable<Int4>,
+        produto_pai_id -> Nullable<Int4>,
+        metodo_custo -> Int2,
+        controla_lote -> Bool,
+        controla_serie -> Bool,
+    }
+}
+
+table! {
+    produto_atributo (produto_id, atributo) {
+        produto_id -> Int4,
+        atributo -> Varchar,
+        valor -> Varchar,
+    }
+}
+
+table! {
+    produto_componente (kit_id, componente_id) {
+        kit_id -> Int4,
+        componente_id -> Int4,
+        quantidade -> Numeric,
+    }
+}
+
+table! {
+    produto_gtin (gtin) {
+        gtin -> Varchar,
+        produto_id -> Int4,
+    }
+}
+
+table! {
+    produto_unidade (produto_id, unidade) {
+        produto_id -> Int4,
+        unidade -> Varchar,
+        fator -> Numeric,
+    }
+}
+
+table! {
+    reserva (id) {
+        id -> Int4,
+        produto_id -> Int4,
+        deposito_id -> Int4,
+        docto -> Varchar,
+        quantidade -> Numeric,
+        validade -> Date,
+        situacao -> Int2,
+        datahora -> Timestamptz,
+        mov_estoque_id -> Nullable<Int4>,
+    }
+}
+
+table! {
+    tabela_preco (id) {
+        id -> Int4,
+        descricao -> Varchar,
+        cliente_id -> Nullable<Int4>,
+        tipo_cliente -> Nullable<Int2>,
+    }
+}
+
+table! {
+    titulo (id) {
+        id -> Int4,
+        cliente_id -> Int4,
+        docto -> Varchar,
+        valor -> Numeric,
+        valor_pago -> Numeric,
+        vencimento -> Date,
+        datahora -> Timestamptz,
+    }
+}
+
+table! {
+    unidade_medida (codigo) {
+        codigo -> Varchar,
+        descricao -> Varchar,
+        casas_decimais -> Int2,
     }
 }
 
@@ -70,17 +303,69 @@ table! {
         nome -> Varchar,
         email -> Nullable<Varchar>,
         senha_hash -> Bytea,
+        perfil -> Int2,
     }
 }
 
+joinable!(arquivo -> mov_estoque (mov_estoque_id));
+joinable!(arquivo -> produto (produto_id));
+joinable!(camada_custo -> mov_estoque (mov_estoque_id));
+joinable!(documento_estoque -> cliente (fornecedor_id));
+joinable!(documento_estoque -> deposito (deposito_id));
 joinable!(endereco -> cliente (cliente_id));
+joinable!(estoque -> deposito (deposito_id));
+joinable!(inventario -> deposito (deposito_id));
+joinable!(inventario_item -> inventario (inventario_id));
+joinable!(inventario_item -> produto (produto_id));
+joinable!(mov_estoque -> cliente (cliente_id));
+joinable!(mov_estoque -> deposito (deposito_id));
+joinable!(mov_estoque -> documento_estoque (documento_estoque_id));
+joinable!(mov_estoque -> inventario (inventario_id));
+joinable!(mov_estoque_lote -> lote (lote_id));
+joinable!(mov_estoque_lote -> mov_estoque (mov_estoque_id));
+joinable!(mov_estoque_serie -> mov_estoque (mov_estoque_id));
+joinable!(mov_estoque_serie -> numero_serie (numero_serie_id));
+joinable!(numero_serie -> deposito (deposito_id));
+joinable!(numero_serie -> produto (produto_id));
+joinable!(preco_produto -> produto (produto_id));
+joinable!(preco_produto -> tabela_preco (tabela_preco_id));
+joinable!(produto -> categoria (categoria_id));
+joinable!(produto -> unidade_medida (unidsaida));
+joinable!(produto_atributo -> produto (produto_id));
+joinable!(produto_gtin -> produto (produto_id));
+joinable!(produto_unidade -> produto (produto_id));
+joinable!(produto_unidade -> unidade_medida (unidade));
+joinable!(reserva -> mov_estoque (mov_estoque_id));
+joinable!(tabela_preco -> cliente (cliente_id));
+joinable!(titulo -> cliente (cliente_id));
 
 allow_tables_to_appear_in_same_query!(
+    arquivo,
+    camada_custo,
+    categoria,
     cliente,
+    deposito,
+    documento_estoque,
     endereco,
     estoque,
+    inventario,
+    inventario_contagem,
+    inventario_item,
     logdb,
+    lote,
     mov_estoque,
+    mov_estoque_lote,
+    mov_estoque_serie,
+    numero_serie,
+    preco_produto,
     produto,
+    produto_atributo,
+    produto_componente,
+    produto_gtin,
+    produto_unidade,
+    reserva,
+    tabela_preco,
+    titulo,
+    unidade_medida,
     usuario,
 );
@@ -0,0 +1,81 @@
+// model/serie.rs -- Uma parte de Minerva.rs
+// Copyright (C) 2021 Lucas S. Vieira
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with this program.  If not, see <https://www.gnu.org/licenses/>.
+
+//! Utilitários de modelagem de números de série de produtos para banco de
+//! dados e regras de negócio.
+//!
+//! Este módulo define estruturas para o tráfego de dados de números de série
+//! entre as partes respectivas do sistema. Produtos com controle de números de
+//! série possuem um número de série para cada unidade, que deve ser informado
+//! em cada movimentação de estoque da unidade.
+
+use super::estoque::MovEstoque;
+use super::schema::{mov_estoque_serie, numero_serie};
+use serde::Serialize;
+
+/// Representa um número de série de um produto, da forma como é armazenado na
+/// tabela `numero_serie`.
+#[derive(Queryable, Identifiable, Serialize, Debug, Clone)]
+#[table_name = "numero_serie"]
+pub struct NumeroSerie {
+    /// Id do número de série no banco de dados.
+    pub id: i32,
+    /// Id do produto do número de série, na tabela `produto`.
+    pub produto_id: i32,
+    /// Número de série da unidade, conforme informado pelo fabricante. É
+    /// único para cada produto.
+    pub numero: String,
+    /// Id do depósito em que a unidade se encontra, na tabela `deposito`.
+    /// Unidades que não estejam em estoque (ex: unidades vendidas) não possuem
+    /// depósito.
+    pub deposito_id: Option<i32>,
+}
+
+/// Representa os dados de um novo número de série a ser inserido no banco de
+/// dados. Ver [`NumeroSerie`].
+#[derive(Insertable, Clone)]
+#[table_name = "numero_serie"]
+pub struct NovoNumeroSerie {
+    /// Ver [`NumeroSerie::produto_id`].
+    pub produto_id: i32,
+    /// Ver [`NumeroSerie::numero`].
+    pub numero: String,
+    /// Ver [`NumeroSerie::deposito_id`].
+    pub deposito_id: Option<i32>,
+}
+
+/// Representa um número de série movimentado por uma movimentação de estoque,
+/// como armazenado na tabela `mov_estoque_serie`.
+#[derive(Queryable, Insertable, Clone)]
+#[table_name = "mov_estoque_serie"]
+pub struct MovEstoqueSerie {
+    /// Id da movimentação de estoque, na tabela `mov_estoque`.
+    pub mov_estoque_id: i32,
+    /// Id do número de série movimentado, na tabela `numero_serie`.
+    pub numero_serie_id: i32,
+}
+
+/// Representa o histórico de um número de série: o número de série e todas as
+/// movimentações de estoque da unidade, em ordem cronológica.
+#[derive(Serialize, Clone)]
+pub struct HistoricoSerie {
+    /// Dados do número de série.
+    pub numero_serie: NumeroSerie,
+    /// Movimentações de estoque da unidade, da mais antiga para a mais
+    /// recente. Movimentações referentes a um cliente (ex: vendas) informam o
+    /// cliente em [`MovEstoque::cliente_id`].
+    pub movimentos: Vec<MovEstoque>,
+}
@@ -0,0 +1,134 @@
+// model/titulo.rs -- Uma parte de Minerva.rs
+// Copyright (C) 2021 Lucas S. Vieira
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with this program.  If not, see <https://www.gnu.org/licenses/>.
+
+//! Utilitários de modelagem de títulos a receber de clientes para banco de
+//! dados e regras de negócio.
+//!
+//! Este módulo define estruturas para o tráfego de dados de títulos entre as
+//! partes respectivas do sistema. Os títulos em aberto de um cliente compõem o
+//! seu saldo devedor, utilizado nas regras de crédito do mesmo.
+
+use super::schema::titulo;
+use bigdecimal::BigDecimal;
+use chrono::{DateTime, NaiveDate};
+use serde::{Deserialize, Serialize};
+
+/// Representa um título a receber de um cliente, da forma como é armazenado
+/// na tabela `titulo`.
+#[derive(Queryable, Clone, Identifiable, Serialize)]
+#[table_name = "titulo"]
+pub struct Titulo {
+    /// Id do título no banco de dados.
+    pub id: i32,
+    /// Id do cliente devedor, na tabela `cliente`.
+    pub cliente_id: i32,
+    /// Documento relacionado ao título (ex: número da nota fiscal ou da
+    /// duplicata).
+    pub docto: String,
+    /// Valor do título. Deverá ser maior que zero. Admite até duas casas
+    /// decimais. Valor máximo: `99999999999.99`.
+    pub valor: BigDecimal,
+    /// Valor já pago do título. Não poderá ser maior que o valor do título.
+    /// Admite até duas casas decimais.
+    pub valor_pago: BigDecimal,
+    /// Data de vencimento do título. Um título em aberto após esta data
+    /// será considerado vencido.
+    pub vencimento: NaiveDate,
+    /// Data e hora de registro do título no sistema.
+    pub datahora: DateTime<chrono::Utc>,
+}
+
+/// Representa os dados de um título a ser inserido no banco de dados.
+#[derive(Insertable, Clone)]
+#[table_name = "titulo"]
+pub struct NovoTitulo {
+    /// Id do cliente devedor. Ver [`Titulo::cliente_id`].
+    pub cliente_id: i32,
+    /// Documento relacionado ao título. Ver [`Titulo::docto`].
+    pub docto: String,
+    /// Valor do título. Ver [`Titulo::valor`].
+    pub valor: BigDecimal,
+    /// Data de vencimento do título. Ver [`Titulo::vencimento`].
+    pub vencimento: NaiveDate,
+    /// Data e hora de registro do título. Ver [`Titulo::datahora`].
+    pub datahora: DateTime<chrono::Utc>,
+}
+
+/// Representa os dados de um título a serem recebidos como corpo de uma
+/// requisição.
+///
+/// Um título a ser registrado deverá ser recebido com uma estrutura similar à
+/// seguir, em JSON:
+///
+/// ```json
+/// {
+///   "docto": "00000000",
+///   "valor": 350.00,
+///   "vencimento": "2021-09-30"
+/// }
+/// ```
+///
+/// O cliente devedor será informado através da rota da requisição. A data e a
+/// hora de registro do título serão as do momento em que a requisição for
+/// tratada.
+#[derive(Deserialize, Clone)]
+pub struct TituloRecv {
+    /// Documento relacionado ao título. Ver [`Titulo::docto`].
+    pub docto: String,
+    /// Valor do título. Ver [`Titulo::valor`].
+    pub valor: BigDecimal,
+    /// Data de vencimento do título. Ver [`Titulo::vencimento`].
+    pub vencimento: NaiveDate,
+}
+
+/// Representa os dados de um pagamento de título a serem recebidos como corpo
+/// de uma requisição.
+///
+/// ```json
+/// {
+///   "valor": 100.00
+/// }
+/// ```
+#[derive(Deserialize, Clone)]
+pub struct PagamentoTituloRecv {
+    /// Valor pago. Deverá ser maior que zero e não poderá ser maior que o
+    /// valor em aberto do título.
+    pub valor: BigDecimal,
+}
+
+impl Titulo {
+    /// Retorna o valor ainda em aberto do título.
+    pub fn valor_aberto(&self) -> BigDecimal {
+        self.valor.clone() - self.valor_pago.clone()
+    }
+}
+
+impl From<(i32, TituloRecv)> for NovoTitulo {
+    /// Gera uma estrutura de cadastro de um novo título a partir do id do
+    /// cliente devedor e dos dados do título recebidos via requisição.
+    ///
+    /// A data e hora de registro do título serão atribuídas no momento desse
+    /// processo de conversão.
+    fn from((cliente_id, recv): (i32, TituloRecv)) -> Self {
+        Self {
+            cliente_id,
+            docto: recv.docto,
+            valor: recv.valor,
+            vencimento: recv.vencimento,
+            datahora: chrono::offset::Utc::now(),
+        }
+    }
+}
@@ -0,0 +1,92 @@
+// model/unidade.rs -- Uma parte de Minerva.rs
+// Copyright (C) 2021 Lucas S. Vieira
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with this program.  If not, see <https://www.gnu.org/licenses/>.
+
+//! Utilitários de modelagem de unidades de medida para banco de dados e regras
+//! de negócio.
+//!
+//! Este módulo define estruturas para o tráfego de dados de unidades de medida
+//! e de fatores de conversão entre unidades de medida de produtos, entre as
+//! partes respectivas do sistema.
+
+use super::schema::{produto_unidade, unidade_medida};
+use bigdecimal::BigDecimal;
+use serde::{Deserialize, Serialize};
+
+/// Representa uma unidade de medida cadastrada no sistema, da forma como é
+/// armazenada na tabela `unidade_medida`.
+///
+/// Uma unidade de medida também pode ser recebida como corpo de uma requisição
+/// para seu cadastro, com uma estrutura similar à seguir, em JSON:
+///
+/// ```json
+/// {
+///   "codigo": "CX",
+///   "descricao": "Caixa",
+///   "casas_decimais": 0
+/// }
+/// ```
+#[derive(Queryable, Insertable, Identifiable, Serialize, Deserialize, Clone, Debug)]
+#[table_name = "unidade_medida"]
+#[primary_key(codigo)]
+pub struct UnidadeMedida {
+    /// Código da unidade de medida. Deve ser armazenado em uppercase.
+    /// Ex: UN (Unidade), KG (Quilograma), CX (Caixa), L (Litro), etc.
+    pub codigo: String,
+    /// Descrição textual da unidade de medida.
+    pub descricao: String,
+    /// Quantidade de casas decimais permitidas em quantidades informadas nesta
+    /// unidade de medida. Deve estar entre 0 e 3.
+    pub casas_decimais: i16,
+}
+
+/// Representa um fator de conversão entre uma unidade de medida qualquer e a
+/// unidade de saída de um produto, da forma como é armazenado na tabela
+/// `produto_unidade`.
+///
+/// Por exemplo, um produto com unidade de saída `UN` que é comprado em caixas
+/// com doze unidades terá um fator de conversão `12` para a unidade `CX`.
+#[derive(Queryable, Insertable, Identifiable, Serialize, Clone, Debug)]
+#[table_name = "produto_unidade"]
+#[primary_key(produto_id, unidade)]
+pub struct ProdutoUnidade {
+    /// Id do produto na tabela `produto`.
+    pub produto_id: i32,
+    /// Código da unidade de medida, na tabela `unidade_medida`.
+    pub unidade: String,
+    /// Quantidade da unidade de saída do produto equivalente a uma quantidade
+    /// da unidade de medida. Deve ser maior que zero. Admite até seis casas
+    /// decimais.
+    pub fator: BigDecimal,
+}
+
+/// Representa os dados de um fator de conversão de unidade de um produto, a
+/// serem recebidos como corpo de uma requisição.
+///
+/// ```json
+/// {
+///   "unidade": "CX",
+///   "fator": 12
+/// }
+/// ```
+///
+/// O produto será informado através da rota da requisição.
+#[derive(Deserialize, Clone)]
+pub struct ProdutoUnidadeRecv {
+    /// Código da unidade de medida. Ver [`ProdutoUnidade::unidade`].
+    pub unidade: String,
+    /// Fator de conversão. Ver [`ProdutoUnidade::fator`].
+    pub fator: BigDecimal,
+}
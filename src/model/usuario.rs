@@ -24,9 +24,75 @@
 //! Para informações relacionadas a login e autenticação, veja o módulo
 //! [`login`][`super::login`] e o módulo [`auth`][`crate::auth`].
 
+use super::enum_error::EnumError;
 use crate::bo::usuarios;
 use crate::model::schema::usuario;
+use diesel::sql_types::SmallInt;
+use diesel_enum::DbEnum;
+use num_derive::FromPrimitive;
 use serde::{Deserialize, Serialize};
+use serde_repr::{Deserialize_repr, Serialize_repr};
+
+/// Representa o perfil de acesso de um usuário do sistema.
+///
+/// O perfil determina quais operações sensíveis o usuário poderá realizar,
+/// como operações que envolvam dados pessoais de clientes.
+#[derive(
+    FromPrimitive,
+    ToPrimitive,
+    Debug,
+    Clone,
+    Copy,
+    PartialEq,
+    Eq,
+    AsExpression,
+    FromSqlRow,
+    DbEnum,
+    Serialize_repr,
+    Deserialize_repr,
+    Default,
+)]
+#[sql_type = "SmallInt"]
+#[error_fn = "EnumError::nao_encontrado"]
+#[error_type = "EnumError"]
+#[repr(i16)]
+pub enum PerfilUsuario {
+    /// Usuário comum, sem acesso a operações privilegiadas.
+    #[default]
+    Comum = 0,
+    /// Administrador do sistema, com acesso a todas as operações.
+    Administrador = 1,
+    /// Usuário da equipe de vendas.
+    Vendas = 2,
+    /// Usuário da equipe financeira.
+    Financeiro = 3,
+}
+
+impl PerfilUsuario {
+    /// Informa se o perfil permite a realização de operações privilegiadas.
+    pub fn is_privilegiado(&self) -> bool {
+        *self == PerfilUsuario::Administrador
+    }
+
+    /// Informa se o perfil permite visualizar os documentos (CPF/CNPJ) dos
+    /// clientes por completo. Caso não permita, os documentos deverão ser
+    /// mascarados.
+    pub fn ve_documentos(&self) -> bool {
+        matches!(
+            self,
+            PerfilUsuario::Administrador | PerfilUsuario::Financeiro
+        )
+    }
+
+    /// Informa se o perfil permite gerenciar o crédito dos clientes, como
+    /// alterar seus limites de crédito.
+    pub fn gerencia_credito(&self) -> bool {
+        matches!(
+            self,
+            PerfilUsuario::Administrador | PerfilUsuario::Financeiro
+        )
+    }
+}
 
 /// Representa um usuário do sistema cadastrado no banco de dados.
 ///
@@ -51,6 +117,8 @@ pub struct Usuario {
     /// [função de geração de hash][`crate::bo::usuarios::gera_hash_senha`].
     #[serde(skip_serializing)]
     pub senha_hash: Vec<u8>,
+    /// Perfil de acesso do usuário. Ver [`PerfilUsuario`].
+    pub perfil: PerfilUsuario,
 }
 
 /// Representa os dados de inserção de um novo usuário no banco de dados.
@@ -69,6 +137,9 @@ pub struct NovoUsuario {
     /// Hash da senha do usuário.
     /// Veja [`Usuario::senha_hash`].
     pub senha_hash: Vec<u8>,
+    /// Perfil de acesso do usuário.
+    /// Veja [`Usuario::perfil`].
+    pub perfil: PerfilUsuario,
 }
 
 /// Representa os dados de criação de um novo usuário, recebidos via requisição
@@ -85,12 +156,14 @@ pub struct NovoUsuario {
 ///   "login": "fulano",
 ///   "nome": "Fulano de Tal",
 ///   "email": "fulano@exemplo.com",
-///   "senha": "senhadofulano"
+///   "senha": "senhadofulano",
+///   "perfil": 0
 /// }
 /// ```
 ///
 /// Veja que o e-mail do usuário sendo cadastrado é opcional, podendo ser
-/// omitido ou definido como `null`.
+/// omitido ou definido como `null`. O perfil também é opcional e, caso omitido,
+/// o usuário será cadastrado com o perfil [`PerfilUsuario::Comum`].
 #[derive(Deserialize, Clone)]
 pub struct UsuarioRecv<'r> {
     /// Login do usuário a ser cadastrado.
@@ -107,6 +180,10 @@ pub struct UsuarioRecv<'r> {
     /// Veja [`Usuario::senha_hash`].
     #[serde(skip_serializing)]
     pub senha: &'r str,
+    /// Perfil de acesso do usuário a ser cadastrado. Opcional.
+    /// Veja [`Usuario::perfil`].
+    #[serde(default)]
+    pub perfil: Option<PerfilUsuario>,
 }
 
 impl<'r> From<&UsuarioRecv<'r>> for NovoUsuario {
@@ -126,6 +203,7 @@ impl<'r> From<&UsuarioRecv<'r>> for NovoUsuario {
             senha_hash: usuarios::gera_hash_senha(usr.senha.trim().as_bytes())
                 .0
                 .to_vec(),
+            perfil: usr.perfil.unwrap_or_default(),
         }
     }
 }
@@ -0,0 +1,153 @@
+// routes/arquivos.rs -- Uma parte de Minerva.rs
+// Copyright (C) 2021 Lucas S. Vieira
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with this program.  If not, see <https://www.gnu.org/licenses/>.
+
+//! Rotas para requisições envolvendo arquivos anexos.
+//!
+//! O envio de arquivos é feito através das rotas dos donos dos arquivos (ex:
+//! `POST /produtos/<id>/imagens`); as rotas deste módulo permitem recuperar e
+//! remover arquivos já enviados.
+
+use super::respostas::Resposta;
+use crate::bo;
+use crate::bo::arquivos::{FileStore, TipoAnexo};
+use crate::bo::auth::AuthKey;
+use crate::bo::db::ConexaoPool;
+use crate::controller::arquivos;
+use crate::model::arquivo::{ArquivoRecv, NovoArquivo};
+use rocket::http::ContentType;
+use rocket::{Route, State};
+use serde_json::json;
+
+/// Constrói as subrotas da rota `/arquivos`.
+///
+/// As rotas construídas estão listadas a seguir:
+/// - `GET /<id>` (conteúdo do arquivo -- requer autenticação);
+/// - `DELETE /<id>` (requer autenticação).
+pub fn constroi_rotas() -> Vec<Route> {
+    routes![retorna_arquivo, deleta]
+}
+
+/// Valida um arquivo recebido via formulário para uma finalidade, e prepara
+/// seus metadados para registro.
+///
+/// A chave do conteúdo é gerada sob o prefixo informado. O dono do arquivo
+/// deve ser informado posteriormente nos metadados retornados. Caso o arquivo
+/// seja inválido, será retornado um erro 422.
+pub fn recebe_arquivo(
+    recv: &ArquivoRecv<'_>,
+    tipo: TipoAnexo,
+    prefixo: &str,
+    usuario: &str,
+) -> Result<(NovoArquivo, Vec<u8>), Resposta> {
+    if !recv.arquivo.is_complete() {
+        return Err(Resposta::ErroSemantico(
+            json!({
+                "mensagem": format!(
+                    "O arquivo excede o tamanho máximo de {} bytes",
+                    tipo.tamanho_maximo()
+                )
+            })
+            .to_string(),
+        ));
+    }
+
+    let conteudo = match recv.arquivo.path().map(std::fs::read) {
+        Some(Ok(c)) => c,
+        Some(Err(_)) => {
+            return Err(Resposta::ErroInterno(
+                json!({
+                    "mensagem":
+                        "Erro interno ao receber arquivo. \
+                         Contate o suporte para mais informações."
+                })
+                .to_string(),
+            ))
+        }
+        None => {
+            return Err(Resposta::ErroSemantico(
+                json!({
+                    "mensagem": "O campo \"arquivo\" deve ser enviado como arquivo"
+                })
+                .to_string(),
+            ))
+        }
+    };
+
+    let declarado = recv
+        .arquivo
+        .content_type()
+        .map(|ct| format!("{}/{}", ct.top(), ct.sub()));
+    let content_type = bo::arquivos::valida_arquivo(tipo, declarado.as_deref(), &conteudo)
+        .map_err(Resposta::ErroSemantico)?;
+
+    let nome = recv
+        .arquivo
+        .raw_name()
+        .map(|n| n.dangerous_unsafe_unsanitized_raw().as_str());
+
+    Ok((
+        NovoArquivo {
+            chave: bo::arquivos::gera_chave(prefixo, content_type),
+            nome: bo::arquivos::normaliza_nome(nome),
+            content_type: content_type.to_owned(),
+            tamanho: conteudo.len() as i32,
+            sha256: bo::arquivos::hex_sha256(&conteudo),
+            produto_id: None,
+            mov_estoque_id: None,
+            usuario: usuario.to_owned(),
+        },
+        conteudo,
+    ))
+}
+
+#[get("/<arquivo_id>")]
+fn retorna_arquivo(
+    pool: &State<ConexaoPool>,
+    armazenamento: &State<Box<dyn FileStore>>,
+    arquivo_id: i32,
+    _auth: AuthKey<'_>,
+) -> Result<(ContentType, Vec<u8>), Resposta> {
+    let conexao = pool.get().unwrap();
+    let (dados, conteudo) =
+        arquivos::le_arquivo(&conexao, armazenamento.inner().as_ref(), arquivo_id)?;
+    let tipo = ContentType::parse_flexible(&dados.content_type).unwrap_or(ContentType::Binary);
+    Ok((tipo, conteudo))
+}
+
+#[delete("/<arquivo_id>")]
+fn deleta(
+    pool: &State<ConexaoPool>,
+    armazenamento: &State<Box<dyn FileStore>>,
+    arquivo_id: i32,
+    auth: AuthKey<'_>,
+) -> Resposta {
+    let conexao = pool.get().unwrap();
+    if arquivos::deleta_arquivo(
+        &conexao,
+        armazenamento.inner().as_ref(),
+        arquivo_id,
+        &auth.login(),
+    ) {
+        Resposta::Ok(json!({ "id": arquivo_id }).to_string())
+    } else {
+        Resposta::NaoEncontrado(
+            json!({
+                "mensagem": "Arquivo não encontrado"
+            })
+            .to_string(),
+        )
+    }
+}
@@ -0,0 +1,116 @@
+// routes/categorias.rs -- Uma parte de Minerva.rs
+// Copyright (C) 2021 Lucas S. Vieira
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with this program.  If not, see <https://www.gnu.org/licenses/>.
+
+//! Rotas para requisições envolvendo manipulação de categorias de produtos.
+
+use super::respostas::Resposta;
+use crate::bo;
+use crate::bo::auth::AuthKey;
+use crate::bo::db::ConexaoPool;
+use crate::controller::categorias;
+use crate::model::categoria::NovaCategoria;
+use rocket::serde::json::Json;
+use rocket::{Route, State};
+use serde_json::json;
+
+/// Constrói as subrotas da rota `/categorias`.
+///
+/// As rotas construídas estão listadas a seguir:
+/// - `GET /` (requer autenticação);
+/// - `GET /arvore` (requer autenticação);
+/// - `POST /` (requer autenticação);
+/// - `GET /<id>` (requer autenticação);
+/// - `PUT /<id>` (requer autenticação);
+/// - `DELETE /<id>` (requer autenticação).
+pub fn constroi_rotas() -> Vec<Route> {
+    routes![index, arvore, retorna_categoria, cadastra, altera, deleta]
+}
+
+/// Gera uma resposta padrão para categorias não encontradas.
+fn categoria_nao_encontrada() -> Resposta {
+    Resposta::NaoEncontrado(
+        json!({
+            "mensagem": "Categoria não encontrada"
+        })
+        .to_string(),
+    )
+}
+
+#[get("/")]
+fn index(pool: &State<ConexaoPool>, _auth: AuthKey<'_>) -> Resposta {
+    let conexao = pool.get().unwrap();
+    let vec_categorias = categorias::lista_categorias(&conexao);
+    Resposta::Ok(serde_json::to_string(&vec_categorias).unwrap())
+}
+
+#[get("/arvore")]
+fn arvore(pool: &State<ConexaoPool>, _auth: AuthKey<'_>) -> Resposta {
+    let conexao = pool.get().unwrap();
+    let arvore = bo::categorias::monta_arvore(&categorias::lista_categorias(&conexao));
+    Resposta::Ok(serde_json::to_string(&arvore).unwrap())
+}
+
+#[get("/<cat_id>")]
+fn retorna_categoria(pool: &State<ConexaoPool>, cat_id: i32, _auth: AuthKey<'_>) -> Resposta {
+    let conexao = pool.get().unwrap();
+    match categorias::get_categoria(&conexao, cat_id) {
+        None => categoria_nao_encontrada(),
+        Some(c) => Resposta::Ok(serde_json::to_string(&c).unwrap()),
+    }
+}
+
+#[post("/", data = "<dados>")]
+fn cadastra(pool: &State<ConexaoPool>, dados: Json<NovaCategoria>, auth: AuthKey<'_>) -> Resposta {
+    let conexao = pool.get().unwrap();
+    let lista = categorias::lista_categorias(&conexao);
+    if let Err(s) = bo::categorias::valida_categoria(&lista, None, &dados) {
+        return Resposta::ErroSemantico(s);
+    }
+
+    match categorias::registra_categoria(&conexao, dados.clone(), &auth.login()) {
+        Ok(id) => Resposta::Ok(json!({ "id": id }).to_string()),
+        Err(msg) => Resposta::ErroSemantico(json!({ "mensagem": msg }).to_string()),
+    }
+}
+
+#[put("/<cat_id>", data = "<dados>")]
+fn altera(
+    pool: &State<ConexaoPool>,
+    cat_id: i32,
+    dados: Json<NovaCategoria>,
+    auth: AuthKey<'_>,
+) -> Resposta {
+    let conexao = pool.get().unwrap();
+    let lista = categorias::lista_categorias(&conexao);
+    if !lista.iter().any(|c| c.id == cat_id) {
+        return categoria_nao_encontrada();
+    }
+
+    if let Err(s) = bo::categorias::valida_categoria(&lista, Some(cat_id), &dados) {
+        return Resposta::ErroSemantico(s);
+    }
+
+    match categorias::altera_categoria(&conexao, cat_id, dados.clone(), &auth.login()) {
+        Ok(c) => Resposta::Ok(serde_json::to_string(&c).unwrap()),
+        Err(msg) => Resposta::ErroSemantico(json!({ "mensagem": msg }).to_string()),
+    }
+}
+
+#[delete("/<cat_id>")]
+fn deleta(pool: &State<ConexaoPool>, cat_id: i32, auth: AuthKey<'_>) -> Resposta {
+    let conexao = pool.get().unwrap();
+    categorias::deleta_categoria(&conexao, cat_id, &auth.login())
+}
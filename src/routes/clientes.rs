@@ -18,10 +18,16 @@
 
 use super::respostas::Resposta;
 use crate::bo;
-use crate::bo::auth::AuthKey;
+use crate::bo::auth::{self, AuthKey};
+use crate::bo::cripto::Chaveiro;
 use crate::bo::db::ConexaoPool;
-use crate::controller::clientes;
-use crate::model::cliente::ClienteRecv;
+use crate::controller::{clientes, estoque, titulos};
+use crate::model::cliente::{
+    ClienteRecv, ErroImportacao, LiberacaoCreditoRecv, LimiteCreditoRecv, RelatorioImportacao,
+};
+use crate::model::titulo::{PagamentoTituloRecv, TituloRecv};
+use crate::model::usuario::PerfilUsuario;
+use rocket::data::{Data, ToByteUnit};
 use rocket::serde::json::Json;
 use rocket::Route;
 use rocket::State;
@@ -33,23 +39,56 @@ use serde_json::json;
 /// - `GET /` (requer autenticação);
 /// - `GET /<id>` (requer autenticação);
 /// - `POST /` (requer autenticação);
+/// - `POST /importacao` (requer autenticação);
 /// - `DELETE /<id>` (requer autenticação);
-/// - `DELETE /all` (requer autenticação).
+/// - `DELETE /all` (requer autenticação privilegiada);
+/// - `GET /<id>/exportacao` (requer autenticação);
+/// - `POST /<id>/anonimizacao` (requer autenticação privilegiada);
+/// - `GET /<id>/credito` (requer autenticação);
+/// - `PUT /<id>/credito` (requer autenticação com perfil financeiro);
+/// - `POST /<id>/credito/liberacao` (requer autenticação privilegiada);
+/// - `POST /<id>/credito/avaliacao` (requer autenticação);
+/// - `GET /<id>/titulos` (requer autenticação);
+/// - `POST /<id>/titulos` (requer autenticação);
+/// - `POST /<id>/titulos/<titulo>/pagamento` (requer autenticação).
 pub fn constroi_rotas() -> Vec<Route> {
-    routes![index, deleta_todos, retorna_usuario, cadastra, deleta]
+    routes![
+        index,
+        deleta_todos,
+        retorna_usuario,
+        cadastra,
+        importa,
+        deleta,
+        exporta,
+        anonimiza,
+        credito,
+        altera_limite_credito,
+        libera_credito,
+        avalia_credito,
+        lista_titulos,
+        registra_titulo,
+        registra_pagamento
+    ]
 }
 
 #[get("/")]
-fn index(pool: &State<ConexaoPool>, _auth: AuthKey<'_>) -> Resposta {
+fn index(pool: &State<ConexaoPool>, chaveiro: &State<Chaveiro>, auth: AuthKey<'_>) -> Resposta {
     let conexao = pool.get().unwrap();
-    let vec_clientes = clientes::lista_clientes(&conexao, 100);
+    let perfil = auth::perfil_usuario(&conexao, &auth);
+    let vec_clientes = clientes::lista_clientes(&conexao, chaveiro, perfil, 100);
     Resposta::Ok(serde_json::to_string(&vec_clientes).unwrap())
 }
 
 #[get("/<ident>")]
-fn retorna_usuario(pool: &State<ConexaoPool>, ident: i32, _auth: AuthKey<'_>) -> Resposta {
+fn retorna_usuario(
+    pool: &State<ConexaoPool>,
+    chaveiro: &State<Chaveiro>,
+    ident: i32,
+    auth: AuthKey<'_>,
+) -> Resposta {
     let conexao = pool.get().unwrap();
-    match clientes::get_cliente(&conexao, ident) {
+    let perfil = auth::perfil_usuario(&conexao, &auth);
+    match clientes::get_cliente(&conexao, chaveiro, perfil, ident) {
         None => Resposta::NaoEncontrado(
             json!({
                 "mensagem": "Cliente não encontrado"
@@ -61,26 +100,143 @@ fn retorna_usuario(pool: &State<ConexaoPool>, ident: i32, _auth: AuthKey<'_>) ->
 }
 
 #[post("/", data = "<dados>")]
-fn cadastra(pool: &State<ConexaoPool>, dados: Json<ClienteRecv>, _auth: AuthKey<'_>) -> Resposta {
+fn cadastra(
+    pool: &State<ConexaoPool>,
+    chaveiro: &State<Chaveiro>,
+    dados: Json<ClienteRecv>,
+    auth: AuthKey<'_>,
+) -> Resposta {
     let conexao = pool.get().unwrap();
+    let perfil = auth::perfil_usuario(&conexao, &auth);
     if let Err(s) = bo::clientes::valida_dados(&dados) {
         Resposta::ErroSemantico(s)
+    } else if clientes::encontra_cliente_por_docto(&conexao, chaveiro, perfil, &dados.docto)
+        .is_some()
+    {
+        Resposta::ErroSemantico(
+            json!({
+                "mensagem": format!("Já existe um cliente com o documento {}", dados.docto)
+            })
+            .to_string(),
+        )
     } else {
-        let id = clientes::registra_cliente(&conexao, dados.clone());
+        let id = clientes::registra_cliente(&conexao, chaveiro, dados.clone());
         Resposta::Ok(json!({ "id": id }).to_string())
     }
 }
 
+/// Tamanho máximo de um arquivo de importação de clientes.
+const LIMITE_IMPORTACAO_MIB: usize = 5;
+
+#[post("/importacao?<simulacao>", data = "<dados>")]
+async fn importa(
+    pool: &State<ConexaoPool>,
+    chaveiro: &State<Chaveiro>,
+    simulacao: Option<bool>,
+    dados: Data<'_>,
+    auth: AuthKey<'_>,
+) -> Resposta {
+    let texto = match dados
+        .open(LIMITE_IMPORTACAO_MIB.mebibytes())
+        .into_string()
+        .await
+    {
+        Ok(texto) if texto.is_complete() => texto.into_inner(),
+        Ok(_) => {
+            return Resposta::ErroSemantico(
+                json!({
+                    "mensagem": format!(
+                        "Arquivo de importação excede o limite de {} MiB",
+                        LIMITE_IMPORTACAO_MIB
+                    )
+                })
+                .to_string(),
+            )
+        }
+        Err(_) => {
+            return Resposta::ErroSemantico(
+                json!({
+                    "mensagem": "Arquivo de importação deve estar codificado em UTF-8"
+                })
+                .to_string(),
+            )
+        }
+    };
+
+    let conexao = pool.get().unwrap();
+    let simulacao = simulacao.unwrap_or(false);
+    let (validos, mut erros) = bo::clientes::valida_importacao(&texto);
+    let total = validos.len() + erros.iter().filter(|e| e.linha > 1).count();
+
+    let doctos: Vec<String> = validos.iter().map(|(_, c)| c.docto.clone()).collect();
+    let cadastrados = clientes::doctos_cadastrados(&conexao, chaveiro, &doctos);
+    erros.extend(
+        validos
+            .iter()
+            .filter(|(_, c)| cadastrados.contains(&c.docto))
+            .map(|(linha, c)| ErroImportacao {
+                linha: *linha,
+                mensagem: format!("Já existe um cliente com o documento {}", c.docto),
+            }),
+    );
+    erros.sort_by_key(|e| e.linha);
+
+    let mut relatorio = RelatorioImportacao {
+        simulacao,
+        total,
+        importados: vec![],
+        erros,
+    };
+
+    if !relatorio.erros.is_empty() {
+        return Resposta::ErroSemantico(serde_json::to_string(&relatorio).unwrap());
+    }
+
+    if !simulacao {
+        let dados = validos.into_iter().map(|(_, c)| c).collect();
+        match clientes::importa_clientes(&conexao, chaveiro, dados, &auth.login()) {
+            Ok(ids) => relatorio.importados = ids,
+            Err(msg) => return Resposta::ErroSemantico(json!({ "mensagem": msg }).to_string()),
+        }
+    }
+
+    Resposta::Ok(serde_json::to_string(&relatorio).unwrap())
+}
+
 #[delete("/<ident>")]
-fn deleta(pool: &State<ConexaoPool>, ident: i32, _auth: AuthKey<'_>) -> Resposta {
+fn deleta(
+    pool: &State<ConexaoPool>,
+    chaveiro: &State<Chaveiro>,
+    ident: i32,
+    auth: AuthKey<'_>,
+) -> Resposta {
     let conexao = pool.get().unwrap();
-    match clientes::get_cliente(&conexao, ident) {
+    let perfil = auth::perfil_usuario(&conexao, &auth);
+    match clientes::get_cliente(&conexao, chaveiro, perfil, ident) {
         None => Resposta::NaoEncontrado(
             json!({
                 "mensagem": "Cliente não encontrado"
             })
             .to_string(),
         ),
+        Some(c) if titulos::possui_titulos(&conexao, c.id) => Resposta::ErroSemantico(
+            json!({
+                "mensagem": format!(
+                    "O cliente {} possui títulos registrados e não pode ser removido",
+                    c.id
+                )
+            })
+            .to_string(),
+        ),
+        Some(c) if estoque::possui_movimentos_cliente(&conexao, c.id) => Resposta::ErroSemantico(
+            json!({
+                "mensagem": format!(
+                    "O cliente {} possui movimentações de estoque e não pode ser removido",
+                    c.id
+                )
+            })
+            .to_string(),
+        ),
         Some(c) => {
             let id = c.id;
             clientes::deleta_cliente(&conexao, c);
@@ -90,8 +246,36 @@ fn deleta(pool: &State<ConexaoPool>, ident: i32, _auth: AuthKey<'_>) -> Resposta
 }
 
 #[delete("/all")]
-fn deleta_todos(pool: &State<ConexaoPool>, _auth: AuthKey<'_>) -> Resposta {
+fn deleta_todos(pool: &State<ConexaoPool>, auth: AuthKey<'_>) -> Resposta {
     let conexao = pool.get().unwrap();
+
+    if !auth::usuario_privilegiado(&conexao, &auth) {
+        return Resposta::Proibido(
+            json!({
+                "mensagem": "Apenas usuários privilegiados podem remover todos os clientes"
+            })
+            .to_string(),
+        );
+    }
+
+    if titulos::existem_titulos(&conexao) {
+        return Resposta::Conflito(
+            json!({
+                "mensagem": "Existem títulos registrados para clientes, e os clientes não podem ser removidos"
+            })
+            .to_string(),
+        );
+    }
+
+    if estoque::existem_movimentos_clientes(&conexao) {
+        return Resposta::Conflito(
+            json!({
+                "mensagem": "Existem movimentações de estoque referentes a clientes, e os clientes não podem ser removidos"
+            })
+            .to_string(),
+        );
+    }
+
     let (num_end, num_cl) = clientes::deleta_todos(&conexao);
     Resposta::Ok(
         json!({
@@ -101,3 +285,233 @@ fn deleta_todos(pool: &State<ConexaoPool>, _auth: AuthKey<'_>) -> Resposta {
         .to_string(),
     )
 }
+
+#[get("/<ident>/exportacao")]
+fn exporta(
+    pool: &State<ConexaoPool>,
+    chaveiro: &State<Chaveiro>,
+    ident: i32,
+    auth: AuthKey<'_>,
+) -> Resposta {
+    let conexao = pool.get().unwrap();
+    let perfil = auth::perfil_usuario(&conexao, &auth);
+    match clientes::exporta_dados_cliente(&conexao, chaveiro, perfil, ident) {
+        None => Resposta::NaoEncontrado(
+            json!({
+                "mensagem": "Cliente não encontrado"
+            })
+            .to_string(),
+        ),
+        Some(exp) => Resposta::Ok(serde_json::to_string(&exp).unwrap()),
+    }
+}
+
+#[post("/<ident>/anonimizacao")]
+fn anonimiza(
+    pool: &State<ConexaoPool>,
+    chaveiro: &State<Chaveiro>,
+    ident: i32,
+    auth: AuthKey<'_>,
+) -> Resposta {
+    let conexao = pool.get().unwrap();
+
+    if !auth::usuario_privilegiado(&conexao, &auth) {
+        return Resposta::Proibido(
+            json!({
+                "mensagem": "Apenas usuários privilegiados podem anonimizar clientes"
+            })
+            .to_string(),
+        );
+    }
+
+    match clientes::get_cliente(&conexao, chaveiro, PerfilUsuario::Administrador, ident) {
+        None => Resposta::NaoEncontrado(
+            json!({
+                "mensagem": "Cliente não encontrado"
+            })
+            .to_string(),
+        ),
+        Some(c) if c.anonimizado => Resposta::ErroSemantico(
+            json!({
+                "mensagem": format!("O cliente {} já foi anonimizado", c.id)
+            })
+            .to_string(),
+        ),
+        Some(c) => match clientes::anonimiza_cliente(&conexao, c, &auth.login()) {
+            Ok(id) => Resposta::Ok(json!({ "id": id }).to_string()),
+            Err(msg) => Resposta::ErroSemantico(json!({ "mensagem": msg }).to_string()),
+        },
+    }
+}
+
+/// Gera uma resposta para um cliente não encontrado.
+fn cliente_nao_encontrado() -> Resposta {
+    Resposta::NaoEncontrado(
+        json!({
+            "mensagem": "Cliente não encontrado"
+        })
+        .to_string(),
+    )
+}
+
+#[get("/<ident>/credito")]
+fn credito(pool: &State<ConexaoPool>, ident: i32, _auth: AuthKey<'_>) -> Resposta {
+    let conexao = pool.get().unwrap();
+    let hoje = chrono::Local::today().naive_local();
+    match clientes::situacao_credito(&conexao, ident, hoje) {
+        None => cliente_nao_encontrado(),
+        Some(situacao) => Resposta::Ok(serde_json::to_string(&situacao).unwrap()),
+    }
+}
+
+#[post("/<ident>/credito/avaliacao")]
+fn avalia_credito(pool: &State<ConexaoPool>, ident: i32, _auth: AuthKey<'_>) -> Resposta {
+    let conexao = pool.get().unwrap();
+    match clientes::aplica_regras_credito(&conexao, ident) {
+        Err(_) => clientes::erro_regras_credito(),
+        Ok(None) => cliente_nao_encontrado(),
+        Ok(Some(situacao)) => Resposta::Ok(serde_json::to_string(&situacao).unwrap()),
+    }
+}
+
+#[put("/<ident>/credito", data = "<dados>")]
+fn altera_limite_credito(
+    pool: &State<ConexaoPool>,
+    ident: i32,
+    dados: Json<LimiteCreditoRecv>,
+    auth: AuthKey<'_>,
+) -> Resposta {
+    let conexao = pool.get().unwrap();
+
+    if !auth::perfil_usuario(&conexao, &auth).gerencia_credito() {
+        return Resposta::Proibido(
+            json!({
+                "mensagem": "Usuário não possui permissão para alterar limites de crédito"
+            })
+            .to_string(),
+        );
+    }
+
+    if !clientes::existe_cliente(&conexao, ident) {
+        return cliente_nao_encontrado();
+    }
+
+    if let Err(s) = bo::clientes::valida_limite_credito(&dados) {
+        return Resposta::ErroSemantico(s);
+    }
+
+    match clientes::altera_limite_credito(&conexao, ident, &dados.limite_credito, &auth.login()) {
+        Ok(_) => match clientes::aplica_regras_credito(&conexao, ident) {
+            Ok(situacao) => Resposta::Ok(serde_json::to_string(&situacao).unwrap()),
+            Err(_) => clientes::erro_regras_credito(),
+        },
+        Err(e) => Resposta::ErroSemantico(json!({ "mensagem": e.to_string() }).to_string()),
+    }
+}
+
+#[post("/<ident>/credito/liberacao", data = "<dados>")]
+fn libera_credito(
+    pool: &State<ConexaoPool>,
+    chaveiro: &State<Chaveiro>,
+    ident: i32,
+    dados: Json<LiberacaoCreditoRecv>,
+    auth: AuthKey<'_>,
+) -> Resposta {
+    let conexao = pool.get().unwrap();
+
+    if !auth::usuario_privilegiado(&conexao, &auth) {
+        return Resposta::Proibido(
+            json!({
+                "mensagem": "Apenas usuários privilegiados podem liberar o crédito de clientes"
+            })
+            .to_string(),
+        );
+    }
+
+    match clientes::get_cliente(&conexao, chaveiro, PerfilUsuario::Administrador, ident) {
+        None => cliente_nao_encontrado(),
+        Some(c) if c.anonimizado => Resposta::ErroSemantico(
+            json!({
+                "mensagem": format!("O cliente {} foi anonimizado", c.id)
+            })
+            .to_string(),
+        ),
+        Some(c) => {
+            let hoje = chrono::Local::today().naive_local();
+            if let Err(s) = bo::clientes::valida_liberacao_credito(&dados, hoje) {
+                return Resposta::ErroSemantico(s);
+            }
+            match clientes::libera_credito(&conexao, c.id, &dados, &auth.login()) {
+                Ok(_) => match clientes::aplica_regras_credito(&conexao, c.id) {
+                    Ok(situacao) => Resposta::Ok(serde_json::to_string(&situacao).unwrap()),
+                    Err(_) => clientes::erro_regras_credito(),
+                },
+                Err(e) => Resposta::ErroSemantico(json!({ "mensagem": e.to_string() }).to_string()),
+            }
+        }
+    }
+}
+
+#[get("/<ident>/titulos")]
+fn lista_titulos(pool: &State<ConexaoPool>, ident: i32, _auth: AuthKey<'_>) -> Resposta {
+    let conexao = pool.get().unwrap();
+    if !clientes::existe_cliente(&conexao, ident) {
+        return cliente_nao_encontrado();
+    }
+    let vec_titulos = titulos::lista_titulos_cliente(&conexao, ident);
+    Resposta::Ok(serde_json::to_string(&vec_titulos).unwrap())
+}
+
+#[post("/<ident>/titulos", data = "<dados>")]
+fn registra_titulo(
+    pool: &State<ConexaoPool>,
+    ident: i32,
+    dados: Json<TituloRecv>,
+    auth: AuthKey<'_>,
+) -> Resposta {
+    let conexao = pool.get().unwrap();
+
+    if !clientes::existe_cliente(&conexao, ident) {
+        return cliente_nao_encontrado();
+    }
+
+    if let Err(s) = bo::clientes::valida_titulo(&dados) {
+        return Resposta::ErroSemantico(s);
+    }
+
+    match titulos::registra_titulo(&conexao, ident, dados.clone(), &auth.login()) {
+        Ok(titulo) => match clientes::aplica_regras_credito(&conexao, ident) {
+            Ok(_) => Resposta::Ok(serde_json::to_string(&titulo).unwrap()),
+            Err(_) => clientes::erro_regras_credito(),
+        },
+        Err(e) => Resposta::ErroSemantico(json!({ "mensagem": e.to_string() }).to_string()),
+    }
+}
+
+#[post("/<ident>/titulos/<titulo>/pagamento", data = "<dados>")]
+fn registra_pagamento(
+    pool: &State<ConexaoPool>,
+    ident: i32,
+    titulo: i32,
+    dados: Json<PagamentoTituloRecv>,
+    auth: AuthKey<'_>,
+) -> Resposta {
+    let conexao = pool.get().unwrap();
+    match titulos::get_titulo(&conexao, ident, titulo) {
+        None => Resposta::NaoEncontrado(
+            json!({
+                "mensagem": "Título não encontrado"
+            })
+            .to_string(),
+        ),
+        Some(t) => {
+            if let Err(s) = bo::clientes::valida_pagamento(&t, &dados) {
+                return Resposta::ErroSemantico(s);
+            }
+            match titulos::registra_pagamento(&conexao, &t, dados.clone(), &auth.login()) {
+                Ok(t) => Resposta::Ok(serde_json::to_string(&t).unwrap()),
+                Err(e) => Resposta::ErroSemantico(json!({ "mensagem": e.to_string() }).to_string()),
+            }
+        }
+    }
+}
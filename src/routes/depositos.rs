@@ -0,0 +1,106 @@
+// routes/depositos.rs -- Uma parte de Minerva.rs
+// Copyright (C) 2021 Lucas S. Vieira
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with this program.  If not, see <https://www.gnu.org/licenses/>.
+
+//! Rotas para requisições envolvendo manipulação de depósitos.
+
+use super::respostas::Resposta;
+use crate::bo;
+use crate::bo::auth::AuthKey;
+use crate::bo::db::ConexaoPool;
+use crate::controller::depositos;
+use crate::model::deposito::NovoDeposito;
+use rocket::serde::json::Json;
+use rocket::{Route, State};
+use serde_json::json;
+
+/// Constrói as subrotas da rota `/depositos`.
+///
+/// As rotas construídas estão listadas a seguir:
+/// - `GET /` (requer autenticação);
+/// - `POST /` (requer autenticação);
+/// - `GET /<id>` (requer autenticação);
+/// - `PUT /<id>` (requer autenticação);
+/// - `DELETE /<id>` (requer autenticação).
+pub fn constroi_rotas() -> Vec<Route> {
+    routes![index, retorna_deposito, cadastra, altera, deleta]
+}
+
+/// Gera uma resposta padrão para depósitos não encontrados.
+fn deposito_nao_encontrado() -> Resposta {
+    Resposta::NaoEncontrado(
+        json!({
+            "mensagem": "Depósito não encontrado"
+        })
+        .to_string(),
+    )
+}
+
+#[get("/")]
+fn index(pool: &State<ConexaoPool>, _auth: AuthKey<'_>) -> Resposta {
+    let conexao = pool.get().unwrap();
+    let vec_depositos = depositos::lista_depositos(&conexao);
+    Resposta::Ok(serde_json::to_string(&vec_depositos).unwrap())
+}
+
+#[get("/<dep_id>")]
+fn retorna_deposito(pool: &State<ConexaoPool>, dep_id: i32, _auth: AuthKey<'_>) -> Resposta {
+    let conexao = pool.get().unwrap();
+    match depositos::get_deposito(&conexao, dep_id) {
+        None => deposito_nao_encontrado(),
+        Some(d) => Resposta::Ok(serde_json::to_string(&d).unwrap()),
+    }
+}
+
+#[post("/", data = "<dados>")]
+fn cadastra(pool: &State<ConexaoPool>, dados: Json<NovoDeposito>, auth: AuthKey<'_>) -> Resposta {
+    let conexao = pool.get().unwrap();
+    if let Err(s) = bo::estoque::valida_deposito(&dados) {
+        return Resposta::ErroSemantico(s);
+    }
+
+    match depositos::registra_deposito(&conexao, dados.clone(), &auth.login()) {
+        Ok(id) => Resposta::Ok(json!({ "id": id }).to_string()),
+        Err(msg) => Resposta::ErroSemantico(json!({ "mensagem": msg }).to_string()),
+    }
+}
+
+#[put("/<dep_id>", data = "<dados>")]
+fn altera(
+    pool: &State<ConexaoPool>,
+    dep_id: i32,
+    dados: Json<NovoDeposito>,
+    auth: AuthKey<'_>,
+) -> Resposta {
+    let conexao = pool.get().unwrap();
+    if depositos::get_deposito(&conexao, dep_id).is_none() {
+        return deposito_nao_encontrado();
+    }
+
+    if let Err(s) = bo::estoque::valida_deposito(&dados) {
+        return Resposta::ErroSemantico(s);
+    }
+
+    match depositos::altera_deposito(&conexao, dep_id, dados.clone(), &auth.login()) {
+        Ok(d) => Resposta::Ok(serde_json::to_string(&d).unwrap()),
+        Err(msg) => Resposta::ErroSemantico(json!({ "mensagem": msg }).to_string()),
+    }
+}
+
+#[delete("/<dep_id>")]
+fn deleta(pool: &State<ConexaoPool>, dep_id: i32, auth: AuthKey<'_>) -> Resposta {
+    let conexao = pool.get().unwrap();
+    depositos::deleta_deposito(&conexao, dep_id, &auth.login())
+}
@@ -18,10 +18,23 @@
 //! de produtos.
 
 use super::respostas::Resposta;
+use crate::bo::arquivos::{FileStore, TipoAnexo};
 use crate::bo::auth::AuthKey;
 use crate::bo::db::ConexaoPool;
-use crate::controller::estoque;
-use crate::model::estoque::{Estoque, MovEstoqueRecv};
+use crate::controller::{
+    arquivos, categorias, depositos, documentos, estoque, inventarios, lotes, produtos, reservas,
+    series,
+};
+use crate::model::arquivo::ArquivoRecv;
+use crate::model::documento::DocumentoEstoqueRecv;
+use crate::model::estoque::{
+    EstornoRecv, InicioEstoqueRecv, MovEstoqueGtinRecv, MovEstoqueRecv, PrecoVendaRecv,
+    TransferenciaRecv,
+};
+use crate::model::inventario::{ContagemRecv, InventarioRecv};
+use crate::model::reserva::{ConversaoReservaRecv, ReservaRecv};
+use diesel::PgConnection;
+use rocket::form::Form;
 use rocket::serde::json::Json;
 use rocket::Route;
 use rocket::State;
@@ -32,56 +45,358 @@ use serde_json::json;
 /// As rotas construídas estão listadas a seguir:
 ///
 /// ## Rotas de posição de estoque
-/// - `GET /` (requer autenticação);
-/// - `GET /<id>` (requer autenticação);
+/// - `GET /?<categoria>&<deposito>&<agrupa>&<data>` (requer autenticação);
+/// - `GET /<id>?<deposito>&<data>` (requer autenticação);
 /// - `POST /` (requer autenticação);
+/// - `PUT /<id>/preco` (requer autenticação);
+/// - `GET /categorias` (requer autenticação);
+/// - `GET /camadas?<produto>&<deposito>` (requer autenticação);
+/// - `GET /valorizacao?<deposito>` (requer autenticação);
+/// - `GET /lotes?<produto>&<deposito>` (requer autenticação);
+/// - `GET /lotes/vencendo?<dias>&<deposito>` (requer autenticação);
+/// - `GET /series?<produto>&<deposito>` (requer autenticação);
+/// - `GET /series/<produto>/<numero>` (requer autenticação);
+///
+/// ## Rotas de reservas de estoque
+/// - `GET /reservas?<produto>&<deposito>&<docto>` (requer autenticação);
+/// - `POST /reservas` (requer autenticação);
+/// - `POST /reservas/<id>/liberacao` (requer autenticação);
+/// - `POST /reservas/<id>/conversao` (requer autenticação);
+///
+/// ## Rotas de inventário
+/// - `GET /inventarios?<deposito>` (requer autenticação);
+/// - `POST /inventarios` (requer autenticação);
+/// - `GET /inventarios/<id>` (requer autenticação);
+/// - `POST /inventarios/<id>/contagens` (requer autenticação);
+/// - `POST /inventarios/<id>/fechamento` (requer autenticação);
 ///
 /// ## Rotas de movimentação de estoque
 /// - `GET /mov` (requer autenticação);
 /// - `POST /mov` (requer autenticação);
+/// - `POST /mov/gtin` (requer autenticação);
+/// - `POST /transferencias` (requer autenticação);
+/// - `GET /documentos?<docto>&<fornecedor>` (requer autenticação);
+/// - `GET /documentos/<id>` (requer autenticação);
+/// - `POST /documentos` (requer autenticação);
 /// - `GET /mov/entradas` (requer autenticação);
 /// - `GET /mov/saidas` (requer autenticação);
 /// - `GET /mov/txt` (texto plano -- requer autenticação);
 /// - `GET /mov/entradas/txt` (texto plano -- requer autenticação);
-/// - `GET /mov/saidas/txt` (texto plano -- requer autenticação).
+/// - `GET /mov/saidas/txt` (texto plano -- requer autenticação);
+/// - `GET /mov/<id>/lotes` (requer autenticação);
+/// - `POST /mov/<id>/estorno` (requer autenticação);
+/// - `GET /mov/<id>/documentos` (requer autenticação);
+/// - `POST /mov/<id>/documentos` (multipart -- requer autenticação).
 pub fn constroi_rotas() -> Vec<Route> {
     routes![
         inicia_estoque,
         lista_estoque,
+        totais_por_categoria,
+        lista_camadas,
+        valoriza_estoque,
+        lista_lotes,
+        lotes_vencendo,
+        lista_series,
+        historico_serie,
+        lista_reservas,
+        registra_reserva,
+        libera_reserva,
+        converte_reserva,
+        lista_inventarios,
+        abre_inventario,
+        revisa_inventario,
+        registra_contagem,
+        fecha_inventario,
         mostra_estoque,
+        altera_preco_venda,
         movimenta_estoque,
+        movimenta_estoque_gtin,
+        transfere_estoque,
+        lista_documentos_estoque,
+        mostra_documento_estoque,
+        registra_documento_estoque,
         mostra_movimentos,
         mostra_movimentos_txt,
         mostra_entradas,
         mostra_entradas_txt,
         mostra_saidas,
         mostra_saidas_txt,
+        lotes_movimento,
+        estorna_movimento,
+        lista_documentos,
+        cadastra_documento,
     ]
 }
 
-#[get("/<prod_id>")]
-fn mostra_estoque(pool: &State<ConexaoPool>, prod_id: i32, _auth: AuthKey<'_>) -> Resposta {
+/// Gera uma resposta padrão para depósitos não encontrados, caso um depósito
+/// tenha sido informado e não exista.
+fn verifica_deposito(conexao: &PgConnection, deposito: Option<i32>) -> Result<(), Resposta> {
+    match deposito {
+        Some(deposito) if depositos::get_deposito(conexao, deposito).is_none() => {
+            Err(Resposta::NaoEncontrado(
+                json!({
+                    "mensagem": "Depósito não encontrado"
+                })
+                .to_string(),
+            ))
+        }
+        _ => Ok(()),
+    }
+}
+
+/// Interpreta uma data informada no formato `AAAA-MM-DD`, gerando uma
+/// resposta padrão caso a data seja inválida.
+fn interpreta_data(data: &str) -> Result<chrono::NaiveDate, Resposta> {
+    chrono::NaiveDate::parse_from_str(data, "%Y-%m-%d").map_err(|_| {
+        Resposta::ErroSemantico(
+            json!({
+                "mensagem": "A data deve ser informada no formato AAAA-MM-DD"
+            })
+            .to_string(),
+        )
+    })
+}
+
+#[get("/<prod_id>?<deposito>&<data>")]
+fn mostra_estoque(
+    pool: &State<ConexaoPool>,
+    prod_id: i32,
+    deposito: Option<i32>,
+    data: Option<&str>,
+    _auth: AuthKey<'_>,
+) -> Resposta {
     let conexao = pool.get().unwrap();
-    match estoque::mostra_estoque(&conexao, prod_id) {
-        None => Resposta::NaoEncontrado(
+    if let Err(resposta) = verifica_deposito(&conexao, deposito) {
+        return resposta;
+    }
+    let nao_encontrado = || {
+        Resposta::NaoEncontrado(
             json!({
                 "mensagem": "Produto não encontrado"
             })
             .to_string(),
-        ),
+        )
+    };
+    if let Some(data) = data {
+        let data = match interpreta_data(data) {
+            Ok(d) => d,
+            Err(resposta) => return resposta,
+        };
+        return match estoque::posicoes_na_data(&conexao, data, Some(prod_id), None, deposito, 1)
+            .pop()
+        {
+            None => nao_encontrado(),
+            Some(e) => Resposta::Ok(serde_json::to_string(&e).unwrap()),
+        };
+    }
+    match estoque::mostra_estoque(&conexao, prod_id, deposito) {
+        None => nao_encontrado(),
         Some(e) => Resposta::Ok(serde_json::to_string(&e).unwrap()),
     }
 }
 
-#[get("/")]
-fn lista_estoque(pool: &State<ConexaoPool>, _auth: AuthKey<'_>) -> Resposta {
+#[put("/<prod_id>/preco", data = "<dados>")]
+fn altera_preco_venda(
+    pool: &State<ConexaoPool>,
+    prod_id: i32,
+    dados: Json<PrecoVendaRecv>,
+    auth: AuthKey<'_>,
+) -> Resposta {
     let conexao = pool.get().unwrap();
-    let lista = estoque::lista_estoque(&conexao, 100);
+    if let Err(resposta) = verifica_deposito(&conexao, dados.deposito_id) {
+        return resposta;
+    }
+    estoque::altera_preco_venda(&conexao, prod_id, dados.into_inner(), &auth.login())
+}
+
+#[get("/?<categoria>&<deposito>&<agrupa>&<data>")]
+fn lista_estoque(
+    pool: &State<ConexaoPool>,
+    categoria: Option<i32>,
+    deposito: Option<i32>,
+    agrupa: Option<bool>,
+    data: Option<&str>,
+    _auth: AuthKey<'_>,
+) -> Resposta {
+    let conexao = pool.get().unwrap();
+    if let Err(resposta) = verifica_deposito(&conexao, deposito) {
+        return resposta;
+    }
+    if let Some(categoria) = categoria {
+        if categorias::get_categoria(&conexao, categoria).is_none() {
+            return Resposta::NaoEncontrado(
+                json!({
+                    "mensagem": "Categoria não encontrada"
+                })
+                .to_string(),
+            );
+        }
+    }
+    if let Some(data) = data {
+        let data = match interpreta_data(data) {
+            Ok(d) => d,
+            Err(resposta) => return resposta,
+        };
+        if agrupa.unwrap_or(false) {
+            return Resposta::ErroSemantico(
+                json!({
+                    "mensagem": "Posições de estoque em uma data não podem ser agrupadas"
+                })
+                .to_string(),
+            );
+        }
+        let lista = estoque::posicoes_na_data(&conexao, data, None, categoria, deposito, 100);
+        return Resposta::Ok(serde_json::to_string(&lista).unwrap());
+    }
+    let lista = estoque::lista_estoque(&conexao, 100, categoria, deposito, agrupa.unwrap_or(false));
     Resposta::Ok(serde_json::to_string(&lista).unwrap())
 }
 
+#[get("/categorias")]
+fn totais_por_categoria(pool: &State<ConexaoPool>, _auth: AuthKey<'_>) -> Resposta {
+    let conexao = pool.get().unwrap();
+    let totais = estoque::totais_por_categoria(&conexao);
+    Resposta::Ok(serde_json::to_string(&totais).unwrap())
+}
+
+#[get("/camadas?<produto>&<deposito>")]
+fn lista_camadas(
+    pool: &State<ConexaoPool>,
+    produto: Option<i32>,
+    deposito: Option<i32>,
+    _auth: AuthKey<'_>,
+) -> Resposta {
+    let conexao = pool.get().unwrap();
+    if let Err(resposta) = verifica_deposito(&conexao, deposito) {
+        return resposta;
+    }
+    if let Some(produto) = produto {
+        if produtos::get_produto(&conexao, produto).is_none() {
+            return Resposta::NaoEncontrado(
+                json!({
+                    "mensagem": "Produto não encontrado"
+                })
+                .to_string(),
+            );
+        }
+    }
+    let camadas = estoque::lista_camadas(&conexao, produto, deposito);
+    Resposta::Ok(serde_json::to_string(&camadas).unwrap())
+}
+
+#[get("/valorizacao?<deposito>")]
+fn valoriza_estoque(
+    pool: &State<ConexaoPool>,
+    deposito: Option<i32>,
+    _auth: AuthKey<'_>,
+) -> Resposta {
+    let conexao = pool.get().unwrap();
+    if let Err(resposta) = verifica_deposito(&conexao, deposito) {
+        return resposta;
+    }
+    let valorizacao = estoque::valoriza_estoque(&conexao, deposito);
+    Resposta::Ok(serde_json::to_string(&valorizacao).unwrap())
+}
+
+#[get("/lotes?<produto>&<deposito>")]
+fn lista_lotes(
+    pool: &State<ConexaoPool>,
+    produto: Option<i32>,
+    deposito: Option<i32>,
+    _auth: AuthKey<'_>,
+) -> Resposta {
+    let conexao = pool.get().unwrap();
+    if let Err(resposta) = verifica_deposito(&conexao, deposito) {
+        return resposta;
+    }
+    if let Some(produto) = produto {
+        if produtos::get_produto(&conexao, produto).is_none() {
+            return Resposta::NaoEncontrado(
+                json!({
+                    "mensagem": "Produto não encontrado"
+                })
+                .to_string(),
+            );
+        }
+    }
+    let lista = lotes::lista_lotes(&conexao, produto, deposito);
+    Resposta::Ok(serde_json::to_string(&lista).unwrap())
+}
+
+#[get("/lotes/vencendo?<dias>&<deposito>")]
+fn lotes_vencendo(
+    pool: &State<ConexaoPool>,
+    dias: Option<i64>,
+    deposito: Option<i32>,
+    _auth: AuthKey<'_>,
+) -> Resposta {
+    let dias = dias.unwrap_or(30);
+    if !(0..=3650).contains(&dias) {
+        return Resposta::ErroSemantico(
+            json!({
+                "mensagem": "A quantidade de dias deve estar entre 0 e 3650"
+            })
+            .to_string(),
+        );
+    }
+    let conexao = pool.get().unwrap();
+    if let Err(resposta) = verifica_deposito(&conexao, deposito) {
+        return resposta;
+    }
+    let lista = lotes::lotes_vencendo(&conexao, dias, deposito);
+    Resposta::Ok(serde_json::to_string(&lista).unwrap())
+}
+
+#[get("/series?<produto>&<deposito>")]
+fn lista_series(
+    pool: &State<ConexaoPool>,
+    produto: Option<i32>,
+    deposito: Option<i32>,
+    _auth: AuthKey<'_>,
+) -> Resposta {
+    let conexao = pool.get().unwrap();
+    if let Err(resposta) = verifica_deposito(&conexao, deposito) {
+        return resposta;
+    }
+    if let Some(produto) = produto {
+        if produtos::get_produto(&conexao, produto).is_none() {
+            return Resposta::NaoEncontrado(
+                json!({
+                    "mensagem": "Produto não encontrado"
+                })
+                .to_string(),
+            );
+        }
+    }
+    let lista = series::lista_series(&conexao, produto, deposito);
+    Resposta::Ok(serde_json::to_string(&lista).unwrap())
+}
+
+#[get("/series/<produto>/<numero>")]
+fn historico_serie(
+    pool: &State<ConexaoPool>,
+    produto: i32,
+    numero: String,
+    _auth: AuthKey<'_>,
+) -> Resposta {
+    let conexao = pool.get().unwrap();
+    match series::historico_serie(&conexao, produto, &numero) {
+        None => Resposta::NaoEncontrado(
+            json!({
+                "mensagem": "Número de série não encontrado"
+            })
+            .to_string(),
+        ),
+        Some(historico) => Resposta::Ok(serde_json::to_string(&historico).unwrap()),
+    }
+}
+
 #[post("/", data = "<dados>")]
-fn inicia_estoque(pool: &State<ConexaoPool>, dados: Json<Estoque>, _auth: AuthKey<'_>) -> Resposta {
+fn inicia_estoque(
+    pool: &State<ConexaoPool>,
+    dados: Json<InicioEstoqueRecv>,
+    _auth: AuthKey<'_>,
+) -> Resposta {
     let conexao = pool.get().unwrap();
     estoque::inicia_estoque(&conexao, dados.clone())
 }
@@ -96,6 +411,178 @@ fn movimenta_estoque(
     estoque::movimenta_estoque(&conexao, dados.clone())
 }
 
+#[post("/mov/gtin", data = "<dados>")]
+fn movimenta_estoque_gtin(
+    pool: &State<ConexaoPool>,
+    dados: Json<MovEstoqueGtinRecv>,
+    _auth: AuthKey<'_>,
+) -> Resposta {
+    let conexao = pool.get().unwrap();
+    match produtos::get_gtin(&conexao, &dados.gtin) {
+        None => Resposta::NaoEncontrado(
+            json!({
+                "mensagem": "Produto não encontrado"
+            })
+            .to_string(),
+        ),
+        Some(g) => {
+            estoque::movimenta_estoque(&conexao, dados.into_inner().para_produto(g.produto_id))
+        }
+    }
+}
+
+#[post("/transferencias", data = "<dados>")]
+fn transfere_estoque(
+    pool: &State<ConexaoPool>,
+    dados: Json<TransferenciaRecv>,
+    auth: AuthKey<'_>,
+) -> Resposta {
+    let conexao = pool.get().unwrap();
+    estoque::transfere_estoque(&conexao, dados.into_inner(), &auth.login())
+}
+
+#[get("/reservas?<produto>&<deposito>&<docto>")]
+fn lista_reservas(
+    pool: &State<ConexaoPool>,
+    produto: Option<i32>,
+    deposito: Option<i32>,
+    docto: Option<String>,
+    _auth: AuthKey<'_>,
+) -> Resposta {
+    let conexao = pool.get().unwrap();
+    if let Err(resposta) = verifica_deposito(&conexao, deposito) {
+        return resposta;
+    }
+    let lista = reservas::lista_reservas(&conexao, produto, deposito, docto);
+    Resposta::Ok(serde_json::to_string(&lista).unwrap())
+}
+
+#[post("/reservas", data = "<dados>")]
+fn registra_reserva(
+    pool: &State<ConexaoPool>,
+    dados: Json<ReservaRecv>,
+    auth: AuthKey<'_>,
+) -> Resposta {
+    let conexao = pool.get().unwrap();
+    reservas::registra_reserva(&conexao, dados.into_inner(), &auth.login())
+}
+
+#[post("/reservas/<reserva_id>/liberacao")]
+fn libera_reserva(pool: &State<ConexaoPool>, reserva_id: i32, auth: AuthKey<'_>) -> Resposta {
+    let conexao = pool.get().unwrap();
+    reservas::libera_reserva(&conexao, reserva_id, &auth.login())
+}
+
+#[post("/reservas/<reserva_id>/conversao", data = "<dados>")]
+fn converte_reserva(
+    pool: &State<ConexaoPool>,
+    reserva_id: i32,
+    dados: Json<ConversaoReservaRecv>,
+    auth: AuthKey<'_>,
+) -> Resposta {
+    let conexao = pool.get().unwrap();
+    estoque::converte_reserva(&conexao, reserva_id, dados.into_inner(), &auth.login())
+}
+
+#[get("/inventarios?<deposito>")]
+fn lista_inventarios(
+    pool: &State<ConexaoPool>,
+    deposito: Option<i32>,
+    _auth: AuthKey<'_>,
+) -> Resposta {
+    let conexao = pool.get().unwrap();
+    if let Err(resposta) = verifica_deposito(&conexao, deposito) {
+        return resposta;
+    }
+    let lista = inventarios::lista_inventarios(&conexao, deposito);
+    Resposta::Ok(serde_json::to_string(&lista).unwrap())
+}
+
+#[post("/inventarios", data = "<dados>")]
+fn abre_inventario(
+    pool: &State<ConexaoPool>,
+    dados: Json<InventarioRecv>,
+    auth: AuthKey<'_>,
+) -> Resposta {
+    let conexao = pool.get().unwrap();
+    inventarios::abre_inventario(&conexao, dados.into_inner(), &auth.login())
+}
+
+#[get("/inventarios/<inventario_id>")]
+fn revisa_inventario(
+    pool: &State<ConexaoPool>,
+    inventario_id: i32,
+    _auth: AuthKey<'_>,
+) -> Resposta {
+    let conexao = pool.get().unwrap();
+    match inventarios::revisa_inventario(&conexao, inventario_id) {
+        None => Resposta::NaoEncontrado(
+            json!({
+                "mensagem": "Inventário não encontrado"
+            })
+            .to_string(),
+        ),
+        Some(revisao) => Resposta::Ok(serde_json::to_string(&revisao).unwrap()),
+    }
+}
+
+#[post("/inventarios/<inventario_id>/contagens", data = "<dados>")]
+fn registra_contagem(
+    pool: &State<ConexaoPool>,
+    inventario_id: i32,
+    dados: Json<ContagemRecv>,
+    auth: AuthKey<'_>,
+) -> Resposta {
+    let conexao = pool.get().unwrap();
+    inventarios::registra_contagem(&conexao, inventario_id, dados.into_inner(), &auth.login())
+}
+
+#[post("/inventarios/<inventario_id>/fechamento")]
+fn fecha_inventario(pool: &State<ConexaoPool>, inventario_id: i32, auth: AuthKey<'_>) -> Resposta {
+    let conexao = pool.get().unwrap();
+    estoque::fecha_inventario(&conexao, inventario_id, &auth.login())
+}
+
+#[get("/documentos?<docto>&<fornecedor>")]
+fn lista_documentos_estoque(
+    pool: &State<ConexaoPool>,
+    docto: Option<String>,
+    fornecedor: Option<i32>,
+    _auth: AuthKey<'_>,
+) -> Resposta {
+    let conexao = pool.get().unwrap();
+    let lista = documentos::lista_documentos(&conexao, docto, fornecedor, 100);
+    Resposta::Ok(serde_json::to_string(&lista).unwrap())
+}
+
+#[get("/documentos/<documento_id>")]
+fn mostra_documento_estoque(
+    pool: &State<ConexaoPool>,
+    documento_id: i32,
+    _auth: AuthKey<'_>,
+) -> Resposta {
+    let conexao = pool.get().unwrap();
+    match documentos::get_documento(&conexao, documento_id) {
+        None => Resposta::NaoEncontrado(
+            json!({
+                "mensagem": "Documento de estoque não encontrado"
+            })
+            .to_string(),
+        ),
+        Some(documento) => Resposta::Ok(serde_json::to_string(&documento).unwrap()),
+    }
+}
+
+#[post("/documentos", data = "<dados>")]
+fn registra_documento_estoque(
+    pool: &State<ConexaoPool>,
+    dados: Json<DocumentoEstoqueRecv>,
+    auth: AuthKey<'_>,
+) -> Resposta {
+    let conexao = pool.get().unwrap();
+    estoque::registra_documento(&conexao, dados.into_inner(), &auth.login())
+}
+
 #[get("/mov")]
 fn mostra_movimentos(pool: &State<ConexaoPool>, _auth: AuthKey<'_>) -> Resposta {
     let conexao = pool.get().unwrap();
@@ -140,3 +627,72 @@ fn mostra_movimentos_txt(pool: &State<ConexaoPool>, _auth: AuthKey<'_>) -> Respo
     let conexao = pool.get().unwrap();
     Resposta::OkTexto(estoque::lista_movimentos_texto(&conexao, 100))
 }
+
+/// Gera uma resposta padrão para movimentações de estoque não encontradas.
+fn movimento_nao_encontrado() -> Resposta {
+    Resposta::NaoEncontrado(
+        json!({
+            "mensagem": "Movimentação de estoque não encontrada"
+        })
+        .to_string(),
+    )
+}
+
+#[get("/mov/<mov_id>/lotes")]
+fn lotes_movimento(pool: &State<ConexaoPool>, mov_id: i32, _auth: AuthKey<'_>) -> Resposta {
+    let conexao = pool.get().unwrap();
+    if estoque::get_movimento(&conexao, mov_id).is_none() {
+        return movimento_nao_encontrado();
+    }
+    let lista = lotes::lotes_movimento(&conexao, mov_id);
+    Resposta::Ok(serde_json::to_string(&lista).unwrap())
+}
+
+#[post("/mov/<mov_id>/estorno", data = "<dados>")]
+fn estorna_movimento(
+    pool: &State<ConexaoPool>,
+    mov_id: i32,
+    dados: Json<EstornoRecv>,
+    auth: AuthKey<'_>,
+) -> Resposta {
+    let conexao = pool.get().unwrap();
+    estoque::estorna_movimento(&conexao, mov_id, dados.into_inner(), &auth.login())
+}
+
+#[get("/mov/<mov_id>/documentos")]
+fn lista_documentos(pool: &State<ConexaoPool>, mov_id: i32, _auth: AuthKey<'_>) -> Resposta {
+    let conexao = pool.get().unwrap();
+    if estoque::get_movimento(&conexao, mov_id).is_none() {
+        return movimento_nao_encontrado();
+    }
+    let documentos = arquivos::lista_arquivos_movimento(&conexao, mov_id);
+    Resposta::Ok(serde_json::to_string(&documentos).unwrap())
+}
+
+#[post("/mov/<mov_id>/documentos", data = "<dados>")]
+fn cadastra_documento(
+    pool: &State<ConexaoPool>,
+    armazenamento: &State<Box<dyn FileStore>>,
+    mov_id: i32,
+    dados: Form<ArquivoRecv<'_>>,
+    auth: AuthKey<'_>,
+) -> Resposta {
+    let conexao = pool.get().unwrap();
+    if estoque::get_movimento(&conexao, mov_id).is_none() {
+        return movimento_nao_encontrado();
+    }
+
+    let prefixo = format!("movimentos/{}", mov_id);
+    match super::arquivos::recebe_arquivo(
+        &dados,
+        TipoAnexo::DocumentoMovimentacao,
+        &prefixo,
+        &auth.login(),
+    ) {
+        Err(resposta) => resposta,
+        Ok((mut novo, conteudo)) => {
+            novo.mov_estoque_id = Some(mov_id);
+            arquivos::registra_arquivo(&conexao, armazenamento.inner().as_ref(), novo, &conteudo)
+        }
+    }
+}
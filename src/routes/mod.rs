@@ -19,12 +19,17 @@
 //! Este módulo contém as rotas HTTP para requisições envolvendo todos os
 //! recursos da aplicação que possam ser manipulados por algum usuário.
 
+pub mod arquivos;
+pub mod categorias;
 pub mod clientes;
+pub mod depositos;
 pub mod estoque;
 pub mod log;
 pub mod login;
+pub mod precos;
 pub mod produtos;
 pub mod respostas;
+pub mod unidades;
 pub mod usuarios;
 
 use crate::bo::redis::RedisPool;
@@ -60,14 +65,236 @@ pub fn index(redis_pool: &State<RedisPool>) -> Resposta {
     table.add_row(vec!["GET", "/produtos", "Lista de produtos"]);
     table.add_row(vec!["POST", "/produtos", "Cadastra um produto"]);
     table.add_row(vec!["GET", "/produtos/<id>", "Mostra um produto"]);
+    table.add_row(vec!["PUT", "/produtos/<id>", "Altera um produto"]);
+    table.add_row(vec![
+        "PATCH",
+        "/produtos/<id>",
+        "Altera parcialmente um produto",
+    ]);
     table.add_row(vec!["DELETE", "/produtos/<id>", "Remove um produto"]);
     table.add_row(vec!["DELETE", "/produtos/all", "Remove todos os produtos"]);
+    table.add_row(vec![
+        "GET",
+        "/produtos/<id>/unidades",
+        "Conversões de unidade de um produto",
+    ]);
+    table.add_row(vec![
+        "POST",
+        "/produtos/<id>/unidades",
+        "Cadastra conversão de unidade",
+    ]);
+    table.add_row(vec![
+        "DELETE",
+        "/produtos/<id>/unidades/<unidade>",
+        "Remove conversão de unidade",
+    ]);
+    table.add_row(vec![
+        "GET",
+        "/produtos/gtin/<gtin>",
+        "Mostra um produto pelo código GTIN",
+    ]);
+    table.add_row(vec![
+        "GET",
+        "/produtos/<id>/gtin",
+        "Códigos GTIN de um produto",
+    ]);
+    table.add_row(vec![
+        "POST",
+        "/produtos/<id>/gtin",
+        "Associa um código GTIN a um produto",
+    ]);
+    table.add_row(vec![
+        "DELETE",
+        "/produtos/<id>/gtin/<gtin>",
+        "Remove um código GTIN de um produto",
+    ]);
+    table.add_row(vec![
+        "GET",
+        "/produtos/<id>/variantes",
+        "Variantes de um produto",
+    ]);
+    table.add_row(vec![
+        "POST",
+        "/produtos/<id>/variantes",
+        "Cadastra uma variante de um produto",
+    ]);
+    table.add_row(vec![
+        "GET",
+        "/produtos/<id>/componentes",
+        "Componentes de um kit",
+    ]);
+    table.add_row(vec![
+        "POST",
+        "/produtos/<id>/componentes",
+        "Cadastra um componente de um kit",
+    ]);
+    table.add_row(vec![
+        "DELETE",
+        "/produtos/<id>/componentes/<componente>",
+        "Remove um componente de um kit",
+    ]);
+    table.add_row(vec![
+        "GET",
+        "/produtos/<id>/preco",
+        "Preço efetivo de um produto",
+    ]);
+    table.add_row(vec![
+        "GET",
+        "/produtos/<id>/imagens",
+        "Imagens de um produto",
+    ]);
+    table.add_row(vec![
+        "POST",
+        "/produtos/<id>/imagens",
+        "Envia uma imagem de um produto",
+    ]);
+
+    table.add_row(vec!["GET", "/categorias", "Lista de categorias"]);
+    table.add_row(vec!["GET", "/categorias/arvore", "Árvore de categorias"]);
+    table.add_row(vec!["POST", "/categorias", "Cadastra uma categoria"]);
+    table.add_row(vec!["GET", "/categorias/<id>", "Mostra uma categoria"]);
+    table.add_row(vec!["PUT", "/categorias/<id>", "Altera uma categoria"]);
+    table.add_row(vec!["DELETE", "/categorias/<id>", "Remove uma categoria"]);
+
+    table.add_row(vec!["GET", "/depositos", "Lista de depósitos"]);
+    table.add_row(vec!["POST", "/depositos", "Cadastra um depósito"]);
+    table.add_row(vec!["GET", "/depositos/<id>", "Mostra um depósito"]);
+    table.add_row(vec!["PUT", "/depositos/<id>", "Altera um depósito"]);
+    table.add_row(vec!["DELETE", "/depositos/<id>", "Remove um depósito"]);
+
+    table.add_row(vec!["GET", "/precos", "Lista de tabelas de preço"]);
+    table.add_row(vec!["POST", "/precos", "Cadastra uma tabela de preço"]);
+    table.add_row(vec!["GET", "/precos/<id>", "Mostra uma tabela de preço"]);
+    table.add_row(vec!["PUT", "/precos/<id>", "Altera uma tabela de preço"]);
+    table.add_row(vec!["DELETE", "/precos/<id>", "Remove uma tabela de preço"]);
+    table.add_row(vec![
+        "GET",
+        "/precos/<id>/produtos",
+        "Preços de uma tabela de preço",
+    ]);
+    table.add_row(vec![
+        "POST",
+        "/precos/<id>/produtos",
+        "Cadastra preço de um produto",
+    ]);
+    table.add_row(vec![
+        "DELETE",
+        "/precos/<id>/produtos/<preco>",
+        "Remove preço de um produto",
+    ]);
+
+    table.add_row(vec!["GET", "/unidades", "Lista de unidades de medida"]);
+    table.add_row(vec!["POST", "/unidades", "Cadastra uma unidade de medida"]);
+    table.add_row(vec![
+        "GET",
+        "/unidades/<codigo>",
+        "Mostra uma unidade de medida",
+    ]);
+    table.add_row(vec![
+        "DELETE",
+        "/unidades/<codigo>",
+        "Remove uma unidade de medida",
+    ]);
 
     table.add_row(vec!["GET", "/estoque", "Lista de estoques"]);
     table.add_row(vec!["POST", "/estoque", "Realiza início de estoque"]);
     table.add_row(vec!["GET", "/estoque/<id>", "Mostra um estoque"]);
+    table.add_row(vec![
+        "PUT",
+        "/estoque/<id>/preco",
+        "Altera o preço de venda de um produto",
+    ]);
+    table.add_row(vec![
+        "GET",
+        "/estoque/categorias",
+        "Totais de estoque por categoria",
+    ]);
+    table.add_row(vec!["GET", "/estoque/camadas", "Camadas de custo (PEPS)"]);
+    table.add_row(vec![
+        "GET",
+        "/estoque/valorizacao",
+        "Valoração do estoque por método",
+    ]);
+    table.add_row(vec!["GET", "/estoque/lotes", "Lotes com saldo"]);
+    table.add_row(vec!["GET", "/estoque/lotes/vencendo", "Lotes a vencer"]);
+    table.add_row(vec![
+        "GET",
+        "/estoque/series",
+        "Números de série em estoque",
+    ]);
+    table.add_row(vec![
+        "GET",
+        "/estoque/series/<produto>/<numero>",
+        "Histórico de um número de série",
+    ]);
+    table.add_row(vec![
+        "GET",
+        "/estoque/reservas",
+        "Reservas de estoque ativas",
+    ]);
+    table.add_row(vec!["POST", "/estoque/reservas", "Reserva estoque"]);
+    table.add_row(vec![
+        "POST",
+        "/estoque/reservas/<id>/liberacao",
+        "Libera uma reserva de estoque",
+    ]);
+    table.add_row(vec![
+        "POST",
+        "/estoque/reservas/<id>/conversao",
+        "Converte uma reserva em saída",
+    ]);
+    table.add_row(vec![
+        "GET",
+        "/estoque/inventarios",
+        "Lista sessões de inventário",
+    ]);
+    table.add_row(vec![
+        "POST",
+        "/estoque/inventarios",
+        "Abre uma sessão de inventário",
+    ]);
+    table.add_row(vec![
+        "GET",
+        "/estoque/inventarios/<id>",
+        "Revisa uma sessão de inventário",
+    ]);
+    table.add_row(vec![
+        "POST",
+        "/estoque/inventarios/<id>/contagens",
+        "Registra uma contagem de inventário",
+    ]);
+    table.add_row(vec![
+        "POST",
+        "/estoque/inventarios/<id>/fechamento",
+        "Fecha uma sessão de inventário",
+    ]);
     table.add_row(vec!["GET", "/estoque/mov", "Movimentos de estoque"]);
     table.add_row(vec!["POST", "/estoque/mov", "Faz movimentação de estoque"]);
+    table.add_row(vec![
+        "POST",
+        "/estoque/mov/gtin",
+        "Faz movimentação de estoque por GTIN",
+    ]);
+    table.add_row(vec![
+        "POST",
+        "/estoque/transferencias",
+        "Transfere estoque entre depósitos",
+    ]);
+    table.add_row(vec![
+        "GET",
+        "/estoque/documentos",
+        "Lista documentos de estoque",
+    ]);
+    table.add_row(vec![
+        "GET",
+        "/estoque/documentos/<id>",
+        "Mostra um documento de estoque",
+    ]);
+    table.add_row(vec![
+        "POST",
+        "/estoque/documentos",
+        "Registra um documento de estoque",
+    ]);
     table.add_row(vec![
         "GET",
         "/estoque/mov/txt",
@@ -85,12 +312,85 @@ pub fn index(redis_pool: &State<RedisPool>) -> Resposta {
         "/estoque/mov/saidas/txt",
         "Saídas de estoque (texto plano)",
     ]);
+    table.add_row(vec![
+        "GET",
+        "/estoque/mov/<id>/lotes",
+        "Lotes de uma movimentação",
+    ]);
+    table.add_row(vec![
+        "POST",
+        "/estoque/mov/<id>/estorno",
+        "Estorna uma movimentação",
+    ]);
+    table.add_row(vec![
+        "GET",
+        "/estoque/mov/<id>/documentos",
+        "Documentos de uma movimentação",
+    ]);
+    table.add_row(vec![
+        "POST",
+        "/estoque/mov/<id>/documentos",
+        "Envia um documento de uma movimentação",
+    ]);
+
+    table.add_row(vec!["GET", "/arquivos/<id>", "Conteúdo de um arquivo"]);
+    table.add_row(vec!["DELETE", "/arquivos/<id>", "Remove um arquivo"]);
 
     table.add_row(vec!["GET", "/clientes", "Lista de clientes"]);
     table.add_row(vec!["POST", "/clientes", "Cadastra um cliente"]);
+    table.add_row(vec![
+        "POST",
+        "/clientes/importacao",
+        "Importa clientes de um arquivo CSV",
+    ]);
     table.add_row(vec!["GET", "/clientes/<id>", "Mostra um cliente"]);
     table.add_row(vec!["DELETE", "/clientes/<id>", "Deleta um cliente"]);
     table.add_row(vec!["DELETE", "/clientes/all", "Deleta todos os clientes"]);
+    table.add_row(vec![
+        "GET",
+        "/clientes/<id>/exportacao",
+        "Exporta os dados de um cliente (LGPD)",
+    ]);
+    table.add_row(vec![
+        "POST",
+        "/clientes/<id>/anonimizacao",
+        "Anonimiza um cliente (LGPD)",
+    ]);
+    table.add_row(vec![
+        "GET",
+        "/clientes/<id>/credito",
+        "Situação de crédito de um cliente",
+    ]);
+    table.add_row(vec![
+        "PUT",
+        "/clientes/<id>/credito",
+        "Altera o limite de crédito de um cliente",
+    ]);
+    table.add_row(vec![
+        "POST",
+        "/clientes/<id>/credito/liberacao",
+        "Libera o crédito de um cliente",
+    ]);
+    table.add_row(vec![
+        "POST",
+        "/clientes/<id>/credito/avaliacao",
+        "Aplica as regras de crédito a um cliente",
+    ]);
+    table.add_row(vec![
+        "GET",
+        "/clientes/<id>/titulos",
+        "Lista os títulos de um cliente",
+    ]);
+    table.add_row(vec![
+        "POST",
+        "/clientes/<id>/titulos",
+        "Registra um título para um cliente",
+    ]);
+    table.add_row(vec![
+        "POST",
+        "/clientes/<id>/titulos/<titulo>/pagamento",
+        "Registra o pagamento de um título",
+    ]);
 
     table.add_row(vec!["GET", "/usuarios", "Lista de usuários"]);
     table.add_row(vec!["POST", "/usuarios", "Cadastra um usuário"]);
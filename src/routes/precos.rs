@@ -0,0 +1,199 @@
+// routes/precos.rs -- Uma parte de Minerva.rs
+// Copyright (C) 2021 Lucas S. Vieira
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with this program.  If not, see <https://www.gnu.org/licenses/>.
+
+//! Rotas para requisições envolvendo manipulação de tabelas de preço.
+
+use super::respostas::Resposta;
+use crate::bo;
+use crate::bo::auth::AuthKey;
+use crate::bo::db::ConexaoPool;
+use crate::controller::{clientes, precos};
+use crate::model::preco::{NovaTabelaPreco, PrecoProdutoRecv};
+use diesel::PgConnection;
+use rocket::serde::json::Json;
+use rocket::{Route, State};
+use serde_json::json;
+
+/// Constrói as subrotas da rota `/precos`.
+///
+/// As rotas construídas estão listadas a seguir:
+/// - `GET /` (requer autenticação);
+/// - `POST /` (requer autenticação);
+/// - `GET /<id>` (requer autenticação);
+/// - `PUT /<id>` (requer autenticação);
+/// - `DELETE /<id>` (requer autenticação);
+/// - `GET /<id>/produtos` (requer autenticação);
+/// - `POST /<id>/produtos` (requer autenticação);
+/// - `DELETE /<id>/produtos/<preco_id>` (requer autenticação).
+pub fn constroi_rotas() -> Vec<Route> {
+    routes![
+        index,
+        retorna_tabela,
+        cadastra,
+        altera,
+        deleta,
+        lista_precos,
+        cadastra_preco,
+        deleta_preco
+    ]
+}
+
+/// Gera uma resposta padrão para tabelas de preço não encontradas.
+fn tabela_nao_encontrada() -> Resposta {
+    Resposta::NaoEncontrado(
+        json!({
+            "mensagem": "Tabela de preço não encontrada"
+        })
+        .to_string(),
+    )
+}
+
+/// Valida os dados de uma tabela de preço, inclusive a existência do cliente
+/// informado.
+fn valida_tabela(conexao: &PgConnection, dados: &NovaTabelaPreco) -> Result<(), Resposta> {
+    bo::precos::valida_tabela(dados).map_err(Resposta::ErroSemantico)?;
+    match dados.cliente_id {
+        Some(cliente) if !clientes::existe_cliente(conexao, cliente) => {
+            Err(Resposta::ErroSemantico(
+                json!({
+                    "mensagem": format!("O cliente {} não existe", cliente)
+                })
+                .to_string(),
+            ))
+        }
+        _ => Ok(()),
+    }
+}
+
+#[get("/")]
+fn index(pool: &State<ConexaoPool>, _auth: AuthKey<'_>) -> Resposta {
+    let conexao = pool.get().unwrap();
+    let tabelas = precos::lista_tabelas(&conexao);
+    Resposta::Ok(serde_json::to_string(&tabelas).unwrap())
+}
+
+#[get("/<tabela_id>")]
+fn retorna_tabela(pool: &State<ConexaoPool>, tabela_id: i32, _auth: AuthKey<'_>) -> Resposta {
+    let conexao = pool.get().unwrap();
+    match precos::get_tabela(&conexao, tabela_id) {
+        None => tabela_nao_encontrada(),
+        Some(t) => Resposta::Ok(serde_json::to_string(&t).unwrap()),
+    }
+}
+
+#[post("/", data = "<dados>")]
+fn cadastra(
+    pool: &State<ConexaoPool>,
+    dados: Json<NovaTabelaPreco>,
+    auth: AuthKey<'_>,
+) -> Resposta {
+    let conexao = pool.get().unwrap();
+    if let Err(resposta) = valida_tabela(&conexao, &dados) {
+        return resposta;
+    }
+
+    match precos::registra_tabela(&conexao, dados.clone(), &auth.login()) {
+        Ok(id) => Resposta::Ok(json!({ "id": id }).to_string()),
+        Err(msg) => Resposta::ErroSemantico(json!({ "mensagem": msg }).to_string()),
+    }
+}
+
+#[put("/<tabela_id>", data = "<dados>")]
+fn altera(
+    pool: &State<ConexaoPool>,
+    tabela_id: i32,
+    dados: Json<NovaTabelaPreco>,
+    auth: AuthKey<'_>,
+) -> Resposta {
+    let conexao = pool.get().unwrap();
+    if precos::get_tabela(&conexao, tabela_id).is_none() {
+        return tabela_nao_encontrada();
+    }
+
+    if let Err(resposta) = valida_tabela(&conexao, &dados) {
+        return resposta;
+    }
+
+    match precos::altera_tabela(&conexao, tabela_id, dados.clone(), &auth.login()) {
+        Ok(t) => Resposta::Ok(serde_json::to_string(&t).unwrap()),
+        Err(msg) => Resposta::ErroSemantico(json!({ "mensagem": msg }).to_string()),
+    }
+}
+
+#[delete("/<tabela_id>")]
+fn deleta(pool: &State<ConexaoPool>, tabela_id: i32, auth: AuthKey<'_>) -> Resposta {
+    let conexao = pool.get().unwrap();
+    if precos::deleta_tabela(&conexao, tabela_id, &auth.login()) {
+        Resposta::Ok(json!({ "id": tabela_id }).to_string())
+    } else {
+        tabela_nao_encontrada()
+    }
+}
+
+#[get("/<tabela_id>/produtos")]
+fn lista_precos(pool: &State<ConexaoPool>, tabela_id: i32, _auth: AuthKey<'_>) -> Resposta {
+    let conexao = pool.get().unwrap();
+    if precos::get_tabela(&conexao, tabela_id).is_none() {
+        return tabela_nao_encontrada();
+    }
+    let lista = precos::lista_precos(&conexao, tabela_id);
+    Resposta::Ok(serde_json::to_string(&lista).unwrap())
+}
+
+#[post("/<tabela_id>/produtos", data = "<dados>")]
+fn cadastra_preco(
+    pool: &State<ConexaoPool>,
+    tabela_id: i32,
+    dados: Json<PrecoProdutoRecv>,
+    auth: AuthKey<'_>,
+) -> Resposta {
+    let conexao = pool.get().unwrap();
+    if precos::get_tabela(&conexao, tabela_id).is_none() {
+        return tabela_nao_encontrada();
+    }
+
+    if let Err(s) = bo::precos::valida_preco(&dados) {
+        return Resposta::ErroSemantico(s);
+    }
+
+    precos::registra_preco(&conexao, tabela_id, dados.into_inner(), &auth.login())
+}
+
+#[delete("/<tabela_id>/produtos/<preco_id>")]
+fn deleta_preco(
+    pool: &State<ConexaoPool>,
+    tabela_id: i32,
+    preco_id: i32,
+    auth: AuthKey<'_>,
+) -> Resposta {
+    let conexao = pool.get().unwrap();
+    if precos::deleta_preco(&conexao, tabela_id, preco_id, &auth.login()) {
+        Resposta::Ok(
+            json!({
+                "tabela_preco_id": tabela_id,
+                "id": preco_id
+            })
+            .to_string(),
+        )
+    } else {
+        Resposta::NaoEncontrado(
+            json!({
+                "mensagem": "Preço não encontrado para a tabela de preço"
+            })
+            .to_string(),
+        )
+    }
+}
@@ -17,10 +17,17 @@
 //! Rotas para requisições envolvendo manipulação de produtos.
 
 use super::respostas::Resposta;
+use crate::bo;
+use crate::bo::arquivos::{FileStore, TipoAnexo};
 use crate::bo::auth::AuthKey;
 use crate::bo::db::ConexaoPool;
-use crate::controller::produtos;
-use crate::model::produto::NovoProduto;
+use crate::controller::{arquivos, categorias, clientes, precos, produtos, unidades};
+use crate::model::arquivo::ArquivoRecv;
+use crate::model::produto::{
+    AlteracaoProduto, ComponenteRecv, GtinRecv, NovoProduto, VarianteRecv,
+};
+use crate::model::unidade::ProdutoUnidadeRecv;
+use rocket::form::Form;
 use rocket::serde::json::Json;
 use rocket::Route;
 use rocket::State;
@@ -29,19 +36,92 @@ use serde_json::json;
 /// Constrói as subrotas da rota `/produtos`.
 ///
 /// As rotas construídas estão listadas a seguir:
-/// - `GET /` (requer autenticação);
+/// - `GET /?<inativos>&<capitulo>&<categoria>` (requer autenticação);
 /// - `POST /` (requer autenticação);
 /// - `GET /<id>` (requer autenticação);
+/// - `PUT /<id>` (requer autenticação);
+/// - `PATCH /<id>` (requer autenticação);
 /// - `DELETE /<id>` (requer autenticação);
-/// - `DELETE /all` (requer autenticação).
+/// - `DELETE /all` (requer autenticação);
+/// - `GET /<id>/unidades` (requer autenticação);
+/// - `POST /<id>/unidades` (requer autenticação);
+/// - `DELETE /<id>/unidades/<unidade>` (requer autenticação);
+/// - `GET /gtin/<gtin>` (requer autenticação);
+/// - `GET /<id>/gtin` (requer autenticação);
+/// - `POST /<id>/gtin` (requer autenticação);
+/// - `DELETE /<id>/gtin/<gtin>` (requer autenticação);
+/// - `GET /<id>/variantes` (requer autenticação);
+/// - `POST /<id>/variantes` (requer autenticação);
+/// - `GET /<id>/componentes` (requer autenticação);
+/// - `POST /<id>/componentes` (requer autenticação);
+/// - `DELETE /<id>/componentes/<componente>` (requer autenticação);
+/// - `GET /<id>/preco?<cliente>&<data>` (requer autenticação);
+/// - `GET /<id>/imagens` (requer autenticação);
+/// - `POST /<id>/imagens` (multipart -- requer autenticação).
 pub fn constroi_rotas() -> Vec<Route> {
-    routes![index, retorna_produto, deleta_todos, deleta, cadastra]
+    routes![
+        index,
+        retorna_produto,
+        deleta_todos,
+        deleta,
+        cadastra,
+        substitui,
+        altera,
+        lista_conversoes,
+        cadastra_conversao,
+        deleta_conversao,
+        retorna_por_gtin,
+        lista_gtins,
+        cadastra_gtin,
+        deleta_gtin,
+        lista_variantes,
+        cadastra_variante,
+        lista_componentes,
+        cadastra_componente,
+        deleta_componente,
+        retorna_preco,
+        lista_imagens,
+        cadastra_imagem
+    ]
 }
 
-#[get("/")]
-fn index(pool: &State<ConexaoPool>, _auth: AuthKey<'_>) -> Resposta {
+#[get("/?<inativos>&<capitulo>&<categoria>")]
+fn index(
+    pool: &State<ConexaoPool>,
+    inativos: Option<bool>,
+    capitulo: Option<&str>,
+    categoria: Option<i32>,
+    _auth: AuthKey<'_>,
+) -> Resposta {
+    let capitulo = match capitulo {
+        None => None,
+        Some(c) if !c.is_empty() && c.len() <= 2 && c.chars().all(|d| d.is_ascii_digit()) => {
+            Some(format!("{:0>2}", c))
+        }
+        Some(_) => {
+            return Resposta::ErroSemantico(
+                json!({
+                    "mensagem": "O capítulo NCM deve possuir até dois dígitos"
+                })
+                .to_string(),
+            )
+        }
+    };
+
     let conexao = pool.get().unwrap();
-    let vec_produtos = produtos::lista_produtos(&conexao, 100);
+    if let Some(categoria) = categoria {
+        if categorias::get_categoria(&conexao, categoria).is_none() {
+            return categoria_nao_encontrada();
+        }
+    }
+
+    let vec_produtos = produtos::lista_produtos(
+        &conexao,
+        100,
+        inativos.unwrap_or(false),
+        capitulo.as_deref(),
+        categoria,
+    );
     Resposta::Ok(serde_json::to_string(&vec_produtos).unwrap())
 }
 
@@ -49,46 +129,512 @@ fn index(pool: &State<ConexaoPool>, _auth: AuthKey<'_>) -> Resposta {
 fn retorna_produto(pool: &State<ConexaoPool>, prod_id: i32, _auth: AuthKey<'_>) -> Resposta {
     let conexao = pool.get().unwrap();
     match produtos::get_produto(&conexao, prod_id) {
-        None => Resposta::NaoEncontrado(
+        None => produto_nao_encontrado(),
+        Some(p) => Resposta::Ok(serde_json::to_string(&p).unwrap()),
+    }
+}
+
+#[delete("/<prod_id>")]
+fn deleta(
+    pool: &State<ConexaoPool>,
+    armazenamento: &State<Box<dyn FileStore>>,
+    prod_id: i32,
+    auth: AuthKey<'_>,
+) -> Resposta {
+    let conexao = pool.get().unwrap();
+    let imagens = arquivos::chaves_produtos(&conexao, Some(prod_id));
+    let resposta = produtos::deleta_produto(&conexao, prod_id, &auth.login());
+    arquivos::remove_orfaos(&conexao, armazenamento.inner().as_ref(), imagens);
+    resposta
+}
+
+#[delete("/all")]
+fn deleta_todos(
+    pool: &State<ConexaoPool>,
+    armazenamento: &State<Box<dyn FileStore>>,
+    _auth: AuthKey<'_>,
+) -> Resposta {
+    let conexao = pool.get().unwrap();
+    let imagens = arquivos::chaves_produtos(&conexao, None);
+    let (num_del, num_inat) = produtos::deleta_todos(&conexao);
+    arquivos::remove_orfaos(&conexao, armazenamento.inner().as_ref(), imagens);
+    Resposta::Ok(
+        json!({
+            "produtos": num_del,
+            "inativados": num_inat
+        })
+        .to_string(),
+    )
+}
+
+#[post("/", data = "<dados>")]
+fn cadastra(pool: &State<ConexaoPool>, dados: Json<NovoProduto>, _auth: AuthKey<'_>) -> Resposta {
+    if let Err(s) = bo::produtos::valida_produto(&dados) {
+        return Resposta::ErroSemantico(s);
+    }
+
+    let conexao = pool.get().unwrap();
+    if unidades::get_unidade(&conexao, &dados.unidsaida).is_none() {
+        return unidade_nao_cadastrada(&dados.unidsaida);
+    }
+
+    if let Some(categoria) = dados.categoria_id {
+        if categorias::get_categoria(&conexao, categoria).is_none() {
+            return categoria_inexistente(categoria);
+        }
+    }
+
+    let result = produtos::registra_produto(&conexao, dados.clone());
+    match result {
+        Ok(id) => Resposta::Ok(json!({ "id": id }).to_string()),
+        Err(msg) => Resposta::ErroSemantico(json!({ "mensagem": msg }).to_string()),
+    }
+}
+
+#[put("/<prod_id>", data = "<dados>")]
+fn substitui(
+    pool: &State<ConexaoPool>,
+    prod_id: i32,
+    dados: Json<NovoProduto>,
+    auth: AuthKey<'_>,
+) -> Resposta {
+    altera_produto(pool, prod_id, dados.clone().into(), auth)
+}
+
+#[patch("/<prod_id>", data = "<dados>")]
+fn altera(
+    pool: &State<ConexaoPool>,
+    prod_id: i32,
+    dados: Json<AlteracaoProduto>,
+    auth: AuthKey<'_>,
+) -> Resposta {
+    altera_produto(pool, prod_id, dados.clone(), auth)
+}
+
+/// Realiza a alteração de um produto, comum às requisições PUT e PATCH.
+fn altera_produto(
+    pool: &State<ConexaoPool>,
+    prod_id: i32,
+    dados: AlteracaoProduto,
+    auth: AuthKey<'_>,
+) -> Resposta {
+    let conexao = pool.get().unwrap();
+    let produto = match produtos::get_produto(&conexao, prod_id) {
+        None => return produto_nao_encontrado(),
+        Some(p) => p,
+    };
+
+    if let Err(s) = bo::produtos::valida_alteracao(&dados) {
+        return Resposta::ErroSemantico(s);
+    }
+
+    if let Some(unidsaida) = &dados.unidsaida {
+        if unidades::get_unidade(&conexao, unidsaida).is_none() {
+            return unidade_nao_cadastrada(unidsaida);
+        }
+
+        if unidsaida.to_uppercase() != produto.unidsaida
+            && produtos::possui_historico(&conexao, prod_id)
+        {
+            return Resposta::Conflito(
+                json!({
+                    "mensagem": "A unidade de saída de um produto com estoque \
+                                 ou movimentações não pode ser alterada"
+                })
+                .to_string(),
+            );
+        }
+    }
+
+    if let Some(Some(categoria)) = dados.categoria_id {
+        if categorias::get_categoria(&conexao, categoria).is_none() {
+            return categoria_inexistente(categoria);
+        }
+    }
+
+    if dados
+        .controla_lote
+        .is_some_and(|c| c != produto.controla_lote)
+        && produtos::possui_historico(&conexao, prod_id)
+    {
+        return Resposta::Conflito(
+            json!({
+                "mensagem": "O controle de lotes de um produto com estoque \
+                             ou movimentações não pode ser alterado"
+            })
+            .to_string(),
+        );
+    }
+
+    if dados
+        .controla_serie
+        .is_some_and(|c| c != produto.controla_serie)
+        && produtos::possui_historico(&conexao, prod_id)
+    {
+        return Resposta::Conflito(
+            json!({
+                "mensagem": "O controle de números de série de um produto com \
+                             estoque ou movimentações não pode ser alterado"
+            })
+            .to_string(),
+        );
+    }
+
+    match produtos::altera_produto(&conexao, prod_id, dados, &auth.login()) {
+        Ok(p) => Resposta::Ok(serde_json::to_string(&p).unwrap()),
+        Err(msg) => Resposta::ErroSemantico(json!({ "mensagem": msg }).to_string()),
+    }
+}
+
+/// Gera uma resposta padrão para unidades de medida não cadastradas.
+fn unidade_nao_cadastrada(unidade: &str) -> Resposta {
+    Resposta::ErroSemantico(
+        json!({
+            "mensagem": format!("Unidade de medida {} não cadastrada", unidade)
+        })
+        .to_string(),
+    )
+}
+
+/// Gera uma resposta padrão para categorias informadas nos dados de um produto,
+/// mas não cadastradas.
+fn categoria_inexistente(categoria: i32) -> Resposta {
+    Resposta::ErroSemantico(
+        json!({
+            "mensagem": format!("Categoria {} não encontrada", categoria)
+        })
+        .to_string(),
+    )
+}
+
+/// Gera uma resposta padrão para categorias informadas como filtro, mas não
+/// cadastradas.
+fn categoria_nao_encontrada() -> Resposta {
+    Resposta::NaoEncontrado(
+        json!({
+            "mensagem": "Categoria não encontrada"
+        })
+        .to_string(),
+    )
+}
+
+/// Gera uma resposta padrão para produtos não encontrados.
+fn produto_nao_encontrado() -> Resposta {
+    Resposta::NaoEncontrado(
+        json!({
+            "mensagem": "Produto não encontrado"
+        })
+        .to_string(),
+    )
+}
+
+#[get("/<prod_id>/unidades")]
+fn lista_conversoes(pool: &State<ConexaoPool>, prod_id: i32, _auth: AuthKey<'_>) -> Resposta {
+    let conexao = pool.get().unwrap();
+    if produtos::get_produto(&conexao, prod_id).is_none() {
+        return produto_nao_encontrado();
+    }
+    let conversoes = unidades::lista_conversoes(&conexao, prod_id);
+    Resposta::Ok(serde_json::to_string(&conversoes).unwrap())
+}
+
+#[post("/<prod_id>/unidades", data = "<dados>")]
+fn cadastra_conversao(
+    pool: &State<ConexaoPool>,
+    prod_id: i32,
+    dados: Json<ProdutoUnidadeRecv>,
+    auth: AuthKey<'_>,
+) -> Resposta {
+    let conexao = pool.get().unwrap();
+    let produto = match produtos::get_produto(&conexao, prod_id) {
+        None => return produto_nao_encontrado(),
+        Some(p) => p,
+    };
+
+    if let Err(s) = bo::produtos::valida_conversao(&dados) {
+        return Resposta::ErroSemantico(s);
+    }
+
+    if unidades::get_unidade(&conexao, &dados.unidade).is_none() {
+        return unidade_nao_cadastrada(&dados.unidade);
+    }
+
+    if dados.unidade.to_uppercase() == produto.unidsaida {
+        return Resposta::ErroSemantico(
             json!({
-                "mensagem": "Produto não encontrado"
+                "mensagem": "A unidade de saída do produto não necessita de conversão"
+            })
+            .to_string(),
+        );
+    }
+
+    match unidades::registra_conversao(&conexao, prod_id, dados.clone(), &auth.login()) {
+        Ok(c) => Resposta::Ok(serde_json::to_string(&c).unwrap()),
+        Err(_) => Resposta::ErroInterno(
+            json!({
+                "mensagem": "Erro interno ao cadastrar conversão de unidade. \
+                             Contate o suporte para mais informações."
             })
             .to_string(),
         ),
+    }
+}
+
+#[delete("/<prod_id>/unidades/<unidade>")]
+fn deleta_conversao(
+    pool: &State<ConexaoPool>,
+    prod_id: i32,
+    unidade: &str,
+    auth: AuthKey<'_>,
+) -> Resposta {
+    let conexao = pool.get().unwrap();
+    if unidades::deleta_conversao(&conexao, prod_id, unidade, &auth.login()) {
+        Resposta::Ok(
+            json!({
+                "produto_id": prod_id,
+                "unidade": unidade.to_uppercase()
+            })
+            .to_string(),
+        )
+    } else {
+        Resposta::NaoEncontrado(
+            json!({
+                "mensagem": "Conversão de unidade não encontrada"
+            })
+            .to_string(),
+        )
+    }
+}
+
+#[get("/gtin/<gtin>", rank = 2)]
+fn retorna_por_gtin(pool: &State<ConexaoPool>, gtin: &str, _auth: AuthKey<'_>) -> Resposta {
+    let conexao = pool.get().unwrap();
+    match produtos::get_produto_por_gtin(&conexao, gtin) {
+        None => produto_nao_encontrado(),
         Some(p) => Resposta::Ok(serde_json::to_string(&p).unwrap()),
     }
 }
 
-#[delete("/<prod_id>")]
-fn deleta(pool: &State<ConexaoPool>, prod_id: i32, _auth: AuthKey<'_>) -> Resposta {
+#[get("/<prod_id>/gtin")]
+fn lista_gtins(pool: &State<ConexaoPool>, prod_id: i32, _auth: AuthKey<'_>) -> Resposta {
     let conexao = pool.get().unwrap();
-    match produtos::get_produto(&conexao, prod_id) {
+    if produtos::get_produto(&conexao, prod_id).is_none() {
+        return produto_nao_encontrado();
+    }
+    let gtins = produtos::lista_gtins(&conexao, prod_id);
+    Resposta::Ok(serde_json::to_string(&gtins).unwrap())
+}
+
+#[post("/<prod_id>/gtin", data = "<dados>")]
+fn cadastra_gtin(
+    pool: &State<ConexaoPool>,
+    prod_id: i32,
+    dados: Json<GtinRecv>,
+    auth: AuthKey<'_>,
+) -> Resposta {
+    let conexao = pool.get().unwrap();
+    if produtos::get_produto(&conexao, prod_id).is_none() {
+        return produto_nao_encontrado();
+    }
+
+    if let Err(s) = bo::produtos::valida_novo_gtin(&dados) {
+        return Resposta::ErroSemantico(s);
+    }
+
+    produtos::registra_gtin(&conexao, prod_id, &dados.gtin, &auth.login())
+}
+
+#[delete("/<prod_id>/gtin/<gtin>")]
+fn deleta_gtin(pool: &State<ConexaoPool>, prod_id: i32, gtin: &str, auth: AuthKey<'_>) -> Resposta {
+    let conexao = pool.get().unwrap();
+    if produtos::deleta_gtin(&conexao, prod_id, gtin, &auth.login()) {
+        Resposta::Ok(
+            json!({
+                "produto_id": prod_id,
+                "gtin": gtin
+            })
+            .to_string(),
+        )
+    } else {
+        Resposta::NaoEncontrado(
+            json!({
+                "mensagem": "Código GTIN não encontrado para o produto"
+            })
+            .to_string(),
+        )
+    }
+}
+
+#[get("/<prod_id>/variantes")]
+fn lista_variantes(pool: &State<ConexaoPool>, prod_id: i32, _auth: AuthKey<'_>) -> Resposta {
+    let conexao = pool.get().unwrap();
+    if produtos::get_produto(&conexao, prod_id).is_none() {
+        return produto_nao_encontrado();
+    }
+    let variantes = produtos::lista_variantes(&conexao, prod_id);
+    Resposta::Ok(serde_json::to_string(&variantes).unwrap())
+}
+
+#[post("/<prod_id>/variantes", data = "<dados>")]
+fn cadastra_variante(
+    pool: &State<ConexaoPool>,
+    prod_id: i32,
+    dados: Json<VarianteRecv>,
+    auth: AuthKey<'_>,
+) -> Resposta {
+    let conexao = pool.get().unwrap();
+    let pai = match produtos::get_produto(&conexao, prod_id) {
+        None => return produto_nao_encontrado(),
+        Some(p) => p,
+    };
+
+    if let Err(s) = bo::produtos::valida_variante(&dados) {
+        return Resposta::ErroSemantico(s);
+    }
+
+    produtos::registra_variante(&conexao, &pai, dados.into_inner(), &auth.login())
+}
+
+#[get("/<prod_id>/componentes")]
+fn lista_componentes(pool: &State<ConexaoPool>, prod_id: i32, _auth: AuthKey<'_>) -> Resposta {
+    let conexao = pool.get().unwrap();
+    if produtos::get_produto(&conexao, prod_id).is_none() {
+        return produto_nao_encontrado();
+    }
+    let componentes = produtos::lista_componentes(&conexao, prod_id);
+    Resposta::Ok(serde_json::to_string(&componentes).unwrap())
+}
+
+#[post("/<prod_id>/componentes", data = "<dados>")]
+fn cadastra_componente(
+    pool: &State<ConexaoPool>,
+    prod_id: i32,
+    dados: Json<ComponenteRecv>,
+    auth: AuthKey<'_>,
+) -> Resposta {
+    let conexao = pool.get().unwrap();
+    let kit = match produtos::get_produto(&conexao, prod_id) {
+        None => return produto_nao_encontrado(),
+        Some(p) => p,
+    };
+
+    if let Err(s) = bo::produtos::valida_componente(&dados) {
+        return Resposta::ErroSemantico(s);
+    }
+
+    produtos::registra_componente(&conexao, &kit, dados.into_inner(), &auth.login())
+}
+
+#[delete("/<prod_id>/componentes/<componente>")]
+fn deleta_componente(
+    pool: &State<ConexaoPool>,
+    prod_id: i32,
+    componente: i32,
+    auth: AuthKey<'_>,
+) -> Resposta {
+    let conexao = pool.get().unwrap();
+    if produtos::deleta_componente(&conexao, prod_id, componente, &auth.login()) {
+        Resposta::Ok(
+            json!({
+                "kit_id": prod_id,
+                "componente_id": componente
+            })
+            .to_string(),
+        )
+    } else {
+        Resposta::NaoEncontrado(
+            json!({
+                "mensagem": "Componente não encontrado para o kit"
+            })
+            .to_string(),
+        )
+    }
+}
+
+#[get("/<prod_id>/preco?<cliente>&<data>")]
+fn retorna_preco(
+    pool: &State<ConexaoPool>,
+    prod_id: i32,
+    cliente: Option<i32>,
+    data: Option<&str>,
+    _auth: AuthKey<'_>,
+) -> Resposta {
+    let conexao = pool.get().unwrap();
+    if produtos::get_produto(&conexao, prod_id).is_none() {
+        return produto_nao_encontrado();
+    }
+
+    let data = match data {
+        None => chrono::Local::today().naive_local(),
+        Some(d) => match chrono::NaiveDate::parse_from_str(d, "%Y-%m-%d") {
+            Ok(d) => d,
+            Err(_) => {
+                return Resposta::ErroSemantico(
+                    json!({
+                        "mensagem": "A data deve ser informada no formato AAAA-MM-DD"
+                    })
+                    .to_string(),
+                )
+            }
+        },
+    };
+
+    let cliente = match cliente {
+        None => None,
+        Some(id) => match clientes::get_tipo_cliente(&conexao, id) {
+            None => {
+                return Resposta::NaoEncontrado(
+                    json!({
+                        "mensagem": "Cliente não encontrado"
+                    })
+                    .to_string(),
+                )
+            }
+            Some(tipo) => Some((id, tipo)),
+        },
+    };
+
+    match precos::preco_efetivo(&conexao, prod_id, cliente, data) {
+        Some(p) => Resposta::Ok(serde_json::to_string(&p).unwrap()),
         None => Resposta::NaoEncontrado(
             json!({
-                "mensagem": "Produto não encontrado"
+                "mensagem": "O produto não possui preço vigente nem posição de estoque"
             })
             .to_string(),
         ),
-        Some(_) => {
-            produtos::deleta_produto(&conexao, prod_id);
-            Resposta::Ok(json!({ "id": prod_id }).to_string())
-        }
     }
 }
 
-#[delete("/all")]
-fn deleta_todos(pool: &State<ConexaoPool>, _auth: AuthKey<'_>) -> Resposta {
+#[get("/<prod_id>/imagens")]
+fn lista_imagens(pool: &State<ConexaoPool>, prod_id: i32, _auth: AuthKey<'_>) -> Resposta {
     let conexao = pool.get().unwrap();
-    let num_del = produtos::deleta_todos(&conexao);
-    Resposta::Ok(json!({ "produtos": num_del }).to_string())
+    if produtos::get_produto(&conexao, prod_id).is_none() {
+        return produto_nao_encontrado();
+    }
+    let imagens = arquivos::lista_arquivos_produto(&conexao, prod_id);
+    Resposta::Ok(serde_json::to_string(&imagens).unwrap())
 }
 
-#[post("/", data = "<dados>")]
-fn cadastra(pool: &State<ConexaoPool>, dados: Json<NovoProduto>, _auth: AuthKey<'_>) -> Resposta {
+#[post("/<prod_id>/imagens", data = "<dados>")]
+fn cadastra_imagem(
+    pool: &State<ConexaoPool>,
+    armazenamento: &State<Box<dyn FileStore>>,
+    prod_id: i32,
+    dados: Form<ArquivoRecv<'_>>,
+    auth: AuthKey<'_>,
+) -> Resposta {
     let conexao = pool.get().unwrap();
-    let result = produtos::registra_produto(&conexao, dados.clone());
-    match result {
-        Ok(id) => Resposta::Ok(json!({ "id": id }).to_string()),
-        Err(msg) => Resposta::ErroSemantico(json!({ "mensagem": msg }).to_string()),
+    if produtos::get_produto(&conexao, prod_id).is_none() {
+        return produto_nao_encontrado();
+    }
+
+    let prefixo = format!("produtos/{}", prod_id);
+    match super::arquivos::recebe_arquivo(&dados, TipoAnexo::ImagemProduto, &prefixo, &auth.login())
+    {
+        Err(resposta) => resposta,
+        Ok((mut novo, conteudo)) => {
+            novo.produto_id = Some(prod_id);
+            arquivos::registra_arquivo(&conexao, armazenamento.inner().as_ref(), novo, &conteudo)
+        }
     }
 }
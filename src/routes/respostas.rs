@@ -33,10 +33,18 @@ pub enum Resposta {
     /// em que o usuário não estiver autorizado a acessar o recurso.
     #[response(status = 401, content_type = "json")]
     NaoAutorizado(String),
+    /// Resposta de erro 403 com retorno em JSON, para situações
+    /// em que o usuário não possuir permissão para realizar a operação.
+    #[response(status = 403, content_type = "json")]
+    Proibido(String),
     /// Resposta de erro 404 com retorno em JSON, para situações
     /// em que o recurso acessado não for encontrado.
     #[response(status = 404, content_type = "json")]
     NaoEncontrado(String),
+    /// Resposta de erro 409 com retorno em JSON, para situações
+    /// em que a operação conflitar com o estado atual do recurso.
+    #[response(status = 409, content_type = "json")]
+    Conflito(String),
     /// Resposta de erro 418 com retorno em texto plano, caso o
     /// usuário queira muito tomar um cafezinho.
     #[response(status = 418, content_type = "text")]
@@ -0,0 +1,90 @@
+// routes/unidades.rs -- Uma parte de Minerva.rs
+// Copyright (C) 2021 Lucas S. Vieira
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with this program.  If not, see <https://www.gnu.org/licenses/>.
+
+//! Rotas para requisições envolvendo manipulação de unidades de medida.
+//!
+//! Os fatores de conversão entre unidades de medida de um produto específico
+//! são manipulados através das [rotas de produtos][`super::produtos`].
+
+use super::respostas::Resposta;
+use crate::bo;
+use crate::bo::auth::AuthKey;
+use crate::bo::db::ConexaoPool;
+use crate::controller::unidades;
+use crate::model::unidade::UnidadeMedida;
+use rocket::serde::json::Json;
+use rocket::{Route, State};
+use serde_json::json;
+
+/// Constrói as subrotas da rota `/unidades`.
+///
+/// As rotas construídas estão listadas a seguir:
+/// - `GET /` (requer autenticação);
+/// - `POST /` (requer autenticação);
+/// - `GET /<codigo>` (requer autenticação);
+/// - `DELETE /<codigo>` (requer autenticação).
+pub fn constroi_rotas() -> Vec<Route> {
+    routes![index, retorna_unidade, cadastra, deleta]
+}
+
+#[get("/")]
+fn index(pool: &State<ConexaoPool>, _auth: AuthKey<'_>) -> Resposta {
+    let conexao = pool.get().unwrap();
+    let vec_unidades = unidades::lista_unidades(&conexao);
+    Resposta::Ok(serde_json::to_string(&vec_unidades).unwrap())
+}
+
+#[get("/<codigo>")]
+fn retorna_unidade(pool: &State<ConexaoPool>, codigo: &str, _auth: AuthKey<'_>) -> Resposta {
+    let conexao = pool.get().unwrap();
+    match unidades::get_unidade(&conexao, codigo) {
+        None => Resposta::NaoEncontrado(
+            json!({
+                "mensagem": "Unidade de medida não encontrada"
+            })
+            .to_string(),
+        ),
+        Some(u) => Resposta::Ok(serde_json::to_string(&u).unwrap()),
+    }
+}
+
+#[post("/", data = "<dados>")]
+fn cadastra(pool: &State<ConexaoPool>, dados: Json<UnidadeMedida>, auth: AuthKey<'_>) -> Resposta {
+    let conexao = pool.get().unwrap();
+    if let Err(s) = bo::produtos::valida_unidade(&dados) {
+        return Resposta::ErroSemantico(s);
+    }
+
+    if unidades::get_unidade(&conexao, &dados.codigo).is_some() {
+        return Resposta::Conflito(
+            json!({
+                "mensagem": "Unidade de medida já cadastrada"
+            })
+            .to_string(),
+        );
+    }
+
+    match unidades::registra_unidade(&conexao, dados.clone(), &auth.login()) {
+        Ok(codigo) => Resposta::Ok(json!({ "codigo": codigo }).to_string()),
+        Err(msg) => Resposta::ErroSemantico(json!({ "mensagem": msg }).to_string()),
+    }
+}
+
+#[delete("/<codigo>")]
+fn deleta(pool: &State<ConexaoPool>, codigo: &str, auth: AuthKey<'_>) -> Resposta {
+    let conexao = pool.get().unwrap();
+    unidades::deleta_unidade(&conexao, codigo, &auth.login())
+}
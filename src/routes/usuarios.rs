@@ -20,10 +20,11 @@
 //! visualização de dados de usuários do sistema.
 
 use super::respostas::Resposta;
-use crate::bo::auth::AuthKey;
+use crate::bo;
+use crate::bo::auth::{self, AuthKey};
 use crate::bo::db::ConexaoPool;
 use crate::controller::usuarios;
-use crate::model::usuario::UsuarioRecv;
+use crate::model::usuario::{PerfilUsuario, UsuarioRecv};
 use rocket::serde::json::Json;
 use rocket::{Route, State};
 use serde_json::json;
@@ -84,9 +85,17 @@ fn retorna_por_login(pool: &State<ConexaoPool>, usr_login: &str, _auth: AuthKey<
 }
 
 #[post("/", data = "<dados>")]
-fn cadastra(pool: &State<ConexaoPool>, dados: Json<UsuarioRecv>, _auth: AuthKey<'_>) -> Resposta {
+fn cadastra(pool: &State<ConexaoPool>, dados: Json<UsuarioRecv>, auth: AuthKey<'_>) -> Resposta {
     let conexao = pool.get().unwrap();
 
+    let perfil = dados.perfil.unwrap_or_default();
+    if perfil != PerfilUsuario::Comum {
+        let privilegiado = auth::usuario_privilegiado(&conexao, &auth);
+        if let Err(s) = bo::usuarios::valida_perfil_cadastro(perfil, privilegiado) {
+            return Resposta::Proibido(s);
+        }
+    }
+
     if usuarios::encontra_usuario(&conexao, dados.login).is_some() {
         return Resposta::ErroSemantico(
             json!({ "mensagem": format!("O nome de usuário \"{}\" já existe", dados.login) })